@@ -0,0 +1,73 @@
+use libyang::*;
+use std::fs;
+use std::process::exit;
+
+// Check the backwards compatibility of the new revision of a module file
+// against the old one and print the report in JSON.
+//
+//   yang-compat [-p path]... old.yang new.yang
+//
+// Imports are searched in the load paths of "-p" and YANGPATH.  Exit
+// status is 0 when compatible, 1 when not and 2 on errors.
+fn load(paths: &[String], file: &str) -> Result<(String, Modules), String> {
+    let text = fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?;
+    let mut memory = MemorySource::new();
+    let file_name = memory
+        .add(&text)
+        .ok_or_else(|| format!("{}: not a YANG module", file))?;
+    let name = file_name
+        .trim_end_matches(".yang")
+        .split('@')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+
+    let mut yang = Yang::new();
+    for path in paths.iter() {
+        yang.add_path(path);
+    }
+    yang.add_env_path();
+
+    let mut sources = ModuleSources::new();
+    sources.add(memory);
+    sources.add(yang);
+    let mut ms = Modules::new();
+    ms.load(&sources, &name)
+        .map_err(|e| format!("{}: {}", file, e))?;
+    Ok((name, ms))
+}
+
+fn usage() -> ! {
+    eprintln!("usage: yang-compat [-p path]... old.yang new.yang");
+    exit(2);
+}
+
+fn main() {
+    let mut paths = vec![];
+    let mut files = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-p" => paths.push(args.next().unwrap_or_else(|| usage())),
+            _ => files.push(arg),
+        }
+    }
+    if files.len() != 2 {
+        usage();
+    }
+
+    let result = load(&paths, &files[0]).and_then(|(name, old)| {
+        let (_, new) = load(&paths, &files[1])?;
+        old.check_update(&new, &name).map_err(|e| e.to_string())
+    });
+    match result {
+        Ok(report) => {
+            println!("{}", report.to_json().to_string_pretty());
+            exit(if report.is_compatible() { 0 } else { 1 });
+        }
+        Err(e) => {
+            eprintln!("yang-compat: {}", e);
+            exit(2);
+        }
+    }
+}
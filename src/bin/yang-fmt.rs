@@ -0,0 +1,81 @@
+use libyang::*;
+use std::fs;
+use std::io::{self, Read};
+use std::process::exit;
+
+// Format YANG files and print them to the standard output, or report the
+// lines which differ from the formatted text with "--check".
+//
+//   yang-fmt [--check] [--indent N] [--width N] [--keep-order] [file]...
+//
+// The standard input is read when no file is given.  Exit status is 0 on
+// success, 1 when "--check" finds differences and 2 on errors.
+fn usage() -> ! {
+    eprintln!("usage: yang-fmt [--check] [--indent N] [--width N] [--keep-order] [file]...");
+    exit(2);
+}
+
+fn number(arg: Option<String>) -> usize {
+    arg.and_then(|v| v.parse().ok()).unwrap_or_else(|| usage())
+}
+
+fn read(file: &str) -> Result<String, String> {
+    if file == "-" {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("stdin: {}", e))?;
+        return Ok(text);
+    }
+    fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))
+}
+
+fn main() {
+    let mut opts = FormatOptions::default();
+    let mut check = false;
+    let mut files = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--indent" => opts.indent = number(args.next()),
+            "--width" => opts.line_width = number(args.next()),
+            "--keep-order" => opts.canonical = false,
+            _ if arg.starts_with("--") => usage(),
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        files.push(String::from("-"));
+    }
+
+    let mut status = 0;
+    for file in files.iter() {
+        let result = read(file).and_then(|text| {
+            if check {
+                check_format(&text, &opts)
+                    .map(|diffs| {
+                        for diff in diffs.iter() {
+                            println!("{}: {}", file, diff);
+                        }
+                        !diffs.is_empty()
+                    })
+                    .map_err(|e| format!("{}: {}", file, e))
+            } else {
+                format_yang(&text, &opts)
+                    .map(|formatted| print!("{}", formatted))
+                    .map(|_| false)
+                    .map_err(|e| format!("{}: {}", file, e))
+            }
+        });
+        match result {
+            Ok(false) => {}
+            Ok(true) => status = status.max(1),
+            Err(e) => {
+                eprintln!("yang-fmt: {}", e);
+                status = 2;
+            }
+        }
+    }
+    exit(status);
+}
@@ -0,0 +1,757 @@
+use crate::data::restriction::restriction_intervals;
+use crate::data::*;
+use crate::modules::*;
+use std::fmt;
+use std::rc::Rc;
+use thiserror::Error;
+
+// RFC7950 11.  Updating a Module
+//     As experience is gained with a module, it may be desirable to revise
+//     that module.  However, changes to published modules are not allowed
+//     if they have any potential to cause interoperability problems
+//     between a client using an original specification and a server using
+//     an updated specification.
+//
+// Backwards-incompatible change found between the old and the new revision
+// of a module.  Values are the old one and the new one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompatChange {
+    NamespaceChanged(String, String),
+    RevisionNotAdded(Option<String>, Option<String>),
+    FeatureRemoved(String),
+    IdentityRemoved(String),
+    NodeRemoved,
+    KindChanged(SchemaKind, SchemaKind),
+    ConfigChanged(bool, bool),
+    PresenceChanged(bool, bool),
+    MandatoryAdded,
+    MandatoryNodeAdded,
+    KeyChanged(Vec<String>, Vec<String>),
+    MinElementsIncreased(Option<u64>, Option<u64>),
+    MaxElementsDecreased(Option<u64>, Option<u64>),
+    DefaultChanged(Vec<String>, Vec<String>),
+    MustAdded(String),
+    WhenAdded(String),
+    UniqueAdded(String),
+    TypeChanged(String, String),
+    RangeNarrowed(Option<String>, Option<String>),
+    LengthNarrowed(Option<String>, Option<String>),
+    PatternAdded(String),
+    EnumRemoved(String),
+    EnumValueChanged(String, i64, i64),
+    BitRemoved(String),
+    BitPositionChanged(String, u32, u32),
+    BaseChanged(Vec<String>, Vec<String>),
+    PathChanged(Option<String>, Option<String>),
+}
+
+impl CompatChange {
+    // Stable identifier of the change for the report.
+    pub fn code(&self) -> &'static str {
+        match self {
+            CompatChange::NamespaceChanged(..) => "namespace-changed",
+            CompatChange::RevisionNotAdded(..) => "revision-not-added",
+            CompatChange::FeatureRemoved(_) => "feature-removed",
+            CompatChange::IdentityRemoved(_) => "identity-removed",
+            CompatChange::NodeRemoved => "node-removed",
+            CompatChange::KindChanged(..) => "kind-changed",
+            CompatChange::ConfigChanged(..) => "config-changed",
+            CompatChange::PresenceChanged(..) => "presence-changed",
+            CompatChange::MandatoryAdded => "mandatory-added",
+            CompatChange::MandatoryNodeAdded => "mandatory-node-added",
+            CompatChange::KeyChanged(..) => "key-changed",
+            CompatChange::MinElementsIncreased(..) => "min-elements-increased",
+            CompatChange::MaxElementsDecreased(..) => "max-elements-decreased",
+            CompatChange::DefaultChanged(..) => "default-changed",
+            CompatChange::MustAdded(_) => "must-added",
+            CompatChange::WhenAdded(_) => "when-added",
+            CompatChange::UniqueAdded(_) => "unique-added",
+            CompatChange::TypeChanged(..) => "type-changed",
+            CompatChange::RangeNarrowed(..) => "range-narrowed",
+            CompatChange::LengthNarrowed(..) => "length-narrowed",
+            CompatChange::PatternAdded(_) => "pattern-added",
+            CompatChange::EnumRemoved(_) => "enum-removed",
+            CompatChange::EnumValueChanged(..) => "enum-value-changed",
+            CompatChange::BitRemoved(_) => "bit-removed",
+            CompatChange::BitPositionChanged(..) => "bit-position-changed",
+            CompatChange::BaseChanged(..) => "base-changed",
+            CompatChange::PathChanged(..) => "path-changed",
+        }
+    }
+}
+
+fn kind_name(kind: SchemaKind) -> &'static str {
+    match kind {
+        SchemaKind::Container => "container",
+        SchemaKind::List => "list",
+        SchemaKind::Leaf => "leaf",
+        SchemaKind::LeafList => "leaf-list",
+        SchemaKind::Anydata => "anydata",
+    }
+}
+
+fn or_none<T: fmt::Display>(v: &Option<T>) -> String {
+    match v {
+        Some(v) => format!("\"{}\"", v),
+        None => String::from("none"),
+    }
+}
+
+impl fmt::Display for CompatChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompatChange::NamespaceChanged(old, new) => {
+                write!(f, "namespace changed from \"{}\" to \"{}\"", old, new)
+            }
+            CompatChange::RevisionNotAdded(old, new) => write!(
+                f,
+                "revision {} is not newer than {}",
+                or_none(new),
+                or_none(old)
+            ),
+            CompatChange::FeatureRemoved(name) => write!(f, "feature \"{}\" removed", name),
+            CompatChange::IdentityRemoved(name) => write!(f, "identity \"{}\" removed", name),
+            CompatChange::NodeRemoved => write!(f, "node removed"),
+            CompatChange::KindChanged(old, new) => {
+                write!(f, "{} changed to {}", kind_name(*old), kind_name(*new))
+            }
+            CompatChange::ConfigChanged(old, new) => {
+                write!(f, "config changed from {} to {}", old, new)
+            }
+            CompatChange::PresenceChanged(old, new) => {
+                write!(f, "presence changed from {} to {}", old, new)
+            }
+            CompatChange::MandatoryAdded => write!(f, "node became mandatory"),
+            CompatChange::MandatoryNodeAdded => write!(f, "mandatory node added"),
+            CompatChange::KeyChanged(old, new) => write!(
+                f,
+                "key changed from \"{}\" to \"{}\"",
+                old.join(" "),
+                new.join(" ")
+            ),
+            CompatChange::MinElementsIncreased(old, new) => write!(
+                f,
+                "min-elements increased from {} to {}",
+                or_none(old),
+                or_none(new)
+            ),
+            CompatChange::MaxElementsDecreased(old, new) => write!(
+                f,
+                "max-elements decreased from {} to {}",
+                or_none(old),
+                or_none(new)
+            ),
+            CompatChange::DefaultChanged(old, new) => write!(
+                f,
+                "default changed from \"{}\" to \"{}\"",
+                old.join(" "),
+                new.join(" ")
+            ),
+            CompatChange::MustAdded(must) => write!(f, "must \"{}\" added", must),
+            CompatChange::WhenAdded(when) => write!(f, "when \"{}\" added", when),
+            CompatChange::UniqueAdded(unique) => write!(f, "unique \"{}\" added", unique),
+            CompatChange::TypeChanged(old, new) => {
+                write!(f, "type changed from \"{}\" to \"{}\"", old, new)
+            }
+            CompatChange::RangeNarrowed(old, new) => write!(
+                f,
+                "range narrowed from {} to {}",
+                or_none(old),
+                or_none(new)
+            ),
+            CompatChange::LengthNarrowed(old, new) => write!(
+                f,
+                "length narrowed from {} to {}",
+                or_none(old),
+                or_none(new)
+            ),
+            CompatChange::PatternAdded(pattern) => write!(f, "pattern \"{}\" added", pattern),
+            CompatChange::EnumRemoved(name) => write!(f, "enum \"{}\" removed", name),
+            CompatChange::EnumValueChanged(name, old, new) => write!(
+                f,
+                "value of enum \"{}\" changed from {} to {}",
+                name, old, new
+            ),
+            CompatChange::BitRemoved(name) => write!(f, "bit \"{}\" removed", name),
+            CompatChange::BitPositionChanged(name, old, new) => write!(
+                f,
+                "position of bit \"{}\" changed from {} to {}",
+                name, old, new
+            ),
+            CompatChange::BaseChanged(old, new) => write!(
+                f,
+                "base changed from \"{}\" to \"{}\"",
+                old.join(" "),
+                new.join(" ")
+            ),
+            CompatChange::PathChanged(old, new) => write!(
+                f,
+                "leafref path changed from {} to {}",
+                or_none(old),
+                or_none(new)
+            ),
+        }
+    }
+}
+
+// Change at the schema node path such as "/ietf-dhcp:dhcp/server".  The
+// path is "/" for the changes of the module statements.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct CompatIssue {
+    pub path: String,
+    pub change: CompatChange,
+}
+
+impl fmt::Display for CompatIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.change)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct CompatReport {
+    pub module: String,
+    pub old_revision: Option<String>,
+    pub new_revision: Option<String>,
+    #[new(default)]
+    pub issues: Vec<CompatIssue>,
+}
+
+impl CompatReport {
+    pub fn is_compatible(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    // JSON form of the report for CI tools.
+    pub fn to_json(&self) -> JsonValue {
+        let string = |s: &str| JsonValue::String(s.to_owned());
+        let revision = |r: &Option<String>| match r {
+            Some(r) => string(r),
+            None => JsonValue::Null,
+        };
+        let issues = self.issues.iter().map(|issue| {
+            JsonValue::Object(vec![
+                (String::from("path"), string(&issue.path)),
+                (String::from("change"), string(issue.change.code())),
+                (String::from("message"), string(&issue.change.to_string())),
+            ])
+        });
+        JsonValue::Object(vec![
+            (String::from("module"), string(&self.module)),
+            (String::from("old-revision"), revision(&self.old_revision)),
+            (String::from("new-revision"), revision(&self.new_revision)),
+            (
+                String::from("compatible"),
+                JsonValue::Bool(self.is_compatible()),
+            ),
+            (String::from("issues"), JsonValue::Array(issues.collect())),
+        ])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CompatError {
+    #[error("module \"{0}\" not found")]
+    ModuleNotFound(String),
+    #[error(transparent)]
+    Schema(#[from] SchemaError),
+}
+
+pub(crate) fn implemented_module<'a>(
+    ms: &'a Modules,
+    name: &str,
+) -> Result<&'a Module, CompatError> {
+    ms.implemented()
+        .into_iter()
+        .find(|m| m.name == name)
+        .ok_or_else(|| CompatError::ModuleNotFound(name.to_owned()))
+}
+
+// RFC7950 3.  Terminology
+//     mandatory node: A mandatory node is one of:
+//     *  A leaf, choice, anydata, or anyxml node with a "mandatory"
+//        statement with the value "true".
+//     *  A list or leaf-list node with a "min-elements" statement with a
+//        value greater than zero.
+//     *  A container node without a "presence" statement and that has at
+//        least one mandatory node as a child.
+//
+// The nodes in the cases are children of the choice, not of the container.
+fn is_mandatory(node: &SchemaNode) -> bool {
+    match node.kind {
+        SchemaKind::Leaf | SchemaKind::Anydata => node.mandatory,
+        SchemaKind::List | SchemaKind::LeafList => node.min_elements.unwrap_or(0) > 0,
+        SchemaKind::Container => {
+            !node.presence
+                && (node.choices.iter().any(|c| c.mandatory)
+                    || node
+                        .children
+                        .iter()
+                        .any(|c| !in_case(&node.choices, c) && is_mandatory(c)))
+        }
+    }
+}
+
+// Whether "node" is a data node of a case of "choices".
+fn in_case(choices: &[SchemaChoice], node: &Rc<SchemaNode>) -> bool {
+    choices.iter().any(|c| {
+        c.cases
+            .iter()
+            .any(|s| s.nodes.iter().any(|n| Rc::ptr_eq(n, node)))
+    })
+}
+
+// The module of the choice, the module of the data nodes in its cases.
+fn choice_module(choice: &SchemaChoice) -> Option<&str> {
+    choice
+        .cases
+        .iter()
+        .flat_map(|s| s.nodes.iter())
+        .map(|n| n.module.as_str())
+        .next()
+}
+
+fn node_path(parent: &str, parent_module: Option<&str>, node: &SchemaNode) -> String {
+    if parent_module == Some(node.module.as_str()) {
+        format!("{}/{}", parent, node.name)
+    } else {
+        format!("{}/{}:{}", parent, node.module, node.name)
+    }
+}
+
+// Whether every interval of "old" is in one of the merged intervals of
+// "new".
+fn intervals_contained(old: &[(i128, i128)], new: &[(i128, i128)]) -> bool {
+    let mut sorted = new.to_vec();
+    sorted.sort();
+    let mut merged: Vec<(i128, i128)> = vec![];
+    for (start, end) in sorted {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    old.iter().all(|(start, end)| {
+        merged
+            .iter()
+            .any(|(s, e)| s <= start && end <= e && start <= end)
+    })
+}
+
+// Whether the restriction of the new type accepts all of the values the
+// old one accepts.  Unparsable restrictions are compared as text.
+fn restriction_kept(old: &TypeNode, new: &TypeNode, length: bool) -> bool {
+    let (o, n) = if length {
+        (old.length.as_deref(), new.length.as_deref())
+    } else {
+        (old.range.as_deref(), new.range.as_deref())
+    };
+    if o == n {
+        return true;
+    }
+    match (
+        restriction_intervals(old, o, length),
+        restriction_intervals(new, n, length),
+    ) {
+        (Some(o), Some(n)) => intervals_contained(&o, &n),
+        _ => false,
+    }
+}
+
+// RFC7950 9.6.4.2.  The "value" Statement
+//     If a value is not specified, then one will be automatically
+//     assigned.  If the "enum" substatement is the first one defined, the
+//     assigned value is zero (0); otherwise, the assigned value is one
+//     greater than the current highest enum value.
+fn enum_values(typ: &TypeNode) -> Vec<(&str, i64)> {
+    let mut values = vec![];
+    let mut highest: Option<i64> = None;
+    for e in typ.enums.iter() {
+        let value = e.value().unwrap_or_else(|| highest.map_or(0, |h| h + 1));
+        highest = Some(highest.map_or(value, |h| h.max(value)));
+        values.push((e.name.as_str(), value));
+    }
+    values
+}
+
+// RFC7950 9.7.4.2.  The "position" Statement
+//     If a bit position is not specified, then one will be automatically
+//     assigned.
+fn bit_positions(typ: &TypeNode) -> Vec<(&str, u32)> {
+    let mut positions = vec![];
+    let mut highest: Option<u32> = None;
+    for b in typ.bits.iter() {
+        let position = b.position.unwrap_or_else(|| highest.map_or(0, |h| h + 1));
+        highest = Some(highest.map_or(position, |h| h.max(position)));
+        positions.push((b.name.as_str(), position));
+    }
+    positions
+}
+
+struct Checker<'a> {
+    module: &'a str,
+    issues: Vec<CompatIssue>,
+}
+
+impl<'a> Checker<'a> {
+    fn push(&mut self, path: &str, change: CompatChange) {
+        self.issues.push(CompatIssue::new(path.to_owned(), change));
+    }
+
+    // RFC7950 11.  Updating a Module
+    //     A "type" statement may be replaced with another "type" statement
+    //     that does not change the syntax or semantics of the type.
+    fn check_type(&mut self, path: &str, old: &TypeNode, new: &TypeNode) {
+        if old.kind != new.kind || old.fraction_digits != new.fraction_digits {
+            self.push(
+                path,
+                CompatChange::TypeChanged(old.name.clone(), new.name.clone()),
+            );
+            return;
+        }
+        if !restriction_kept(old, new, false) {
+            self.push(
+                path,
+                CompatChange::RangeNarrowed(old.range.clone(), new.range.clone()),
+            );
+        }
+        if !restriction_kept(old, new, true) {
+            self.push(
+                path,
+                CompatChange::LengthNarrowed(old.length.clone(), new.length.clone()),
+            );
+        }
+        for pattern in new.patterns.iter() {
+            if !old.patterns.contains(pattern) {
+                self.push(path, CompatChange::PatternAdded(pattern.to_string()));
+            }
+        }
+        let new_enums = enum_values(new);
+        for (name, value) in enum_values(old) {
+            match new_enums.iter().find(|(n, _)| *n == name) {
+                None => self.push(path, CompatChange::EnumRemoved(name.to_owned())),
+                Some((_, v)) if *v != value => self.push(
+                    path,
+                    CompatChange::EnumValueChanged(name.to_owned(), value, *v),
+                ),
+                _ => {}
+            }
+        }
+        let new_bits = bit_positions(new);
+        for (name, position) in bit_positions(old) {
+            match new_bits.iter().find(|(n, _)| *n == name) {
+                None => self.push(path, CompatChange::BitRemoved(name.to_owned())),
+                Some((_, p)) if *p != position => self.push(
+                    path,
+                    CompatChange::BitPositionChanged(name.to_owned(), position, *p),
+                ),
+                _ => {}
+            }
+        }
+        if old.bases != new.bases {
+            self.push(
+                path,
+                CompatChange::BaseChanged(old.bases.clone(), new.bases.clone()),
+            );
+        }
+        if old.path != new.path {
+            self.push(
+                path,
+                CompatChange::PathChanged(old.path.clone(), new.path.clone()),
+            );
+        }
+        // Member types may be added to the end of the union.
+        for (i, member) in old.union.iter().enumerate() {
+            match new.union.get(i) {
+                Some(m) => self.check_type(path, member, m),
+                None => self.push(
+                    path,
+                    CompatChange::TypeChanged(old.name.clone(), new.name.clone()),
+                ),
+            }
+        }
+    }
+
+    // "config_changed" is whether the config of the parent is changed, the
+    // inherited change is reported only at the parent.
+    fn check_node(&mut self, path: &str, old: &SchemaNode, new: &SchemaNode, config_changed: bool) {
+        if old.kind != new.kind {
+            self.push(path, CompatChange::KindChanged(old.kind, new.kind));
+            return;
+        }
+        if old.config != new.config && !config_changed {
+            self.push(path, CompatChange::ConfigChanged(old.config, new.config));
+        }
+        if old.presence != new.presence {
+            self.push(
+                path,
+                CompatChange::PresenceChanged(old.presence, new.presence),
+            );
+        }
+        if !old.mandatory && new.mandatory {
+            self.push(path, CompatChange::MandatoryAdded);
+        }
+        if old.keys != new.keys {
+            self.push(
+                path,
+                CompatChange::KeyChanged(old.keys.clone(), new.keys.clone()),
+            );
+        }
+        if new.min_elements.unwrap_or(0) > old.min_elements.unwrap_or(0) {
+            self.push(
+                path,
+                CompatChange::MinElementsIncreased(old.min_elements, new.min_elements),
+            );
+        }
+        if new.max_elements.unwrap_or(u64::MAX) < old.max_elements.unwrap_or(u64::MAX) {
+            self.push(
+                path,
+                CompatChange::MaxElementsDecreased(old.max_elements, new.max_elements),
+            );
+        }
+        // RFC7950 11.  Updating a Module
+        //     A "default" statement may be added to a leaf that does not
+        //     have a default value (either directly or indirectly through
+        //     its type).
+        if !old.default.is_empty() && old.default != new.default {
+            self.push(
+                path,
+                CompatChange::DefaultChanged(old.default.clone(), new.default.clone()),
+            );
+        }
+        for must in new.must.iter() {
+            if !old.must.iter().any(|m| m.name == must.name) {
+                self.push(path, CompatChange::MustAdded(must.name.clone()));
+            }
+        }
+        if let Some(when) = &new.when {
+            if old.when.as_ref().is_none_or(|w| w.name != when.name) {
+                self.push(path, CompatChange::WhenAdded(when.name.clone()));
+            }
+        }
+        for unique in new.unique.iter() {
+            if !old.unique.contains(unique) {
+                self.push(path, CompatChange::UniqueAdded(unique.clone()));
+            }
+        }
+        if let (Some(o), Some(n)) = (&old.typ, &new.typ) {
+            self.check_type(path, o, n);
+        }
+        self.check_children(
+            path,
+            Some(&old.module),
+            (&old.children, &old.choices),
+            (&new.children, &new.choices),
+            old.config != new.config,
+        );
+    }
+
+    // RFC7950 11.  Updating a Module
+    //     New data definition statements may be added if they do not add
+    //     mandatory nodes (Section 3) to existing nodes or at the top level
+    //     in a module or submodule.
+    //
+    // The nodes added in the cases are checked by check_choices().
+    fn check_children(
+        &mut self,
+        parent: &str,
+        parent_module: Option<&str>,
+        (old, old_choices): (&[Rc<SchemaNode>], &[SchemaChoice]),
+        (new, new_choices): (&[Rc<SchemaNode>], &[SchemaChoice]),
+        config_changed: bool,
+    ) {
+        for o in old.iter() {
+            let path = node_path(parent, parent_module, o);
+            match find_schema(new, Some(&o.module), &o.name) {
+                Some(n) => self.check_node(&path, o, n, config_changed),
+                None => self.push(&path, CompatChange::NodeRemoved),
+            }
+        }
+        for n in new.iter() {
+            if find_schema(old, Some(&n.module), &n.name).is_none()
+                && !in_case(new_choices, n)
+                && is_mandatory(n)
+            {
+                let path = node_path(parent, parent_module, n);
+                self.push(&path, CompatChange::MandatoryNodeAdded);
+            }
+        }
+        self.check_choices(parent, parent_module, old_choices, new_choices);
+    }
+
+    // RFC7950 3.  Terminology
+    //     mandatory node: A mandatory node is one of:
+    //     *  A leaf, choice, anydata, or anyxml node with a "mandatory"
+    //        statement with the value "true".
+    //
+    // Choices are looked up by the name.  Mandatory nodes may be added in
+    // new cases, not in the existing ones.
+    fn check_choices(
+        &mut self,
+        parent: &str,
+        parent_module: Option<&str>,
+        old: &[SchemaChoice],
+        new: &[SchemaChoice],
+    ) {
+        for n in new.iter() {
+            let path = match choice_module(n) {
+                Some(module) if parent_module != Some(module) => {
+                    format!("{}/{}:{}", parent, module, n.name)
+                }
+                _ => format!("{}/{}", parent, n.name),
+            };
+            let o = match old.iter().find(|o| o.name == n.name) {
+                Some(o) => o,
+                None => {
+                    if n.mandatory {
+                        self.push(&path, CompatChange::MandatoryNodeAdded);
+                    }
+                    continue;
+                }
+            };
+            if !o.mandatory && n.mandatory {
+                self.push(&path, CompatChange::MandatoryAdded);
+            }
+            for case in n.cases.iter() {
+                let old_case = match o.cases.iter().find(|c| c.name == case.name) {
+                    Some(old_case) => old_case,
+                    None => continue,
+                };
+                for node in case.nodes.iter() {
+                    if find_schema(&old_case.nodes, Some(&node.module), &node.name).is_none()
+                        && !in_case(&case.choices, node)
+                        && is_mandatory(node)
+                    {
+                        let path = node_path(parent, parent_module, node);
+                        self.push(&path, CompatChange::MandatoryNodeAdded);
+                    }
+                }
+                self.check_choices(parent, parent_module, &old_case.choices, &case.choices);
+            }
+        }
+    }
+
+    fn check_schema(&mut self, old: &Schema, new: &Schema) {
+        let module = self.module;
+        let top = |schema: &Schema| -> Vec<Rc<SchemaNode>> {
+            schema
+                .nodes
+                .iter()
+                .filter(|n| n.module == module)
+                .cloned()
+                .collect()
+        };
+        let top_choices = |schema: &Schema| -> Vec<SchemaChoice> {
+            schema
+                .choices
+                .iter()
+                .filter(|c| choice_module(c) == Some(module))
+                .cloned()
+                .collect()
+        };
+        self.check_children(
+            "",
+            None,
+            (&top(old), &top_choices(old)),
+            (&top(new), &top_choices(new)),
+            false,
+        );
+    }
+}
+
+impl Modules {
+    // Check the backwards compatibility of the module "name" in "new"
+    // against the one in "self", the old revision, by the rules of RFC7950
+    // section 11.  The implemented modules of both are compiled and the
+    // schema nodes of the module are compared.
+    pub fn check_update<'a>(
+        &'a self,
+        new: &'a Modules,
+        name: &str,
+    ) -> Result<CompatReport, CompatError> {
+        let old_module = implemented_module(self, name)?;
+        let new_module = implemented_module(new, name)?;
+        let old_revision = old_module.revision().map(String::from);
+        let new_revision = new_module.revision().map(String::from);
+        let mut checker = Checker {
+            module: name,
+            issues: vec![],
+        };
+
+        if old_module.namespace != new_module.namespace {
+            checker.push(
+                "/",
+                CompatChange::NamespaceChanged(
+                    old_module.namespace.clone(),
+                    new_module.namespace.clone(),
+                ),
+            );
+        }
+        // RFC7950 11.  Updating a Module
+        //     For any published change, a new "revision" statement
+        //     (Section 7.1.9) MUST be included in front of the existing
+        //     "revision" statements.
+        if old_module.revision_date() >= new_module.revision_date() {
+            checker.push(
+                "/",
+                CompatChange::RevisionNotAdded(old_revision.clone(), new_revision.clone()),
+            );
+        }
+        let mut features: Vec<&String> = old_module.features.keys().collect();
+        features.sort();
+        for feature in features {
+            if !new_module.features.contains_key(feature) {
+                checker.push("/", CompatChange::FeatureRemoved(feature.clone()));
+            }
+        }
+        let mut identities: Vec<&String> = old_module.identities.keys().collect();
+        identities.sort();
+        for identity in identities {
+            if !new_module.identities.contains_key(identity) {
+                checker.push("/", CompatChange::IdentityRemoved(identity.clone()));
+            }
+        }
+
+        checker.check_schema(&*self.schema()?, &*new.schema()?);
+
+        let mut report = CompatReport::new(name.to_owned(), old_revision, new_revision);
+        report.issues = checker.issues;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Node;
+
+    #[test]
+    fn intervals_contained_test() {
+        assert!(intervals_contained(&[(1, 10)], &[(0, 5), (6, 20)]));
+        assert!(intervals_contained(&[(1, 1), (5, 5)], &[(1, 5)]));
+        assert!(!intervals_contained(&[(1, 10)], &[(1, 5), (7, 10)]));
+        assert!(!intervals_contained(&[(0, 10)], &[(1, 10)]));
+    }
+
+    #[test]
+    fn enum_values_test() {
+        let mut typ = TypeNode::new(TypeKind::Yenumeration);
+        for name in &["a", "b"] {
+            typ.enums.push(EnumNode {
+                name: name.to_string(),
+                nodes: (vec![],),
+            });
+        }
+        typ.enums.insert(
+            1,
+            EnumNode {
+                name: String::from("x"),
+                nodes: (vec![Node::ValueNode(Box::new(ValueNode {
+                    name: String::from("10"),
+                    nodes: (),
+                }))],),
+            },
+        );
+        assert_eq!(enum_values(&typ), vec![("a", 0), ("x", 10), ("b", 11)]);
+    }
+}
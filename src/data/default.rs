@@ -0,0 +1,260 @@
+use crate::data::*;
+use crate::xpath::evaluate_bool;
+use std::rc::Rc;
+
+impl SchemaNode {
+    // Default values of leaf or leaf-list in the value space.  The defaults
+    // are checked when the schema is compiled.
+    pub fn default_values(&self, schema: &Schema) -> Vec<LeafValue> {
+        self.default
+            .iter()
+            .filter_map(|d| LeafValue::parse(schema, self, d).ok())
+            .collect()
+    }
+}
+
+fn child_schemas(tree: &DataTree, index: &[usize]) -> Vec<Rc<SchemaNode>> {
+    match tree.node(index) {
+        Some(node) => node.schema().children.clone(),
+        None => tree.schema.nodes.clone(),
+    }
+}
+
+fn child_choices(tree: &DataTree, index: &[usize]) -> Vec<SchemaChoice> {
+    match tree.node(index) {
+        Some(node) => node.schema().choices.clone(),
+        None => tree.schema.choices.clone(),
+    }
+}
+
+fn children<'a>(tree: &'a DataTree, index: &[usize]) -> &'a [DataNode] {
+    match tree.node(index) {
+        Some(node) => node.children(),
+        None => &tree.nodes,
+    }
+}
+
+fn children_mut<'a>(tree: &'a mut DataTree, index: &[usize]) -> Option<&'a mut Vec<DataNode>> {
+    let mut nodes = &mut tree.nodes;
+    for i in index.iter() {
+        nodes = nodes.get_mut(*i)?.children_mut()?;
+    }
+    Some(nodes)
+}
+
+// RFC7950 7.21.5.  The "when" Statement
+//     If the "when" statement is a child of any other data definition
+//     statement, the accessible tree is tentatively altered during the
+//     processing of the XPath expression by replacing all instances of the
+//     data node for which the "when" statement is defined with a single
+//     dummy node with the same name, but with no value and no children.
+//
+// The first instance of "schema" under "index" is the context node.
+fn when_true(tree: &DataTree, index: &[usize], schema: &Rc<SchemaNode>) -> bool {
+    let xpath = match schema.when.as_ref().and_then(|w| w.xpath.as_ref()) {
+        Some(xpath) => xpath,
+        None => return true,
+    };
+    let pos = match children(tree, index)
+        .iter()
+        .position(|n| Rc::ptr_eq(n.schema(), schema))
+    {
+        Some(pos) => pos,
+        None => return false,
+    };
+    let mut node = index.to_vec();
+    node.push(pos);
+    evaluate_bool(tree, xpath, &node).unwrap_or(false)
+}
+
+// RFC7950 7.9.3.  The choice's "default" Statement
+//     The default values for nodes under the default case are used if none
+//     of the nodes under any of the case branches exists.
+//     ...
+//     The default values for nodes under a case are only used if one of
+//     the nodes under that case is present.
+//
+// Fill defaults in the children of the node at "index".
+fn fill(tree: &mut DataTree, index: &mut Vec<usize>) {
+    let schemas = child_schemas(tree, index);
+    let inactive = inactive_case_nodes(&child_choices(tree, index), children(tree, index), true);
+    let mut created = vec![];
+    for schema in schemas.iter() {
+        if inactive.iter().any(|s| Rc::ptr_eq(s, schema))
+            || children(tree, index)
+                .iter()
+                .any(|n| Rc::ptr_eq(n.schema(), schema))
+        {
+            continue;
+        }
+        let values = schema.default_values(&tree.schema);
+        let nodes: Vec<DataNode> = match schema.kind {
+            SchemaKind::Leaf => values
+                .into_iter()
+                .take(1)
+                .map(|v| DataNode::new(schema.clone(), DataContent::Leaf(v)))
+                .collect(),
+            SchemaKind::LeafList => values
+                .into_iter()
+                .map(|v| DataNode::new(schema.clone(), DataContent::LeafList(v)))
+                .collect(),
+            // Non-presence container is created to hold the defaults of the
+            // descendants and removed if it has none.
+            SchemaKind::Container if !schema.presence => vec![DataNode::new(
+                schema.clone(),
+                DataContent::Container(vec![]),
+            )],
+            _ => vec![],
+        };
+        if nodes.is_empty() {
+            continue;
+        }
+        if let Some(siblings) = children_mut(tree, index) {
+            for node in nodes {
+                insert_ordered(siblings, &schemas, node);
+            }
+        }
+        if !when_true(tree, index, schema) {
+            if let Some(siblings) = children_mut(tree, index) {
+                siblings.retain(|n| !Rc::ptr_eq(n.schema(), schema));
+            }
+        } else if schema.kind == SchemaKind::Container {
+            created.push(schema.clone());
+        }
+    }
+
+    for i in 0..children(tree, index).len() {
+        index.push(i);
+        fill(tree, index);
+        index.pop();
+    }
+
+    if let Some(siblings) = children_mut(tree, index) {
+        siblings.retain(|n| {
+            !(n.children().is_empty() && created.iter().any(|s| Rc::ptr_eq(s, n.schema())))
+        });
+    }
+}
+
+// Canonical values of the instances, sorted unless the order is
+// significant.
+fn instance_values(schema: &SchemaNode, values: Vec<&LeafValue>) -> Vec<String> {
+    let mut values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    if !schema.ordered_by_user {
+        values.sort();
+    }
+    values
+}
+
+// Whether the instances of leaf or leaf-list "snode" in "nodes" are the
+// same as its default values.
+fn is_default(schema: &Schema, nodes: &[DataNode], snode: &Rc<SchemaNode>) -> bool {
+    if snode.default.is_empty() {
+        return false;
+    }
+    let defaults = snode.default_values(schema);
+    let instances: Vec<&LeafValue> = nodes
+        .iter()
+        .filter(|n| Rc::ptr_eq(n.schema(), snode))
+        .filter_map(|n| n.value())
+        .collect();
+    match snode.kind {
+        SchemaKind::Leaf => instances.len() == 1 && defaults.first() == Some(instances[0]),
+        SchemaKind::LeafList => {
+            instance_values(snode, instances) == instance_values(snode, defaults.iter().collect())
+        }
+        _ => false,
+    }
+}
+
+// Nodes of the cases other than the default case are kept since the
+// default case would become active without them.
+fn trim(
+    schema: &Schema,
+    nodes: &mut Vec<DataNode>,
+    schemas: &[Rc<SchemaNode>],
+    choices: &[SchemaChoice],
+) {
+    let other_cases = inactive_case_nodes(choices, &[], true);
+    for snode in schemas.iter() {
+        if other_cases.iter().any(|s| Rc::ptr_eq(s, snode)) {
+            continue;
+        }
+        if is_default(schema, nodes, snode) {
+            nodes.retain(|n| !Rc::ptr_eq(n.schema(), snode));
+        }
+    }
+
+    nodes.retain_mut(|node| {
+        let snode = node.schema().clone();
+        let had_children = !node.children().is_empty();
+        match node.children_mut() {
+            Some(children) => trim(schema, children, &snode.children, &snode.choices),
+            None => return true,
+        }
+        // Non-presence container emptied by trimming has no meaning.
+        !(snode.kind == SchemaKind::Container
+            && !snode.presence
+            && had_children
+            && node.children().is_empty())
+    });
+}
+
+const WITH_DEFAULTS_MODULE: &str = "ietf-netconf-with-defaults";
+
+fn tag(schema: &Schema, nodes: &mut [DataNode], schemas: &[Rc<SchemaNode>]) {
+    for snode in schemas.iter() {
+        if !is_default(schema, nodes, snode) {
+            continue;
+        }
+        for node in nodes.iter_mut().filter(|n| Rc::ptr_eq(n.schema(), snode)) {
+            if node.annotation(WITH_DEFAULTS_MODULE, "default").is_none() {
+                node.annotations.push(Annotation::new(
+                    WITH_DEFAULTS_MODULE.to_owned(),
+                    String::from("default"),
+                    String::from("true"),
+                ));
+            }
+        }
+    }
+    for node in nodes.iter_mut() {
+        let snode = node.schema().clone();
+        if let Some(children) = node.children_mut() {
+            tag(schema, children, &snode.children);
+        }
+    }
+}
+
+impl DataTree {
+    // RFC7950 7.6.1.  The Leaf's Default Value
+    //     The default value of a leaf is the value that the server uses if
+    //     the leaf does not exist in the data tree.
+    //
+    // Create the leaves and leaf-lists which are not in the tree with their
+    // default values.  Defaults are filled under the existing nodes and the
+    // non-presence containers, and are not filled when "when" condition is
+    // false.  Of the choices, defaults are filled in the active case, or in
+    // the default case when no case is active.
+    pub fn fill_defaults(&mut self) {
+        fill(self, &mut vec![]);
+    }
+
+    // Remove the leaves and leaf-lists whose values are the same as their
+    // default values, and the non-presence containers which become empty.
+    pub fn trim_defaults(&mut self) {
+        let schema = self.schema.clone();
+        trim(&schema, &mut self.nodes, &schema.nodes, &schema.choices);
+    }
+
+    // RFC6243 3.4.  'report-all-tagged' Retrieval Mode
+    //     In this mode, the server MUST return all data nodes, the same as
+    //     the 'report-all' mode, except a data node that is set to its
+    //     schema default value MUST be tagged with a "default" attribute.
+    //
+    // Tag the leaves and leaf-lists whose values are the same as their
+    // default values with "ietf-netconf-with-defaults:default" annotation.
+    pub fn tag_defaults(&mut self) {
+        let schema = self.schema.clone();
+        tag(&schema, &mut self.nodes, &schema.nodes);
+    }
+}
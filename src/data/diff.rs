@@ -0,0 +1,302 @@
+use crate::data::*;
+use std::rc::Rc;
+
+// Change of a data node from the source tree to the target tree.  "after"
+// is the instance path of the preceding instance of user ordered list
+// entry or leaf-list value, None for the first one.  It is None for the
+// other nodes which are placed in schema order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffOp {
+    // Node only in the target tree, with its descendants.
+    Create(DataNode, Option<String>),
+    // Node only in the source tree, with its descendants.
+    Delete(DataNode),
+    // Leaf or anydata whose content differs, the source and target nodes.
+    Replace(DataNode, DataNode),
+    // User ordered instance placed at a different position.
+    Move(Option<String>),
+}
+
+// Change at the instance path in the JSON form.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct DiffEntry {
+    pub path: String,
+    pub op: DiffOp,
+}
+
+// Nodes of the same schema node, and of the same keys for list entries or
+// the same value for leaf-lists.
+pub(crate) fn same_instance(a: &DataNode, b: &DataNode) -> bool {
+    if !same_schema(a.schema(), b.schema()) {
+        return false;
+    }
+    match (&a.content, &b.content) {
+        (DataContent::ListEntry(_), DataContent::ListEntry(_)) => a.key_values() == b.key_values(),
+        (DataContent::LeafList(x), DataContent::LeafList(y)) => x == y,
+        _ => true,
+    }
+}
+
+fn is_user_ordered(node: &DataNode) -> bool {
+    node.schema().ordered_by_user
+        && matches!(
+            node.content,
+            DataContent::ListEntry(_) | DataContent::LeafList(_)
+        )
+}
+
+// Order of the instances of a user ordered node while the target order is
+// built from the source order.  "index" is the position of the next
+// instance and "prev" is the path of the previous instance in the target.
+struct Order {
+    schema: Rc<SchemaNode>,
+    current: Vec<String>,
+    index: usize,
+    prev: Option<String>,
+}
+
+fn diff_nodes(
+    parent: &str,
+    parent_module: Option<&str>,
+    source: &[DataNode],
+    target: &[DataNode],
+    diff: &mut Vec<DiffEntry>,
+) {
+    for node in source.iter() {
+        if !target.iter().any(|n| same_instance(n, node)) {
+            let path = instance_path(parent, parent_module, node);
+            diff.push(DiffEntry::new(path, DiffOp::Delete(node.clone())));
+        }
+    }
+
+    let mut orders: Vec<Order> = vec![];
+    for node in target.iter() {
+        let path = instance_path(parent, parent_module, node);
+        let old = source.iter().find(|n| same_instance(n, node));
+
+        if is_user_ordered(node) {
+            let i = match orders
+                .iter()
+                .position(|o| same_schema(&o.schema, node.schema()))
+            {
+                Some(i) => i,
+                None => {
+                    let current = source
+                        .iter()
+                        .filter(|n| same_schema(n.schema(), node.schema()))
+                        .filter(|n| target.iter().any(|t| same_instance(t, n)))
+                        .map(|n| instance_path(parent, parent_module, n))
+                        .collect();
+                    orders.push(Order {
+                        schema: node.schema().clone(),
+                        current,
+                        index: 0,
+                        prev: None,
+                    });
+                    orders.len() - 1
+                }
+            };
+            let order = &mut orders[i];
+            let after = order.prev.replace(path.clone());
+            let index = order.index;
+            order.index += 1;
+            match order.current.iter().position(|p| p == &path) {
+                None => {
+                    order.current.insert(index, path.clone());
+                    diff.push(DiffEntry::new(path, DiffOp::Create(node.clone(), after)));
+                    continue;
+                }
+                Some(pos) if pos != index => {
+                    let moved = order.current.remove(pos);
+                    order.current.insert(index, moved);
+                    diff.push(DiffEntry::new(path.clone(), DiffOp::Move(after)));
+                }
+                Some(_) => {}
+            }
+        }
+
+        let old = match old {
+            Some(old) => old,
+            None => {
+                diff.push(DiffEntry::new(path, DiffOp::Create(node.clone(), None)));
+                continue;
+            }
+        };
+        match &node.content {
+            DataContent::Container(children) | DataContent::ListEntry(children) => {
+                let module = Some(node.schema().module.as_str());
+                diff_nodes(&path, module, old.children(), children, diff);
+            }
+            DataContent::Leaf(_) | DataContent::Anydata(_) if old != node => {
+                diff.push(DiffEntry::new(
+                    path,
+                    DiffOp::Replace(old.clone(), node.clone()),
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+// Sibling nodes at the parent of a path, "module" is the module of the
+// parent and "schemas" are the schema nodes of the siblings.
+struct Siblings<'a> {
+    nodes: &'a mut Vec<DataNode>,
+    parent: String,
+    module: Option<String>,
+    schemas: Vec<Rc<SchemaNode>>,
+}
+
+impl<'a> Siblings<'a> {
+    fn of(tree: &'a mut DataTree, path: &str) -> Result<Self, DataError> {
+        let segments = data_path_parse(path)?;
+        let parent = data_path_string(&segments[..segments.len() - 1]);
+        if parent.is_empty() {
+            return Ok(Siblings {
+                schemas: tree.schema.nodes.clone(),
+                nodes: &mut tree.nodes,
+                parent,
+                module: None,
+            });
+        }
+        let node = tree.get_mut(&parent)?;
+        let schema = node.schema().clone();
+        match node.children_mut() {
+            Some(nodes) => Ok(Siblings {
+                nodes,
+                parent,
+                module: Some(schema.module.clone()),
+                schemas: schema.children.clone(),
+            }),
+            None => Err(DataError::InvalidPath(path.to_owned())),
+        }
+    }
+
+    fn position(&self, path: &str) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|n| instance_path(&self.parent, self.module.as_deref(), n) == path)
+    }
+
+    // Node of the diff bound to the schema nodes of the tree.
+    fn rebind(&self, node: &DataNode) -> Result<DataNode, DataError> {
+        rebind(&self.parent, self.module.as_deref(), node, &self.schemas)
+    }
+
+    // Insert user ordered instance after the instance "after", or before
+    // the first instance.  Other nodes are inserted in schema order.
+    fn insert(&mut self, after: Option<&str>, node: DataNode) -> Result<(), DataError> {
+        if !is_user_ordered(&node) {
+            insert_ordered(self.nodes, &self.schemas, node);
+            return Ok(());
+        }
+        let index = match after {
+            Some(after) => {
+                self.position(after)
+                    .ok_or_else(|| DataError::NotFound(after.to_owned()))?
+                    + 1
+            }
+            None => match self
+                .nodes
+                .iter()
+                .position(|n| same_schema(n.schema(), node.schema()))
+            {
+                Some(index) => index,
+                None => {
+                    insert_ordered(self.nodes, &self.schemas, node);
+                    return Ok(());
+                }
+            },
+        };
+        self.nodes.insert(index, node);
+        Ok(())
+    }
+}
+
+fn apply_entry(tree: &mut DataTree, entry: &DiffEntry) -> Result<(), DataError> {
+    let path = entry.path.as_str();
+    let mut siblings = Siblings::of(tree, path)?;
+    let found = siblings.position(path);
+    match (&entry.op, found) {
+        (DiffOp::Create(node, _), _) if siblings.nodes.iter().any(|n| same_instance(n, node)) => {
+            Err(DataError::Exists(path.to_owned()))
+        }
+        (DiffOp::Create(node, after), _) => {
+            let node = siblings.rebind(node)?;
+            siblings.insert(after.as_deref(), node)
+        }
+        (_, None) => Err(DataError::NotFound(path.to_owned())),
+        (DiffOp::Delete(_), Some(index)) => {
+            siblings.nodes.remove(index);
+            Ok(())
+        }
+        (DiffOp::Replace(_, node), Some(index)) => {
+            siblings.nodes[index] = siblings.rebind(node)?;
+            Ok(())
+        }
+        (DiffOp::Move(after), Some(index)) => {
+            let node = siblings.nodes.remove(index);
+            siblings.insert(after.as_deref(), node)
+        }
+    }
+}
+
+pub(crate) fn merge_nodes(
+    nodes: &mut Vec<DataNode>,
+    schemas: &[Rc<SchemaNode>],
+    other: &[DataNode],
+) {
+    for node in other.iter() {
+        match nodes.iter().position(|n| same_instance(n, node)) {
+            Some(index) => {
+                let base = &mut nodes[index];
+                let schema = base.schema().clone();
+                match base.children_mut() {
+                    Some(children) => merge_nodes(children, &schema.children, node.children()),
+                    None => *base = node.clone(),
+                }
+            }
+            None => insert_ordered(nodes, schemas, node.clone()),
+        }
+    }
+}
+
+impl DataTree {
+    // Changes which turn this tree into "target".  List entries are
+    // matched by the keys and leaf-list values by the values.  Deletions
+    // of the siblings come first, then the other changes in the target
+    // order.
+    pub fn diff(&self, target: &DataTree) -> Vec<DiffEntry> {
+        let mut diff = vec![];
+        diff_nodes("", None, &self.nodes, &target.nodes, &mut diff);
+        diff
+    }
+
+    // Apply the changes made by "diff".  The tree is not modified if any
+    // of the changes can't be applied.  Nodes of the diff may be built from
+    // a separately compiled schema of the same modules.
+    pub fn apply(&mut self, diff: &[DiffEntry]) -> Result<(), DataError> {
+        let mut tree = self.clone();
+        for entry in diff.iter() {
+            apply_entry(&mut tree, entry)?;
+        }
+        *self = tree;
+        Ok(())
+    }
+
+    // Merge "other" into this tree.  Leaves of "other" replace the ones of
+    // this tree, list entries and containers are merged and new user
+    // ordered instances are appended.  "other" may be built from a
+    // separately compiled schema, the tree is not modified if it has nodes
+    // which this tree's schema doesn't have.
+    pub fn merge(&mut self, other: &DataTree) -> Result<(), DataError> {
+        let schema = self.schema.clone();
+        let other = other
+            .nodes
+            .iter()
+            .map(|n| rebind("", None, n, &schema.nodes))
+            .collect::<Result<Vec<_>, _>>()?;
+        merge_nodes(&mut self.nodes, &schema.nodes, &other);
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use crate::data::*;
+use crate::xpath::{evaluate, Document, Value, XPath};
+
+// Nodes of the data tree are addressed by the index path from the root so
+// that the ordering of the handles is the document order.
+impl Document for DataTree {
+    type Node = Vec<usize>;
+
+    fn root(&self) -> Vec<usize> {
+        vec![]
+    }
+
+    fn parent(&self, node: &Vec<usize>) -> Option<Vec<usize>> {
+        let (_, parent) = node.split_last()?;
+        Some(parent.to_vec())
+    }
+
+    fn children(&self, node: &Vec<usize>) -> Vec<Vec<usize>> {
+        let len = if node.is_empty() {
+            self.nodes.len()
+        } else {
+            self.node(node).map(|n| n.children().len()).unwrap_or(0)
+        };
+        (0..len)
+            .map(|i| {
+                let mut child = node.clone();
+                child.push(i);
+                child
+            })
+            .collect()
+    }
+
+    fn name(&self, node: &Vec<usize>) -> Option<(&str, &str)> {
+        let schema = self.node(node)?.schema();
+        Some((&schema.module, &schema.name))
+    }
+
+    fn value(&self, node: &Vec<usize>) -> Option<String> {
+        self.node(node)?.value().map(|v| v.to_string())
+    }
+
+    fn namespace(&self, node: &Vec<usize>) -> Option<&str> {
+        Some(&self.node(node)?.schema().namespace)
+    }
+
+    fn deref(&self, node: &Vec<usize>) -> Vec<Vec<usize>> {
+        let n = match self.node(node) {
+            Some(n) => n,
+            None => return vec![],
+        };
+        if let Some(LeafValue::InstanceIdentifier(text)) = n.value() {
+            // Prefixes of instance-identifier in the canonical form are
+            // module names, module prefixes are accepted as well.
+            let mut xpath = match XPath::compile(text) {
+                Ok(xpath) => xpath,
+                Err(_) => return vec![],
+            };
+            for m in self.schema.modules.iter() {
+                xpath.prefixes.insert(m.prefix.clone(), m.name.clone());
+                xpath.prefixes.insert(m.name.clone(), m.name.clone());
+            }
+            return match evaluate(self, &xpath, &self.root()) {
+                Ok(Value::Nodes(nodes)) => nodes,
+                _ => vec![],
+            };
+        }
+        let leafref = match &n.schema().leafref {
+            Some(leafref) => leafref,
+            None => return vec![],
+        };
+        let value = self.value(node);
+        match evaluate(self, &leafref.path, node) {
+            Ok(Value::Nodes(nodes)) => nodes
+                .into_iter()
+                .filter(|t| self.value(t) == value)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn identity(&self, node: &Vec<usize>) -> Option<(String, String)> {
+        match self.node(node)?.value()? {
+            LeafValue::Identity(module, name) => Some((module.clone(), name.clone())),
+            _ => None,
+        }
+    }
+
+    fn derived_from(&self, identity: (&str, &str), base: (&str, &str)) -> bool {
+        self.schema.derived_from(identity, base)
+    }
+
+    fn enum_value(&self, node: &Vec<usize>) -> Option<i64> {
+        match self.node(node)?.value()? {
+            LeafValue::Enum(_, value) => Some(*value),
+            _ => None,
+        }
+    }
+}
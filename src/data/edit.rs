@@ -0,0 +1,465 @@
+use crate::data::*;
+use std::rc::Rc;
+use thiserror::Error;
+
+// RFC6241 7.2.  <edit-config>
+//     The "operation" attribute has one of the following values: merge,
+//     replace, create, delete, remove.
+//
+// "None" is the default operation which only traverses the nodes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditOperation {
+    Merge,
+    Replace,
+    Create,
+    Delete,
+    Remove,
+    None,
+}
+
+impl EditOperation {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "merge" => Some(EditOperation::Merge),
+            "replace" => Some(EditOperation::Replace),
+            "create" => Some(EditOperation::Create),
+            "delete" => Some(EditOperation::Delete),
+            "remove" => Some(EditOperation::Remove),
+            "none" => Some(EditOperation::None),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EditOperation::Merge => "merge",
+            EditOperation::Replace => "replace",
+            EditOperation::Create => "create",
+            EditOperation::Delete => "delete",
+            EditOperation::Remove => "remove",
+            EditOperation::None => "none",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum EditError {
+    #[error("\"{0}\" already exists")]
+    DataExists(String),
+    #[error("\"{0}\" does not exist")]
+    DataMissing(String),
+    #[error("invalid value \"{2}\" of attribute \"{1}\" in \"{0}\"")]
+    BadAttribute(String, String, String),
+    #[error("missing attribute \"{1}\" in \"{0}\"")]
+    MissingAttribute(String, String),
+    #[error("instance \"{1}\" to insert \"{0}\" at does not exist")]
+    MissingInstance(String, String),
+    #[error("unknown element \"{1}\" in \"{0}\"")]
+    UnknownElement(String, String),
+    #[error(transparent)]
+    Data(#[from] DataError),
+}
+
+impl EditError {
+    pub fn path(&self) -> &str {
+        match self {
+            EditError::DataExists(path)
+            | EditError::DataMissing(path)
+            | EditError::BadAttribute(path, _, _)
+            | EditError::MissingAttribute(path, _)
+            | EditError::MissingInstance(path, _)
+            | EditError::UnknownElement(path, _) => path,
+            EditError::Data(_) => "",
+        }
+    }
+
+    // RFC6241 Appendix A.  NETCONF Error List
+    pub fn error_tag(&self) -> &'static str {
+        match self {
+            EditError::DataExists(_) => "data-exists",
+            EditError::DataMissing(_) => "data-missing",
+            EditError::BadAttribute(..) => "bad-attribute",
+            EditError::MissingAttribute(..) => "missing-attribute",
+            EditError::MissingInstance(..) => "bad-attribute",
+            EditError::UnknownElement(..) => "unknown-element",
+            EditError::Data(_) => "operation-failed",
+        }
+    }
+
+    // RFC7950 15.7.  Error Message for Data That Violates a "insert"
+    //     If the "insert" and "key" or "value" attributes are used in an
+    //     <edit-config> for a list or leaf-list node and the "key" or
+    //     "value" refers to an instance that does not exist, the following
+    //     error MUST be returned:
+    //       error-tag:      bad-attribute
+    //       error-app-tag:  missing-instance
+    pub fn app_tag(&self) -> Option<&'static str> {
+        match self {
+            EditError::MissingInstance(..) => Some("missing-instance"),
+            _ => None,
+        }
+    }
+}
+
+const NETCONF_MODULE: &str = "ietf-netconf";
+const YANG_MODULE: &str = "yang";
+
+// Node without the annotations of edit-config.
+fn strip(node: &DataNode) -> DataNode {
+    let mut node = node.clone();
+    strip_mut(&mut node);
+    node
+}
+
+fn strip_mut(node: &mut DataNode) {
+    strip_annotations(node);
+    if let Some(children) = node.children_mut() {
+        children.iter_mut().for_each(strip_mut);
+    }
+}
+
+fn strip_annotations(node: &mut DataNode) {
+    node.annotations.retain(|a| {
+        !(a.module == NETCONF_MODULE && a.name == "operation")
+            && !(a.module == YANG_MODULE
+                && (a.name == "insert" || a.name == "key" || a.name == "value"))
+    });
+}
+
+// Node of the same schema node without descendants, list entries have the
+// key leaves.
+fn shell(node: &DataNode) -> DataNode {
+    let content = match &node.content {
+        DataContent::Container(_) => DataContent::Container(vec![]),
+        DataContent::ListEntry(children) => DataContent::ListEntry(
+            children
+                .iter()
+                .filter(|c| node.schema().is_key(c.name()))
+                .map(strip)
+                .collect(),
+        ),
+        content => content.clone(),
+    };
+    DataNode::new(node.schema().clone(), content)
+}
+
+struct Editor<'a> {
+    schema: &'a Schema,
+}
+
+impl<'a> Editor<'a> {
+    fn operation(
+        &self,
+        node: &DataNode,
+        path: &str,
+        parent: EditOperation,
+    ) -> Result<EditOperation, EditError> {
+        match node.annotation(NETCONF_MODULE, "operation") {
+            Some(name) => EditOperation::from_name(name)
+                .filter(|op| *op != EditOperation::None)
+                .ok_or_else(|| {
+                    EditError::BadAttribute(
+                        path.to_owned(),
+                        String::from("operation"),
+                        name.to_owned(),
+                    )
+                }),
+            None => Ok(parent),
+        }
+    }
+
+    // Sibling referred to by "key" attribute of list entry or "value"
+    // attribute of leaf-list.
+    fn reference(
+        &self,
+        siblings: &[DataNode],
+        node: &DataNode,
+        path: &str,
+    ) -> Result<usize, EditError> {
+        let schema = node.schema();
+        let attr = match schema.kind {
+            SchemaKind::List => "key",
+            _ => "value",
+        };
+        let text = node
+            .annotation(YANG_MODULE, attr)
+            .ok_or_else(|| EditError::MissingAttribute(path.to_owned(), attr.to_owned()))?;
+        let bad = || EditError::BadAttribute(path.to_owned(), attr.to_owned(), text.to_owned());
+        let missing = || EditError::MissingInstance(path.to_owned(), text.to_owned());
+        let matches: Box<dyn Fn(&DataNode) -> bool> = match schema.kind {
+            SchemaKind::List => {
+                let segments =
+                    data_path_parse(&format!("/{}{}", schema.name, text)).map_err(|_| bad())?;
+                let mut keys = vec![];
+                for key in schema.keys.iter() {
+                    let value = segments[0]
+                        .predicates
+                        .iter()
+                        .find(|(k, _)| k == key)
+                        .ok_or_else(bad)?;
+                    let kschema = schema.child(None, key).ok_or_else(bad)?;
+                    keys.push(LeafValue::parse(self.schema, kschema, &value.1).map_err(|_| bad())?);
+                }
+                Box::new(move |n| n.key_values().into_iter().eq(keys.iter()))
+            }
+            _ => {
+                let value = LeafValue::parse(self.schema, schema, text).map_err(|_| bad())?;
+                Box::new(move |n| n.value() == Some(&value))
+            }
+        };
+        siblings
+            .iter()
+            .position(|n| same_schema(n.schema(), schema) && matches(n))
+            .ok_or_else(missing)
+    }
+
+    // Node which replaces "old", the same instance in the target if any.
+    // The descendants of "edit" whose operation is "delete" or "remove" are
+    // not in the node, and "delete" fails if the instance doesn't exist in
+    // "old".
+    fn replacement(
+        &self,
+        old: Option<&DataNode>,
+        edit: &DataNode,
+        path: &str,
+    ) -> Result<DataNode, EditError> {
+        let mut node = edit.clone();
+        strip_annotations(&mut node);
+        let module = edit.schema().module.as_str();
+        if let Some(children) = node.children_mut() {
+            children.clear();
+            for child in edit.children().iter() {
+                let path = instance_path(path, Some(module), child);
+                let old = old.and_then(|o| o.children().iter().find(|n| same_instance(n, child)));
+                match self.operation(child, &path, EditOperation::Replace)? {
+                    EditOperation::Delete if old.is_none() => {
+                        return Err(EditError::DataMissing(path))
+                    }
+                    EditOperation::Delete | EditOperation::Remove => {}
+                    _ => children.push(self.replacement(old, child, &path)?),
+                }
+            }
+        }
+        Ok(node)
+    }
+
+    // RFC7950 7.8.6.  NETCONF <edit-config> Operations
+    //     The "insert" attribute can take the values "first", "last",
+    //     "before", and "after".  If the value is "before" or "after", the
+    //     "key" attribute MUST also be used, to specify an existing element
+    //     in the list.
+    //     If no "insert" attribute is present in the "create" operation, it
+    //     defaults to "last".
+    fn insert(
+        &self,
+        siblings: &mut Vec<DataNode>,
+        schemas: &[Rc<SchemaNode>],
+        edit: &DataNode,
+        node: DataNode,
+        path: &str,
+    ) -> Result<(), EditError> {
+        let user_ordered = edit.schema().ordered_by_user
+            && matches!(
+                edit.content,
+                DataContent::ListEntry(_) | DataContent::LeafList(_)
+            );
+        let insert = match edit.annotation(YANG_MODULE, "insert") {
+            Some(insert) if user_ordered && insert != "last" => insert,
+            _ => {
+                insert_ordered(siblings, schemas, node);
+                return Ok(());
+            }
+        };
+        let index = match insert {
+            "first" => match siblings
+                .iter()
+                .position(|n| same_schema(n.schema(), edit.schema()))
+            {
+                Some(index) => index,
+                None => {
+                    insert_ordered(siblings, schemas, node);
+                    return Ok(());
+                }
+            },
+            "before" => self.reference(siblings, edit, path)?,
+            "after" => self.reference(siblings, edit, path)? + 1,
+            value => {
+                return Err(EditError::BadAttribute(
+                    path.to_owned(),
+                    String::from("insert"),
+                    value.to_owned(),
+                ))
+            }
+        };
+        siblings.insert(index, node);
+        Ok(())
+    }
+
+    fn nodes(
+        &self,
+        target: &mut Vec<DataNode>,
+        parent: Option<&SchemaNode>,
+        schemas: &[Rc<SchemaNode>],
+        edits: &[DataNode],
+        operation: EditOperation,
+        path: &str,
+    ) -> Result<(), EditError> {
+        let module = parent.map(|p| p.module.as_str());
+        for edit in edits.iter() {
+            // Keys identify the list entry.
+            if parent.is_some_and(|p| p.kind == SchemaKind::List && p.is_key(edit.name())) {
+                continue;
+            }
+            let path = instance_path(path, module, edit);
+            let op = self.operation(edit, &path, operation)?;
+            let pos = target.iter().position(|n| same_instance(n, edit));
+            let has_children = matches!(
+                edit.content,
+                DataContent::Container(_) | DataContent::ListEntry(_)
+            );
+            match (op, pos) {
+                (EditOperation::Create, Some(_)) => return Err(EditError::DataExists(path)),
+                (EditOperation::Delete, None) => return Err(EditError::DataMissing(path)),
+                (EditOperation::Remove, None) => {}
+                (EditOperation::Delete, Some(pos)) | (EditOperation::Remove, Some(pos)) => {
+                    target.remove(pos);
+                }
+                (EditOperation::Create, None) => {
+                    self.insert(target, schemas, edit, strip(edit), &path)?;
+                }
+                (EditOperation::Replace, None) => {
+                    let node = self.replacement(None, edit, &path)?;
+                    self.insert(target, schemas, edit, node, &path)?;
+                }
+                // RFC6241 7.2.
+                //     replace:  The configuration data identified by the
+                //        element containing this attribute replaces any
+                //        related configuration in the configuration datastore
+                //        identified by the <target> parameter.
+                //
+                // Nested "delete" and "remove" are applied to the new node.
+                (EditOperation::Replace, Some(pos)) => {
+                    let old = target.remove(pos);
+                    let node = self.replacement(Some(&old), edit, &path)?;
+                    if edit.annotation(YANG_MODULE, "insert").is_some() {
+                        self.insert(target, schemas, edit, node, &path)?;
+                    } else {
+                        target.insert(pos, node);
+                    }
+                }
+                // Nested operations of the new node are applied to the
+                // node without the descendants.
+                (EditOperation::Merge, None) => {
+                    let node = if has_children {
+                        shell(edit)
+                    } else {
+                        strip(edit)
+                    };
+                    self.insert(target, schemas, edit, node, &path)?;
+                    if has_children {
+                        self.children(target, edit, op, &path)?;
+                    }
+                }
+                (EditOperation::Merge, Some(pos)) | (EditOperation::None, Some(pos)) => {
+                    let mut pos = pos;
+                    if edit.annotation(YANG_MODULE, "insert").is_some() && op != EditOperation::None
+                    {
+                        let node = target.remove(pos);
+                        self.insert(target, schemas, edit, node, &path)?;
+                        pos = target
+                            .iter()
+                            .position(|n| same_instance(n, edit))
+                            .unwrap_or(pos);
+                    }
+                    if has_children {
+                        self.children(target, edit, op, &path)?;
+                    } else if op == EditOperation::Merge {
+                        let annotations = target[pos].annotations.clone();
+                        target[pos] = strip(edit);
+                        if target[pos].annotations.is_empty() {
+                            target[pos].annotations = annotations;
+                        }
+                    }
+                }
+                // RFC6241 7.2.
+                //     If the configuration in the <config> parameter contains
+                //     data for which there is not a corresponding level in
+                //     the target datastore, an <rpc-error> is returned with
+                //     an <error-tag> value of data-missing.
+                (EditOperation::None, None) if has_children => {
+                    return Err(EditError::DataMissing(path));
+                }
+                (EditOperation::None, None) => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Apply the children of "edit" to the children of the same instance in
+    // "target".
+    fn children(
+        &self,
+        target: &mut [DataNode],
+        edit: &DataNode,
+        operation: EditOperation,
+        path: &str,
+    ) -> Result<(), EditError> {
+        let node = match target.iter_mut().find(|n| same_instance(n, edit)) {
+            Some(node) => node,
+            None => return Err(EditError::DataMissing(path.to_owned())),
+        };
+        let schema = node.schema().clone();
+        match node.children_mut() {
+            Some(children) => self.nodes(
+                children,
+                Some(&schema),
+                &schema.children,
+                edit.children(),
+                operation,
+                path,
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+impl DataTree {
+    // RFC6241 7.2.  <edit-config>
+    //     The <config> subtree is applied to the target datastore with the
+    //     operation of each node, which is inherited from the parent and is
+    //     "default_operation" at the top.
+    //
+    // The operations are "ietf-netconf:operation" annotations of the nodes
+    // of "edit", and "yang:insert", "yang:key" and "yang:value" position
+    // user ordered instances.  The tree is not modified if any of the edits
+    // fails.  "edit" may be built from a separately compiled schema of the
+    // same modules.
+    pub fn edit(
+        &mut self,
+        edit: &DataTree,
+        default_operation: EditOperation,
+    ) -> Result<(), EditError> {
+        let schema = self.schema.clone();
+        let edits = edit
+            .nodes
+            .iter()
+            .map(|n| rebind("", None, n, &schema.nodes))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| match e {
+                DataError::UnknownNode(path, name) => EditError::UnknownElement(path, name),
+                e => EditError::Data(e),
+            })?;
+        let editor = Editor { schema: &schema };
+        let mut nodes = self.nodes.clone();
+        editor.nodes(
+            &mut nodes,
+            None,
+            &schema.nodes,
+            &edits,
+            default_operation,
+            "",
+        )?;
+        self.nodes = nodes;
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+use crate::data::*;
+
+// RFC6241 6.2.  Subtree Filter Components
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterContent {
+    // Element which has child elements, the child elements are the
+    // filter of the descendants.
+    Containment(Vec<FilterNode>),
+    // Empty element which selects the node and all its descendants.
+    Selection,
+    // Leaf element with the value to compare.
+    ContentMatch(String),
+}
+
+// Node of subtree filter.  Filter node without namespace matches the
+// nodes of any namespace.  "attributes" are the attribute match
+// expressions and "scope" are the namespace declarations in effect for
+// the prefixes of the content match value.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct FilterNode {
+    pub name: String,
+    pub namespace: Option<String>,
+    pub content: FilterContent,
+    #[new(default)]
+    pub attributes: Vec<XmlAttribute>,
+    #[new(default)]
+    pub scope: Vec<(String, String)>,
+}
+
+// RFC6241 6.  Subtree Filtering
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct SubtreeFilter {
+    pub nodes: Vec<FilterNode>,
+}
+
+fn filter_nodes(elements: &[XmlElement], scope: &mut Vec<(String, String)>) -> Vec<FilterNode> {
+    let mut nodes = vec![];
+    for element in elements {
+        let depth = scope.len();
+        scope.extend(element.namespaces.iter().cloned());
+        let content = if !element.children.is_empty() {
+            FilterContent::Containment(filter_nodes(&element.children, scope))
+        } else if element.text.trim().is_empty() {
+            FilterContent::Selection
+        } else {
+            FilterContent::ContentMatch(element.text.trim().to_owned())
+        };
+        // Elements which inherit the namespace of <filter> have no data
+        // model namespace.
+        let namespace = element
+            .namespace
+            .clone()
+            .filter(|ns| ns != NETCONF_NAMESPACE);
+        let mut node = FilterNode::new(element.name.clone(), namespace, content);
+        node.attributes = element.attributes.clone();
+        node.scope = scope.clone();
+        nodes.push(node);
+        scope.truncate(depth);
+    }
+    nodes
+}
+
+impl SubtreeFilter {
+    // Filter of the content of <filter> element.
+    pub fn from_xml(elements: &[XmlElement]) -> Self {
+        SubtreeFilter::new(filter_nodes(elements, &mut vec![]))
+    }
+
+    // Decode XML text which is either <filter> element of type "subtree"
+    // or its content.
+    pub fn xml_decode(text: &str) -> Result<Self, DataError> {
+        let elements = xml_parse(text)?;
+        match elements.as_slice() {
+            [root]
+                if root.name == "filter"
+                    && root.namespace.as_deref() == Some(NETCONF_NAMESPACE) =>
+            {
+                if let Some(typ) = root
+                    .attributes
+                    .iter()
+                    .find(|a| a.name == "type" && a.prefix.is_none())
+                    .filter(|a| a.value != "subtree")
+                {
+                    return Err(DataError::InvalidValue(
+                        String::from("/filter"),
+                        typ.value.clone(),
+                        String::from("not a subtree filter"),
+                    ));
+                }
+                let mut scope = root.namespaces.clone();
+                Ok(SubtreeFilter::new(filter_nodes(&root.children, &mut scope)))
+            }
+            _ => Ok(SubtreeFilter::from_xml(&elements)),
+        }
+    }
+}
+
+struct Filter<'a> {
+    schema: &'a Schema,
+}
+
+impl<'a> Filter<'a> {
+    // Name, namespace and attribute match.  Attributes are matched with
+    // the metadata annotations of the node.
+    fn matches(&self, filter: &FilterNode, node: &DataNode) -> bool {
+        node.name() == filter.name
+            && filter
+                .namespace
+                .as_ref()
+                .is_none_or(|ns| ns == &node.schema().namespace)
+            && filter.attributes.iter().all(|a| {
+                a.namespace
+                    .as_deref()
+                    .and_then(|ns| self.schema.namespace_module(ns))
+                    .and_then(|module| node.annotation(module, &a.name))
+                    == Some(a.value.as_str())
+            })
+    }
+
+    fn content_matches(&self, filter: &FilterNode, node: &DataNode) -> bool {
+        match (&filter.content, node.value()) {
+            (FilterContent::ContentMatch(text), Some(value)) => {
+                xml_value(self.schema, node.schema(), text, &filter.scope).as_ref() == Ok(value)
+            }
+            _ => false,
+        }
+    }
+
+    // RFC6241 6.2.5.  Content Match Nodes
+    //     If all specified sibling content match nodes in a subtree filter
+    //     expression are "true", then the filter output nodes are selected
+    //     in the following manner:
+    //     o  Each content match node in the sibling set is included in the
+    //        filter output.
+    //     o  If any containment nodes are present in the sibling set, then
+    //        they are processed further and included if any nested
+    //        filter criteria are also met.
+    //     o  If any selection nodes are present in the sibling set, then
+    //        all of them are included in the filter output.
+    //     o  If any sibling content match node tests are false, then no
+    //        further filter processing is performed on that sibling set,
+    //        and none of the sibling subtrees are selected by the filter,
+    //        including the content match node(s).
+    //     o  If no selection or containment nodes are present in the
+    //        sibling set, then all sibling nodes are included in the filter
+    //        output.
+    fn nodes(&self, nodes: &[DataNode], filters: &[FilterNode]) -> Option<Vec<DataNode>> {
+        let is_match = |f: &&FilterNode| matches!(f.content, FilterContent::ContentMatch(_));
+        for filter in filters.iter().filter(is_match) {
+            if !nodes
+                .iter()
+                .any(|n| self.matches(filter, n) && self.content_matches(filter, n))
+            {
+                return None;
+            }
+        }
+        if filters.iter().all(|f| is_match(&f)) {
+            return Some(nodes.to_vec());
+        }
+
+        let mut result = vec![];
+        for node in nodes {
+            let mut selected: Option<DataNode> = None;
+            for filter in filters.iter().filter(|f| self.matches(f, node)) {
+                let part = match &filter.content {
+                    FilterContent::Selection => Some(node.clone()),
+                    FilterContent::ContentMatch(_) if self.content_matches(filter, node) => {
+                        Some(node.clone())
+                    }
+                    FilterContent::ContentMatch(_) => None,
+                    FilterContent::Containment(children) => self.containment(node, children),
+                };
+                // Selections of several filter nodes of the same list
+                // entry are merged.
+                selected = match (selected, part) {
+                    (Some(mut a), Some(b)) => {
+                        let schema = a.schema().clone();
+                        if let Some(children) = a.children_mut() {
+                            merge_nodes(children, &schema.children, b.children());
+                        }
+                        Some(a)
+                    }
+                    (a, b) => a.or(b),
+                };
+            }
+            result.extend(selected);
+        }
+        Some(result)
+    }
+
+    // Containment node selects the container or list entry when any of
+    // its descendants is selected.  List entries always have their keys.
+    fn containment(&self, node: &DataNode, filters: &[FilterNode]) -> Option<DataNode> {
+        let children = self.nodes(node.children(), filters)?;
+        if children.is_empty() {
+            return None;
+        }
+        let content = match &node.content {
+            DataContent::Container(_) => DataContent::Container(children),
+            DataContent::ListEntry(_) => DataContent::ListEntry(children),
+            _ => return None,
+        };
+        let mut copy = DataNode::new(node.schema().clone(), content);
+        copy.annotations = node.annotations.clone();
+        for key in node.schema().keys.iter() {
+            if copy.child(key).is_none() {
+                if let Some(leaf) = node.child(key) {
+                    copy.insert(leaf.clone());
+                }
+            }
+        }
+        Some(copy)
+    }
+}
+
+impl DataTree {
+    // Copy of the tree with the nodes selected by the subtree filter.
+    // Empty filter selects no data.
+    pub fn filter(&self, filter: &SubtreeFilter) -> DataTree {
+        let nodes = if filter.nodes.is_empty() {
+            vec![]
+        } else {
+            let f = Filter {
+                schema: &self.schema,
+            };
+            f.nodes(&self.nodes, &filter.nodes).unwrap_or_default()
+        };
+        DataTree {
+            schema: self.schema.clone(),
+            nodes,
+        }
+    }
+}
@@ -0,0 +1,434 @@
+use crate::data::diff::merge_nodes;
+use crate::data::json_codec::{decode_inner, decode_value};
+use crate::data::*;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fmt;
+use std::rc::Rc;
+
+// gNMI PathElem.  "name" is qualified by the module name as "module:name"
+// when the node is at the top level or in a different module than its
+// parent.  Keys are the list keys in key order.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct PathElem {
+    pub name: String,
+    #[new(default)]
+    pub key: Vec<(String, String)>,
+}
+
+// gNMI Path.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct GnmiPath {
+    #[new(default)]
+    pub origin: Option<String>,
+    pub elem: Vec<PathElem>,
+    #[new(default)]
+    pub target: Option<String>,
+}
+
+// gNMI TypedValue.  "Decimal" is the Decimal64 message of digits and
+// precision, and "JsonIetf" is RFC7951 JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedValue {
+    String(String),
+    Int(i64),
+    Uint(u64),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    Double(f64),
+    Decimal(i64, u32),
+    LeafList(Vec<TypedValue>),
+    JsonIetf(JsonValue),
+    Ascii(String),
+}
+
+// gNMI Update.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct Update {
+    pub path: GnmiPath,
+    pub val: TypedValue,
+}
+
+// gNMI Notification.  Paths of the updates and deletes are relative to
+// "prefix".
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct Notification {
+    pub timestamp: i64,
+    #[new(default)]
+    pub prefix: Option<GnmiPath>,
+    #[new(default)]
+    pub update: Vec<Update>,
+    #[new(default)]
+    pub delete: Vec<GnmiPath>,
+}
+
+// gNMI Path Strings
+//     Within a key value, the characters "]" and "\" MUST be escaped by
+//     a preceding "\".
+impl fmt::Display for GnmiPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(origin) = &self.origin {
+            write!(f, "{}:", origin)?;
+        }
+        if self.elem.is_empty() {
+            return write!(f, "/");
+        }
+        for elem in self.elem.iter() {
+            write!(f, "/{}", elem.name.replace('/', "\\/"))?;
+            for (name, value) in elem.key.iter() {
+                let value = value.replace('\\', "\\\\").replace(']', "\\]");
+                write!(f, "[{}={}]", name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<&LeafValue> for TypedValue {
+    fn from(value: &LeafValue) -> Self {
+        match value {
+            LeafValue::Int(v) => TypedValue::Int(*v),
+            LeafValue::Uint(v) => TypedValue::Uint(*v),
+            LeafValue::Decimal64(v, digits) => TypedValue::Decimal(*v, *digits as u32),
+            LeafValue::Boolean(v) => TypedValue::Bool(*v),
+            LeafValue::Binary(v) => TypedValue::Bytes(v.clone()),
+            LeafValue::Empty => TypedValue::JsonIetf(JsonValue::Array(vec![JsonValue::Null])),
+            // Enum, bits, identity and instance-identifier values are in the
+            // RFC7951 form.
+            _ => TypedValue::String(value.to_string()),
+        }
+    }
+}
+
+impl TypedValue {
+    // Value of leaf or leaf-list "node".
+    pub fn to_leaf(
+        &self,
+        schema: &Schema,
+        node: &SchemaNode,
+        path: &str,
+    ) -> Result<LeafValue, DataError> {
+        let text = match self {
+            TypedValue::String(v) | TypedValue::Ascii(v) => v.clone(),
+            TypedValue::Int(v) => v.to_string(),
+            TypedValue::Uint(v) => v.to_string(),
+            TypedValue::Bool(v) => v.to_string(),
+            TypedValue::Bytes(v) => STANDARD.encode(v),
+            TypedValue::Double(v) => v.to_string(),
+            TypedValue::Decimal(digits, precision) if *precision <= 18 => {
+                LeafValue::Decimal64(*digits, *precision as u8).to_string()
+            }
+            TypedValue::JsonIetf(json) => return decode_value(schema, node, json, path),
+            _ => {
+                return Err(DataError::InvalidValue(
+                    path.to_owned(),
+                    format!("{:?}", self),
+                    String::from("not a leaf value"),
+                ))
+            }
+        };
+        LeafValue::parse(schema, node, &text)
+            .map_err(|e| DataError::InvalidValue(path.to_owned(), text, e))
+    }
+}
+
+fn elem_name(node: &SchemaNode, parent_module: Option<&str>) -> String {
+    match parent_module {
+        Some(m) if m == node.module => node.name.clone(),
+        _ => format!("{}:{}", node.module, node.name),
+    }
+}
+
+// Updates of the leaves and leaf-lists under "elem".  Empty containers
+// and anydata are JSON values.
+fn updates(
+    nodes: &[DataNode],
+    elem: &mut Vec<PathElem>,
+    parent_module: Option<&str>,
+    out: &mut Vec<Update>,
+) {
+    let mut i = 0;
+    while i < nodes.len() {
+        let node = &nodes[i];
+        let schema = node.schema();
+        let mut path_elem = PathElem::new(elem_name(schema, parent_module));
+        if let DataContent::ListEntry(_) = node.content {
+            for key in schema.keys.iter() {
+                if let Some(value) = node.child(key).and_then(|c| c.value()) {
+                    path_elem.key.push((key.clone(), value.to_string()));
+                }
+            }
+        }
+        elem.push(path_elem);
+        let path = GnmiPath::new(elem.clone());
+        match &node.content {
+            DataContent::Container(children) | DataContent::ListEntry(children)
+                if !children.is_empty() =>
+            {
+                updates(children, elem, Some(&schema.module), out);
+            }
+            DataContent::Container(_) | DataContent::ListEntry(_) | DataContent::Anydata(_) => {
+                out.push(Update::new(path, TypedValue::JsonIetf(node.to_json())));
+            }
+            DataContent::Leaf(value) => out.push(Update::new(path, value.into())),
+            DataContent::LeafList(_) => {
+                let values: Vec<TypedValue> = nodes[i..]
+                    .iter()
+                    .take_while(|n| Rc::ptr_eq(n.schema(), schema))
+                    .filter_map(|n| n.value())
+                    .map(TypedValue::from)
+                    .collect();
+                i += values.len() - 1;
+                out.push(Update::new(path, TypedValue::LeafList(values)));
+            }
+        }
+        elem.pop();
+        i += 1;
+    }
+}
+
+// Full path of the path relative to "prefix".
+fn full_path(prefix: Option<&GnmiPath>, path: &GnmiPath) -> GnmiPath {
+    match prefix {
+        Some(prefix) => {
+            let mut full = prefix.clone();
+            full.elem.extend(path.elem.iter().cloned());
+            if path.origin.is_some() {
+                full.origin = path.origin.clone();
+            }
+            full
+        }
+        None => path.clone(),
+    }
+}
+
+// Remove all of the instances of leaf-list "snode" under "path".
+fn remove_leaf_list(
+    tree: &mut DataTree,
+    path: &str,
+    snode: &Rc<SchemaNode>,
+) -> Result<(), DataError> {
+    let segments = data_path_parse(path)?;
+    let parent = data_path_string(&segments[..segments.len() - 1]);
+    let nodes = match parent.as_str() {
+        "" => &mut tree.nodes,
+        _ => match tree.get_mut(&parent) {
+            Ok(node) => node
+                .children_mut()
+                .ok_or_else(|| DataError::InvalidPath(path.to_owned()))?,
+            Err(DataError::NotFound(_)) => return Ok(()),
+            Err(e) => return Err(e),
+        },
+    };
+    nodes.retain(|n| !Rc::ptr_eq(n.schema(), snode));
+    Ok(())
+}
+
+fn apply_update(
+    tree: &mut DataTree,
+    update: &Update,
+    prefix: Option<&GnmiPath>,
+) -> Result<(), DataError> {
+    let schema = tree.schema.clone();
+    let path = schema.gnmi_data_path(&full_path(prefix, &update.path))?;
+    let snode = schema.find_path(&path)?;
+    let invalid = |reason: &str| {
+        DataError::InvalidValue(path.clone(), format!("{:?}", update.val), reason.to_owned())
+    };
+    match (snode.kind, &update.val) {
+        (SchemaKind::Leaf, val) => {
+            let value = val.to_leaf(&schema, &snode, &path)?;
+            tree.set(&path, &value.to_string())
+        }
+        // Leaf-list is replaced as a whole.
+        (SchemaKind::LeafList, TypedValue::LeafList(values)) => {
+            let mut segments = data_path_parse(&path)?;
+            remove_leaf_list(tree, &path, &snode)?;
+            for val in values.iter() {
+                let value = val.to_leaf(&schema, &snode, &path)?;
+                if let Some(last) = segments.last_mut() {
+                    last.predicates = vec![(String::from("."), value.to_string())];
+                }
+                tree.create(&data_path_string(&segments))?;
+            }
+            Ok(())
+        }
+        // gNMI 2.3.1.  JSON_IETF is the RFC7951 encoding of the value.
+        //
+        // The children are merged into the container or the list entry, and
+        // the content of anydata is replaced.
+        (SchemaKind::Container, TypedValue::JsonIetf(json))
+        | (SchemaKind::List, TypedValue::JsonIetf(json))
+        | (SchemaKind::Anydata, TypedValue::JsonIetf(json)) => {
+            let decoded = decode_inner(&schema, &snode, json, &path)?;
+            tree.create(&path)?;
+            let node = tree.get_mut(&path)?;
+            match node.children_mut() {
+                Some(children) => {
+                    // Keys in the value must be the keys of the path.
+                    for child in decoded.children() {
+                        if snode.is_key(child.name())
+                            && children.iter().find(|c| c.name() == child.name()) != Some(child)
+                        {
+                            return Err(DataError::KeyModify(path.clone()));
+                        }
+                    }
+                    merge_nodes(children, &snode.children, decoded.children());
+                }
+                None => node.content = decoded.content,
+            }
+            Ok(())
+        }
+        _ => Err(invalid("not supported for the node")),
+    }
+}
+
+fn apply_delete(
+    tree: &mut DataTree,
+    path: &GnmiPath,
+    prefix: Option<&GnmiPath>,
+) -> Result<(), DataError> {
+    let schema = tree.schema.clone();
+    let path = schema.gnmi_data_path(&full_path(prefix, path))?;
+    let snode = schema.find_path(&path)?;
+    if snode.kind == SchemaKind::LeafList {
+        return remove_leaf_list(tree, &path, &snode);
+    }
+    // gNMI 3.4.6.  Deleting non-existent paths MUST be silently accepted.
+    match tree.delete(&path) {
+        Ok(_) | Err(DataError::NotFound(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+impl Schema {
+    // gNMI path of data path.  Leaf-list instances can't be addressed.
+    pub fn gnmi_path(&self, data_path: &str) -> Result<GnmiPath, DataError> {
+        let segments = data_path_parse(data_path)?;
+        let nodes = self.resolve_path(&segments)?;
+        let mut elem = vec![];
+        let mut parent_module: Option<&str> = None;
+        for (segment, node) in segments.iter().zip(nodes.iter()) {
+            let mut path_elem = PathElem::new(elem_name(node, parent_module));
+            match node.kind {
+                SchemaKind::List if !segment.predicates.is_empty() => {
+                    for key in node.keys.iter() {
+                        let value = segment
+                            .predicates
+                            .iter()
+                            .find(|(k, _)| k == key)
+                            .ok_or_else(|| {
+                                DataError::MissingKey(data_path.to_owned(), key.clone())
+                            })?;
+                        path_elem.key.push(value.clone());
+                    }
+                }
+                _ if !segment.predicates.is_empty() => {
+                    return Err(DataError::InvalidPath(data_path.to_owned()))
+                }
+                _ => {}
+            }
+            elem.push(path_elem);
+            parent_module = Some(&node.module);
+        }
+        Ok(GnmiPath::new(elem))
+    }
+
+    // Data path in the JSON form of gNMI path.  Element names without
+    // module are in the module of the parent, or in the module named by
+    // "origin" at the top level.
+    pub fn gnmi_data_path(&self, path: &GnmiPath) -> Result<String, DataError> {
+        let mut segments = vec![];
+        let mut parent: Option<Rc<SchemaNode>> = None;
+        for (i, elem) in path.elem.iter().enumerate() {
+            let (module, name) = match elem.name.split_once(':') {
+                Some((module, name)) => (Some(module), name),
+                None => (None, elem.name.as_str()),
+            };
+            let unknown = || DataError::UnknownNode(data_path_string(&segments), name.to_owned());
+            let module = match (module, &parent) {
+                (Some(module), _) => Some(self.module(module).ok_or_else(unknown)?.name.as_str()),
+                (None, Some(p)) => Some(p.module.as_str()),
+                (None, None) => path
+                    .origin
+                    .as_deref()
+                    .and_then(|o| self.module(o))
+                    .map(|m| m.name.as_str()),
+            };
+            let candidates = parent.as_ref().map_or(&self.nodes, |p| &p.children);
+            let node = find_schema(candidates, module, name)
+                .ok_or_else(unknown)?
+                .clone();
+
+            let prefix = match &parent {
+                Some(p) if p.module == node.module => None,
+                _ => Some(node.module.clone()),
+            };
+            let mut segment = PathSegment::new(prefix, node.name.clone());
+            let last = i == path.elem.len() - 1;
+            match node.kind {
+                SchemaKind::List if !elem.key.is_empty() || !last => {
+                    for key in node.keys.iter() {
+                        let value =
+                            elem.key.iter().find(|(k, _)| k == key).ok_or_else(|| {
+                                DataError::MissingKey(path.to_string(), key.clone())
+                            })?;
+                        segment.predicates.push(value.clone());
+                    }
+                    if elem.key.len() != node.keys.len() {
+                        return Err(DataError::InvalidPath(path.to_string()));
+                    }
+                }
+                _ if !elem.key.is_empty() => return Err(DataError::InvalidPath(path.to_string())),
+                _ => {}
+            }
+            segments.push(segment);
+            parent = Some(node);
+        }
+        Ok(data_path_string(&segments))
+    }
+}
+
+impl DataTree {
+    // Updates of all of the leaves and leaf-lists of the tree.  Each
+    // leaf-list is one update of all its values.
+    pub fn gnmi_updates(&self) -> Vec<Update> {
+        let mut out = vec![];
+        updates(&self.nodes, &mut vec![], None, &mut out);
+        out
+    }
+
+    pub fn gnmi_notification(&self, timestamp: i64) -> Notification {
+        let mut notification = Notification::new(timestamp);
+        notification.update = self.gnmi_updates();
+        notification
+    }
+
+    // Apply the deletes and then the updates of the notification.  Missing
+    // containers and list entries of the updates are created.  The tree is
+    // not modified if any of them can't be applied.
+    pub fn gnmi_apply(&mut self, notification: &Notification) -> Result<(), DataError> {
+        let mut tree = self.clone();
+        let prefix = notification.prefix.as_ref();
+        for path in notification.delete.iter() {
+            apply_delete(&mut tree, path, prefix)?;
+        }
+        for update in notification.update.iter() {
+            apply_update(&mut tree, update, prefix)?;
+        }
+        *self = tree;
+        Ok(())
+    }
+
+    // Tree built from the notifications in order.
+    pub fn from_gnmi(
+        schema: Rc<Schema>,
+        notifications: &[Notification],
+    ) -> Result<DataTree, DataError> {
+        let mut tree = DataTree::new(schema);
+        for notification in notifications.iter() {
+            tree.gnmi_apply(notification)?;
+        }
+        Ok(tree)
+    }
+}
@@ -0,0 +1,315 @@
+use crate::data::*;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while, take_while1};
+use nom::character::complete::{char, multispace0, one_of};
+use nom::combinator::{map, opt, recognize};
+use nom::error::{make_error, ErrorKind};
+use nom::multi::separated_list;
+use nom::sequence::{delimited, pair, preceded, tuple};
+use nom::{Err, IResult};
+use std::fmt;
+
+// JSON value.  Numbers are kept in the text form and object members are
+// kept in the document order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    // Member of object.
+    pub fn get(&self, name: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    // Pretty printed text with two spaces indentation.
+    pub fn to_string_pretty(&self) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        out
+    }
+
+    fn write_pretty(&self, out: &mut String, depth: usize) {
+        let indent = |out: &mut String, depth: usize| {
+            out.push('\n');
+            out.push_str(&"  ".repeat(depth));
+        };
+        match self {
+            JsonValue::Array(values) if !values.is_empty() => {
+                out.push('[');
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    indent(out, depth + 1);
+                    v.write_pretty(out, depth + 1);
+                }
+                indent(out, depth);
+                out.push(']');
+            }
+            JsonValue::Object(members) if !members.is_empty() => {
+                out.push('{');
+                for (i, (name, v)) in members.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    indent(out, depth + 1);
+                    write_string(out, name);
+                    out.push_str(": ");
+                    v.write_pretty(out, depth + 1);
+                }
+                indent(out, depth);
+                out.push('}');
+            }
+            _ => out.push_str(&self.to_string()),
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(v) => write!(f, "{}", v),
+            JsonValue::Number(v) => write!(f, "{}", v),
+            JsonValue::String(v) => {
+                let mut out = String::new();
+                write_string(&mut out, v);
+                write!(f, "{}", out)
+            }
+            JsonValue::Array(values) => {
+                write!(f, "[")?;
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", v)?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (name, v)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    let mut out = String::new();
+                    write_string(&mut out, name);
+                    write!(f, "{}:{}", out, v)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn hex4(s: &str) -> IResult<&str, u32> {
+    let digits = s
+        .get(..4)
+        .filter(|d| d.chars().all(|c| c.is_ascii_hexdigit()));
+    match digits {
+        Some(d) => Ok((&s[4..], u32::from_str_radix(d, 16).unwrap_or(0))),
+        None => Err(Err::Error(make_error(s, ErrorKind::HexDigit))),
+    }
+}
+
+// RFC8259 7.  Strings
+fn json_string(s: &str) -> IResult<&str, String> {
+    let (mut s, _) = char('"')(s)?;
+    let mut out = String::new();
+    loop {
+        let (rest, chunk) = take_while(|c: char| c != '"' && c != '\\' && c >= ' ')(s)?;
+        out.push_str(chunk);
+        s = rest;
+        let c = match s.chars().next() {
+            Some(c) => c,
+            None => return Err(Err::Error(make_error(s, ErrorKind::Char))),
+        };
+        match c {
+            '"' => return Ok((&s[1..], out)),
+            '\\' => {
+                let (rest, e) = one_of("\"\\/bfnrtu")(&s[1..])?;
+                s = rest;
+                match e {
+                    'b' => out.push('\u{8}'),
+                    'f' => out.push('\u{c}'),
+                    'n' => out.push('\n'),
+                    'r' => out.push('\r'),
+                    't' => out.push('\t'),
+                    'u' => {
+                        let (rest, mut code) = hex4(s)?;
+                        s = rest;
+                        // Surrogate pair.  A high surrogate must be
+                        // followed by a low surrogate, a lone low surrogate
+                        // is not a character.
+                        if (0xd800..0xdc00).contains(&code) {
+                            let (rest, low) = preceded(tag("\\u"), hex4)(s)?;
+                            if !(0xdc00..0xe000).contains(&low) {
+                                return Err(Err::Error(make_error(s, ErrorKind::Char)));
+                            }
+                            s = rest;
+                            code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                        }
+                        match char::from_u32(code) {
+                            Some(c) => out.push(c),
+                            None => return Err(Err::Error(make_error(s, ErrorKind::Char))),
+                        }
+                    }
+                    e => out.push(e),
+                }
+            }
+            _ => return Err(Err::Error(make_error(s, ErrorKind::Char))),
+        }
+    }
+}
+
+fn json_number(s: &str) -> IResult<&str, &str> {
+    let digits = |s| take_while1(|c: char| c.is_ascii_digit())(s);
+    recognize(tuple((
+        opt(char('-')),
+        alt((tag("0"), digits)),
+        opt(pair(char('.'), digits)),
+        opt(tuple((one_of("eE"), opt(one_of("+-")), digits))),
+    )))(s)
+}
+
+fn json_member(s: &str, depth: usize) -> IResult<&str, (String, JsonValue)> {
+    let (s, name) = delimited(multispace0, json_string, multispace0)(s)?;
+    let (s, _) = char(':')(s)?;
+    let (s, value) = json_value(s, depth)?;
+    Ok((s, (name, value)))
+}
+
+// RFC8259 9.  Parsers
+//     An implementation may set limits on the maximum depth of nesting.
+const MAX_DEPTH: usize = 128;
+
+fn json_value(s: &str, depth: usize) -> IResult<&str, JsonValue> {
+    if depth > MAX_DEPTH {
+        return Err(Err::Failure(make_error(s, ErrorKind::TooLarge)));
+    }
+    delimited(
+        multispace0,
+        alt((
+            map(tag("null"), |_| JsonValue::Null),
+            map(tag("true"), |_| JsonValue::Bool(true)),
+            map(tag("false"), |_| JsonValue::Bool(false)),
+            map(json_number, |n: &str| JsonValue::Number(n.to_owned())),
+            map(json_string, JsonValue::String),
+            map(
+                delimited(
+                    char('['),
+                    separated_list(char(','), |s| json_value(s, depth + 1)),
+                    preceded(multispace0, char(']')),
+                ),
+                JsonValue::Array,
+            ),
+            map(
+                delimited(
+                    char('{'),
+                    separated_list(char(','), |s| json_member(s, depth + 1)),
+                    preceded(multispace0, char('}')),
+                ),
+                JsonValue::Object,
+            ),
+        )),
+        multispace0,
+    )(s)
+}
+
+// Parse JSON text.
+pub fn json_parse(s: &str) -> Result<JsonValue, DataError> {
+    match json_value(s, 0) {
+        Ok(("", value)) => Ok(value),
+        Ok((rest, _)) => Err(DataError::Syntax(rest.chars().take(32).collect())),
+        Err(Err::Failure((_, ErrorKind::TooLarge))) => Err(DataError::TooDeep(MAX_DEPTH)),
+        Err(Err::Error((rest, _))) | Err(Err::Failure((rest, _))) => {
+            Err(DataError::Syntax(rest.chars().take(32).collect()))
+        }
+        Err(Err::Incomplete(_)) => Err(DataError::Syntax(String::new())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_parse_test() {
+        let text = r#" { "a" : [1, -2.5e3, true, null], "b\"\u00e9\ud83d\ude00" : {}, "c": [] } "#;
+        let value = json_parse(text).unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Object(vec![
+                (
+                    String::from("a"),
+                    JsonValue::Array(vec![
+                        JsonValue::Number(String::from("1")),
+                        JsonValue::Number(String::from("-2.5e3")),
+                        JsonValue::Bool(true),
+                        JsonValue::Null,
+                    ])
+                ),
+                (String::from("b\"é😀"), JsonValue::Object(vec![])),
+                (String::from("c"), JsonValue::Array(vec![])),
+            ])
+        );
+        assert_eq!(
+            value.to_string(),
+            "{\"a\":[1,-2.5e3,true,null],\"b\\\"é😀\":{},\"c\":[]}"
+        );
+        assert_eq!(json_parse(&value.to_string_pretty()).unwrap(), value);
+
+        assert!(json_parse("{\"a\": 01}").is_err());
+        assert!(json_parse("{\"a\" 1}").is_err());
+        assert!(json_parse("[1,]").is_err());
+        assert!(json_parse("\"\\x\"").is_err());
+
+        // Surrogates must be paired.
+        assert!(json_parse(r#""\ud83d""#).is_err());
+        assert!(json_parse(r#""\ud83d\u0041""#).is_err());
+        assert!(json_parse(r#""\ud83d\ud83d""#).is_err());
+        assert!(json_parse(r#""\ude00""#).is_err());
+
+        // Nesting depth is limited.
+        let nested = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        assert!(json_parse(&nested).is_ok());
+        let nested = format!("{}{}", "[".repeat(129), "]".repeat(129));
+        assert_eq!(json_parse(&nested), Err(DataError::TooDeep(128)));
+        let nested = "{\"a\":".repeat(100_000);
+        assert_eq!(json_parse(&nested), Err(DataError::TooDeep(128)));
+    }
+}
@@ -0,0 +1,524 @@
+use crate::data::*;
+use crate::modules::{TypeKind, TypeNode};
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+// RFC7951 6.1.  Numeric Types
+//     A value of the "int8", "int16", "int32", "uint8", "uint16", or
+//     "uint32" type is represented as a JSON number.
+//
+//     A value of the "int64", "uint64", or "decimal64" type is represented
+//     as a JSON string whose content is the lexical representation of the
+//     corresponding YANG type.
+fn is_json_number(kind: TypeKind) -> bool {
+    matches!(
+        kind,
+        TypeKind::Yint8
+            | TypeKind::Yint16
+            | TypeKind::Yint32
+            | TypeKind::Yuint8
+            | TypeKind::Yuint16
+            | TypeKind::Yuint32
+    )
+}
+
+fn fits(kind: TypeKind, value: &LeafValue) -> bool {
+    match (kind, value) {
+        (TypeKind::Yint8, LeafValue::Int(v)) => i8::try_from(*v).is_ok(),
+        (TypeKind::Yint16, LeafValue::Int(v)) => i16::try_from(*v).is_ok(),
+        (TypeKind::Yint32, LeafValue::Int(v)) => i32::try_from(*v).is_ok(),
+        (TypeKind::Yint64, LeafValue::Int(_)) => true,
+        (TypeKind::Yuint8, LeafValue::Uint(v)) => u8::try_from(*v).is_ok(),
+        (TypeKind::Yuint16, LeafValue::Uint(v)) => u16::try_from(*v).is_ok(),
+        (TypeKind::Yuint32, LeafValue::Uint(v)) => u32::try_from(*v).is_ok(),
+        (TypeKind::Yuint64, LeafValue::Uint(_)) => true,
+        _ => false,
+    }
+}
+
+// Integer type which the integer value belongs to.  Union member types
+// are looked up in order as the value was parsed.
+fn integer_kind(typ: &TypeNode, leafref: Option<&Leafref>, value: &LeafValue) -> Option<TypeKind> {
+    match typ.kind {
+        TypeKind::Yunion => typ
+            .union
+            .iter()
+            .find_map(|member| integer_kind(member, None, value)),
+        TypeKind::Yleafref => leafref
+            .and_then(|l| l.typ.as_ref())
+            .and_then(|target| integer_kind(target, None, value)),
+        kind if fits(kind, value) => Some(kind),
+        _ => None,
+    }
+}
+
+pub(crate) fn encode_value(node: &SchemaNode, value: &LeafValue) -> JsonValue {
+    match value {
+        LeafValue::Int(_) | LeafValue::Uint(_) => {
+            let number = node
+                .typ
+                .as_ref()
+                .and_then(|typ| integer_kind(typ, node.leafref.as_ref(), value))
+                .is_some_and(is_json_number);
+            if number {
+                JsonValue::Number(value.to_string())
+            } else {
+                JsonValue::String(value.to_string())
+            }
+        }
+        LeafValue::Boolean(v) => JsonValue::Bool(*v),
+        // RFC7951 6.9.  The "empty" Type
+        //     An "empty" value is represented as "[null]", i.e., an array
+        //     with the "null" literal being its only element.
+        LeafValue::Empty => JsonValue::Array(vec![JsonValue::Null]),
+        // Identity values are always qualified by the module name.
+        _ => JsonValue::String(value.to_string()),
+    }
+}
+
+// RFC7951 6.10.  The "union" Type
+//     A value of the "union" type is encoded as the value of any of the
+//     member types.
+//
+//     Unlike XML, JSON conveys part of the type information already in the
+//     encoding.  When validating a value of the "union" type, this
+//     information MUST also be taken into account.
+pub(crate) fn decode_value(
+    schema: &Schema,
+    node: &SchemaNode,
+    json: &JsonValue,
+    path: &str,
+) -> Result<LeafValue, DataError> {
+    let (text, accept): (String, fn(TypeKind) -> bool) = match json {
+        JsonValue::Number(n) => (n.clone(), is_json_number),
+        JsonValue::String(s) => (s.clone(), |k| {
+            !is_json_number(k) && k != TypeKind::Yboolean && k != TypeKind::Yempty
+        }),
+        JsonValue::Bool(b) => (b.to_string(), |k| k == TypeKind::Yboolean),
+        JsonValue::Array(v) if v == &[JsonValue::Null] => {
+            (String::new(), |k| k == TypeKind::Yempty)
+        }
+        _ => {
+            return Err(DataError::InvalidValue(
+                path.to_owned(),
+                json.to_string(),
+                String::from("not a leaf value"),
+            ))
+        }
+    };
+    LeafValue::parse_with(schema, node, &text, accept)
+        .map_err(|e| DataError::InvalidValue(path.to_owned(), text, e))
+}
+
+// RFC7951 4.  Names and Namespaces
+//     A namespace-qualified member name MUST be used for all members of a
+//     top-level JSON object and then also whenever the namespaces of the
+//     data node and its parent node are different.
+fn member_name(module: &str, name: &str, parent_module: Option<&str>) -> String {
+    if parent_module == Some(module) {
+        name.to_owned()
+    } else {
+        format!("{}:{}", module, name)
+    }
+}
+
+fn split_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((module, name)) => (Some(module), name),
+        None => (None, name),
+    }
+}
+
+// RFC7952 5.2.  The Encoding of Metadata Annotations in JSON
+//     The value of a metadata annotation SHALL be encoded in exactly the
+//     same way as the value of a YANG leaf node having the same type as
+//     the annotation.
+//
+// Annotations of the known boolean type are encoded as JSON literals, the
+// others as strings.
+fn encode_annotations(annotations: &[Annotation]) -> JsonValue {
+    JsonValue::Object(
+        annotations
+            .iter()
+            .map(|a| {
+                let value = match (a.module.as_str(), a.name.as_str(), a.value.as_str()) {
+                    // RFC8040 4.8.9: "default" is of type boolean.
+                    ("ietf-netconf-with-defaults", "default", "true") => JsonValue::Bool(true),
+                    ("ietf-netconf-with-defaults", "default", "false") => JsonValue::Bool(false),
+                    _ => JsonValue::String(a.value.clone()),
+                };
+                (format!("{}:{}", a.module, a.name), value)
+            })
+            .collect(),
+    )
+}
+
+fn encode_any(nodes: &[AnyNode], parent_module: &str) -> JsonValue {
+    let mut members: Vec<(String, JsonValue)> = vec![];
+    let mut done = vec![false; nodes.len()];
+    for i in 0..nodes.len() {
+        if done[i] {
+            continue;
+        }
+        let node = &nodes[i];
+        let module = node.module.as_deref().unwrap_or(parent_module);
+        let mut values = vec![];
+        for j in i..nodes.len() {
+            if nodes[j].name == node.name && nodes[j].module == node.module {
+                done[j] = true;
+                values.push(match &nodes[j].value {
+                    Some(v) => JsonValue::String(v.clone()),
+                    None => encode_any(&nodes[j].children, module),
+                });
+            }
+        }
+        let value = if values.len() == 1 {
+            values.remove(0)
+        } else {
+            JsonValue::Array(values)
+        };
+        members.push((member_name(module, &node.name, Some(parent_module)), value));
+    }
+    JsonValue::Object(members)
+}
+
+fn encode_inner(node: &DataNode) -> JsonValue {
+    let mut members = match &node.content {
+        DataContent::Anydata(any) => match encode_any(any, &node.schema.module) {
+            JsonValue::Object(members) => members,
+            _ => vec![],
+        },
+        _ => encode_nodes(node.children(), Some(&node.schema.module)),
+    };
+    if !node.annotations.is_empty() {
+        members.insert(
+            0,
+            (String::from("@"), encode_annotations(&node.annotations)),
+        );
+    }
+    JsonValue::Object(members)
+}
+
+pub(crate) fn encode_nodes(
+    nodes: &[DataNode],
+    parent_module: Option<&str>,
+) -> Vec<(String, JsonValue)> {
+    let mut members = vec![];
+    let mut i = 0;
+    while i < nodes.len() {
+        let schema = &nodes[i].schema;
+        let group: Vec<&DataNode> = nodes[i..]
+            .iter()
+            .take_while(|n| Rc::ptr_eq(&n.schema, schema))
+            .collect();
+        i += group.len();
+        let name = member_name(&schema.module, &schema.name, parent_module);
+        match schema.kind {
+            SchemaKind::Container | SchemaKind::Anydata => {
+                for node in group {
+                    members.push((name.clone(), encode_inner(node)));
+                }
+            }
+            SchemaKind::List => {
+                let entries = group.into_iter().map(encode_inner).collect();
+                members.push((name, JsonValue::Array(entries)));
+            }
+            SchemaKind::Leaf => {
+                for node in group {
+                    if let Some(value) = node.value() {
+                        members.push((name.clone(), encode_value(schema, value)));
+                    }
+                    if !node.annotations.is_empty() {
+                        members.push((format!("@{}", name), encode_annotations(&node.annotations)));
+                    }
+                }
+            }
+            SchemaKind::LeafList => {
+                let values = group
+                    .iter()
+                    .filter_map(|n| n.value())
+                    .map(|v| encode_value(schema, v))
+                    .collect();
+                members.push((name.clone(), JsonValue::Array(values)));
+                // RFC7952 5.2.3.  Adding Annotations to Leaf-List Instances
+                if group.iter().any(|n| !n.annotations.is_empty()) {
+                    let annotations = group
+                        .iter()
+                        .map(|n| {
+                            if n.annotations.is_empty() {
+                                JsonValue::Null
+                            } else {
+                                encode_annotations(&n.annotations)
+                            }
+                        })
+                        .collect();
+                    members.push((format!("@{}", name), JsonValue::Array(annotations)));
+                }
+            }
+        }
+    }
+    members
+}
+
+struct Decoder<'a> {
+    schema: &'a Schema,
+}
+
+impl<'a> Decoder<'a> {
+    fn object<'j>(
+        &self,
+        json: &'j JsonValue,
+        path: &str,
+    ) -> Result<&'j [(String, JsonValue)], DataError> {
+        match json {
+            JsonValue::Object(members) => Ok(members),
+            _ => Err(DataError::InvalidValue(
+                path.to_owned(),
+                json.to_string(),
+                String::from("not an object"),
+            )),
+        }
+    }
+
+    fn array<'j>(&self, json: &'j JsonValue, path: &str) -> Result<&'j [JsonValue], DataError> {
+        match json {
+            JsonValue::Array(values) => Ok(values),
+            _ => Err(DataError::InvalidValue(
+                path.to_owned(),
+                json.to_string(),
+                String::from("not an array"),
+            )),
+        }
+    }
+
+    fn annotations(&self, json: &JsonValue, path: &str) -> Result<Vec<Annotation>, DataError> {
+        let mut annotations = vec![];
+        for (name, value) in self.object(json, path)? {
+            let (module, name) = match split_name(name) {
+                (Some(module), name) => (module, name),
+                (None, name) => {
+                    return Err(DataError::InvalidValue(
+                        path.to_owned(),
+                        name.to_owned(),
+                        String::from("annotation name must be qualified by module name"),
+                    ))
+                }
+            };
+            let module = self.schema.resolve_module(module).unwrap_or(module);
+            let value = match value {
+                JsonValue::String(s) => s.clone(),
+                v => v.to_string(),
+            };
+            annotations.push(Annotation::new(module.to_owned(), name.to_owned(), value));
+        }
+        Ok(annotations)
+    }
+
+    fn any(&self, members: &[(String, JsonValue)], parent_module: &str) -> Vec<AnyNode> {
+        let mut nodes = vec![];
+        for (name, value) in members {
+            if name == "@" {
+                continue;
+            }
+            let (module, local) = split_name(name);
+            let module = module.unwrap_or(parent_module);
+            let values = match value {
+                JsonValue::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+            for value in values {
+                let mut node = AnyNode::new(local.to_owned());
+                node.module = Some(module.to_owned());
+                match value {
+                    JsonValue::Object(members) => node.children = self.any(members, module),
+                    JsonValue::String(s) => node.value = Some(s.clone()),
+                    JsonValue::Null => {}
+                    v => node.value = Some(v.to_string()),
+                }
+                nodes.push(node);
+            }
+        }
+        nodes
+    }
+
+    fn schema_node(
+        &self,
+        candidates: &[Rc<SchemaNode>],
+        parent_module: Option<&str>,
+        name: &str,
+        path: &str,
+    ) -> Result<Rc<SchemaNode>, DataError> {
+        let (module, local) = split_name(name);
+        let module = match module {
+            Some(module) => self.schema.resolve_module(module),
+            None => parent_module,
+        };
+        module
+            .and_then(|m| find_schema(candidates, Some(m), local))
+            .cloned()
+            .ok_or_else(|| DataError::UnknownNode(path.to_owned(), name.to_owned()))
+    }
+
+    fn nodes(
+        &self,
+        members: &[(String, JsonValue)],
+        candidates: &[Rc<SchemaNode>],
+        parent_module: Option<&str>,
+        path: &str,
+    ) -> Result<Vec<DataNode>, DataError> {
+        let mut nodes = vec![];
+        for (name, value) in members.iter().filter(|(n, _)| !n.starts_with('@')) {
+            let snode = self.schema_node(candidates, parent_module, name, path)?;
+            let path = format!("{}/{}", path, name);
+            match snode.kind {
+                SchemaKind::Container => {
+                    let node = self.inner(&snode, value, DataContent::Container, &path)?;
+                    insert_ordered(&mut nodes, candidates, node);
+                }
+                SchemaKind::List => {
+                    for entry in self.array(value, &path)? {
+                        let node = self.inner(&snode, entry, DataContent::ListEntry, &path)?;
+                        for key in snode.keys.iter() {
+                            if node.child(key).is_none() {
+                                return Err(DataError::MissingKey(path, key.clone()));
+                            }
+                        }
+                        insert_ordered(&mut nodes, candidates, node);
+                    }
+                }
+                SchemaKind::Leaf => {
+                    let value = decode_value(self.schema, &snode, value, &path)?;
+                    let node = DataNode::new(snode.clone(), DataContent::Leaf(value));
+                    insert_ordered(&mut nodes, candidates, node);
+                }
+                SchemaKind::LeafList => {
+                    for value in self.array(value, &path)? {
+                        let value = decode_value(self.schema, &snode, value, &path)?;
+                        let node = DataNode::new(snode.clone(), DataContent::LeafList(value));
+                        insert_ordered(&mut nodes, candidates, node);
+                    }
+                }
+                SchemaKind::Anydata => {
+                    let node = self.anydata(&snode, value, &path)?;
+                    insert_ordered(&mut nodes, candidates, node);
+                }
+            }
+        }
+
+        // Annotations of leaf and leaf-list are siblings prefixed by "@".
+        for (name, value) in members.iter().filter(|(n, _)| n.len() > 1) {
+            let name = match name.strip_prefix('@') {
+                Some(name) => name,
+                None => continue,
+            };
+            let snode = self.schema_node(candidates, parent_module, name, path)?;
+            let path = format!("{}/{}", path, name);
+            let mut targets = nodes.iter_mut().filter(|n| Rc::ptr_eq(&n.schema, &snode));
+            match snode.kind {
+                SchemaKind::Leaf => {
+                    if let Some(node) = targets.next() {
+                        node.annotations = self.annotations(value, &path)?;
+                    }
+                }
+                SchemaKind::LeafList => {
+                    for (node, value) in targets.zip(self.array(value, &path)?) {
+                        if value != &JsonValue::Null {
+                            node.annotations = self.annotations(value, &path)?;
+                        }
+                    }
+                }
+                _ => return Err(DataError::UnknownNode(path, format!("@{}", name))),
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn anydata(
+        &self,
+        snode: &Rc<SchemaNode>,
+        json: &JsonValue,
+        path: &str,
+    ) -> Result<DataNode, DataError> {
+        let members = self.object(json, path)?;
+        let any = self.any(members, &snode.module);
+        let mut node = DataNode::new(snode.clone(), DataContent::Anydata(any));
+        if let Some(annotations) = json.get("@") {
+            node.annotations = self.annotations(annotations, path)?;
+        }
+        Ok(node)
+    }
+
+    fn inner(
+        &self,
+        snode: &Rc<SchemaNode>,
+        json: &JsonValue,
+        content: fn(Vec<DataNode>) -> DataContent,
+        path: &str,
+    ) -> Result<DataNode, DataError> {
+        let members = self.object(json, path)?;
+        let children = self.nodes(members, &snode.children, Some(&snode.module), path)?;
+        let mut node = DataNode::new(snode.clone(), content(children));
+        if let Some(annotations) = json.get("@") {
+            node.annotations = self.annotations(annotations, path)?;
+        }
+        Ok(node)
+    }
+}
+
+// RFC7951 decoding of the content of container, list entry or anydata
+// "snode", which DataNode::to_json encodes.
+pub(crate) fn decode_inner(
+    schema: &Schema,
+    snode: &Rc<SchemaNode>,
+    json: &JsonValue,
+    path: &str,
+) -> Result<DataNode, DataError> {
+    let decoder = Decoder { schema };
+    match snode.kind {
+        SchemaKind::Container => decoder.inner(snode, json, DataContent::Container, path),
+        SchemaKind::List => decoder.inner(snode, json, DataContent::ListEntry, path),
+        SchemaKind::Anydata => decoder.anydata(snode, json, path),
+        SchemaKind::Leaf | SchemaKind::LeafList => Err(DataError::InvalidValue(
+            path.to_owned(),
+            json.to_string(),
+            String::from("not a container, list entry or anydata"),
+        )),
+    }
+}
+
+impl DataNode {
+    // RFC7951 encoding of the node's content.  Containers, list entries and
+    // anydata are encoded as objects, leaves and leaf-list values as
+    // scalars.
+    pub fn to_json(&self) -> JsonValue {
+        match &self.content {
+            DataContent::Leaf(value) | DataContent::LeafList(value) => {
+                encode_value(&self.schema, value)
+            }
+            _ => encode_inner(self),
+        }
+    }
+}
+
+impl DataTree {
+    // RFC7951 JSON encoding of the tree.
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::Object(encode_nodes(&self.nodes, None))
+    }
+
+    pub fn from_json(schema: Rc<Schema>, json: &JsonValue) -> Result<DataTree, DataError> {
+        let decoder = Decoder { schema: &schema };
+        let members = decoder.object(json, "")?;
+        let nodes = decoder.nodes(members, &schema.nodes, None, "")?;
+        Ok(DataTree { schema, nodes })
+    }
+
+    // Pretty printed JSON text.
+    pub fn json_encode(&self) -> String {
+        self.to_json().to_string_pretty()
+    }
+
+    pub fn json_decode(schema: Rc<Schema>, text: &str) -> Result<DataTree, DataError> {
+        DataTree::from_json(schema, &json_parse(text)?)
+    }
+}
@@ -0,0 +1,389 @@
+use crate::data::*;
+use crate::modules::*;
+
+// RFC8525 YANG Library.
+pub const YANG_LIBRARY_MODULE: &str = "ietf-yang-library";
+
+// Identities of the datastores in RFC8342 "ietf-datastores".
+pub const DATASTORE_RUNNING: &str = "ietf-datastores:running";
+pub const DATASTORE_CANDIDATE: &str = "ietf-datastores:candidate";
+pub const DATASTORE_STARTUP: &str = "ietf-datastores:startup";
+pub const DATASTORE_INTENDED: &str = "ietf-datastores:intended";
+pub const DATASTORE_OPERATIONAL: &str = "ietf-datastores:operational";
+
+// Submodule entry of the module.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct LibSubmodule {
+    pub name: String,
+    #[new(default)]
+    pub revision: Option<String>,
+    #[new(default)]
+    pub location: Vec<String>,
+}
+
+// Entry of "module" or "import-only-module" of the module set.  Features
+// and deviations are only for the implemented modules.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct LibModule {
+    pub name: String,
+    #[new(default)]
+    pub revision: Option<String>,
+    #[new(default)]
+    pub namespace: String,
+    #[new(default)]
+    pub location: Vec<String>,
+    #[new(default)]
+    pub submodules: Vec<LibSubmodule>,
+    #[new(default)]
+    pub features: Vec<String>,
+    #[new(default)]
+    pub deviations: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct LibModuleSet {
+    pub name: String,
+    #[new(default)]
+    pub modules: Vec<LibModule>,
+    #[new(default)]
+    pub import_only: Vec<LibModule>,
+}
+
+// Schema is the union of the module sets.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct LibSchema {
+    pub name: String,
+    #[new(default)]
+    pub module_sets: Vec<String>,
+}
+
+// Datastore identity such as "ietf-datastores:running" and its schema.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct LibDatastore {
+    pub name: String,
+    pub schema: String,
+}
+
+// Contents of the "yang-library" container.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct YangLibrary {
+    #[new(default)]
+    pub module_sets: Vec<LibModuleSet>,
+    #[new(default)]
+    pub schemas: Vec<LibSchema>,
+    #[new(default)]
+    pub datastores: Vec<LibDatastore>,
+    #[new(default)]
+    pub content_id: String,
+}
+
+fn lib_module(module: &Module) -> LibModule {
+    let mut lib = LibModule::new(module.name.to_owned());
+    lib.revision = module.revision().map(String::from);
+    lib.namespace = module.namespace.to_owned();
+    lib
+}
+
+// 64-bit FNV-1a hash, which is stable across builds.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl YangLibrary {
+    // Yang library of the modules with a single module set and schema
+    // "complete" for all of "datastores".  All of features of the
+    // implemented modules are listed since the schema is compiled regardless
+    // of "if-feature".
+    pub fn from_modules(ms: &Modules, datastores: &[&str]) -> Self {
+        let name = String::from("complete");
+        let mut set = LibModuleSet::new(name.clone());
+        for module in ms.implemented() {
+            let mut lib = lib_module(module);
+            for include in module.includes.iter() {
+                let mut sub = LibSubmodule::new(include.name.to_owned());
+                if !include.revision_date.is_empty() {
+                    sub.revision = Some(include.revision_date.to_owned());
+                }
+                lib.submodules.push(sub);
+            }
+            lib.features = module.features.keys().cloned().collect();
+            lib.features.sort();
+            lib.deviations = ms
+                .deviation_modules(&module.name)
+                .into_iter()
+                .map(String::from)
+                .collect();
+            set.modules.push(lib);
+        }
+        let mut import_only: Vec<&Module> = ms
+            .modules
+            .values()
+            .filter(|m| !ms.is_implemented(m))
+            .collect();
+        import_only.sort_by_key(|m| m.id());
+        set.import_only = import_only.into_iter().map(lib_module).collect();
+
+        let mut library = YangLibrary::new();
+        library.module_sets.push(set);
+        let mut schema = LibSchema::new(name.clone());
+        schema.module_sets.push(name.clone());
+        library.schemas.push(schema);
+        for datastore in datastores {
+            let datastore = LibDatastore::new(datastore.to_string(), name.clone());
+            library.datastores.push(datastore);
+        }
+        library.content_id = format!("{:016x}", fnv1a(&library.to_json().to_string()));
+        library
+    }
+
+    pub fn module_set(&self, name: &str) -> Option<&LibModuleSet> {
+        self.module_sets.iter().find(|s| s.name == name)
+    }
+
+    // Schema of the datastore such as "ietf-datastores:operational".
+    pub fn datastore_schema(&self, datastore: &str) -> Option<&LibSchema> {
+        let datastore = self.datastores.iter().find(|d| d.name == datastore)?;
+        self.schemas.iter().find(|s| s.name == datastore.schema)
+    }
+
+    // JSON instance document of "ietf-yang-library:yang-library".
+    pub fn to_json(&self) -> JsonValue {
+        let string = |s: &str| JsonValue::String(s.to_owned());
+        let strings = |v: &[String]| JsonValue::Array(v.iter().map(|s| string(s)).collect());
+        let module = |m: &LibModule, import_only: bool| {
+            let mut members = vec![(String::from("name"), string(&m.name))];
+            match &m.revision {
+                Some(revision) => members.push((String::from("revision"), string(revision))),
+                // Revision is a key of the import-only modules.
+                None if import_only => members.push((String::from("revision"), string(""))),
+                None => {}
+            }
+            members.push((String::from("namespace"), string(&m.namespace)));
+            if !m.location.is_empty() {
+                members.push((String::from("location"), strings(&m.location)));
+            }
+            if !m.submodules.is_empty() {
+                let subs = m.submodules.iter().map(|s| {
+                    let mut members = vec![(String::from("name"), string(&s.name))];
+                    if let Some(revision) = &s.revision {
+                        members.push((String::from("revision"), string(revision)));
+                    }
+                    if !s.location.is_empty() {
+                        members.push((String::from("location"), strings(&s.location)));
+                    }
+                    JsonValue::Object(members)
+                });
+                members.push((String::from("submodule"), JsonValue::Array(subs.collect())));
+            }
+            if !m.features.is_empty() {
+                members.push((String::from("feature"), strings(&m.features)));
+            }
+            if !m.deviations.is_empty() {
+                members.push((String::from("deviation"), strings(&m.deviations)));
+            }
+            JsonValue::Object(members)
+        };
+        let sets = self.module_sets.iter().map(|set| {
+            let mut members = vec![(String::from("name"), string(&set.name))];
+            if !set.modules.is_empty() {
+                let modules = set.modules.iter().map(|m| module(m, false));
+                members.push((String::from("module"), JsonValue::Array(modules.collect())));
+            }
+            if !set.import_only.is_empty() {
+                let modules = set.import_only.iter().map(|m| module(m, true));
+                members.push((
+                    String::from("import-only-module"),
+                    JsonValue::Array(modules.collect()),
+                ));
+            }
+            JsonValue::Object(members)
+        });
+        let schemas = self.schemas.iter().map(|schema| {
+            JsonValue::Object(vec![
+                (String::from("name"), string(&schema.name)),
+                (String::from("module-set"), strings(&schema.module_sets)),
+            ])
+        });
+        let datastores = self.datastores.iter().map(|datastore| {
+            JsonValue::Object(vec![
+                (String::from("name"), string(&datastore.name)),
+                (String::from("schema"), string(&datastore.schema)),
+            ])
+        });
+        let library = JsonValue::Object(vec![
+            (String::from("module-set"), JsonValue::Array(sets.collect())),
+            (String::from("schema"), JsonValue::Array(schemas.collect())),
+            (
+                String::from("datastore"),
+                JsonValue::Array(datastores.collect()),
+            ),
+            (String::from("content-id"), string(&self.content_id)),
+        ]);
+        JsonValue::Object(vec![(
+            format!("{}:yang-library", YANG_LIBRARY_MODULE),
+            library,
+        )])
+    }
+
+    // Parse the JSON instance document of "ietf-yang-library:yang-library".
+    pub fn from_json(value: &JsonValue) -> Result<Self, DataError> {
+        let top = format!("{}:yang-library", YANG_LIBRARY_MODULE);
+        let library = value
+            .get(&top)
+            .ok_or_else(|| DataError::NotFound(format!("/{}", top)))?;
+        let path = format!("/{}", top);
+
+        let mut result = YangLibrary::new();
+        for set in json_list(library, &path, "module-set")? {
+            let set_path = format!("{}/module-set", path);
+            let mut lib_set = LibModuleSet::new(json_string(set, &set_path, "name")?);
+            for m in json_list(set, &set_path, "module")? {
+                lib_set
+                    .modules
+                    .push(json_module(m, &format!("{}/module", set_path))?);
+            }
+            for m in json_list(set, &set_path, "import-only-module")? {
+                let module_path = format!("{}/import-only-module", set_path);
+                lib_set.import_only.push(json_module(m, &module_path)?);
+            }
+            result.module_sets.push(lib_set);
+        }
+        for schema in json_list(library, &path, "schema")? {
+            let schema_path = format!("{}/schema", path);
+            let mut lib_schema = LibSchema::new(json_string(schema, &schema_path, "name")?);
+            lib_schema.module_sets = json_strings(schema, &schema_path, "module-set")?;
+            result.schemas.push(lib_schema);
+        }
+        for datastore in json_list(library, &path, "datastore")? {
+            let datastore_path = format!("{}/datastore", path);
+            result.datastores.push(LibDatastore::new(
+                json_string(datastore, &datastore_path, "name")?,
+                json_string(datastore, &datastore_path, "schema")?,
+            ));
+        }
+        result.content_id = json_string(library, &path, "content-id")?;
+        Ok(result)
+    }
+}
+
+fn json_list<'a>(
+    value: &'a JsonValue,
+    path: &str,
+    name: &str,
+) -> Result<&'a [JsonValue], DataError> {
+    match value.get(name) {
+        Some(JsonValue::Array(values)) => Ok(values),
+        Some(v) => Err(DataError::InvalidValue(
+            format!("{}/{}", path, name),
+            v.to_string(),
+            String::from("list must be an array"),
+        )),
+        None => Ok(&[]),
+    }
+}
+
+fn json_string(value: &JsonValue, path: &str, name: &str) -> Result<String, DataError> {
+    match value.get(name) {
+        Some(JsonValue::String(s)) => Ok(s.to_owned()),
+        Some(v) => Err(DataError::InvalidValue(
+            format!("{}/{}", path, name),
+            v.to_string(),
+            String::from("must be a string"),
+        )),
+        None => Err(DataError::MissingValue(format!("{}/{}", path, name))),
+    }
+}
+
+fn json_strings(value: &JsonValue, path: &str, name: &str) -> Result<Vec<String>, DataError> {
+    let values = json_list(value, path, name)?;
+    values
+        .iter()
+        .map(|v| match v {
+            JsonValue::String(s) => Ok(s.to_owned()),
+            _ => Err(DataError::InvalidValue(
+                format!("{}/{}", path, name),
+                v.to_string(),
+                String::from("must be a string"),
+            )),
+        })
+        .collect()
+}
+
+fn json_module(value: &JsonValue, path: &str) -> Result<LibModule, DataError> {
+    let mut module = LibModule::new(json_string(value, path, "name")?);
+    module.revision = match value.get("revision") {
+        Some(_) => Some(json_string(value, path, "revision")?).filter(|r| !r.is_empty()),
+        None => None,
+    };
+    module.namespace = json_string(value, path, "namespace")?;
+    module.location = json_strings(value, path, "location")?;
+    for sub in json_list(value, path, "submodule")? {
+        let sub_path = format!("{}/submodule", path);
+        let mut lib_sub = LibSubmodule::new(json_string(sub, &sub_path, "name")?);
+        if sub.get("revision").is_some() {
+            lib_sub.revision = Some(json_string(sub, &sub_path, "revision")?);
+        }
+        lib_sub.location = json_strings(sub, &sub_path, "location")?;
+        module.submodules.push(lib_sub);
+    }
+    module.features = json_strings(value, path, "feature")?;
+    module.deviations = json_strings(value, path, "deviation")?;
+    Ok(module)
+}
+
+impl Modules {
+    // Load the modules of the yang library from the source.  The module
+    // sets of "schema", or all of the module sets when "schema" is None, are
+    // loaded with the exact revisions.  Locations in the library are not
+    // used, the modules are found in the source by name and revision.
+    pub fn load_library(
+        &mut self,
+        source: &dyn ModuleSource,
+        library: &YangLibrary,
+        schema: Option<&str>,
+    ) -> Result<(), ModuleError> {
+        let sets: Vec<&LibModuleSet> = match schema {
+            Some(name) => {
+                let schema = library
+                    .schemas
+                    .iter()
+                    .find(|s| s.name == name)
+                    .ok_or_else(|| ModuleError::UnknownSchema(name.to_owned()))?;
+                schema
+                    .module_sets
+                    .iter()
+                    .map(|set| {
+                        library
+                            .module_set(set)
+                            .ok_or_else(|| ModuleError::UnknownModuleSet(set.to_owned()))
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            None => library.module_sets.iter().collect(),
+        };
+        // Modules which are not listed as implemented, including the imports
+        // not listed in the library, are import-only.
+        let loaded: Vec<ModuleId> = self.modules.keys().cloned().collect();
+        let mut implemented = vec![];
+        for set in sets.iter() {
+            for lib in set.modules.iter() {
+                let revision = lib.revision.as_deref();
+                implemented.push(self.load_revision(source, &lib.name, revision)?.id());
+            }
+        }
+        for set in sets.iter() {
+            for lib in set.import_only.iter() {
+                self.load_revision(source, &lib.name, lib.revision.as_deref())?;
+            }
+        }
+        let ids: Vec<ModuleId> = self.modules.keys().cloned().collect();
+        for id in ids {
+            if !loaded.contains(&id) && !implemented.contains(&id) {
+                self.import_only.insert(id);
+            }
+        }
+        Ok(())
+    }
+}
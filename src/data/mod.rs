@@ -0,0 +1,36 @@
+pub mod compat;
+pub mod default;
+pub mod diff;
+pub mod document;
+pub mod edit;
+pub mod filter;
+pub mod gnmi;
+pub mod json;
+pub mod json_codec;
+pub mod library;
+pub mod path;
+pub mod restconf;
+pub mod restriction;
+pub mod schema;
+pub mod schema_diff;
+pub mod tree;
+pub mod validate;
+pub mod value;
+pub mod xml;
+pub mod xml_codec;
+pub use compat::*;
+pub use diff::*;
+pub use edit::*;
+pub use filter::*;
+pub use gnmi::*;
+pub use json::*;
+pub use library::*;
+pub use path::*;
+pub use restconf::*;
+pub use schema::*;
+pub use schema_diff::*;
+pub use tree::*;
+pub use validate::*;
+pub use value::*;
+pub use xml::*;
+pub use xml_codec::*;
@@ -0,0 +1,468 @@
+use crate::data::*;
+use crate::parser::identifier;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_until};
+use nom::character::complete::{char, multispace0};
+use nom::combinator::opt;
+use nom::multi::{many0, many1};
+use nom::sequence::{delimited, terminated};
+use nom::IResult;
+use std::fmt;
+use std::rc::Rc;
+
+// Segment of data path such as "ietf-interfaces:interface[name='eth0']".
+// "prefix" is module name or prefix of the module.  Predicates are pairs
+// of key leaf name and value, "." for leaf-list value.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct PathSegment {
+    pub prefix: Option<String>,
+    pub name: String,
+    #[new(default)]
+    pub predicates: Vec<(String, String)>,
+}
+
+fn quoted_parse(s: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(char('\''), take_until("'"), char('\'')),
+        delimited(char('"'), take_until("\""), char('"')),
+    ))(s)
+}
+
+fn predicate_parse(s: &str) -> IResult<&str, (String, String)> {
+    let (s, _) = char('[')(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, name) = alt((tag("."), |s| {
+        // Key name may have prefix which is ignored.
+        let (s, _) = opt(terminated(identifier, char(':')))(s)?;
+        identifier(s)
+    }))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('=')(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, value) = quoted_parse(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char(']')(s)?;
+    Ok((s, (name.to_owned(), value.to_owned())))
+}
+
+fn segment_parse(s: &str) -> IResult<&str, PathSegment> {
+    let (s, _) = char('/')(s)?;
+    let (s, prefix) = opt(terminated(identifier, char(':')))(s)?;
+    let (s, name) = identifier(s)?;
+    let (s, predicates) = many0(predicate_parse)(s)?;
+    let mut segment = PathSegment::new(prefix.map(String::from), name.to_owned());
+    segment.predicates = predicates;
+    Ok((s, segment))
+}
+
+// Parse absolute data path such as
+// "/ietf-interfaces:interfaces/interface[name='eth0']/mtu".
+pub fn data_path_parse(path: &str) -> Result<Vec<PathSegment>, DataError> {
+    match many1(segment_parse)(path) {
+        Ok(("", segments)) => Ok(segments),
+        _ => Err(DataError::InvalidPath(path.to_owned())),
+    }
+}
+
+// Predicate value quoted by the quote which doesn't appear in the value.
+fn quote(value: &str) -> String {
+    if value.contains('\'') {
+        format!("\"{}\"", value)
+    } else {
+        format!("'{}'", value)
+    }
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/")?;
+        if let Some(prefix) = &self.prefix {
+            write!(f, "{}:", prefix)?;
+        }
+        write!(f, "{}", self.name)?;
+        for (name, value) in self.predicates.iter() {
+            write!(f, "[{}={}]", name, quote(value))?;
+        }
+        Ok(())
+    }
+}
+
+impl PathSegment {
+    // Segment of the data node, qualified by the module name when the
+    // parent is in a different module or is the root.
+    pub fn of(node: &DataNode, parent_module: Option<&str>) -> Self {
+        let schema = node.schema();
+        let prefix = match parent_module {
+            Some(m) if m == schema.module => None,
+            _ => Some(schema.module.clone()),
+        };
+        let mut segment = PathSegment::new(prefix, schema.name.clone());
+        match &node.content {
+            DataContent::ListEntry(_) => {
+                for key in schema.keys.iter() {
+                    if let Some(value) = node.child(key).and_then(|c| c.value()) {
+                        segment.predicates.push((key.clone(), value.to_string()));
+                    }
+                }
+            }
+            DataContent::LeafList(value) => {
+                segment
+                    .predicates
+                    .push((String::from("."), value.to_string()));
+            }
+            _ => {}
+        }
+        segment
+    }
+}
+
+// Instance path of the node in the JSON form under the parent path.
+pub fn instance_path(parent: &str, parent_module: Option<&str>, node: &DataNode) -> String {
+    format!("{}{}", parent, PathSegment::of(node, parent_module))
+}
+
+// Path of the segments, the empty string for the root.
+pub fn data_path_string(segments: &[PathSegment]) -> String {
+    segments.iter().map(|s| s.to_string()).collect()
+}
+
+// RFC7950 6.5.  Schema Node Identifier
+//     A schema node identifier is a string that identifies a node in the
+//     schema tree.  It has two forms, "absolute" and "descendant",
+//     defined by the rules "absolute-schema-nodeid" and
+//     "descendant-schema-nodeid" in Section 14, respectively.
+//
+// Segments of the schema node identifier have no predicates.  "absolute"
+// is false for the descendant form.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct SchemaNodeId {
+    pub absolute: bool,
+    pub segments: Vec<PathSegment>,
+}
+
+fn node_identifier_parse(s: &str) -> IResult<&str, PathSegment> {
+    let (s, prefix) = opt(terminated(identifier, char(':')))(s)?;
+    let (s, name) = identifier(s)?;
+    Ok((
+        s,
+        PathSegment::new(prefix.map(String::from), name.to_owned()),
+    ))
+}
+
+fn absolute_nodeid_parse(s: &str) -> IResult<&str, Vec<PathSegment>> {
+    many1(|s| {
+        let (s, _) = char('/')(s)?;
+        node_identifier_parse(s)
+    })(s)
+}
+
+// Parse absolute schema node identifier such as
+// "/if:interfaces/if:interface/if:name", or descendant schema node
+// identifier such as "if:interface/if:name".
+pub fn schema_nodeid_parse(s: &str) -> Result<SchemaNodeId, DataError> {
+    let result = if s.starts_with('/') {
+        absolute_nodeid_parse(s).map(|(rest, segments)| (rest, SchemaNodeId::new(true, segments)))
+    } else {
+        node_identifier_parse(s).and_then(|(rest, first)| {
+            let (rest, mut segments) = many0(|s| {
+                let (s, _) = char('/')(s)?;
+                node_identifier_parse(s)
+            })(rest)?;
+            segments.insert(0, first);
+            Ok((rest, SchemaNodeId::new(false, segments)))
+        })
+    };
+    match result {
+        Ok(("", nodeid)) => Ok(nodeid),
+        _ => Err(DataError::InvalidPath(s.to_owned())),
+    }
+}
+
+impl fmt::Display for SchemaNodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = data_path_string(&self.segments);
+        match self.absolute {
+            true => write!(f, "{}", path),
+            false => write!(f, "{}", &path[1..]),
+        }
+    }
+}
+
+// RFC3986 2.3.  Unreserved Characters
+fn is_unreserved(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b"-._~".contains(&c)
+}
+
+// Percent-encode the characters other than the unreserved characters.
+pub fn percent_encode(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.bytes() {
+        if is_unreserved(c) {
+            out.push(c as char);
+        } else {
+            out.push_str(&format!("%{:02X}", c));
+        }
+    }
+    out
+}
+
+pub fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut rest = s.as_bytes();
+    while let Some((&c, tail)) = rest.split_first() {
+        if c == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(c);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+impl Schema {
+    // Schema nodes of the segments of data path or absolute schema node
+    // identifier.  Prefix is module name or prefix of the module, segment
+    // without prefix is in the module of the parent.  Predicates are not
+    // checked.
+    pub fn resolve_path(&self, segments: &[PathSegment]) -> Result<Vec<Rc<SchemaNode>>, DataError> {
+        let mut nodes: Vec<Rc<SchemaNode>> = vec![];
+        let mut path = String::new();
+        for segment in segments.iter() {
+            let unknown = || DataError::UnknownNode(path.clone(), segment.name.clone());
+            let parent = nodes.last();
+            let module = match &segment.prefix {
+                Some(prefix) => Some(self.resolve_module(prefix).ok_or_else(unknown)?),
+                None => parent.map(|p| p.module.as_str()),
+            };
+            let candidates = parent.map_or(&self.nodes, |p| &p.children);
+            let node = find_schema(candidates, module, &segment.name)
+                .ok_or_else(unknown)?
+                .clone();
+            path.push_str(&segment.to_string());
+            nodes.push(node);
+        }
+        Ok(nodes)
+    }
+
+    // Schema node of data path or absolute schema node identifier.
+    pub fn find_path(&self, path: &str) -> Result<Rc<SchemaNode>, DataError> {
+        let segments = data_path_parse(path)?;
+        let nodes = self.resolve_path(&segments)?;
+        Ok(nodes[nodes.len() - 1].clone())
+    }
+
+    // Schema node of descendant schema node identifier under "parent".
+    pub fn find_descendant(
+        &self,
+        parent: &SchemaNode,
+        nodeid: &SchemaNodeId,
+    ) -> Result<Rc<SchemaNode>, DataError> {
+        let mut node: Option<Rc<SchemaNode>> = None;
+        for segment in nodeid.segments.iter() {
+            let current = node.as_deref().unwrap_or(parent);
+            let unknown = || DataError::UnknownNode(current.name.clone(), segment.name.clone());
+            let module = match &segment.prefix {
+                Some(prefix) => self.resolve_module(prefix).ok_or_else(unknown)?,
+                None => &current.module,
+            };
+            node = Some(
+                current
+                    .child(Some(module), &segment.name)
+                    .ok_or_else(unknown)?
+                    .clone(),
+            );
+        }
+        node.ok_or_else(|| DataError::InvalidPath(nodeid.to_string()))
+    }
+
+    // Absolute schema node identifier of data path, each node qualified by
+    // the prefix of its module.
+    pub fn schema_path(&self, data_path: &str) -> Result<SchemaNodeId, DataError> {
+        let segments = data_path_parse(data_path)?;
+        let segments = self
+            .resolve_path(&segments)?
+            .iter()
+            .map(|node| {
+                let prefix = self.module(&node.module).map(|m| m.prefix.clone());
+                PathSegment::new(prefix, node.name.clone())
+            })
+            .collect();
+        Ok(SchemaNodeId::new(true, segments))
+    }
+
+    // RFC8040 3.5.3.  Encoding Data Resource Identifiers in the Request URI
+    //     api-path = root *("/" (api-identifier / list-instance))
+    //
+    // RESTCONF data resource identifier of data path.  Nodes are qualified
+    // by the module name when the parent is in a different module or is
+    // the root, and key values are in key order.
+    pub fn restconf_path(&self, data_path: &str) -> Result<String, DataError> {
+        let segments = data_path_parse(data_path)?;
+        let nodes = self.resolve_path(&segments)?;
+        let mut out = String::new();
+        let mut parent_module: Option<&str> = None;
+        for (segment, node) in segments.iter().zip(nodes.iter()) {
+            out.push('/');
+            if parent_module != Some(node.module.as_str()) {
+                out.push_str(&format!("{}:", node.module));
+            }
+            out.push_str(&node.name);
+            parent_module = Some(&node.module);
+
+            let predicate = |name: &str| {
+                segment
+                    .predicates
+                    .iter()
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| v.as_str())
+            };
+            let values = match node.kind {
+                SchemaKind::List if !segment.predicates.is_empty() => node
+                    .keys
+                    .iter()
+                    .map(|key| {
+                        predicate(key)
+                            .ok_or_else(|| DataError::MissingKey(data_path.to_owned(), key.clone()))
+                    })
+                    .collect::<Result<Vec<&str>, DataError>>()?,
+                SchemaKind::LeafList => predicate(".").into_iter().collect(),
+                _ => vec![],
+            };
+            if !values.is_empty() {
+                let values: Vec<String> = values.iter().map(|v| percent_encode(v)).collect();
+                out.push_str(&format!("={}", values.join(",")));
+            }
+        }
+        Ok(out)
+    }
+
+    // Data path in the JSON form of RESTCONF data resource identifier.
+    pub fn restconf_data_path(&self, api_path: &str) -> Result<String, DataError> {
+        let invalid = || DataError::InvalidPath(api_path.to_owned());
+        let rest = api_path.strip_prefix('/').ok_or_else(invalid)?;
+        if rest.is_empty() {
+            return Ok(String::new());
+        }
+        let mut segments = vec![];
+        let mut parent: Option<Rc<SchemaNode>> = None;
+        for part in rest.split('/') {
+            let (identifier, values) = match part.split_once('=') {
+                Some((identifier, values)) => (identifier, Some(values)),
+                None => (part, None),
+            };
+            let (module, name) = match identifier.split_once(':') {
+                Some((module, name)) => (Some(module), name),
+                None => (None, identifier),
+            };
+            let path = data_path_string(&segments);
+            let unknown = || DataError::UnknownNode(path.clone(), name.to_owned());
+            // RFC8040 3.5.3.  If the data node is a top-level node, or if
+            // it is defined in a different module than its parent, the
+            // module name MUST be used.
+            let module = match (module, &parent) {
+                (Some(module), _) => self.module(module).ok_or_else(unknown)?.name.as_str(),
+                (None, Some(p)) => p.module.as_str(),
+                (None, None) => return Err(unknown()),
+            };
+            let candidates = parent.as_ref().map_or(&self.nodes, |p| &p.children);
+            let node = find_schema(candidates, Some(module), name)
+                .ok_or_else(unknown)?
+                .clone();
+
+            let prefix = match &parent {
+                Some(p) if p.module == node.module => None,
+                _ => Some(node.module.clone()),
+            };
+            let mut segment = PathSegment::new(prefix, node.name.clone());
+            if let Some(values) = values {
+                let values = values
+                    .split(',')
+                    .map(percent_decode)
+                    .collect::<Option<Vec<String>>>()
+                    .ok_or_else(invalid)?;
+                match node.kind {
+                    SchemaKind::List if values.len() == node.keys.len() => {
+                        segment.predicates = node.keys.iter().cloned().zip(values).collect();
+                    }
+                    SchemaKind::LeafList if values.len() == 1 => {
+                        segment.predicates = vec![(String::from("."), values[0].clone())];
+                    }
+                    _ => return Err(invalid()),
+                }
+            }
+            segments.push(segment);
+            parent = Some(node);
+        }
+        Ok(data_path_string(&segments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_path_parse_test() {
+        let segments =
+            data_path_parse("/if:interfaces/interface[name='eth0']/ipv4/address[ip=\"10.0.0.1\"]")
+                .unwrap();
+        assert_eq!(segments.len(), 4);
+        assert_eq!(segments[0].prefix, Some(String::from("if")));
+        assert_eq!(segments[0].name, "interfaces");
+        assert_eq!(
+            segments[1].predicates,
+            vec![(String::from("name"), String::from("eth0"))]
+        );
+        assert_eq!(
+            segments[3].predicates,
+            vec![(String::from("ip"), String::from("10.0.0.1"))]
+        );
+
+        let segments = data_path_parse("/ex:list[a='1'][ex:b = '2']/tags[.='x y']").unwrap();
+        assert_eq!(segments[0].predicates.len(), 2);
+        assert_eq!(segments[0].predicates[1].0, "b");
+        assert_eq!(
+            segments[1].predicates,
+            vec![(String::from("."), String::from("x y"))]
+        );
+
+        let path = "/ex:list[a='1'][b=\"it's\"]/tags[.='x y']";
+        assert_eq!(data_path_string(&data_path_parse(path).unwrap()), path);
+
+        assert!(data_path_parse("interfaces").is_err());
+        assert!(data_path_parse("/interfaces[name]").is_err());
+        assert!(data_path_parse("/").is_err());
+    }
+
+    #[test]
+    fn schema_nodeid_parse_test() {
+        let nodeid = schema_nodeid_parse("/if:interfaces/if:interface/if:name").unwrap();
+        assert!(nodeid.absolute);
+        assert_eq!(nodeid.segments.len(), 3);
+        assert_eq!(nodeid.segments[2].prefix, Some(String::from("if")));
+        assert_eq!(nodeid.to_string(), "/if:interfaces/if:interface/if:name");
+
+        let nodeid = schema_nodeid_parse("interface/ip:ipv4").unwrap();
+        assert!(!nodeid.absolute);
+        assert_eq!(nodeid.segments[0].prefix, None);
+        assert_eq!(nodeid.to_string(), "interface/ip:ipv4");
+
+        assert!(schema_nodeid_parse("/if:interfaces/").is_err());
+        assert!(schema_nodeid_parse("/if:interface[name='eth0']").is_err());
+        assert!(schema_nodeid_parse("").is_err());
+    }
+
+    #[test]
+    fn percent_encode_test() {
+        assert_eq!(percent_encode("eth0/1,a b:é"), "eth0%2F1%2Ca%20b%3A%C3%A9");
+        assert_eq!(
+            percent_decode("eth0%2F1%2Ca%20b%3A%C3%A9").unwrap(),
+            "eth0/1,a b:é"
+        );
+        assert_eq!(percent_decode("a%2"), None);
+        assert_eq!(percent_decode("%FF"), None);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::data::json_codec::encode_nodes;
+use crate::data::*;
+use crate::parser::identifier;
+use nom::character::complete::char;
+use nom::combinator::opt;
+use nom::multi::separated_nonempty_list;
+use nom::sequence::{delimited, terminated};
+use nom::IResult;
+
+// RFC8040 3.1.  Root Resource Discovery
+pub const RESTCONF_ROOT: &str = "/restconf";
+
+// RFC8040 4.8.1.  The "content" Query Parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContentParam {
+    All,
+    Config,
+    Nonconfig,
+}
+
+// RFC8040 4.8.9.  The "with-defaults" Query Parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WithDefaults {
+    ReportAll,
+    Trim,
+    Explicit,
+    ReportAllTagged,
+}
+
+// Node of "fields" expression.  Node without children selects the node
+// and all its descendants.  Node without module is in the module of the
+// parent.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct FieldNode {
+    pub module: Option<String>,
+    pub name: String,
+    #[new(default)]
+    pub children: Vec<FieldNode>,
+}
+
+// Query parameters of GET on data resources.  "depth" None is
+// "unbounded" and "fields" empty selects all the nodes.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct QueryParams {
+    #[new(value = "ContentParam::All")]
+    pub content: ContentParam,
+    #[new(default)]
+    pub depth: Option<u16>,
+    #[new(default)]
+    pub fields: Vec<FieldNode>,
+    #[new(default)]
+    pub with_defaults: Option<WithDefaults>,
+}
+
+// Data resource URI.  "path" is the data path in the JSON form, the empty
+// string for the datastore resource.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct RestconfUri {
+    pub path: String,
+    pub query: QueryParams,
+}
+
+// RFC8040 4.8.3.  The "fields" Query Parameter
+//     fields-expr = path "(" fields-expr ")" / path ";" fields-expr / path
+//     path = api-identifier [ "/" path ]
+fn field_parse(s: &str) -> IResult<&str, FieldNode> {
+    let (s, module) = opt(terminated(identifier, char(':')))(s)?;
+    let (s, name) = identifier(s)?;
+    let mut node = FieldNode::new(module.map(String::from), name.to_owned());
+    let (s, child) = opt(|s| {
+        let (s, _) = char('/')(s)?;
+        field_parse(s)
+    })(s)?;
+    if let Some(child) = child {
+        node.children.push(child);
+        return Ok((s, node));
+    }
+    let (s, children) = opt(delimited(char('('), fields_parse, char(')')))(s)?;
+    node.children = children.unwrap_or_default();
+    Ok((s, node))
+}
+
+fn fields_parse(s: &str) -> IResult<&str, Vec<FieldNode>> {
+    separated_nonempty_list(char(';'), field_parse)(s)
+}
+
+fn invalid_param(name: &str, value: &str, reason: &str) -> DataError {
+    DataError::InvalidValue(format!("?{}", name), value.to_owned(), reason.to_owned())
+}
+
+impl QueryParams {
+    // Parse the query component of URI without "?".
+    pub fn parse(query: &str) -> Result<Self, DataError> {
+        let mut params = QueryParams::new();
+        let mut seen: Vec<&str> = vec![];
+        for param in query.split('&').filter(|p| !p.is_empty()) {
+            let (name, value) = param.split_once('=').unwrap_or((param, ""));
+            let value = percent_decode(value)
+                .ok_or_else(|| invalid_param(name, value, "invalid percent-encoding"))?;
+            let invalid = |reason: &str| invalid_param(name, &value, reason);
+            // RFC8040 4.8.  Query Parameters
+            //     Query parameters can be given in any order.  Each
+            //     parameter can appear at most once in a request URI.
+            if seen.contains(&name) {
+                return Err(invalid("duplicate parameter"));
+            }
+            seen.push(name);
+            match name {
+                "content" => {
+                    params.content = match value.as_str() {
+                        "all" => ContentParam::All,
+                        "config" => ContentParam::Config,
+                        "nonconfig" => ContentParam::Nonconfig,
+                        _ => return Err(invalid("unknown content")),
+                    }
+                }
+                // RFC8040 4.8.2.  The "depth" Query Parameter
+                //     ["1".."65535" / "unbounded"]
+                "depth" => {
+                    params.depth = match value.as_str() {
+                        "unbounded" => None,
+                        v => match v.parse::<u16>() {
+                            Ok(depth) if depth > 0 && !v.starts_with('+') => Some(depth),
+                            _ => return Err(invalid("depth must be 1..65535 or unbounded")),
+                        },
+                    }
+                }
+                "fields" => {
+                    params.fields = match fields_parse(&value) {
+                        Ok(("", fields)) => fields,
+                        _ => return Err(invalid("invalid fields expression")),
+                    }
+                }
+                "with-defaults" => {
+                    params.with_defaults = Some(match value.as_str() {
+                        "report-all" => WithDefaults::ReportAll,
+                        "trim" => WithDefaults::Trim,
+                        "explicit" => WithDefaults::Explicit,
+                        "report-all-tagged" => WithDefaults::ReportAllTagged,
+                        _ => return Err(invalid("unknown with-defaults mode")),
+                    })
+                }
+                _ => return Err(invalid("unsupported parameter")),
+            }
+        }
+        Ok(params)
+    }
+}
+
+impl RestconfUri {
+    // Parse the path and query of URI of data resource such as
+    // "/restconf/data/ietf-interfaces:interfaces/interface=eth0?depth=2".
+    pub fn parse(schema: &Schema, uri: &str) -> Result<Self, DataError> {
+        let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+        let api_path = path
+            .strip_prefix(RESTCONF_ROOT)
+            .and_then(|p| p.strip_prefix("/data"))
+            .ok_or_else(|| DataError::InvalidPath(path.to_owned()))?;
+        let path = match api_path {
+            "" | "/" => String::new(),
+            _ => schema.restconf_data_path(api_path)?,
+        };
+        Ok(RestconfUri::new(path, QueryParams::parse(query)?))
+    }
+}
+
+// Selection of the nodes by the query parameters.
+struct Query<'a> {
+    params: &'a QueryParams,
+}
+
+impl<'a> Query<'a> {
+    fn field_matches(field: &FieldNode, node: &DataNode, parent_module: Option<&str>) -> bool {
+        node.name() == field.name
+            && field
+                .module
+                .as_deref()
+                .or(parent_module)
+                .is_none_or(|m| m == node.schema().module)
+    }
+
+    fn content_matches(&self, node: &DataNode) -> bool {
+        match self.params.content {
+            ContentParam::All => true,
+            ContentParam::Config => node.schema().config,
+            ContentParam::Nonconfig => !node.schema().config,
+        }
+    }
+
+    // Selected copies of "nodes" at depth "level".  "fields" None selects
+    // all the nodes.
+    //
+    // RFC8040 4.8.2.  The "depth" Query Parameter
+    //     If the "fields" parameter (Section 4.8.3) is used to select
+    //     descendant data nodes, then these nodes and all of their ancestors
+    //     have a "depth" value of "1".
+    fn nodes(
+        &self,
+        nodes: &[DataNode],
+        fields: Option<&[FieldNode]>,
+        parent_module: Option<&str>,
+        level: u16,
+    ) -> Vec<DataNode> {
+        let mut result = vec![];
+        for node in nodes {
+            let (children_fields, level) = match fields {
+                None => (None, level),
+                Some(fields) => {
+                    let matched: Vec<&FieldNode> = fields
+                        .iter()
+                        .filter(|f| Query::field_matches(f, node, parent_module))
+                        .collect();
+                    if matched.is_empty() {
+                        continue;
+                    }
+                    if matched.iter().any(|f| f.children.is_empty()) {
+                        (None, 1)
+                    } else {
+                        let children = matched.iter().flat_map(|f| f.children.clone());
+                        (Some(children.collect::<Vec<FieldNode>>()), 1)
+                    }
+                }
+            };
+            if self.params.depth.is_some_and(|depth| level > depth) {
+                continue;
+            }
+            if let Some(node) = self.node(node, children_fields.as_deref(), level) {
+                result.push(node);
+            }
+        }
+        result
+    }
+
+    // Containers and list entries are kept when they are selected by the
+    // "content" parameter or have the selected descendants.  List entries
+    // always have their keys.
+    fn node(&self, node: &DataNode, fields: Option<&[FieldNode]>, level: u16) -> Option<DataNode> {
+        let schema = node.schema().clone();
+        let children = match &node.content {
+            DataContent::Container(children) | DataContent::ListEntry(children) => children,
+            _ if self.content_matches(node) => return Some(node.clone()),
+            _ => return None,
+        };
+        let next = match fields {
+            Some(_) => 1,
+            None => level.saturating_add(1),
+        };
+        let mut selected = self.nodes(children, fields, Some(&schema.module), next);
+        if selected.is_empty() && (fields.is_some() || !self.content_matches(node)) {
+            return None;
+        }
+        for key in schema.keys.iter().rev() {
+            if !selected.iter().any(|n| n.name() == key) {
+                if let Some(leaf) = node.child(key) {
+                    selected.insert(0, leaf.clone());
+                }
+            }
+        }
+        let mut copy = node.clone();
+        if let Some(children) = copy.children_mut() {
+            *children = vec![];
+            for child in selected {
+                insert_ordered(children, &schema.children, child);
+            }
+        }
+        Some(copy)
+    }
+}
+
+impl DataTree {
+    // RFC8040 4.3.  GET
+    //
+    // JSON message body of the response to GET on the data resource.  The
+    // target node is the member of the top level object, and the
+    // datastore resource is "ietf-restconf:data".
+    pub fn restconf_get(&self, uri: &RestconfUri) -> Result<JsonValue, DataError> {
+        let mut tree = self.clone();
+        match uri.query.with_defaults {
+            Some(WithDefaults::ReportAll) => tree.fill_defaults(),
+            Some(WithDefaults::ReportAllTagged) => {
+                tree.fill_defaults();
+                tree.tag_defaults();
+            }
+            Some(WithDefaults::Trim) => tree.trim_defaults(),
+            Some(WithDefaults::Explicit) | None => {}
+        }
+
+        let query = Query { params: &uri.query };
+        let fields = match uri.query.fields.is_empty() {
+            true => None,
+            false => Some(uri.query.fields.as_slice()),
+        };
+        if uri.path.is_empty() {
+            let nodes = query.nodes(&tree.nodes, fields, None, 1);
+            let data = JsonValue::Object(encode_nodes(&nodes, None));
+            return Ok(JsonValue::Object(vec![(
+                String::from("ietf-restconf:data"),
+                data,
+            )]));
+        }
+        let target = tree.get(&uri.path)?;
+        let node = match fields {
+            // Fields are the descendants of the target.
+            Some(_) => query.node(target, fields, 1),
+            None => query
+                .nodes(std::slice::from_ref(target), None, None, 1)
+                .pop(),
+        };
+        let node = node.ok_or_else(|| DataError::NotFound(uri.path.clone()))?;
+        Ok(JsonValue::Object(encode_nodes(&[node], None)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_params_parse_test() {
+        let params =
+            QueryParams::parse("depth=2&content=nonconfig&fields=a:b(c;d/e);f&with-defaults=trim")
+                .unwrap();
+        assert_eq!(params.depth, Some(2));
+        assert_eq!(params.content, ContentParam::Nonconfig);
+        assert_eq!(params.with_defaults, Some(WithDefaults::Trim));
+        let mut b = FieldNode::new(Some(String::from("a")), String::from("b"));
+        let mut d = FieldNode::new(None, String::from("d"));
+        d.children.push(FieldNode::new(None, String::from("e")));
+        b.children = vec![FieldNode::new(None, String::from("c")), d];
+        assert_eq!(
+            params.fields,
+            vec![b, FieldNode::new(None, String::from("f"))]
+        );
+
+        // Percent-encoded value.
+        let params = QueryParams::parse("fields=a%28b%3Bc%29").unwrap();
+        assert_eq!(params.fields[0].children.len(), 2);
+        assert_eq!(QueryParams::parse("").unwrap(), QueryParams::new());
+        assert_eq!(QueryParams::parse("depth=unbounded").unwrap().depth, None);
+
+        for query in &[
+            "depth=0",
+            "depth=65536",
+            "depth=+1",
+            "content=state",
+            "fields=a(b",
+            "fields=a;",
+            "with-defaults=all",
+            "depth=1&depth=2",
+            "filter=a",
+        ] {
+            assert!(QueryParams::parse(query).is_err(), "{}", query);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use crate::data::value::parse_decimal64;
+use crate::data::*;
+use crate::modules::{pattern_regex, Range, RangeUint, RangeVal, TypeKind, TypeNode};
+use crate::parser::{range_int_parse, range_uint_parse};
+
+// Bounds of a range part, a single value has no end.
+fn bounds<T: Copy>(range: &Range<T>, min: T, max: T) -> (T, T) {
+    let value = |v: &RangeVal<T>, none: T| match v {
+        RangeVal::Min => min,
+        RangeVal::Max => max,
+        RangeVal::Val(v) => *v,
+        RangeVal::None => none,
+    };
+    let start = value(&range.start, min);
+    (start, value(&range.end, start))
+}
+
+fn in_ranges<T: Copy + PartialOrd>(ranges: &[Range<T>], min: T, max: T, v: T) -> bool {
+    ranges.iter().any(|r| {
+        let (start, end) = bounds(r, min, max);
+        start <= v && v <= end
+    })
+}
+
+fn int_bounds(kind: TypeKind) -> (i64, i64) {
+    match kind {
+        TypeKind::Yint8 => (i8::MIN as i64, i8::MAX as i64),
+        TypeKind::Yint16 => (i16::MIN as i64, i16::MAX as i64),
+        TypeKind::Yint32 => (i32::MIN as i64, i32::MAX as i64),
+        _ => (i64::MIN, i64::MAX),
+    }
+}
+
+fn uint_bounds(kind: TypeKind) -> u64 {
+    match kind {
+        TypeKind::Yuint8 => u8::MAX as u64,
+        TypeKind::Yuint16 => u16::MAX as u64,
+        TypeKind::Yuint32 => u32::MAX as u64,
+        _ => u64::MAX,
+    }
+}
+
+// Decimal64 range such as "-1.5 .. 2.25 | 10" scaled by the fraction
+// digits.  The range of typedef reference is not parsed by the type parser
+// since the base type is not known there.
+fn decimal_intervals(range: &str, digits: u8) -> Option<Vec<(i64, i64)>> {
+    let scaled = |s: &str| match s.trim() {
+        "min" => Some(i64::MIN),
+        "max" => Some(i64::MAX),
+        s => match parse_decimal64(digits, s) {
+            Ok(LeafValue::Decimal64(v, _)) => Some(v),
+            _ => None,
+        },
+    };
+    let mut intervals = vec![];
+    for part in range.split('|') {
+        intervals.push(match part.split_once("..") {
+            Some((start, end)) => (scaled(start)?, scaled(end)?),
+            None => (scaled(part)?, scaled(part)?),
+        });
+    }
+    Some(intervals)
+}
+
+fn in_decimal_range(range: &str, digits: u8, v: i64) -> Option<bool> {
+    let intervals = decimal_intervals(range, digits)?;
+    Some(
+        intervals
+            .iter()
+            .any(|(start, end)| *start <= v && v <= *end),
+    )
+}
+
+// Intervals of the value space allowed by "range", or by "length" when
+// "length" is true.  The whole value space of the type when the
+// restriction is None.  None when the restriction can't be parsed or the
+// type has no such restriction.
+pub(crate) fn restriction_intervals(
+    typ: &TypeNode,
+    restriction: Option<&str>,
+    length: bool,
+) -> Option<Vec<(i128, i128)>> {
+    let widen = |r: Vec<(i64, i64)>| r.into_iter().map(|(s, e)| (s as i128, e as i128)).collect();
+    let uint = |r: &[RangeUint], max: u64| {
+        r.iter()
+            .map(|r| bounds(r, 0, max))
+            .map(|(s, e)| (s as i128, e as i128))
+            .collect()
+    };
+    if length {
+        return match restriction {
+            Some(length) => Some(uint(&range_uint_parse(length).ok()?.1, u64::MAX)),
+            None => Some(vec![(0, u64::MAX as i128)]),
+        };
+    }
+    if typ.kind.is_int() {
+        let (min, max) = int_bounds(typ.kind);
+        match restriction {
+            Some(range) => {
+                let r = range_int_parse(range).ok()?.1;
+                Some(widen(r.iter().map(|r| bounds(r, min, max)).collect()))
+            }
+            None => Some(vec![(min as i128, max as i128)]),
+        }
+    } else if typ.kind.is_uint() {
+        let max = uint_bounds(typ.kind);
+        match restriction {
+            Some(range) => Some(uint(&range_uint_parse(range).ok()?.1, max)),
+            None => Some(vec![(0, max as i128)]),
+        }
+    } else if typ.kind == TypeKind::Ydecimal64 {
+        match restriction {
+            Some(range) => Some(widen(decimal_intervals(range, typ.fraction_digits?)?)),
+            None => Some(vec![(i64::MIN as i128, i64::MAX as i128)]),
+        }
+    } else {
+        None
+    }
+}
+
+fn kind_accepts(kind: TypeKind, value: &LeafValue) -> bool {
+    match value {
+        LeafValue::Int(_) => kind.is_int(),
+        LeafValue::Uint(_) => kind.is_uint(),
+        LeafValue::Decimal64(..) => kind == TypeKind::Ydecimal64,
+        LeafValue::String(_) => kind == TypeKind::Ystring || kind == TypeKind::Ynone,
+        LeafValue::Boolean(_) => kind == TypeKind::Yboolean,
+        LeafValue::Enum(..) => kind == TypeKind::Yenumeration,
+        LeafValue::Bits(_) => kind == TypeKind::Ybits,
+        LeafValue::Binary(_) => kind == TypeKind::Ybinary,
+        LeafValue::Identity(..) => kind == TypeKind::Yidentityref,
+        LeafValue::Empty => kind == TypeKind::Yempty,
+        LeafValue::InstanceIdentifier(_) => kind == TypeKind::YinstanceIdentifier,
+    }
+}
+
+impl TypeNode {
+    // Check the value against the restrictions of the type, "range",
+    // "length" and "pattern".  The value space of the built-in type is
+    // checked when the value is parsed.  "leafref" is the target of
+    // leafref type.
+    pub fn validate(&self, leafref: Option<&Leafref>, value: &LeafValue) -> Result<(), String> {
+        match self.kind {
+            TypeKind::Yunion => {
+                let mut error = String::from("no union member type accepts the value");
+                for member in self.union.iter() {
+                    if member.kind != TypeKind::Yunion
+                        && member.kind != TypeKind::Yleafref
+                        && !kind_accepts(member.kind, value)
+                    {
+                        continue;
+                    }
+                    match member.validate(None, value) {
+                        Ok(()) => return Ok(()),
+                        Err(e) => error = e,
+                    }
+                }
+                return Err(error);
+            }
+            TypeKind::Yleafref => {
+                return match leafref.and_then(|l| l.typ.as_ref()) {
+                    Some(target) => target.validate(None, value),
+                    None => Ok(()),
+                }
+            }
+            _ => {}
+        }
+        if let Some(range) = &self.range {
+            let ok = match value {
+                LeafValue::Int(v) => {
+                    let (min, max) = int_bounds(self.kind);
+                    range_int_parse(range)
+                        .ok()
+                        .map(|(_, r)| in_ranges(&r, min, max, *v))
+                }
+                LeafValue::Uint(v) => range_uint_parse(range)
+                    .ok()
+                    .map(|(_, r)| in_ranges(&r, 0, uint_bounds(self.kind), *v)),
+                LeafValue::Decimal64(v, digits) => in_decimal_range(range, *digits, *v),
+                _ => None,
+            };
+            if ok == Some(false) {
+                return Err(format!("value is out of range \"{}\"", range));
+            }
+        }
+        if let Some(length) = &self.length {
+            let len = match value {
+                LeafValue::String(s) => Some(s.chars().count() as u64),
+                LeafValue::Binary(b) => Some(b.len() as u64),
+                _ => None,
+            };
+            if let (Some(len), Ok((_, r))) = (len, range_uint_parse(length)) {
+                if !in_ranges(&r, 0, u64::MAX, len) {
+                    return Err(format!("length is out of range \"{}\"", length));
+                }
+            }
+        }
+        if let LeafValue::String(s) = value {
+            for pattern in self.patterns.iter() {
+                if let Ok(regex) = pattern_regex(&pattern.pattern) {
+                    if regex.is_match(s) == pattern.invert_match {
+                        return Err(if pattern.invert_match {
+                            format!("value matches invert-match pattern \"{}\"", pattern.pattern)
+                        } else {
+                            format!("value does not match pattern \"{}\"", pattern.pattern)
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::PatternNode;
+
+    fn typ(kind: TypeKind) -> TypeNode {
+        TypeNode {
+            kind,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn type_validate_test() {
+        let mut t = typ(TypeKind::Yuint8);
+        t.range = Some(String::from("1..10 | 20 | 30..max"));
+        assert!(t.validate(None, &LeafValue::Uint(5)).is_ok());
+        assert!(t.validate(None, &LeafValue::Uint(20)).is_ok());
+        assert!(t.validate(None, &LeafValue::Uint(255)).is_ok());
+        assert!(t.validate(None, &LeafValue::Uint(0)).is_err());
+        assert!(t.validate(None, &LeafValue::Uint(15)).is_err());
+
+        let mut t = typ(TypeKind::Yint32);
+        t.range = Some(String::from("min..-1"));
+        assert!(t.validate(None, &LeafValue::Int(-5)).is_ok());
+        assert!(t.validate(None, &LeafValue::Int(0)).is_err());
+
+        let mut t = typ(TypeKind::Ydecimal64);
+        t.range = Some(String::from("-1.5 .. 2.25"));
+        assert!(t.validate(None, &LeafValue::Decimal64(225, 2)).is_ok());
+        assert!(t.validate(None, &LeafValue::Decimal64(226, 2)).is_err());
+
+        let mut t = typ(TypeKind::Ystring);
+        t.length = Some(String::from("2..4"));
+        t.patterns.push(PatternNode::new(String::from("[a-z]*")));
+        t.patterns.push(PatternNode::new(String::from("a.*")));
+        assert!(t
+            .validate(None, &LeafValue::String(String::from("abc")))
+            .is_ok());
+        assert!(t
+            .validate(None, &LeafValue::String(String::from("a")))
+            .is_err());
+        assert!(t
+            .validate(None, &LeafValue::String(String::from("bcd")))
+            .is_err());
+        assert!(t
+            .validate(None, &LeafValue::String(String::from("aB")))
+            .is_err());
+
+        // RFC7950 9.4.8: values which start with "xml" are not allowed.
+        let mut inverted = typ(TypeKind::Ystring);
+        inverted
+            .patterns
+            .push(PatternNode::new(String::from("[a-zA-Z_][a-zA-Z0-9\\-_.]*")));
+        let mut xml = PatternNode::new(String::from("[xX][mM][lL].*"));
+        xml.invert_match = true;
+        inverted.patterns.push(xml);
+        assert!(inverted
+            .validate(None, &LeafValue::String(String::from("enabled")))
+            .is_ok());
+        assert_eq!(
+            inverted.validate(None, &LeafValue::String(String::from("xml-element"))),
+            Err(String::from(
+                "value matches invert-match pattern \"[xX][mM][lL].*\""
+            ))
+        );
+        assert!(inverted
+            .validate(None, &LeafValue::String(String::from("10-mbit")))
+            .is_err());
+
+        let mut union = typ(TypeKind::Yunion);
+        union.union.push(t);
+        let mut int = typ(TypeKind::Yint8);
+        int.range = Some(String::from("0..9"));
+        union.union.push(int);
+        assert!(union.validate(None, &LeafValue::Int(3)).is_ok());
+        assert!(union.validate(None, &LeafValue::Int(30)).is_err());
+        assert!(union
+            .validate(None, &LeafValue::String(String::from("ab")))
+            .is_ok());
+    }
+}
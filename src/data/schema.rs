@@ -0,0 +1,846 @@
+use crate::data::{DataNode, LeafValue};
+use crate::modules::*;
+use crate::xpath::{Axis, Expr, NodeTest, XPath};
+use crate::Node;
+use std::collections::HashMap;
+use std::rc::Rc;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaKind {
+    Container,
+    List,
+    Leaf,
+    LeafList,
+    Anydata,
+}
+
+// Compiled leafref.  "path" is bound to the prefixes of the module where
+// the "path" statement is written and "typ" is the type of the target
+// leaf.  The target type is None when the target is not found in the
+// schema, then values are handled as string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leafref {
+    pub path: XPath,
+    pub typ: Option<TypeNode>,
+}
+
+// Schema node compiled from the data definition statements.  Typedef
+// references in "typ" are resolved to the built-in type with the
+// restrictions of the typedef chain, "config" is inherited from the
+// parent and key leaves of a list are placed first in key order.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct SchemaNode {
+    pub kind: SchemaKind,
+    pub name: String,
+    pub module: String,
+    pub namespace: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub config: bool,
+    #[new(default)]
+    pub presence: bool,
+    #[new(default)]
+    pub mandatory: bool,
+    #[new(default)]
+    pub typ: Option<TypeNode>,
+    #[new(default)]
+    pub leafref: Option<Leafref>,
+    #[new(default)]
+    pub default: Vec<String>,
+    #[new(default)]
+    pub units: Option<String>,
+    #[new(default)]
+    pub keys: Vec<String>,
+    #[new(default)]
+    pub unique: Vec<String>,
+    #[new(default)]
+    pub min_elements: Option<u64>,
+    #[new(default)]
+    pub max_elements: Option<u64>,
+    #[new(default)]
+    pub ordered_by_user: bool,
+    #[new(default)]
+    pub must: Vec<MustNode>,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+    #[new(default)]
+    pub children: Vec<Rc<SchemaNode>>,
+    #[new(default)]
+    pub choices: Vec<SchemaChoice>,
+}
+
+impl SchemaNode {
+    // Child schema node.  "module" None matches any module.
+    pub fn child(&self, module: Option<&str>, name: &str) -> Option<&Rc<SchemaNode>> {
+        find_schema(&self.children, module, name)
+    }
+
+    pub fn is_key(&self, name: &str) -> bool {
+        self.keys.iter().any(|k| k == name)
+    }
+}
+
+pub fn find_schema<'a>(
+    nodes: &'a [Rc<SchemaNode>],
+    module: Option<&str>,
+    name: &str,
+) -> Option<&'a Rc<SchemaNode>> {
+    nodes
+        .iter()
+        .find(|n| n.name == name && module.is_none_or(|m| n.module == m))
+}
+
+// RFC7950 7.9.  The "choice" Statement
+//     The "choice" and "case" nodes appear only in the schema tree but not
+//     in the data tree.
+//
+// Data nodes of the cases are placed in the children of the parent with
+// the other children, and the case refers to them.  The data nodes of the
+// shorthand case are in the case named after the data node.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct SchemaChoice {
+    pub name: String,
+    #[new(default)]
+    pub config: bool,
+    #[new(default)]
+    pub mandatory: bool,
+    #[new(default)]
+    pub default: Option<String>,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub cases: Vec<SchemaCase>,
+}
+
+// "nodes" has the data nodes of the nested choices too.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct SchemaCase {
+    pub name: String,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub nodes: Vec<Rc<SchemaNode>>,
+    #[new(default)]
+    pub choices: Vec<SchemaChoice>,
+}
+
+impl SchemaChoice {
+    // The case which has instances in "nodes".
+    pub fn active_case(&self, nodes: &[DataNode]) -> Option<&SchemaCase> {
+        self.cases.iter().find(|c| {
+            c.nodes
+                .iter()
+                .any(|s| nodes.iter().any(|n| Rc::ptr_eq(n.schema(), s)))
+        })
+    }
+
+    pub fn default_case(&self) -> Option<&SchemaCase> {
+        let default = self.default.as_ref()?;
+        self.cases.iter().find(|c| &c.name == default)
+    }
+}
+
+// Data nodes of the cases which are not active in "nodes", the siblings.
+// The default case is taken as active when no case of the choice is
+// active and "use_default" is true.
+pub(crate) fn inactive_case_nodes(
+    choices: &[SchemaChoice],
+    nodes: &[DataNode],
+    use_default: bool,
+) -> Vec<Rc<SchemaNode>> {
+    let mut inactive = vec![];
+    for choice in choices.iter() {
+        let active = choice
+            .active_case(nodes)
+            .or_else(|| choice.default_case().filter(|_| use_default));
+        for case in choice.cases.iter() {
+            match active {
+                Some(active) if active.name == case.name => {
+                    inactive.extend(inactive_case_nodes(&case.choices, nodes, use_default));
+                }
+                _ => inactive.extend(case.nodes.iter().cloned()),
+            }
+        }
+    }
+    inactive
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct SchemaModule {
+    pub name: String,
+    pub prefix: String,
+    pub namespace: String,
+    #[new(default)]
+    pub revision: Option<String>,
+}
+
+// Compiled schema of all of the modules.  Identities are keyed by module
+// name and identity name and have the bases in the same form.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub modules: Vec<SchemaModule>,
+    pub identities: HashMap<(String, String), Vec<(String, String)>>,
+    pub nodes: Vec<Rc<SchemaNode>>,
+    pub choices: Vec<SchemaChoice>,
+}
+
+impl Schema {
+    pub fn module(&self, name: &str) -> Option<&SchemaModule> {
+        self.modules.iter().find(|m| m.name == name)
+    }
+
+    // Resolve module name or prefix of a module to module name.
+    pub fn resolve_module(&self, name: &str) -> Option<&str> {
+        self.modules
+            .iter()
+            .find(|m| m.name == name)
+            .or_else(|| self.modules.iter().find(|m| m.prefix == name))
+            .map(|m| m.name.as_str())
+    }
+
+    pub fn child(&self, module: Option<&str>, name: &str) -> Option<&Rc<SchemaNode>> {
+        find_schema(&self.nodes, module, name)
+    }
+
+    pub fn identity_exists(&self, module: &str, name: &str) -> bool {
+        self.identities
+            .contains_key(&(module.to_owned(), name.to_owned()))
+    }
+
+    // Whether the identity is derived from the base identity directly or
+    // indirectly.
+    pub fn derived_from(&self, identity: (&str, &str), base: (&str, &str)) -> bool {
+        let mut visited = vec![];
+        let mut stack = vec![(identity.0.to_owned(), identity.1.to_owned())];
+        while let Some(current) = stack.pop() {
+            if let Some(bases) = self.identities.get(&current) {
+                for b in bases {
+                    if b.0 == base.0 && b.1 == base.1 {
+                        return true;
+                    }
+                    if !visited.contains(b) {
+                        visited.push(b.clone());
+                        stack.push(b.clone());
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SchemaError {
+    #[error("unknown prefix \"{1}\" in module \"{0}\"")]
+    UnknownPrefix(String, String),
+    #[error("unknown type \"{1}\" in module \"{0}\"")]
+    UnknownType(String, String),
+    #[error("typedef \"{1}\" in module \"{0}\" refers to itself")]
+    TypedefLoop(String, String),
+    #[error("key \"{1}\" of list \"{0}\" not found")]
+    KeyNotFound(String, String),
+    #[error("invalid default \"{1}\" of \"{0}\": {2}")]
+    InvalidDefault(String, String, String),
+    #[error("default case \"{1}\" of choice \"{0}\" not found")]
+    DefaultCaseNotFound(String, String),
+    #[error("unknown grouping \"{1}\" in module \"{0}\"")]
+    UnknownGrouping(String, String),
+    #[error("grouping \"{1}\" in module \"{0}\" uses itself")]
+    GroupingLoop(String, String),
+    #[error("invalid refine \"{1}\" of uses \"{0}\"")]
+    InvalidRefine(String, String),
+}
+
+// Typedef chain deeper than this is treated as a loop.
+const TYPEDEF_DEPTH_MAX: usize = 32;
+
+// "nodes" are the data definitions of the modules with the uses expanded.
+struct Compiler<'a> {
+    modules: &'a Modules,
+    nodes: &'a HashMap<ModuleId, Vec<Node>>,
+}
+
+// Grouping "name" referred in "module" and the module where it is defined.
+// Groupings nested in "scopes", the sibling nodes of the enclosing
+// statements from the outermost, are searched from the innermost and the
+// index of the scope defining the grouping is returned with it.
+fn find_grouping<'a>(
+    modules: &'a Modules,
+    module: &'a Module,
+    scopes: &[&'a [Node]],
+    name: &str,
+) -> Result<(&'a Module, &'a GroupingNode, usize), SchemaError> {
+    let unknown = || SchemaError::UnknownGrouping(module.name.clone(), name.to_owned());
+    let (prefix, local) = split_prefix(name);
+    match prefix {
+        Some(prefix) if prefix != module.prefix => {
+            let gmodule = modules.import_module(module, prefix).ok_or_else(|| {
+                SchemaError::UnknownPrefix(module.name.clone(), prefix.to_owned())
+            })?;
+            let grouping = gmodule.groupings.get(local).ok_or_else(unknown)?;
+            Ok((gmodule, grouping, 0))
+        }
+        _ => {
+            for (index, scope) in scopes.iter().enumerate().rev() {
+                for node in scope.iter() {
+                    match node {
+                        Node::Grouping(g) if g.name == local => return Ok((module, g, index + 1)),
+                        _ => {}
+                    }
+                }
+            }
+            let grouping = module.groupings.get(local).ok_or_else(unknown)?;
+            Ok((module, grouping, 0))
+        }
+    }
+}
+
+fn has_data_node(nodes: &[Node], name: &str) -> bool {
+    nodes.iter().any(|n| match n {
+        Node::Uses(u) => has_data_node(&u.nodes, name),
+        _ => n.name() == Some(name),
+    })
+}
+
+// Child node "name" of the refine target path.  The nodes of the uses are
+// looked up as the siblings.
+fn refine_child<'n>(nodes: &'n mut [Node], name: &str) -> Option<&'n mut Node> {
+    let node = nodes.iter_mut().find(|n| match n {
+        Node::Uses(u) => has_data_node(&u.nodes, name),
+        _ => n.name() == Some(name),
+    })?;
+    match node {
+        Node::Uses(u) => refine_child(&mut u.nodes, name),
+        node => Some(node),
+    }
+}
+
+// RFC7950 7.13.  The "uses" Statement
+//     The effect of a "uses" reference to a grouping is that the nodes
+//     defined by the grouping are copied into the current schema tree and
+//     are then updated according to the "refine" and "augment" statements.
+//
+// Copy of "nodes" written in "module" whose uses have the copies of the
+// groupings.  The groupings are dropped.  "expanding" is the chain of the
+// groupings being copied, a grouping in it is used by itself.
+fn expand_uses<'a>(
+    modules: &'a Modules,
+    module: &'a Module,
+    scopes: &mut Vec<&'a [Node]>,
+    nodes: &'a [Node],
+    expanding: &mut Vec<&'a GroupingNode>,
+) -> Result<Vec<Node>, SchemaError> {
+    scopes.push(nodes);
+    let mut expanded = vec![];
+    for node in nodes.iter() {
+        let uses = match node {
+            Node::Grouping(_) => continue,
+            Node::Uses(uses) => uses,
+            _ => {
+                let mut copy = node.clone();
+                if let (Some(children), Some(copied)) = (node.nodes(), copy.nodes_mut()) {
+                    *copied = expand_uses(modules, module, scopes, children, expanding)?;
+                }
+                expanded.push(copy);
+                continue;
+            }
+        };
+        let (gmodule, grouping, index) = find_grouping(modules, module, scopes, &uses.name)?;
+        if expanding.iter().any(|g| std::ptr::eq(*g, grouping)) {
+            return Err(SchemaError::GroupingLoop(
+                gmodule.name.clone(),
+                grouping.name.clone(),
+            ));
+        }
+        let mut gscopes = scopes[..index].to_vec();
+        let mut copy = uses.as_ref().clone();
+        copy.module = Some(gmodule.id());
+        expanding.push(grouping);
+        copy.nodes = expand_uses(modules, gmodule, &mut gscopes, &grouping.nodes, expanding)?;
+        expanding.pop();
+        for refine in uses.refines.iter() {
+            let invalid = || SchemaError::InvalidRefine(uses.name.clone(), refine.name.clone());
+            let names: Vec<&str> = refine.name.split('/').map(|n| split_prefix(n).1).collect();
+            let (last, parents) = names.split_last().ok_or_else(invalid)?;
+            let mut children = &mut copy.nodes;
+            for name in parents {
+                children = refine_child(children, name)
+                    .and_then(|n| n.nodes_mut())
+                    .ok_or_else(invalid)?;
+            }
+            let target = refine_child(children, last).ok_or_else(invalid)?;
+            if !refine.apply(target) {
+                return Err(invalid());
+            }
+        }
+        expanded.push(Node::Uses(Box::new(copy)));
+    }
+    scopes.pop();
+    Ok(expanded)
+}
+
+// Type resolved to built-in type.  "path_module" is the module where the
+// leafref "path" is written and "default" is the default of the closest
+// typedef in the chain which has one.
+struct ResolvedType {
+    typ: TypeNode,
+    path_module: Option<String>,
+    default: Option<String>,
+}
+
+// Split "prefix:name" into prefix and name.
+fn split_prefix(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, name),
+    }
+}
+
+impl<'a> Compiler<'a> {
+    fn prefix_module(&self, module: &Module, prefix: &str) -> Result<&'a Module, SchemaError> {
+        self.modules
+            .import_module(module, prefix)
+            .ok_or_else(|| SchemaError::UnknownPrefix(module.name.clone(), prefix.to_owned()))
+    }
+
+    fn resolve_type(
+        &self,
+        module: &'a Module,
+        typ: &TypeNode,
+        depth: usize,
+    ) -> Result<ResolvedType, SchemaError> {
+        if typ.kind != TypeKind::Ynone {
+            let mut resolved = typ.clone();
+            resolved.union = vec![];
+            for member in typ.union.iter() {
+                resolved
+                    .union
+                    .push(self.resolve_type(module, member, depth)?.typ);
+            }
+            let path_module = typ.path.as_ref().map(|_| module.name.clone());
+            return Ok(ResolvedType {
+                typ: resolved,
+                path_module,
+                default: None,
+            });
+        }
+        if depth > TYPEDEF_DEPTH_MAX {
+            return Err(SchemaError::TypedefLoop(
+                module.name.clone(),
+                typ.name.clone(),
+            ));
+        }
+        let (prefix, name) = split_prefix(&typ.name);
+        let tmodule = match prefix {
+            Some(prefix) => self.prefix_module(module, prefix)?,
+            None => module,
+        };
+        let typedef = tmodule.typedefs.get(name);
+        let base = typedef
+            .and_then(|t| t.typ.as_ref())
+            .ok_or_else(|| SchemaError::UnknownType(module.name.clone(), typ.name.clone()))?;
+        let mut resolved = self.resolve_type(tmodule, base, depth + 1)?;
+        if let Some(default) = typedef.and_then(|t| t.default.as_ref()) {
+            resolved.default = Some(default.clone());
+        }
+
+        // Restrictions of the derived type.
+        let derived = &mut resolved.typ;
+        if typ.range.is_some() {
+            derived.range = typ.range.clone();
+            derived.range_int = typ.range_int.clone();
+            derived.range_uint = typ.range_uint.clone();
+        }
+        if typ.length.is_some() {
+            derived.length = typ.length.clone();
+        }
+        derived.patterns.extend(typ.patterns.iter().cloned());
+        if !typ.enums.is_empty() {
+            derived.enums = typ.enums.clone();
+        }
+        if !typ.bits.is_empty() {
+            derived.bits = typ.bits.clone();
+        }
+        if derived.fraction_digits.is_none() {
+            derived.fraction_digits = typ.fraction_digits;
+        }
+        if typ.require_instance.is_some() {
+            derived.require_instance = typ.require_instance;
+        }
+        if typ.path.is_some() {
+            derived.path = typ.path.clone();
+            resolved.path_module = Some(module.name.clone());
+        }
+        if !typ.bases.is_empty() {
+            resolved.typ.bases = typ.bases.clone();
+        }
+        resolved.typ.name = typ.name.clone();
+        Ok(resolved)
+    }
+
+    // Find leafref target node.  "ancestors" are the ancestors of the
+    // leafref leaf with the module of the top level node.
+    fn leafref_target(
+        &self,
+        module: &'a Module,
+        ancestors: &[&'a Node],
+        path: &XPath,
+    ) -> Option<(&'a Module, Vec<&'a Node>)> {
+        let location = match &path.expr {
+            Expr::Path(location) => location,
+            _ => return None,
+        };
+        let mut tmodule = module;
+        let mut stack: Vec<&'a Node> = if location.absolute {
+            vec![]
+        } else {
+            ancestors.to_vec()
+        };
+        for step in location.steps.iter() {
+            match (step.axis, &step.test) {
+                (Axis::Parent, NodeTest::Node) => {
+                    stack.pop()?;
+                }
+                (Axis::Child, NodeTest::Name(qname)) => {
+                    let candidates = match stack.last() {
+                        Some(node) => node.nodes()?,
+                        None => {
+                            if let Some(prefix) = &qname.prefix {
+                                tmodule = self.modules.find_module(path.prefix_module(prefix)?)?;
+                            }
+                            self.nodes.get(&tmodule.id())?
+                        }
+                    };
+                    stack.push(find_data_node(candidates, &qname.name)?);
+                }
+                _ => return None,
+            }
+        }
+        Some((tmodule, stack))
+    }
+
+    fn leafref(
+        &self,
+        module: &'a Module,
+        ancestors: &[&'a Node],
+        resolved: &ResolvedType,
+        depth: usize,
+    ) -> Result<Option<Leafref>, SchemaError> {
+        let (text, path_module) = match (&resolved.typ.path, &resolved.path_module) {
+            (Some(text), Some(path_module)) => (text, path_module),
+            _ => return Ok(None),
+        };
+        let pmodule = self.modules.find_module(path_module).unwrap_or(module);
+        let mut path = match XPath::compile(text) {
+            Ok(path) => path,
+            Err(_) => return Ok(None),
+        };
+        path.prefixes = pmodule.prefix_map();
+
+        let mut typ = None;
+        if depth <= TYPEDEF_DEPTH_MAX {
+            if let Some((tmodule, stack)) = self.leafref_target(module, ancestors, &path) {
+                let target_typ = match stack.last() {
+                    Some(Node::Leaf(n)) => n.typ.as_ref(),
+                    Some(Node::LeafList(n)) => n.typ.as_ref(),
+                    _ => None,
+                };
+                if let Some(target_typ) = target_typ {
+                    let target = self.resolve_type(tmodule, target_typ, 0)?;
+                    let parents = &stack[..stack.len() - 1];
+                    typ = match self.leafref(tmodule, parents, &target, depth + 1)? {
+                        Some(chained) => chained.typ,
+                        None => Some(target.typ),
+                    };
+                }
+            }
+        }
+        Ok(Some(Leafref { path, typ }))
+    }
+
+    // "owner" is the module which the node belongs to and "module" is the
+    // module where the node is written, which differ in the groupings
+    // of the other modules.
+    fn compile_node(
+        &self,
+        owner: &'a Module,
+        module: &'a Module,
+        ancestors: &mut Vec<&'a Node>,
+        node: &'a Node,
+        parent_config: bool,
+    ) -> Result<Option<SchemaNode>, SchemaError> {
+        let (kind, name) = match node {
+            Node::Container(n) => (SchemaKind::Container, &n.name),
+            Node::List(n) => (SchemaKind::List, &n.name),
+            Node::Leaf(n) => (SchemaKind::Leaf, &n.name),
+            Node::LeafList(n) => (SchemaKind::LeafList, &n.name),
+            Node::Anydata(n) => (SchemaKind::Anydata, &n.name),
+            _ => return Ok(None),
+        };
+        let mut snode = SchemaNode::new(
+            kind,
+            name.clone(),
+            owner.name.clone(),
+            owner.namespace.clone(),
+        );
+        snode.config = node.config().unwrap_or(parent_config);
+        snode.must = node.must().to_vec();
+        snode.when = node.when().cloned();
+        snode.ext_instances = node.ext_instances().to_vec();
+
+        let typ = match node {
+            Node::Container(n) => {
+                snode.description = n.description.clone();
+                snode.presence = n.presence.is_some();
+                None
+            }
+            Node::List(n) => {
+                snode.description = n.description.clone();
+                snode.keys = n.keys().iter().map(|k| k.to_string()).collect();
+                snode.unique = n.unique.clone();
+                snode.min_elements = n.min_elements;
+                snode.max_elements = n.max_elements;
+                snode.ordered_by_user = n.ordered_by.as_deref() == Some("user");
+                None
+            }
+            Node::Leaf(n) => {
+                snode.description = n.description.clone();
+                snode.mandatory = n.mandatory == Some(true);
+                snode.default = n.default.iter().cloned().collect();
+                snode.units = n.units.clone();
+                n.typ.as_ref()
+            }
+            Node::LeafList(n) => {
+                snode.description = n.description.clone();
+                snode.default = n.default.clone();
+                snode.units = n.units.clone();
+                snode.min_elements = n.min_elements;
+                snode.max_elements = n.max_elements;
+                snode.ordered_by_user = n.ordered_by.as_deref() == Some("user");
+                n.typ.as_ref()
+            }
+            Node::Anydata(n) => {
+                snode.description = n.description.clone();
+                snode.mandatory = n.mandatory == Some(true);
+                None
+            }
+            _ => None,
+        };
+        if let Some(typ) = typ {
+            let resolved = self.resolve_type(module, typ, 0)?;
+            snode.leafref = self.leafref(module, ancestors, &resolved, 0)?;
+            snode.typ = Some(resolved.typ);
+
+            // RFC7950 7.6.1.  The Leaf's Default Value
+            //     If the leaf does not have a "default" statement, its type's
+            //     default value is used, if any.
+            // RFC7950 7.8.2.  The List's "key" Statement
+            //     Any default values in the key leafs or their types are
+            //     ignored.
+            let is_key = match ancestors.last() {
+                Some(Node::List(list)) => list.keys().iter().any(|k| k == name),
+                _ => false,
+            };
+            if is_key {
+                snode.default = vec![];
+            } else if snode.default.is_empty()
+                && !snode.mandatory
+                && snode.min_elements.unwrap_or(0) == 0
+            {
+                snode.default = resolved.default.into_iter().collect();
+            }
+        }
+
+        if let Some(nodes) = node.nodes() {
+            ancestors.push(node);
+            let mut children = vec![];
+            let config = snode.config;
+            self.compile_children(
+                owner,
+                module,
+                ancestors,
+                nodes,
+                config,
+                &mut children,
+                &mut snode.choices,
+            )?;
+            ancestors.pop();
+
+            // Key leaves first in key order.
+            for (index, key) in snode.keys.iter().enumerate() {
+                let pos = children
+                    .iter()
+                    .position(|c| &c.name == key && c.kind == SchemaKind::Leaf)
+                    .ok_or_else(|| SchemaError::KeyNotFound(snode.name.clone(), key.clone()))?;
+                let child = children.remove(pos);
+                children.insert(index, child);
+            }
+            snode.children = children;
+        }
+        Ok(Some(snode))
+    }
+
+    // Compile "nodes" into "children" and "choices".  "ancestors" are the
+    // data nodes, choices, cases and uses are not pushed to them.
+    #[allow(clippy::too_many_arguments)]
+    fn compile_children(
+        &self,
+        owner: &'a Module,
+        module: &'a Module,
+        ancestors: &mut Vec<&'a Node>,
+        nodes: &'a [Node],
+        config: bool,
+        children: &mut Vec<Rc<SchemaNode>>,
+        choices: &mut Vec<SchemaChoice>,
+    ) -> Result<(), SchemaError> {
+        for node in nodes.iter() {
+            let choice = match node {
+                Node::Choice(choice) => choice,
+                Node::Uses(uses) => {
+                    let gmodule = uses
+                        .module
+                        .as_ref()
+                        .and_then(|id| self.modules.modules.get(id))
+                        .unwrap_or(module);
+                    self.compile_children(
+                        owner,
+                        gmodule,
+                        ancestors,
+                        &uses.nodes,
+                        config,
+                        children,
+                        choices,
+                    )?;
+                    continue;
+                }
+                _ => {
+                    let c = self.compile_node(owner, module, ancestors, node, config)?;
+                    if let Some(c) = c {
+                        children.push(Rc::new(c));
+                    }
+                    continue;
+                }
+            };
+            let mut schoice = SchemaChoice::new(choice.name.clone());
+            schoice.config = choice.config.unwrap_or(config);
+            schoice.mandatory = choice.mandatory == Some(true);
+            schoice.default = choice.default.clone();
+            schoice.when = choice.when.clone();
+            for case in choice.nodes.iter() {
+                let (name, when, nodes) = match case {
+                    Node::Case(n) => (n.name.as_str(), n.when.clone(), &n.nodes[..]),
+                    _ => match case.name() {
+                        Some(name) => (name, None, std::slice::from_ref(case)),
+                        None => continue,
+                    },
+                };
+                let mut scase = SchemaCase::new(name.to_owned());
+                scase.when = when;
+                let start = children.len();
+                self.compile_children(
+                    owner,
+                    module,
+                    ancestors,
+                    nodes,
+                    schoice.config,
+                    children,
+                    &mut scase.choices,
+                )?;
+                scase.nodes = children[start..].to_vec();
+                schoice.cases.push(scase);
+            }
+            if let Some(default) = &schoice.default {
+                if schoice.default_case().is_none() {
+                    return Err(SchemaError::DefaultCaseNotFound(
+                        schoice.name.clone(),
+                        default.clone(),
+                    ));
+                }
+            }
+            choices.push(schoice);
+        }
+        Ok(())
+    }
+
+    fn compile(&self) -> Result<Schema, SchemaError> {
+        let mut schema = Schema::default();
+        for module in self.modules.implemented() {
+            let mut smodule = SchemaModule::new(
+                module.name.clone(),
+                module.prefix.clone(),
+                module.namespace.clone(),
+            );
+            smodule.revision = module.revision().map(String::from);
+            schema.modules.push(smodule);
+
+            for identity in module.identities.values() {
+                let mut bases = vec![];
+                for base in identity.bases.iter() {
+                    let (prefix, name) = split_prefix(base);
+                    let bmodule = match prefix {
+                        Some(prefix) => self.prefix_module(module, prefix)?,
+                        None => module,
+                    };
+                    bases.push((bmodule.name.clone(), name.to_owned()));
+                }
+                schema
+                    .identities
+                    .insert((module.name.clone(), identity.name.clone()), bases);
+            }
+
+            self.compile_children(
+                module,
+                module,
+                &mut vec![],
+                &self.nodes[&module.id()],
+                true,
+                &mut schema.nodes,
+                &mut schema.choices,
+            )?;
+        }
+        // Identities of all of the modules are needed to parse defaults.
+        check_defaults(&schema, &schema.nodes)?;
+        Ok(schema)
+    }
+}
+
+// RFC7950 7.6.1.  The Leaf's Default Value
+//     The default value MUST be valid according to the type specified in
+//     the leaf's "type" statement.
+fn check_defaults(schema: &Schema, nodes: &[Rc<SchemaNode>]) -> Result<(), SchemaError> {
+    for node in nodes.iter() {
+        for default in node.default.iter() {
+            LeafValue::parse(schema, node, default)
+                .and_then(|value| match &node.typ {
+                    Some(typ) => typ.validate(node.leafref.as_ref(), &value),
+                    None => Ok(()),
+                })
+                .map_err(|reason| {
+                    SchemaError::InvalidDefault(node.name.clone(), default.clone(), reason)
+                })?;
+        }
+        check_defaults(schema, &node.children)?;
+    }
+    Ok(())
+}
+
+impl Modules {
+    // Compile the schema tree of the modules.  Deviations should be applied
+    // beforehand.
+    pub fn schema(&self) -> Result<Rc<Schema>, SchemaError> {
+        let mut nodes = HashMap::new();
+        for (id, module) in self.modules.iter() {
+            let expanded = expand_uses(self, module, &mut vec![], &module.nodes, &mut vec![])?;
+            nodes.insert(id.clone(), expanded);
+        }
+        let compiler = Compiler {
+            modules: self,
+            nodes: &nodes,
+        };
+        Ok(Rc::new(compiler.compile()?))
+    }
+}
@@ -0,0 +1,512 @@
+use crate::data::compat::implemented_module;
+use crate::data::*;
+use crate::modules::*;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::rc::Rc;
+
+// Definition compared between the revisions.  "ExtInstance" is a usage of
+// an extension such as "nacm:default-deny-write", with the text of the
+// instance.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaItem {
+    Node,
+    Typedef,
+    Identity,
+    Feature,
+    Extension,
+    ExtInstance(String),
+}
+
+impl fmt::Display for SchemaItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaItem::Node => write!(f, "node"),
+            SchemaItem::Typedef => write!(f, "typedef"),
+            SchemaItem::Identity => write!(f, "identity"),
+            SchemaItem::Feature => write!(f, "feature"),
+            SchemaItem::Extension => write!(f, "extension"),
+            SchemaItem::ExtInstance(_) => write!(f, "extension-usage"),
+        }
+    }
+}
+
+// Property of a definition such as "type" or "description" and the old and
+// new value.  None when the property is not present.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct PropertyChange {
+    pub property: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaDiffOp {
+    Added,
+    Removed,
+    Modified(Vec<PropertyChange>),
+}
+
+// "name" is the schema node path for nodes, the name for the other
+// definitions.  It is the owner of the extension usage, the node path,
+// "typedef name" and so on, and "/" for the module statement.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct SchemaDiffEntry {
+    pub item: SchemaItem,
+    pub name: String,
+    pub op: SchemaDiffOp,
+}
+
+impl fmt::Display for SchemaDiffEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mark = match self.op {
+            SchemaDiffOp::Added => '+',
+            SchemaDiffOp::Removed => '-',
+            SchemaDiffOp::Modified(_) => '~',
+        };
+        write!(f, "{} {} {}", mark, self.item, self.name)?;
+        if let SchemaItem::ExtInstance(ext) = &self.item {
+            write!(f, " {}", ext)?;
+        }
+        if let SchemaDiffOp::Modified(changes) = &self.op {
+            let value = |v: &Option<String>| match v {
+                Some(v) => format!("{:?}", v),
+                None => String::from("(none)"),
+            };
+            for c in changes.iter() {
+                write!(
+                    f,
+                    "\n    {}: {} -> {}",
+                    c.property,
+                    value(&c.old),
+                    value(&c.new)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Structural difference of the module between two revisions.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct SchemaDiff {
+    pub module: String,
+    pub old_revision: Option<String>,
+    pub new_revision: Option<String>,
+    #[new(default)]
+    pub entries: Vec<SchemaDiffEntry>,
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let string = |s: &str| JsonValue::String(s.to_owned());
+        let optional = |v: &Option<String>| match v {
+            Some(v) => string(v),
+            None => JsonValue::Null,
+        };
+        let entries = self.entries.iter().map(|entry| {
+            let op = match entry.op {
+                SchemaDiffOp::Added => "added",
+                SchemaDiffOp::Removed => "removed",
+                SchemaDiffOp::Modified(_) => "modified",
+            };
+            let mut members = vec![
+                (String::from("item"), string(&entry.item.to_string())),
+                (String::from("name"), string(&entry.name)),
+                (String::from("op"), string(op)),
+            ];
+            if let SchemaItem::ExtInstance(ext) = &entry.item {
+                members.push((String::from("extension"), string(ext)));
+            }
+            if let SchemaDiffOp::Modified(changes) = &entry.op {
+                let changes = changes.iter().map(|c| {
+                    JsonValue::Object(vec![
+                        (String::from("property"), string(&c.property)),
+                        (String::from("old"), optional(&c.old)),
+                        (String::from("new"), optional(&c.new)),
+                    ])
+                });
+                members.push((String::from("changes"), JsonValue::Array(changes.collect())));
+            }
+            JsonValue::Object(members)
+        });
+        JsonValue::Object(vec![
+            (String::from("module"), string(&self.module)),
+            (String::from("old-revision"), optional(&self.old_revision)),
+            (String::from("new-revision"), optional(&self.new_revision)),
+            (String::from("entries"), JsonValue::Array(entries.collect())),
+        ])
+    }
+}
+
+// Text form, a line per entry with the changed properties indented.
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let revision = |r: &Option<String>| match r {
+            Some(r) => format!("{}@{}", self.module, r),
+            None => self.module.clone(),
+        };
+        writeln!(
+            f,
+            "--- {}\n+++ {}",
+            revision(&self.old_revision),
+            revision(&self.new_revision)
+        )?;
+        for entry in self.entries.iter() {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+// Property values, None when the property is not present.
+type Properties = Vec<(&'static str, Option<String>)>;
+
+fn joined(values: &[String]) -> Option<String> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(" | "))
+    }
+}
+
+fn ext_text(ext: &ExtInstanceNode) -> String {
+    let keyword = match (&ext.module, &ext.prefix) {
+        (Some(module), _) => format!("{}:{}", module, ext.keyword),
+        _ => ext.name(),
+    };
+    match &ext.argument {
+        Some(argument) => format!("{} {:?}", keyword, argument),
+        None => keyword,
+    }
+}
+
+// Properties of the type with the restrictions.  Enums and bits are
+// listed with their value and position.
+fn type_properties(typ: &TypeNode, props: &mut Properties) {
+    props.push(("type", Some(typ.name.clone())));
+    props.push(("range", typ.range.clone()));
+    props.push(("length", typ.length.clone()));
+    let patterns: Vec<String> = typ.patterns.iter().map(|p| p.to_string()).collect();
+    props.push(("pattern", joined(&patterns)));
+    let enums: Vec<String> = typ
+        .enums
+        .iter()
+        .map(|e| match e.value() {
+            Some(v) => format!("{}={}", e.name, v),
+            None => e.name.clone(),
+        })
+        .collect();
+    props.push(("enum", joined(&enums)));
+    let bits: Vec<String> = typ
+        .bits
+        .iter()
+        .map(|b| match b.position {
+            Some(p) => format!("{}={}", b.name, p),
+            None => b.name.clone(),
+        })
+        .collect();
+    props.push(("bit", joined(&bits)));
+    props.push(("base", joined(&typ.bases)));
+    props.push(("path", typ.path.clone()));
+    props.push((
+        "fraction-digits",
+        typ.fraction_digits.map(|d| d.to_string()),
+    ));
+    let members: Vec<String> = typ.union.iter().map(|t| t.name.clone()).collect();
+    props.push(("union", joined(&members)));
+}
+
+fn node_properties(node: &SchemaNode) -> Properties {
+    let kind = match node.kind {
+        SchemaKind::Container => "container",
+        SchemaKind::List => "list",
+        SchemaKind::Leaf => "leaf",
+        SchemaKind::LeafList => "leaf-list",
+        SchemaKind::Anydata => "anydata",
+    };
+    let flag = |b: bool| if b { Some(String::from("true")) } else { None };
+    let mut props = vec![
+        ("kind", Some(kind.to_owned())),
+        ("config", Some(node.config.to_string())),
+        ("presence", flag(node.presence)),
+        ("mandatory", flag(node.mandatory)),
+        ("key", joined(&node.keys)),
+        ("unique", joined(&node.unique)),
+        ("min-elements", node.min_elements.map(|v| v.to_string())),
+        ("max-elements", node.max_elements.map(|v| v.to_string())),
+        (
+            "ordered-by",
+            flag(node.ordered_by_user).map(|_| String::from("user")),
+        ),
+        ("default", joined(&node.default)),
+        ("units", node.units.clone()),
+    ];
+    let must: Vec<String> = node.must.iter().map(|m| m.name.clone()).collect();
+    props.push(("must", joined(&must)));
+    props.push(("when", node.when.as_ref().map(|w| w.name.clone())));
+    if let Some(typ) = &node.typ {
+        type_properties(typ, &mut props);
+    }
+    props.push(("description", node.description.clone()));
+    props
+}
+
+fn typedef_properties(typedef: &TypedefNode) -> Properties {
+    let mut props = vec![];
+    if let Some(typ) = &typedef.typ {
+        type_properties(typ, &mut props);
+    }
+    props.push(("default", typedef.default.clone()));
+    props.push(("units", typedef.units.clone()));
+    props.push(("status", typedef.status.clone()));
+    props.push(("description", typedef.description.clone()));
+    props
+}
+
+fn identity_properties(identity: &IdentityNode) -> Properties {
+    vec![
+        ("base", joined(&identity.bases)),
+        ("if-feature", joined(&identity.if_features)),
+        ("status", identity.status.clone()),
+        ("description", identity.description.clone()),
+    ]
+}
+
+fn feature_properties(feature: &FeatureNode) -> Properties {
+    vec![
+        ("if-feature", joined(&feature.if_features)),
+        ("status", feature.status.clone()),
+        ("description", feature.description.clone()),
+    ]
+}
+
+fn extension_properties(extension: &ExtensionNode) -> Properties {
+    vec![
+        (
+            "argument",
+            extension.argument.as_ref().map(|a| a.name.clone()),
+        ),
+        ("status", extension.status.clone()),
+        ("description", extension.description.clone()),
+    ]
+}
+
+// Properties whose value differs.  Both lists have the same properties
+// in the same order except the type properties of the nodes.
+fn property_changes(
+    old: &[(&'static str, Option<String>)],
+    new: &[(&'static str, Option<String>)],
+) -> Vec<PropertyChange> {
+    let mut changes = vec![];
+    let mut names: Vec<&'static str> = old.iter().map(|(n, _)| *n).collect();
+    for (n, _) in new.iter() {
+        if !names.contains(n) {
+            names.push(n);
+        }
+    }
+    for name in names {
+        let value = |props: &[(&'static str, Option<String>)]| {
+            props
+                .iter()
+                .find(|(n, _)| *n == name)
+                .and_then(|(_, v)| v.clone())
+        };
+        let (o, n) = (value(old), value(new));
+        if o != n {
+            changes.push(PropertyChange::new(name.to_owned(), o, n));
+        }
+    }
+    changes
+}
+
+struct Differ<'a> {
+    module: &'a str,
+    entries: Vec<SchemaDiffEntry>,
+}
+
+impl<'a> Differ<'a> {
+    fn push(&mut self, item: SchemaItem, name: &str, op: SchemaDiffOp) {
+        self.entries
+            .push(SchemaDiffEntry::new(item, name.to_owned(), op));
+    }
+
+    fn diff_exts(&mut self, owner: &str, old: &[ExtInstanceNode], new: &[ExtInstanceNode]) {
+        let old: BTreeSet<String> = old.iter().map(ext_text).collect();
+        let new: BTreeSet<String> = new.iter().map(ext_text).collect();
+        for ext in old.difference(&new) {
+            self.push(
+                SchemaItem::ExtInstance(ext.clone()),
+                owner,
+                SchemaDiffOp::Removed,
+            );
+        }
+        for ext in new.difference(&old) {
+            self.push(
+                SchemaItem::ExtInstance(ext.clone()),
+                owner,
+                SchemaDiffOp::Added,
+            );
+        }
+    }
+
+    fn diff_nodes(
+        &mut self,
+        parent: &str,
+        parent_module: Option<&str>,
+        old: &[Rc<SchemaNode>],
+        new: &[Rc<SchemaNode>],
+    ) {
+        let path = |node: &SchemaNode| {
+            if parent_module == Some(node.module.as_str()) {
+                format!("{}/{}", parent, node.name)
+            } else {
+                format!("{}/{}:{}", parent, node.module, node.name)
+            }
+        };
+        for o in old.iter() {
+            let p = path(o);
+            match find_schema(new, Some(&o.module), &o.name) {
+                Some(n) => {
+                    let changes = property_changes(&node_properties(o), &node_properties(n));
+                    if !changes.is_empty() {
+                        self.push(SchemaItem::Node, &p, SchemaDiffOp::Modified(changes));
+                    }
+                    self.diff_exts(&p, &o.ext_instances, &n.ext_instances);
+                    self.diff_nodes(&p, Some(&o.module), &o.children, &n.children);
+                }
+                None => self.push(SchemaItem::Node, &p, SchemaDiffOp::Removed),
+            }
+        }
+        for n in new.iter() {
+            if find_schema(old, Some(&n.module), &n.name).is_none() {
+                self.push(SchemaItem::Node, &path(n), SchemaDiffOp::Added);
+            }
+        }
+    }
+
+    // Definitions keyed by name in sorted order.  "exts" is for the
+    // extension usages in the definition.
+    fn diff_defs<T>(
+        &mut self,
+        item: SchemaItem,
+        old: &HashMap<String, T>,
+        new: &HashMap<String, T>,
+        props: fn(&T) -> Properties,
+        exts: fn(&T) -> &[ExtInstanceNode],
+    ) {
+        let mut names: BTreeSet<&String> = old.keys().collect();
+        names.extend(new.keys());
+        for name in names {
+            let owner = format!("{} {}", item, name);
+            match (old.get(name), new.get(name)) {
+                (Some(o), Some(n)) => {
+                    let changes = property_changes(&props(o), &props(n));
+                    if !changes.is_empty() {
+                        self.push(item.clone(), name, SchemaDiffOp::Modified(changes));
+                    }
+                    self.diff_exts(&owner, exts(o), exts(n));
+                }
+                (Some(_), None) => self.push(item.clone(), name, SchemaDiffOp::Removed),
+                (None, Some(_)) => self.push(item.clone(), name, SchemaDiffOp::Added),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn diff_modules(&mut self, old: &Module, new: &Module) {
+        self.diff_exts("/", &old.ext_instances, &new.ext_instances);
+        self.diff_defs(
+            SchemaItem::Typedef,
+            &old.typedefs,
+            &new.typedefs,
+            typedef_properties,
+            |t| &t.ext_instances,
+        );
+        self.diff_defs(
+            SchemaItem::Identity,
+            &old.identities,
+            &new.identities,
+            identity_properties,
+            |i| &i.ext_instances,
+        );
+        self.diff_defs(
+            SchemaItem::Feature,
+            &old.features,
+            &new.features,
+            feature_properties,
+            |f| &f.ext_instances,
+        );
+        self.diff_defs(
+            SchemaItem::Extension,
+            &old.extensions,
+            &new.extensions,
+            extension_properties,
+            |e| &e.ext_instances,
+        );
+    }
+
+    fn diff_schemas(&mut self, old: &Schema, new: &Schema) {
+        let module = self.module;
+        let top = |schema: &Schema| -> Vec<Rc<SchemaNode>> {
+            schema
+                .nodes
+                .iter()
+                .filter(|n| n.module == module)
+                .cloned()
+                .collect()
+        };
+        self.diff_nodes("", None, &top(old), &top(new));
+    }
+}
+
+impl Modules {
+    // Structural difference of the module "name" from "self", the old
+    // revision, to "new".  Schema nodes are compared in the compiled
+    // schemas and the other definitions in the modules.
+    pub fn schema_diff(&self, new: &Modules, name: &str) -> Result<SchemaDiff, CompatError> {
+        let old_module = implemented_module(self, name)?;
+        let new_module = implemented_module(new, name)?;
+        let mut differ = Differ {
+            module: name,
+            entries: vec![],
+        };
+        differ.diff_schemas(&*self.schema()?, &*new.schema()?);
+        differ.diff_modules(old_module, new_module);
+
+        let mut diff = SchemaDiff::new(
+            name.to_owned(),
+            old_module.revision().map(String::from),
+            new_module.revision().map(String::from),
+        );
+        diff.entries = differ.entries;
+        Ok(diff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn property_changes_test() {
+        let old = vec![("type", Some(String::from("uint8"))), ("range", None)];
+        let new = vec![
+            ("type", Some(String::from("uint8"))),
+            ("range", Some(String::from("1..10"))),
+            ("units", Some(String::from("seconds"))),
+        ];
+        assert_eq!(
+            property_changes(&old, &new),
+            vec![
+                PropertyChange::new(String::from("range"), None, Some(String::from("1..10"))),
+                PropertyChange::new(String::from("units"), None, Some(String::from("seconds"))),
+            ]
+        );
+        assert_eq!(property_changes(&new, &new), vec![]);
+    }
+}
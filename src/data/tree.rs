@@ -0,0 +1,624 @@
+use crate::data::*;
+use std::fmt;
+use std::rc::Rc;
+use thiserror::Error;
+
+// Content of anydata which has no schema.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct AnyNode {
+    pub name: String,
+    #[new(default)]
+    pub module: Option<String>,
+    #[new(default)]
+    pub value: Option<String>,
+    #[new(default)]
+    pub children: Vec<AnyNode>,
+}
+
+// RFC7952 metadata annotation of data node such as
+// "ietf-netconf:operation".
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct Annotation {
+    pub module: String,
+    pub name: String,
+    pub value: String,
+}
+
+// Content of data node.  Each list entry and each leaf-list value is a
+// separate node.  Children are kept in schema order so that entries of
+// the same list are adjacent and key leaves come first in a list entry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataContent {
+    Container(Vec<DataNode>),
+    ListEntry(Vec<DataNode>),
+    Leaf(LeafValue),
+    LeafList(LeafValue),
+    Anydata(Vec<AnyNode>),
+}
+
+// Instance data node.
+#[derive(Clone, new)]
+pub struct DataNode {
+    pub schema: Rc<SchemaNode>,
+    pub content: DataContent,
+    #[new(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+// Schema nodes are compared by identity, or by module and name when the
+// trees are built from different schemas.
+pub(crate) fn same_schema(a: &Rc<SchemaNode>, b: &Rc<SchemaNode>) -> bool {
+    Rc::ptr_eq(a, b) || (a.module == b.module && a.name == b.name && a.kind == b.kind)
+}
+
+impl PartialEq for DataNode {
+    fn eq(&self, other: &Self) -> bool {
+        same_schema(&self.schema, &other.schema)
+            && self.content == other.content
+            && self.annotations == other.annotations
+    }
+}
+
+impl fmt::Debug for DataNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = format!("{}:{}", self.schema.module, self.schema.name);
+        let mut t = f.debug_tuple("DataNode");
+        t.field(&name).field(&self.content);
+        if !self.annotations.is_empty() {
+            t.field(&self.annotations);
+        }
+        t.finish()
+    }
+}
+
+// Copy of the node which refers to the schema nodes "schemas" and their
+// descendants, for the node of a tree built from a separately compiled
+// schema.  Nodes of the tree refer to the schema nodes of the tree's
+// schema, which are compared by identity.
+pub(crate) fn rebind(
+    parent: &str,
+    parent_module: Option<&str>,
+    node: &DataNode,
+    schemas: &[Rc<SchemaNode>],
+) -> Result<DataNode, DataError> {
+    let schema = schemas
+        .iter()
+        .find(|s| same_schema(s, node.schema()))
+        .ok_or_else(|| DataError::UnknownNode(parent.to_owned(), node.name().to_owned()))?;
+    if Rc::ptr_eq(schema, node.schema()) {
+        return Ok(node.clone());
+    }
+    let path = instance_path(parent, parent_module, node);
+    let children = |children: &[DataNode]| {
+        children
+            .iter()
+            .map(|c| rebind(&path, Some(&schema.module), c, &schema.children))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let content = match &node.content {
+        DataContent::Container(c) => DataContent::Container(children(c)?),
+        DataContent::ListEntry(c) => DataContent::ListEntry(children(c)?),
+        content => content.clone(),
+    };
+    let mut rebound = DataNode::new(schema.clone(), content);
+    rebound.annotations = node.annotations.clone();
+    Ok(rebound)
+}
+
+// Insert node after the last node whose schema is the same or precedes
+// the node's schema in "schemas".
+pub(crate) fn insert_ordered(
+    nodes: &mut Vec<DataNode>,
+    schemas: &[Rc<SchemaNode>],
+    node: DataNode,
+) {
+    let order = |n: &DataNode| schemas.iter().position(|s| Rc::ptr_eq(s, &n.schema));
+    let index = order(&node);
+    let pos = nodes
+        .iter()
+        .rposition(|n| order(n) <= index)
+        .map(|p| p + 1)
+        .unwrap_or(0);
+    nodes.insert(pos, node);
+}
+
+impl DataNode {
+    pub fn schema(&self) -> &Rc<SchemaNode> {
+        &self.schema
+    }
+
+    pub fn name(&self) -> &str {
+        &self.schema.name
+    }
+
+    pub fn children(&self) -> &[DataNode] {
+        match &self.content {
+            DataContent::Container(children) | DataContent::ListEntry(children) => children,
+            _ => &[],
+        }
+    }
+
+    pub fn children_mut(&mut self) -> Option<&mut Vec<DataNode>> {
+        match &mut self.content {
+            DataContent::Container(children) | DataContent::ListEntry(children) => Some(children),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> Option<&LeafValue> {
+        match &self.content {
+            DataContent::Leaf(value) | DataContent::LeafList(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn annotation(&self, module: &str, name: &str) -> Option<&str> {
+        self.annotations
+            .iter()
+            .find(|a| a.module == module && a.name == name)
+            .map(|a| a.value.as_str())
+    }
+
+    // First child of the name.
+    pub fn child(&self, name: &str) -> Option<&DataNode> {
+        self.children().iter().find(|c| c.name() == name)
+    }
+
+    // Key values of list entry in key order.
+    pub fn key_values(&self) -> Vec<&LeafValue> {
+        self.schema()
+            .keys
+            .iter()
+            .filter_map(|k| self.child(k).and_then(|c| c.value()))
+            .collect()
+    }
+
+    // Find entry of list "name" by key values in the canonical form, in key
+    // order.
+    pub fn entry(&self, name: &str, keys: &[&str]) -> Option<&DataNode> {
+        find_entry(self.children(), name, keys)
+    }
+
+    // Insert child in schema order.
+    pub fn insert(&mut self, child: DataNode) {
+        let schema = self.schema.clone();
+        if let Some(children) = self.children_mut() {
+            insert_ordered(children, &schema.children, child);
+        }
+    }
+}
+
+pub fn find_entry<'a>(nodes: &'a [DataNode], name: &str, keys: &[&str]) -> Option<&'a DataNode> {
+    nodes.iter().find(|n| {
+        matches!(n.content, DataContent::ListEntry(_))
+            && n.name() == name
+            && n.key_values()
+                .iter()
+                .map(|v| v.to_string())
+                .eq(keys.iter().map(|k| k.to_string()))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum DataError {
+    #[error("invalid path \"{0}\"")]
+    InvalidPath(String),
+    #[error("unknown node \"{1}\" in \"{0}\"")]
+    UnknownNode(String, String),
+    #[error("\"{0}\" not found")]
+    NotFound(String),
+    #[error("\"{0}\" already exists")]
+    Exists(String),
+    #[error("missing key \"{1}\" in \"{0}\"")]
+    MissingKey(String, String),
+    #[error("invalid value \"{1}\" in \"{0}\": {2}")]
+    InvalidValue(String, String, String),
+    #[error("\"{0}\" requires a value")]
+    MissingValue(String),
+    #[error("\"{0}\" can't have a value")]
+    UnexpectedValue(String),
+    #[error("list key \"{0}\" can't be modified")]
+    KeyModify(String),
+    #[error("syntax error at \"{0}\"")]
+    Syntax(String),
+    #[error("keys of \"{0}\" must be the first child nodes in the key order")]
+    KeyOrder(String),
+    #[error("nesting depth exceeds {0}")]
+    TooDeep(usize),
+}
+
+// Instance data tree of a schema.
+#[derive(Clone)]
+pub struct DataTree {
+    pub schema: Rc<Schema>,
+    pub nodes: Vec<DataNode>,
+}
+
+impl fmt::Debug for DataTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DataTree")
+            .field("nodes", &self.nodes)
+            .finish()
+    }
+}
+
+impl PartialEq for DataTree {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes
+    }
+}
+
+// Data node selected by a path segment.  "keys" are list key values and
+// "value" is the leaf-list value.
+struct Selector {
+    schema: Rc<SchemaNode>,
+    keys: Vec<(Rc<SchemaNode>, LeafValue)>,
+    value: Option<LeafValue>,
+}
+
+impl Selector {
+    fn matches(&self, node: &DataNode) -> bool {
+        if !Rc::ptr_eq(node.schema(), &self.schema) {
+            return false;
+        }
+        match &node.content {
+            DataContent::ListEntry(_) => self
+                .keys
+                .iter()
+                .all(|(k, v)| node.child(&k.name).and_then(|c| c.value()) == Some(v)),
+            DataContent::LeafList(value) => self.value.as_ref() == Some(value),
+            _ => true,
+        }
+    }
+
+    fn position(&self, nodes: &[DataNode]) -> Option<usize> {
+        nodes.iter().position(|n| self.matches(n))
+    }
+
+    // New node of the selector, list entries have the key leaves.
+    fn create(&self) -> DataNode {
+        let value = || self.value.clone().unwrap_or(LeafValue::Empty);
+        let content = match self.schema.kind {
+            SchemaKind::Container => DataContent::Container(vec![]),
+            SchemaKind::List => DataContent::ListEntry(
+                self.keys
+                    .iter()
+                    .map(|(k, v)| DataNode::new(k.clone(), DataContent::Leaf(v.clone())))
+                    .collect(),
+            ),
+            SchemaKind::Leaf => DataContent::Leaf(value()),
+            SchemaKind::LeafList => DataContent::LeafList(value()),
+            SchemaKind::Anydata => DataContent::Anydata(vec![]),
+        };
+        DataNode::new(self.schema.clone(), content)
+    }
+}
+
+fn parse_value(
+    schema: &Schema,
+    node: &SchemaNode,
+    value: &str,
+    path: &str,
+) -> Result<LeafValue, DataError> {
+    LeafValue::parse(schema, node, value)
+        .map_err(|e| DataError::InvalidValue(path.to_owned(), value.to_owned(), e))
+}
+
+// Resolve path segment to schema node and key or leaf-list value.  "value"
+// is the value of the leaf or leaf-list at the end of path.
+fn select(
+    schema: &Schema,
+    candidates: &[Rc<SchemaNode>],
+    parent: Option<&SchemaNode>,
+    segment: &PathSegment,
+    value: Option<&str>,
+    path: &str,
+) -> Result<Selector, DataError> {
+    let unknown = || DataError::UnknownNode(path.to_owned(), segment.name.clone());
+    let module = match &segment.prefix {
+        Some(prefix) => Some(schema.resolve_module(prefix).ok_or_else(unknown)?),
+        None => parent.map(|p| p.module.as_str()),
+    };
+    let snode = find_schema(candidates, module, &segment.name)
+        .ok_or_else(unknown)?
+        .clone();
+    let mut selector = Selector {
+        schema: snode.clone(),
+        keys: vec![],
+        value: None,
+    };
+    let predicate = |name: &str| {
+        segment
+            .predicates
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    };
+    match snode.kind {
+        SchemaKind::List => {
+            for key in snode.keys.iter() {
+                let kvalue = predicate(key)
+                    .ok_or_else(|| DataError::MissingKey(path.to_owned(), key.clone()))?;
+                let knode = snode.child(None, key).ok_or_else(unknown)?;
+                let kvalue = parse_value(schema, knode, kvalue, path)?;
+                selector.keys.push((knode.clone(), kvalue));
+            }
+        }
+        SchemaKind::Leaf | SchemaKind::LeafList => {
+            let v = match snode.kind {
+                SchemaKind::LeafList => predicate(".").or(value),
+                _ => value,
+            };
+            if let Some(v) = v {
+                selector.value = Some(parse_value(schema, &snode, v, path)?);
+            }
+        }
+        _ => {}
+    }
+    Ok(selector)
+}
+
+fn is_key(parent: Option<&SchemaNode>, node: &SchemaNode) -> bool {
+    parent.is_some_and(|p| p.kind == SchemaKind::List && p.is_key(&node.name))
+}
+
+struct Walk<'a> {
+    schema: &'a Schema,
+    path: &'a str,
+}
+
+impl<'a> Walk<'a> {
+    fn get<'n>(
+        &self,
+        nodes: &'n [DataNode],
+        candidates: &[Rc<SchemaNode>],
+        parent: Option<&SchemaNode>,
+        segments: &[PathSegment],
+    ) -> Result<&'n DataNode, DataError> {
+        let selector = select(
+            self.schema,
+            candidates,
+            parent,
+            &segments[0],
+            None,
+            self.path,
+        )?;
+        if selector.schema.kind == SchemaKind::LeafList && selector.value.is_none() {
+            return Err(DataError::MissingValue(self.path.to_owned()));
+        }
+        let index = selector
+            .position(nodes)
+            .ok_or_else(|| DataError::NotFound(self.path.to_owned()))?;
+        let node = &nodes[index];
+        if segments.len() == 1 {
+            return Ok(node);
+        }
+        let snode = &selector.schema;
+        self.get(
+            node.children(),
+            &snode.children,
+            Some(snode),
+            &segments[1..],
+        )
+    }
+
+    fn get_mut<'n>(
+        &self,
+        nodes: &'n mut [DataNode],
+        candidates: &[Rc<SchemaNode>],
+        parent: Option<&SchemaNode>,
+        segments: &[PathSegment],
+    ) -> Result<&'n mut DataNode, DataError> {
+        let selector = select(
+            self.schema,
+            candidates,
+            parent,
+            &segments[0],
+            None,
+            self.path,
+        )?;
+        if selector.schema.kind == SchemaKind::LeafList && selector.value.is_none() {
+            return Err(DataError::MissingValue(self.path.to_owned()));
+        }
+        let index = selector
+            .position(nodes)
+            .ok_or_else(|| DataError::NotFound(self.path.to_owned()))?;
+        let node = &mut nodes[index];
+        if segments.len() == 1 {
+            return Ok(node);
+        }
+        let snode = &selector.schema;
+        match node.children_mut() {
+            Some(children) => self.get_mut(children, &snode.children, Some(snode), &segments[1..]),
+            None => Err(DataError::InvalidPath(self.path.to_owned())),
+        }
+    }
+
+    fn set(
+        &self,
+        nodes: &mut Vec<DataNode>,
+        candidates: &[Rc<SchemaNode>],
+        parent: Option<&SchemaNode>,
+        segments: &[PathSegment],
+        value: Option<&str>,
+    ) -> Result<(), DataError> {
+        let last = segments.len() == 1;
+        let selector = select(
+            self.schema,
+            candidates,
+            parent,
+            &segments[0],
+            if last { value } else { None },
+            self.path,
+        )?;
+        let snode = selector.schema.clone();
+        match snode.kind {
+            SchemaKind::Leaf | SchemaKind::LeafList | SchemaKind::Anydata if !last => {
+                return Err(DataError::InvalidPath(self.path.to_owned()));
+            }
+            SchemaKind::Leaf | SchemaKind::LeafList if selector.value.is_none() => {
+                return Err(DataError::MissingValue(self.path.to_owned()));
+            }
+            SchemaKind::Container | SchemaKind::List | SchemaKind::Anydata
+                if value.is_some() && last =>
+            {
+                return Err(DataError::UnexpectedValue(self.path.to_owned()));
+            }
+            _ => {}
+        }
+        if snode.kind == SchemaKind::Leaf {
+            let node = selector.create();
+            match nodes.iter().position(|n| Rc::ptr_eq(n.schema(), &snode)) {
+                Some(index) => {
+                    if is_key(parent, &snode) && nodes[index] != node {
+                        return Err(DataError::KeyModify(self.path.to_owned()));
+                    }
+                    nodes[index] = node;
+                }
+                None => insert_ordered(nodes, candidates, node),
+            }
+            return Ok(());
+        }
+        let index = match selector.position(nodes) {
+            Some(index) => index,
+            None => {
+                insert_ordered(nodes, candidates, selector.create());
+                selector
+                    .position(nodes)
+                    .ok_or_else(|| DataError::NotFound(self.path.to_owned()))?
+            }
+        };
+        if last {
+            return Ok(());
+        }
+        match nodes[index].children_mut() {
+            Some(children) => self.set(
+                children,
+                &snode.children,
+                Some(&snode),
+                &segments[1..],
+                value,
+            ),
+            None => Err(DataError::InvalidPath(self.path.to_owned())),
+        }
+    }
+
+    fn delete(
+        &self,
+        nodes: &mut Vec<DataNode>,
+        candidates: &[Rc<SchemaNode>],
+        parent: Option<&SchemaNode>,
+        segments: &[PathSegment],
+    ) -> Result<DataNode, DataError> {
+        let selector = select(
+            self.schema,
+            candidates,
+            parent,
+            &segments[0],
+            None,
+            self.path,
+        )?;
+        let snode = selector.schema.clone();
+        if snode.kind == SchemaKind::LeafList && selector.value.is_none() {
+            return Err(DataError::MissingValue(self.path.to_owned()));
+        }
+        let index = selector
+            .position(nodes)
+            .ok_or_else(|| DataError::NotFound(self.path.to_owned()))?;
+        if segments.len() == 1 {
+            if is_key(parent, &snode) {
+                return Err(DataError::KeyModify(self.path.to_owned()));
+            }
+            return Ok(nodes.remove(index));
+        }
+        match nodes[index].children_mut() {
+            Some(children) => self.delete(children, &snode.children, Some(&snode), &segments[1..]),
+            None => Err(DataError::InvalidPath(self.path.to_owned())),
+        }
+    }
+}
+
+impl DataTree {
+    pub fn new(schema: Rc<Schema>) -> Self {
+        DataTree {
+            schema,
+            nodes: vec![],
+        }
+    }
+
+    // Get node by data path such as "/ietf-interfaces:interfaces/
+    // interface[name='eth0']/enabled".  All of keys of list entries are
+    // required.
+    pub fn get(&self, path: &str) -> Result<&DataNode, DataError> {
+        let segments = data_path_parse(path)?;
+        let walk = Walk {
+            schema: &self.schema,
+            path,
+        };
+        walk.get(&self.nodes, &self.schema.nodes, None, &segments)
+    }
+
+    pub fn get_mut(&mut self, path: &str) -> Result<&mut DataNode, DataError> {
+        let segments = data_path_parse(path)?;
+        let schema = self.schema.clone();
+        let walk = Walk {
+            schema: &schema,
+            path,
+        };
+        walk.get_mut(&mut self.nodes, &schema.nodes, None, &segments)
+    }
+
+    // Set value of leaf or leaf-list.  Missing containers and list entries
+    // on the path are created.
+    pub fn set(&mut self, path: &str, value: &str) -> Result<(), DataError> {
+        let segments = data_path_parse(path)?;
+        let schema = self.schema.clone();
+        let walk = Walk {
+            schema: &schema,
+            path,
+        };
+        walk.set(&mut self.nodes, &schema.nodes, None, &segments, Some(value))
+    }
+
+    // Create container, list entry, anydata or leaf-list value given by
+    // "[.='value']" predicate.
+    pub fn create(&mut self, path: &str) -> Result<(), DataError> {
+        let segments = data_path_parse(path)?;
+        let schema = self.schema.clone();
+        let walk = Walk {
+            schema: &schema,
+            path,
+        };
+        walk.set(&mut self.nodes, &schema.nodes, None, &segments, None)
+    }
+
+    // Delete node and return it.  List keys can't be deleted.
+    pub fn delete(&mut self, path: &str) -> Result<DataNode, DataError> {
+        let segments = data_path_parse(path)?;
+        let schema = self.schema.clone();
+        let walk = Walk {
+            schema: &schema,
+            path,
+        };
+        walk.delete(&mut self.nodes, &schema.nodes, None, &segments)
+    }
+
+    // Insert top level node in schema order.
+    pub fn insert(&mut self, node: DataNode) {
+        insert_ordered(&mut self.nodes, &self.schema.nodes, node);
+    }
+
+    pub fn entry(&self, name: &str, keys: &[&str]) -> Option<&DataNode> {
+        find_entry(&self.nodes, name, keys)
+    }
+
+    // Node at the index path from the root.
+    pub fn node(&self, index: &[usize]) -> Option<&DataNode> {
+        let (first, rest) = index.split_first()?;
+        let mut node = self.nodes.get(*first)?;
+        for i in rest {
+            node = node.children().get(*i)?;
+        }
+        Some(node)
+    }
+}
@@ -0,0 +1,425 @@
+use crate::data::*;
+use crate::modules::{Modules, TypeKind};
+use crate::xpath::{evaluate_bool, Document, XPath};
+use std::collections::HashSet;
+use std::rc::Rc;
+use thiserror::Error;
+
+// Violation of a schema constraint.  The first field is the instance path
+// of the node in the JSON form, module names qualify the top level nodes
+// and the nodes whose module differs from the parent.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ValidationError {
+    #[error("module \"{1}\" of \"{0}\" is not loaded")]
+    UnknownModule(String, String),
+    #[error("mandatory node \"{0}\" is missing")]
+    Mandatory(String),
+    #[error("mandatory choice \"{1}\" of \"{0}\" is missing")]
+    MissingChoice(String, String),
+    #[error("\"{0}\" has fewer than {1} elements")]
+    TooFew(String, u64),
+    #[error("\"{0}\" has more than {1} elements")]
+    TooMany(String, u64),
+    #[error("missing key \"{1}\" in \"{0}\"")]
+    MissingKey(String, String),
+    #[error("duplicate list entry \"{0}\"")]
+    DuplicateKey(String),
+    #[error("\"{0}\" violates unique \"{1}\"")]
+    NotUnique(String, String),
+    #[error("duplicate leaf-list value \"{0}\"")]
+    DuplicateValue(String),
+    #[error("invalid value of \"{0}\": {1}")]
+    InvalidValue(String, String),
+    #[error("required instance of \"{0}\" is missing")]
+    MissingInstance(String),
+    #[error("\"{path}\" violates must \"{expr}\"")]
+    Must {
+        path: String,
+        expr: String,
+        error_message: Option<String>,
+        error_app_tag: Option<String>,
+    },
+    #[error("\"{0}\" exists while when \"{1}\" is false")]
+    When(String, String),
+    #[error("XPath of \"{0}\" can't be evaluated: {1}")]
+    XPath(String, String),
+}
+
+impl ValidationError {
+    pub fn path(&self) -> &str {
+        match self {
+            ValidationError::UnknownModule(path, _)
+            | ValidationError::Mandatory(path)
+            | ValidationError::MissingChoice(path, _)
+            | ValidationError::TooFew(path, _)
+            | ValidationError::TooMany(path, _)
+            | ValidationError::MissingKey(path, _)
+            | ValidationError::DuplicateKey(path)
+            | ValidationError::NotUnique(path, _)
+            | ValidationError::DuplicateValue(path)
+            | ValidationError::InvalidValue(path, _)
+            | ValidationError::MissingInstance(path)
+            | ValidationError::Must { path, .. }
+            | ValidationError::When(path, _)
+            | ValidationError::XPath(path, _) => path,
+        }
+    }
+
+    // RFC7950 15.  Error Responses for YANG Related Errors
+    //     The following "error-app-tag" values are defined for errors
+    //     violating YANG constraints.
+    pub fn app_tag(&self) -> Option<&str> {
+        match self {
+            ValidationError::NotUnique(..) => Some("data-not-unique"),
+            ValidationError::TooMany(..) => Some("too-many-elements"),
+            ValidationError::TooFew(..) => Some("too-few-elements"),
+            ValidationError::Must { error_app_tag, .. } => {
+                Some(error_app_tag.as_deref().unwrap_or("must-violation"))
+            }
+            ValidationError::MissingInstance(..) => Some("instance-required"),
+            ValidationError::MissingChoice(..) => Some("missing-choice"),
+            _ => None,
+        }
+    }
+}
+
+fn schema_path(parent: &str, parent_module: Option<&str>, schema: &SchemaNode) -> String {
+    if parent_module == Some(schema.module.as_str()) {
+        format!("{}/{}", parent, schema.name)
+    } else {
+        format!("{}/{}:{}", parent, schema.module, schema.name)
+    }
+}
+
+// Descendant leaf of "unique" argument, prefixes are ignored since the
+// schema node identifier is relative to the list.
+fn descendant<'a>(node: &'a DataNode, id: &str) -> Option<&'a DataNode> {
+    id.split('/')
+        .map(|n| n.rsplit(':').next().unwrap_or(n))
+        .try_fold(node, |node, name| node.child(name))
+}
+
+// Value of the descendant leaf of "unique" argument, or its default value
+// when the leaf doesn't exist.
+fn unique_value(
+    tree: &DataTree,
+    schema: &SchemaNode,
+    entry: &DataNode,
+    id: &str,
+) -> Option<String> {
+    if let Some(node) = descendant(entry, id) {
+        return node.value().map(|v| v.to_string());
+    }
+    let mut names = id.split('/').map(|n| n.rsplit(':').next().unwrap_or(n));
+    let first = schema.child(None, names.next()?)?;
+    let leaf = names.try_fold(first, |s, name| s.child(None, name))?;
+    leaf.default_values(&tree.schema)
+        .first()
+        .map(|v| v.to_string())
+}
+
+struct Validator<'a> {
+    tree: &'a DataTree,
+    modules: &'a Modules,
+    errors: Vec<ValidationError>,
+}
+
+impl<'a> Validator<'a> {
+    fn xpath_bool(&mut self, path: &str, xpath: &XPath, index: &[usize]) -> Option<bool> {
+        match evaluate_bool(self.tree, xpath, &index.to_vec()) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.errors
+                    .push(ValidationError::XPath(path.to_owned(), e.to_string()));
+                None
+            }
+        }
+    }
+
+    // Sibling nodes of "schemas" instances, "index" is the index path of
+    // the parent.  Absence of the nodes of the cases which are not active
+    // is not checked.
+    fn siblings(
+        &mut self,
+        parent: &str,
+        parent_module: Option<&str>,
+        index: &mut Vec<usize>,
+        nodes: &'a [DataNode],
+        schemas: &[Rc<SchemaNode>],
+        choices: &[SchemaChoice],
+    ) {
+        let inactive = inactive_case_nodes(choices, nodes, false);
+        for schema in schemas.iter() {
+            let instances: Vec<&DataNode> = nodes
+                .iter()
+                .filter(|n| Rc::ptr_eq(n.schema(), schema))
+                .collect();
+            if !inactive.iter().any(|s| Rc::ptr_eq(s, schema)) {
+                self.absence(parent, parent_module, schema, instances.len());
+            }
+            if let Some(max) = schema.max_elements {
+                if instances.len() as u64 > max {
+                    let path = schema_path(parent, parent_module, schema);
+                    self.errors.push(ValidationError::TooMany(path, max));
+                }
+            }
+            self.uniqueness(parent, parent_module, schema, &instances);
+        }
+        self.choices(parent, choices, nodes);
+        for (i, node) in nodes.iter().enumerate() {
+            index.push(i);
+            self.node(parent, parent_module, index, node);
+            index.pop();
+        }
+    }
+
+    // RFC7950 3.  Terminology
+    //     mandatory node: A mandatory node is one of:
+    //     o  A leaf, choice, anydata, or anyxml node with a "mandatory"
+    //        statement with the value "true".
+    //     o  A list or leaf-list node with a "min-elements" statement with
+    //        a value greater than zero.
+    //     o  A container node without a "presence" statement and that has
+    //        at least one mandatory node as a child.
+    //
+    // Absence of state data is not checked since configuration has no
+    // state data, nor nodes whose "when" condition may be false.
+    fn absence(
+        &mut self,
+        parent: &str,
+        parent_module: Option<&str>,
+        schema: &Rc<SchemaNode>,
+        count: usize,
+    ) {
+        if !schema.config || schema.when.is_some() {
+            return;
+        }
+        let path = || schema_path(parent, parent_module, schema);
+        match schema.kind {
+            SchemaKind::Leaf | SchemaKind::Anydata if schema.mandatory && count == 0 => {
+                self.errors.push(ValidationError::Mandatory(path()));
+            }
+            SchemaKind::List | SchemaKind::LeafList => {
+                if let Some(min) = schema.min_elements {
+                    if (count as u64) < min {
+                        self.errors.push(ValidationError::TooFew(path(), min));
+                    }
+                }
+            }
+            SchemaKind::Container if !schema.presence && count == 0 => {
+                let path = path();
+                let inactive = inactive_case_nodes(&schema.choices, &[], false);
+                for child in schema.children.iter() {
+                    if !inactive.iter().any(|s| Rc::ptr_eq(s, child)) {
+                        self.absence(&path, Some(&schema.module), child, 0);
+                    }
+                }
+                self.choices(&path, &schema.choices, &[]);
+            }
+            _ => {}
+        }
+    }
+
+    // RFC7950 7.9.4.  The choice's "mandatory" Statement
+    //     If set to "true", at least one node from exactly one of the
+    //     choice's case branches MUST exist.
+    //
+    // Choices in the active cases are checked too.  The error path is the
+    // parent of the choice.
+    fn choices(&mut self, parent: &str, choices: &[SchemaChoice], nodes: &[DataNode]) {
+        for choice in choices.iter() {
+            match choice.active_case(nodes) {
+                Some(case) => self.choices(parent, &case.choices, nodes),
+                None if choice.mandatory && choice.config && choice.when.is_none() => {
+                    let path = if parent.is_empty() { "/" } else { parent };
+                    self.errors.push(ValidationError::MissingChoice(
+                        path.to_owned(),
+                        choice.name.clone(),
+                    ));
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn uniqueness(
+        &mut self,
+        parent: &str,
+        parent_module: Option<&str>,
+        schema: &SchemaNode,
+        instances: &[&DataNode],
+    ) {
+        match schema.kind {
+            SchemaKind::List => {
+                let mut keys = HashSet::new();
+                for entry in instances.iter() {
+                    let values: Vec<String> =
+                        entry.key_values().iter().map(|v| v.to_string()).collect();
+                    if values.len() == schema.keys.len() && !keys.insert(values) {
+                        let path = instance_path(parent, parent_module, entry);
+                        self.errors.push(ValidationError::DuplicateKey(path));
+                    }
+                }
+                // RFC7950 7.8.3.  The list's "unique" Statement
+                //     The combined values of all the leaf instances specified
+                //     in the argument string, including leafs with default
+                //     values, MUST be unique within all list entry instances
+                //     in which all referenced leafs exist or have default
+                //     values.
+                for unique in schema.unique.iter() {
+                    let mut tuples = HashSet::new();
+                    for entry in instances.iter() {
+                        let tuple: Option<Vec<String>> = unique
+                            .split_whitespace()
+                            .map(|id| unique_value(self.tree, schema, entry, id))
+                            .collect();
+                        if let Some(tuple) = tuple {
+                            if !tuples.insert(tuple) {
+                                let path = instance_path(parent, parent_module, entry);
+                                self.errors
+                                    .push(ValidationError::NotUnique(path, unique.clone()));
+                            }
+                        }
+                    }
+                }
+            }
+            // RFC7950 7.7.  The "leaf-list" Statement
+            //     In configuration data, the values in a leaf-list MUST be
+            //     unique.
+            SchemaKind::LeafList if schema.config => {
+                let mut values = HashSet::new();
+                for node in instances.iter() {
+                    if let Some(value) = node.value() {
+                        if !values.insert(value.to_string()) {
+                            let path = instance_path(parent, parent_module, node);
+                            self.errors.push(ValidationError::DuplicateValue(path));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn node(
+        &mut self,
+        parent: &str,
+        parent_module: Option<&str>,
+        index: &mut Vec<usize>,
+        node: &'a DataNode,
+    ) {
+        let schema = node.schema();
+        let path = instance_path(parent, parent_module, node);
+        if parent_module != Some(schema.module.as_str())
+            && self.modules.find_module(&schema.module).is_none()
+        {
+            self.errors.push(ValidationError::UnknownModule(
+                path.clone(),
+                schema.module.clone(),
+            ));
+        }
+
+        if let Some(when) = &schema.when {
+            if let Some(xpath) = &when.xpath {
+                if self.xpath_bool(&path, xpath, index) == Some(false) {
+                    self.errors
+                        .push(ValidationError::When(path.clone(), when.name.clone()));
+                }
+            }
+        }
+        for must in schema.must.iter() {
+            if let Some(xpath) = &must.xpath {
+                if self.xpath_bool(&path, xpath, index) == Some(false) {
+                    self.errors.push(ValidationError::Must {
+                        path: path.clone(),
+                        expr: must.name.clone(),
+                        error_message: must.error_message.clone(),
+                        error_app_tag: must.error_app_tag.clone(),
+                    });
+                }
+            }
+        }
+
+        match &node.content {
+            DataContent::Leaf(value) | DataContent::LeafList(value) => {
+                self.value(&path, index, schema, value);
+            }
+            DataContent::ListEntry(children) => {
+                for key in schema.keys.iter() {
+                    if node.child(key).is_none() {
+                        self.errors
+                            .push(ValidationError::MissingKey(path.clone(), key.clone()));
+                    }
+                }
+                self.siblings(
+                    &path,
+                    Some(&schema.module),
+                    index,
+                    children,
+                    &schema.children,
+                    &schema.choices,
+                );
+            }
+            DataContent::Container(children) => {
+                self.siblings(
+                    &path,
+                    Some(&schema.module),
+                    index,
+                    children,
+                    &schema.children,
+                    &schema.choices,
+                );
+            }
+            DataContent::Anydata(_) => {}
+        }
+    }
+
+    fn value(&mut self, path: &str, index: &[usize], schema: &SchemaNode, value: &LeafValue) {
+        let typ = match &schema.typ {
+            Some(typ) => typ,
+            None => return,
+        };
+        if let Err(reason) = typ.validate(schema.leafref.as_ref(), value) {
+            self.errors
+                .push(ValidationError::InvalidValue(path.to_owned(), reason));
+            return;
+        }
+        // RFC7950 9.9.3.  The "require-instance" Statement
+        //     If this statement is not present, it defaults to "true".
+        let required = match typ.kind {
+            TypeKind::Yleafref => schema.leafref.is_some(),
+            TypeKind::YinstanceIdentifier => true,
+            _ => false,
+        };
+        if required
+            && typ.require_instance != Some(false)
+            && self.tree.deref(&index.to_vec()).is_empty()
+        {
+            self.errors
+                .push(ValidationError::MissingInstance(path.to_owned()));
+        }
+    }
+}
+
+// Validate the data tree against the constraints of the schema.  All of the
+// violations are returned.  Nodes of the tree must be of the modules.
+pub fn validate(tree: &DataTree, modules: &Modules) -> Result<(), Vec<ValidationError>> {
+    let mut validator = Validator {
+        tree,
+        modules,
+        errors: vec![],
+    };
+    validator.siblings(
+        "",
+        None,
+        &mut vec![],
+        &tree.nodes,
+        &tree.schema.nodes,
+        &tree.schema.choices,
+    );
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(validator.errors)
+    }
+}
@@ -0,0 +1,359 @@
+use crate::data::*;
+use crate::modules::*;
+use crate::xpath::XPath;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fmt;
+
+// Typed value of leaf and leaf-list.  Union and leafref values are stored
+// as the value of the member or target type which accepts the value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeafValue {
+    Int(i64),
+    Uint(u64),
+    // Value scaled by 10^fraction-digits and fraction-digits.
+    Decimal64(i64, u8),
+    String(String),
+    Boolean(bool),
+    // Enum name and its assigned value.
+    Enum(String, i64),
+    // Bit names in position order.
+    Bits(Vec<String>),
+    Binary(Vec<u8>),
+    // Module name and identity name.
+    Identity(String, String),
+    Empty,
+    InstanceIdentifier(String),
+}
+
+// RFC7950 9.3.2.  Lexical Representation
+//     A decimal64 value is lexically represented as an optional sign ("+"
+//     or "-"), followed by a sequence of decimal digits, optionally
+//     followed by a period ('.') as a decimal indicator and a sequence of
+//     decimal digits.
+//
+//     The canonical form of a positive decimal64 value does not include
+//     the sign "+".  The decimal point is required.  Leading and trailing
+//     zeros are prohibited, subject to the rule that there MUST be at least
+//     one digit before and after the decimal point.
+impl fmt::Display for LeafValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LeafValue::Int(v) => write!(f, "{}", v),
+            LeafValue::Uint(v) => write!(f, "{}", v),
+            LeafValue::Decimal64(v, digits) => {
+                let scale = 10u64.pow(*digits as u32);
+                let abs = v.unsigned_abs();
+                let fraction = format!("{:0width$}", abs % scale, width = *digits as usize);
+                let fraction = fraction.trim_end_matches('0');
+                let fraction = if fraction.is_empty() { "0" } else { fraction };
+                let sign = if *v < 0 { "-" } else { "" };
+                write!(f, "{}{}.{}", sign, abs / scale, fraction)
+            }
+            LeafValue::String(v) => write!(f, "{}", v),
+            LeafValue::Boolean(v) => write!(f, "{}", v),
+            LeafValue::Enum(name, _) => write!(f, "{}", name),
+            LeafValue::Bits(bits) => write!(f, "{}", bits.join(" ")),
+            LeafValue::Binary(v) => write!(f, "{}", STANDARD.encode(v)),
+            LeafValue::Identity(module, name) => write!(f, "{}:{}", module, name),
+            LeafValue::Empty => Ok(()),
+            LeafValue::InstanceIdentifier(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+fn parse_int(kind: TypeKind, s: &str) -> Result<LeafValue, String> {
+    let (min, max) = match kind {
+        TypeKind::Yint8 => (i8::MIN as i64, i8::MAX as i64),
+        TypeKind::Yint16 => (i16::MIN as i64, i16::MAX as i64),
+        TypeKind::Yint32 => (i32::MIN as i64, i32::MAX as i64),
+        _ => (i64::MIN, i64::MAX),
+    };
+    match s.parse::<i64>() {
+        Ok(v) if v >= min && v <= max => Ok(LeafValue::Int(v)),
+        Ok(_) => Err(String::from("out of range")),
+        Err(_) => Err(String::from("not an integer")),
+    }
+}
+
+fn parse_uint(kind: TypeKind, s: &str) -> Result<LeafValue, String> {
+    let max = match kind {
+        TypeKind::Yuint8 => u8::MAX as u64,
+        TypeKind::Yuint16 => u16::MAX as u64,
+        TypeKind::Yuint32 => u32::MAX as u64,
+        _ => u64::MAX,
+    };
+    match s.parse::<u64>() {
+        Ok(v) if v <= max => Ok(LeafValue::Uint(v)),
+        Ok(_) => Err(String::from("out of range")),
+        Err(_) => Err(String::from("not an unsigned integer")),
+    }
+}
+
+pub(crate) fn parse_decimal64(digits: u8, s: &str) -> Result<LeafValue, String> {
+    let invalid = || String::from("not a decimal64");
+    let (negative, abs) = match s.strip_prefix('-') {
+        Some(abs) => (true, abs),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (integer, fraction) = abs.split_once('.').unwrap_or((abs, ""));
+    let all_digits = |v: &str| v.chars().all(|c| c.is_ascii_digit());
+    if integer.is_empty() || !all_digits(integer) || !all_digits(fraction) {
+        return Err(invalid());
+    }
+    if fraction.len() > digits as usize {
+        return Err(String::from("too many fraction digits"));
+    }
+    let fraction = format!("{:0<width$}", fraction, width = digits as usize);
+    let scaled = format!("{}{}", integer, fraction)
+        .parse::<i64>()
+        .map_err(|_| String::from("out of range"))?;
+    Ok(LeafValue::Decimal64(
+        if negative { -scaled } else { scaled },
+        digits,
+    ))
+}
+
+fn parse_enum(typ: &TypeNode, s: &str) -> Result<LeafValue, String> {
+    let mut next = 0;
+    for e in typ.enums.iter() {
+        let value = e.value().unwrap_or(next);
+        if e.name == s {
+            return Ok(LeafValue::Enum(e.name.clone(), value));
+        }
+        next = value + 1;
+    }
+    Err(String::from("unknown enum"))
+}
+
+fn parse_bits(typ: &TypeNode, s: &str) -> Result<LeafValue, String> {
+    let mut positions = vec![];
+    let mut next = 0;
+    for b in typ.bits.iter() {
+        let position = b.position.unwrap_or(next);
+        positions.push((position, b.name.as_str()));
+        next = position + 1;
+    }
+    let mut set = vec![];
+    for name in s.split_whitespace() {
+        match positions.iter().find(|(_, n)| *n == name) {
+            Some(bit) if !set.contains(bit) => set.push(*bit),
+            Some(_) => return Err(format!("duplicate bit {}", name)),
+            None => return Err(format!("unknown bit {}", name)),
+        }
+    }
+    set.sort();
+    Ok(LeafValue::Bits(
+        set.into_iter().map(|(_, n)| n.to_owned()).collect(),
+    ))
+}
+
+// Identity value is "module:identity" or "prefix:identity".  Identity
+// without prefix belongs to the module of the leaf.
+fn parse_identity(
+    schema: &Schema,
+    typ: &TypeNode,
+    module: &str,
+    s: &str,
+) -> Result<LeafValue, String> {
+    let (imodule, name) = match s.split_once(':') {
+        Some((prefix, name)) => match schema.resolve_module(prefix) {
+            Some(imodule) => (imodule, name),
+            None => return Err(format!("unknown prefix {}", prefix)),
+        },
+        None => (module, s),
+    };
+    if !schema.identity_exists(imodule, name) {
+        return Err(String::from("unknown identity"));
+    }
+    for base in typ.bases.iter() {
+        let (prefix, bname) = base.split_once(':').unwrap_or(("", base));
+        let bmodule = schema.resolve_module(prefix).unwrap_or(module);
+        if !schema.derived_from((imodule, name), (bmodule, bname)) {
+            return Err(format!("identity is not derived from {}", base));
+        }
+    }
+    Ok(LeafValue::Identity(imodule.to_owned(), name.to_owned()))
+}
+
+// "accept" filters built-in types by the encoding of the value, e.g. JSON
+// number can't be a string type value.
+fn parse_type(
+    schema: &Schema,
+    typ: &TypeNode,
+    leafref: Option<&Leafref>,
+    module: &str,
+    s: &str,
+    accept: fn(TypeKind) -> bool,
+) -> Result<LeafValue, String> {
+    match typ.kind {
+        TypeKind::Yunion | TypeKind::Yleafref => {}
+        k if !accept(k) => return Err(String::from("value encoding does not match the type")),
+        _ => {}
+    }
+    match typ.kind {
+        k if k.is_int() => parse_int(k, s),
+        k if k.is_uint() => parse_uint(k, s),
+        TypeKind::Ydecimal64 => parse_decimal64(typ.fraction_digits.unwrap_or(1), s),
+        TypeKind::Yboolean => match s {
+            "true" => Ok(LeafValue::Boolean(true)),
+            "false" => Ok(LeafValue::Boolean(false)),
+            _ => Err(String::from("not a boolean")),
+        },
+        TypeKind::Yenumeration => parse_enum(typ, s),
+        TypeKind::Ybits => parse_bits(typ, s),
+        TypeKind::Ybinary => STANDARD
+            .decode(s)
+            .map(LeafValue::Binary)
+            .map_err(|_| String::from("not a base64 value")),
+        TypeKind::Yidentityref => parse_identity(schema, typ, module, s),
+        TypeKind::Yempty => match s {
+            "" => Ok(LeafValue::Empty),
+            _ => Err(String::from("empty type has no value")),
+        },
+        TypeKind::YinstanceIdentifier => match XPath::compile(s) {
+            Ok(_) => Ok(LeafValue::InstanceIdentifier(s.to_owned())),
+            Err(_) => Err(String::from("not an instance-identifier")),
+        },
+        TypeKind::Yunion => {
+            for member in typ.union.iter() {
+                if let Ok(v) = parse_type(schema, member, None, module, s, accept) {
+                    return Ok(v);
+                }
+            }
+            Err(String::from("no union member type accepts the value"))
+        }
+        TypeKind::Yleafref => match leafref.and_then(|l| l.typ.as_ref()) {
+            Some(target) => parse_type(schema, target, None, module, s, accept),
+            None => Ok(LeafValue::String(s.to_owned())),
+        },
+        _ => Ok(LeafValue::String(s.to_owned())),
+    }
+}
+
+impl LeafValue {
+    // Parse value of leaf or leaf-list.  The lexical representation and the
+    // built-in type's value space are checked, restrictions such as range
+    // and pattern are not.
+    pub fn parse(schema: &Schema, node: &SchemaNode, s: &str) -> Result<LeafValue, String> {
+        LeafValue::parse_with(schema, node, s, |_| true)
+    }
+
+    // Parse value which is accepted only by the types "accept" returns true.
+    // Union member types are tried in order.
+    pub fn parse_with(
+        schema: &Schema,
+        node: &SchemaNode,
+        s: &str,
+        accept: fn(TypeKind) -> bool,
+    ) -> Result<LeafValue, String> {
+        match &node.typ {
+            Some(typ) => parse_type(schema, typ, node.leafref.as_ref(), &node.module, s, accept),
+            None if accept(TypeKind::Ystring) => Ok(LeafValue::String(s.to_owned())),
+            None => Err(String::from("value encoding does not match the type")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Node;
+
+    fn typ(kind: TypeKind) -> TypeNode {
+        TypeNode::new(kind)
+    }
+
+    fn parse(typ: &TypeNode, s: &str) -> Result<LeafValue, String> {
+        parse_type(&Schema::default(), typ, None, "ex", s, |_| true)
+    }
+
+    #[test]
+    fn leaf_value_int_test() {
+        assert_eq!(
+            parse(&typ(TypeKind::Yint8), "-128"),
+            Ok(LeafValue::Int(-128))
+        );
+        assert!(parse(&typ(TypeKind::Yint8), "128").is_err());
+        assert_eq!(
+            parse(&typ(TypeKind::Yuint16), "+0065535")
+                .unwrap()
+                .to_string(),
+            "65535"
+        );
+        assert!(parse(&typ(TypeKind::Yuint16), "-1").is_err());
+        assert!(parse(&typ(TypeKind::Yint32), "1.0").is_err());
+    }
+
+    #[test]
+    fn leaf_value_decimal64_test() {
+        let mut t = typ(TypeKind::Ydecimal64);
+        t.fraction_digits = Some(3);
+        let tests = [
+            ("1", "1.0"),
+            ("-0.5", "-0.5"),
+            ("+12.340", "12.34"),
+            ("0.001", "0.001"),
+        ];
+        for (input, canonical) in tests.iter() {
+            assert_eq!(parse(&t, input).unwrap().to_string(), *canonical);
+        }
+        assert_eq!(parse(&t, "2.5"), Ok(LeafValue::Decimal64(2500, 3)));
+        assert!(parse(&t, "0.0001").is_err());
+        assert!(parse(&t, ".5").is_err());
+    }
+
+    #[test]
+    fn leaf_value_enum_bits_test() {
+        let mut t = typ(TypeKind::Yenumeration);
+        let value = ValueNode {
+            name: String::from("1"),
+            nodes: (),
+        };
+        let up = EnumNode {
+            name: String::from("up"),
+            nodes: (vec![Node::ValueNode(Box::new(value))],),
+        };
+        let down = EnumNode {
+            name: String::from("down"),
+            nodes: (vec![],),
+        };
+        t.enums = vec![up, down];
+        assert_eq!(
+            parse(&t, "down"),
+            Ok(LeafValue::Enum(String::from("down"), 2))
+        );
+        assert!(parse(&t, "testing").is_err());
+
+        let mut t = typ(TypeKind::Ybits);
+        let mut b = BitNode::new(String::from("b"));
+        b.position = Some(5);
+        t.bits = vec![
+            BitNode::new(String::from("a")),
+            b,
+            BitNode::new(String::from("c")),
+        ];
+        assert_eq!(parse(&t, " c  a b").unwrap().to_string(), "a b c");
+        assert!(parse(&t, "a a").is_err());
+        assert!(parse(&t, "d").is_err());
+    }
+
+    #[test]
+    fn leaf_value_misc_test() {
+        assert_eq!(
+            parse(&typ(TypeKind::Ybinary), "AQID"),
+            Ok(LeafValue::Binary(vec![1, 2, 3]))
+        );
+        assert!(parse(&typ(TypeKind::Ybinary), "!").is_err());
+        assert_eq!(parse(&typ(TypeKind::Yempty), ""), Ok(LeafValue::Empty));
+        assert!(parse(&typ(TypeKind::Yboolean), "True").is_err());
+
+        let mut t = typ(TypeKind::Yunion);
+        t.union = vec![typ(TypeKind::Yint8), typ(TypeKind::Ystring)];
+        assert_eq!(parse(&t, "10"), Ok(LeafValue::Int(10)));
+        assert_eq!(
+            parse(&t, "1000"),
+            Ok(LeafValue::String(String::from("1000")))
+        );
+    }
+}
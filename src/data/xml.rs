@@ -0,0 +1,389 @@
+use crate::data::*;
+use nom::bytes::complete::{tag, take_until, take_while, take_while1};
+use nom::character::complete::{char, multispace0, multispace1};
+use nom::error::{make_error, ErrorKind};
+use nom::{Err, IResult};
+use std::fmt;
+
+// XML attribute.  "namespace" is resolved from the prefix, attributes
+// without prefix have no namespace.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct XmlAttribute {
+    pub name: String,
+    pub prefix: Option<String>,
+    #[new(default)]
+    pub namespace: Option<String>,
+    pub value: String,
+}
+
+// XML element.  "namespaces" are the namespace declarations on the element
+// as pairs of prefix and namespace, default namespace has empty prefix.
+// Character data of mixed content is concatenated into "text".
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct XmlElement {
+    pub name: String,
+    #[new(default)]
+    pub prefix: Option<String>,
+    #[new(default)]
+    pub namespace: Option<String>,
+    #[new(default)]
+    pub namespaces: Vec<(String, String)>,
+    #[new(default)]
+    pub attributes: Vec<XmlAttribute>,
+    #[new(default)]
+    pub children: Vec<XmlElement>,
+    #[new(default)]
+    pub text: String,
+}
+
+impl XmlElement {
+    pub fn qname(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    // First child element of the local name.
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    pub fn attribute(&self, namespace: &str, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.name == name && a.namespace.as_deref() == Some(namespace))
+            .map(|a| a.value.as_str())
+    }
+
+    // Pretty printed text with two spaces indentation.  Elements which have
+    // text are printed in one line.
+    pub fn to_string_pretty(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, Some(0));
+        out
+    }
+
+    fn write(&self, out: &mut String, depth: Option<usize>) {
+        let qname = self.qname();
+        out.push('<');
+        out.push_str(&qname);
+        for (prefix, namespace) in self.namespaces.iter() {
+            if prefix.is_empty() {
+                out.push_str(" xmlns=\"");
+            } else {
+                out.push_str(&format!(" xmlns:{}=\"", prefix));
+            }
+            out.push_str(&escape(namespace, true));
+            out.push('"');
+        }
+        for a in self.attributes.iter() {
+            match &a.prefix {
+                Some(prefix) => out.push_str(&format!(" {}:{}=\"", prefix, a.name)),
+                None => out.push_str(&format!(" {}=\"", a.name)),
+            }
+            out.push_str(&escape(&a.value, true));
+            out.push('"');
+        }
+        if self.children.is_empty() && self.text.is_empty() {
+            out.push_str("/>");
+            return;
+        }
+        out.push('>');
+        out.push_str(&escape(&self.text, false));
+        if !self.children.is_empty() {
+            for child in self.children.iter() {
+                if let Some(depth) = depth {
+                    out.push('\n');
+                    out.push_str(&"  ".repeat(depth + 1));
+                }
+                child.write(out, depth.map(|d| d + 1));
+            }
+            if let Some(depth) = depth {
+                out.push('\n');
+                out.push_str(&"  ".repeat(depth));
+            }
+        }
+        out.push_str(&format!("</{}>", qname));
+    }
+}
+
+impl fmt::Display for XmlElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = String::new();
+        self.write(&mut out, None);
+        write!(f, "{}", out)
+    }
+}
+
+fn escape(s: &str, attribute: bool) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' if attribute => out.push_str("&quot;"),
+            // XML 1.0 3.3.3  Attribute-Value Normalization.
+            '\n' if attribute => out.push_str("&#10;"),
+            '\t' if attribute => out.push_str("&#9;"),
+            '\r' => out.push_str("&#13;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// XML 1.0 4.6  Predefined Entities and 4.1  Character References.
+fn unescape(s: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(pos) = rest.find('&') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        let end = rest.find(';')?;
+        let entity = &rest[..end];
+        rest = &rest[end + 1..];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = match entity.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => entity.strip_prefix('#')?.parse::<u32>().ok()?,
+                };
+                char::from_u32(code)?
+            }
+        };
+        out.push(c);
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+fn error<T>(s: &str) -> IResult<&str, T> {
+    Err(Err::Error(make_error(s, ErrorKind::Verify)))
+}
+
+fn xml_name(s: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || "-_.:".contains(c))(s)
+}
+
+fn split_qname(name: &str) -> (Option<String>, String) {
+    match name.split_once(':') {
+        Some((prefix, name)) => (Some(prefix.to_owned()), name.to_owned()),
+        None => (None, name.to_owned()),
+    }
+}
+
+// Comments, processing instructions, XML declaration and DOCTYPE.
+fn xml_misc(s: &str) -> IResult<&str, ()> {
+    let mut s = s;
+    loop {
+        let (rest, _) = multispace0(s)?;
+        s = rest;
+        let end = if s.starts_with("<!--") {
+            "-->"
+        } else if s.starts_with("<?") {
+            "?>"
+        } else if s.starts_with("<!DOCTYPE") {
+            ">"
+        } else {
+            return Ok((s, ()));
+        };
+        let (rest, _) = take_until(end)(s)?;
+        s = &rest[end.len()..];
+    }
+}
+
+fn xml_attribute(s: &str) -> IResult<&str, (String, String)> {
+    let (s, _) = multispace1(s)?;
+    let (s, name) = xml_name(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('=')(s)?;
+    let (s, _) = multispace0(s)?;
+    let quote = match s.chars().next() {
+        Some(c) if c == '"' || c == '\'' => c,
+        _ => return error(s),
+    };
+    let (s, _) = char(quote)(s)?;
+    let (s, value) = take_while(|c| c != quote && c != '<')(s)?;
+    let (s, _) = char(quote)(s)?;
+    match unescape(value) {
+        Some(value) => Ok((s, (name.to_owned(), value))),
+        None => error(s),
+    }
+}
+
+pub(crate) fn resolve_prefix<'a>(scope: &'a [(String, String)], prefix: &str) -> Option<&'a str> {
+    match prefix {
+        "xml" => Some("http://www.w3.org/XML/1998/namespace"),
+        _ => scope
+            .iter()
+            .rev()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, ns)| ns.as_str()),
+    }
+}
+
+fn xml_element<'a>(s: &'a str, scope: &mut Vec<(String, String)>) -> IResult<&'a str, XmlElement> {
+    let (s, _) = char('<')(s)?;
+    let (s, qname) = xml_name(s)?;
+    let mut s = s;
+    let mut attributes = vec![];
+    while let Ok((rest, attribute)) = xml_attribute(s) {
+        attributes.push(attribute);
+        s = rest;
+    }
+    let (s, _) = multispace0(s)?;
+
+    let (prefix, name) = split_qname(qname);
+    let mut element = XmlElement::new(name);
+    element.prefix = prefix;
+    for (name, value) in attributes.into_iter() {
+        match split_qname(&name) {
+            (None, n) if n == "xmlns" => element.namespaces.push((String::new(), value)),
+            (Some(p), n) if p == "xmlns" => element.namespaces.push((n, value)),
+            (prefix, name) => element
+                .attributes
+                .push(XmlAttribute::new(name, prefix, value)),
+        }
+    }
+    let depth = scope.len();
+    scope.extend(element.namespaces.iter().cloned());
+    element.namespace =
+        resolve_prefix(scope, element.prefix.as_deref().unwrap_or("")).map(String::from);
+    if element.prefix.is_some() && element.namespace.is_none() {
+        return error(s);
+    }
+    for a in element.attributes.iter_mut() {
+        if let Some(prefix) = &a.prefix {
+            a.namespace = match resolve_prefix(scope, prefix) {
+                Some(ns) => Some(ns.to_owned()),
+                None => return error(s),
+            };
+        }
+    }
+
+    if let Ok((s, _)) = tag::<_, _, (&str, ErrorKind)>("/>")(s) {
+        scope.truncate(depth);
+        return Ok((s, element));
+    }
+    let (mut s, _) = char('>')(s)?;
+    loop {
+        let (rest, text) = take_while(|c| c != '<')(s)?;
+        match unescape(text) {
+            Some(text) => element.text.push_str(&text),
+            None => return error(s),
+        }
+        s = rest;
+        if s.starts_with("</") {
+            let (rest, _) = tag("</")(s)?;
+            let (rest, end) = xml_name(rest)?;
+            let (rest, _) = multispace0(rest)?;
+            let (rest, _) = char('>')(rest)?;
+            if end != qname {
+                return error(s);
+            }
+            s = rest;
+            break;
+        } else if s.starts_with("<![CDATA[") {
+            let (rest, text) = take_until("]]>")(&s[9..])?;
+            element.text.push_str(text);
+            s = &rest[3..];
+        } else if s.starts_with("<!--") || s.starts_with("<?") {
+            let (rest, _) = xml_misc(s)?;
+            s = rest;
+        } else {
+            let (rest, child) = xml_element(s, scope)?;
+            element.children.push(child);
+            s = rest;
+        }
+    }
+    // Whitespace between child elements is not a value.
+    if !element.children.is_empty() && element.text.trim().is_empty() {
+        element.text.clear();
+    }
+    scope.truncate(depth);
+    Ok((s, element))
+}
+
+// Parse XML document.  Several top level elements are allowed so that a
+// fragment such as the content of NETCONF <config> can be parsed.
+pub fn xml_parse(s: &str) -> Result<Vec<XmlElement>, DataError> {
+    let syntax = |rest: &str| DataError::Syntax(rest.chars().take(32).collect());
+    let mut elements = vec![];
+    let mut s = s;
+    loop {
+        s = match xml_misc(s) {
+            Ok((rest, _)) => rest,
+            Err(_) => return Err(syntax(s)),
+        };
+        if s.is_empty() {
+            break;
+        }
+        match xml_element(s, &mut vec![]) {
+            Ok((rest, element)) => {
+                elements.push(element);
+                s = rest;
+            }
+            Err(Err::Error((rest, _))) | Err(Err::Failure((rest, _))) => return Err(syntax(rest)),
+            Err(Err::Incomplete(_)) => return Err(syntax("")),
+        }
+    }
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xml_parse_test() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- comment -->
+<config xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"
+        xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0">
+  <top xmlns="urn:example" xmlns:ex='urn:example:ex' nc:operation="merge">
+    <name>a &amp; b &#x3c;&#62;</name>
+    <type>ex:eth</type>
+    <empty/>
+    <raw><![CDATA[<x>]]></raw>
+  </top>
+</config>"#;
+        let elements = xml_parse(text).unwrap();
+        assert_eq!(elements.len(), 1);
+        let config = &elements[0];
+        assert_eq!(config.name, "config");
+        assert_eq!(
+            config.namespace.as_deref(),
+            Some("urn:ietf:params:xml:ns:netconf:base:1.0")
+        );
+        let top = &config.children[0];
+        assert_eq!(top.namespace.as_deref(), Some("urn:example"));
+        assert_eq!(
+            top.attribute("urn:ietf:params:xml:ns:netconf:base:1.0", "operation"),
+            Some("merge")
+        );
+        assert_eq!(top.children.len(), 4);
+        assert_eq!(top.child("name").unwrap().text, "a & b <>");
+        assert_eq!(
+            top.child("type").unwrap().namespace.as_deref(),
+            Some("urn:example")
+        );
+        assert_eq!(top.child("raw").unwrap().text, "<x>");
+        assert!(top.text.is_empty());
+
+        let printed = config.to_string_pretty();
+        assert_eq!(xml_parse(&printed).unwrap()[0], *config);
+        assert_eq!(xml_parse(&config.to_string()).unwrap()[0], *config);
+
+        assert!(xml_parse("<a><b></a></b>").is_err());
+        assert!(xml_parse("<p:a/>").is_err());
+        assert!(xml_parse("<a>").is_err());
+        assert!(xml_parse("<a x=\"&bad;\"/>").is_err());
+    }
+}
@@ -0,0 +1,424 @@
+use crate::data::*;
+use crate::modules::{TypeKind, TypeNode};
+use std::rc::Rc;
+
+pub const NETCONF_NAMESPACE: &str = "urn:ietf:params:xml:ns:netconf:base:1.0";
+
+// Module name, prefix and namespace of annotations which are used without
+// loading the modules.
+const WELL_KNOWN_MODULES: &[(&str, &str, &str)] = &[
+    ("ietf-netconf", "nc", NETCONF_NAMESPACE),
+    (
+        "ietf-netconf-with-defaults",
+        "wd",
+        "urn:ietf:params:xml:ns:netconf:default:1.0",
+    ),
+    (
+        "ietf-origin",
+        "or",
+        "urn:ietf:params:xml:ns:yang:ietf-origin",
+    ),
+    ("yang", "yang", "urn:ietf:params:xml:ns:yang:1"),
+];
+
+impl Schema {
+    // Prefix and namespace of the module.
+    pub fn xml_namespace(&self, module: &str) -> Option<(&str, &str)> {
+        match self.module(module) {
+            Some(m) => Some((&m.prefix, &m.namespace)),
+            None => WELL_KNOWN_MODULES
+                .iter()
+                .find(|(name, _, _)| *name == module)
+                .map(|(_, prefix, ns)| (*prefix, *ns)),
+        }
+    }
+
+    // Module name of the namespace.
+    pub fn namespace_module(&self, namespace: &str) -> Option<&str> {
+        match self.modules.iter().find(|m| m.namespace == namespace) {
+            Some(m) => Some(&m.name),
+            None => WELL_KNOWN_MODULES
+                .iter()
+                .find(|(_, _, ns)| *ns == namespace)
+                .map(|(name, _, _)| *name),
+        }
+    }
+}
+
+// Replace prefixes of qualified names which are not in string literals.
+fn map_prefixes<F>(text: &str, mut f: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut out = String::new();
+    let mut quote = None;
+    let mut name = String::new();
+    for c in text.chars() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+                out.push(c);
+                continue;
+            }
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None => {}
+        }
+        if c.is_alphanumeric() || "-_.".contains(c) {
+            name.push(c);
+            continue;
+        }
+        if c == ':' && !name.is_empty() {
+            out.push_str(&f(&name).unwrap_or_else(|| name.clone()));
+        } else {
+            out.push_str(&name);
+        }
+        name.clear();
+        out.push(c);
+    }
+    out.push_str(&name);
+    out
+}
+
+// Whether the value has prefixes which depend on the XML namespace
+// declarations.
+fn has_qname(typ: &TypeNode, leafref: Option<&Leafref>) -> bool {
+    match typ.kind {
+        TypeKind::Yidentityref | TypeKind::YinstanceIdentifier => true,
+        TypeKind::Yunion => typ.union.iter().any(|member| has_qname(member, None)),
+        TypeKind::Yleafref => leafref
+            .and_then(|l| l.typ.as_ref())
+            .is_some_and(|target| has_qname(target, None)),
+        _ => false,
+    }
+}
+
+// Parse the XML text of a leaf or leaf-list value with the namespace
+// declarations in "scope".
+pub(crate) fn xml_value(
+    schema: &Schema,
+    snode: &SchemaNode,
+    text: &str,
+    scope: &[(String, String)],
+) -> Result<LeafValue, String> {
+    let qname = snode
+        .typ
+        .as_ref()
+        .is_some_and(|typ| has_qname(typ, snode.leafref.as_ref()));
+    if !qname {
+        return LeafValue::parse(schema, snode, text);
+    }
+    // Prefixes are converted to module names.  Value with undeclared
+    // prefix can only be a member type other than identityref and
+    // instance-identifier.
+    let mut declared = true;
+    let mapped = map_prefixes(text, |p| {
+        let module = resolve_prefix(scope, p).and_then(|ns| schema.namespace_module(ns));
+        declared &= module.is_some();
+        module.map(String::from)
+    });
+    if declared {
+        LeafValue::parse(schema, snode, &mapped)
+    } else {
+        LeafValue::parse_with(schema, snode, text, |k| {
+            k != TypeKind::Yidentityref && k != TypeKind::YinstanceIdentifier
+        })
+    }
+}
+
+fn declare(element: &mut XmlElement, prefix: &str, namespace: &str) {
+    if !element.namespaces.iter().any(|(p, _)| p == prefix) {
+        element
+            .namespaces
+            .push((prefix.to_owned(), namespace.to_owned()));
+    }
+}
+
+struct Encoder<'a> {
+    schema: &'a Schema,
+}
+
+impl<'a> Encoder<'a> {
+    // RFC7950 9.10.3.  The identityref's Lexical Representation
+    //     An identityref is lexically represented as the referred
+    //     identity's qualified name as defined in [XML-NAMES].  If the
+    //     prefix is not present, the namespace of the identityref is the
+    //     default namespace in effect on the element that contains the
+    //     identityref value.
+    fn value(&self, element: &mut XmlElement, value: &LeafValue) -> String {
+        match value {
+            LeafValue::Identity(module, name) => match self.schema.xml_namespace(module) {
+                Some((prefix, ns)) => {
+                    declare(element, prefix, ns);
+                    format!("{}:{}", prefix, name)
+                }
+                None => value.to_string(),
+            },
+            LeafValue::InstanceIdentifier(text) => map_prefixes(text, |p| {
+                let module = self.schema.resolve_module(p)?;
+                let (prefix, ns) = self.schema.xml_namespace(module)?;
+                declare(element, prefix, ns);
+                Some(prefix.to_owned())
+            }),
+            _ => value.to_string(),
+        }
+    }
+
+    fn annotations(&self, element: &mut XmlElement, annotations: &[Annotation]) {
+        for a in annotations {
+            if let Some((prefix, ns)) = self.schema.xml_namespace(&a.module) {
+                declare(element, prefix, ns);
+                element.attributes.push(XmlAttribute::new(
+                    a.name.clone(),
+                    Some(prefix.to_owned()),
+                    a.value.clone(),
+                ));
+            }
+        }
+    }
+
+    fn any(&self, node: &AnyNode, parent_ns: Option<&str>) -> XmlElement {
+        let mut element = XmlElement::new(node.name.clone());
+        let ns = node
+            .module
+            .as_deref()
+            .and_then(|m| self.schema.xml_namespace(m))
+            .map(|(_, ns)| ns)
+            .or(parent_ns);
+        if let Some(ns) = ns {
+            if Some(ns) != parent_ns {
+                declare(&mut element, "", ns);
+            }
+        }
+        if let Some(value) = &node.value {
+            element.text = value.clone();
+        }
+        for child in node.children.iter() {
+            element.children.push(self.any(child, ns));
+        }
+        element
+    }
+
+    fn element(&self, node: &DataNode, parent_ns: Option<&str>) -> XmlElement {
+        let schema = &node.schema;
+        let ns = schema.namespace.as_str();
+        let mut element = XmlElement::new(schema.name.clone());
+        if parent_ns != Some(ns) {
+            declare(&mut element, "", ns);
+        }
+        self.annotations(&mut element, &node.annotations);
+        match &node.content {
+            DataContent::Container(children) => {
+                for child in children {
+                    element.children.push(self.element(child, Some(ns)));
+                }
+            }
+            // RFC7950 7.8.5.  XML Encoding Rules
+            //     The list's key nodes are encoded as subelements to the
+            //     list's identifier element, in the same order as they are
+            //     defined within the "key" statement.
+            DataContent::ListEntry(children) => {
+                for key in schema.keys.iter() {
+                    if let Some(child) = children.iter().find(|c| c.name() == key) {
+                        element.children.push(self.element(child, Some(ns)));
+                    }
+                }
+                for child in children.iter().filter(|c| !schema.is_key(c.name())) {
+                    element.children.push(self.element(child, Some(ns)));
+                }
+            }
+            DataContent::Leaf(value) | DataContent::LeafList(value) => {
+                element.text = self.value(&mut element, value);
+            }
+            DataContent::Anydata(any) => {
+                for child in any {
+                    element.children.push(self.any(child, Some(ns)));
+                }
+            }
+        }
+        element
+    }
+}
+
+fn is_deletion(element: &XmlElement) -> bool {
+    element
+        .attribute(NETCONF_NAMESPACE, "operation")
+        .is_some_and(|op| op == "delete" || op == "remove")
+}
+
+struct Decoder<'a> {
+    schema: &'a Schema,
+}
+
+impl<'a> Decoder<'a> {
+    fn annotations(&self, element: &XmlElement) -> Vec<Annotation> {
+        element
+            .attributes
+            .iter()
+            .filter_map(|a| {
+                let module = self.schema.namespace_module(a.namespace.as_deref()?)?;
+                Some(Annotation::new(
+                    module.to_owned(),
+                    a.name.clone(),
+                    a.value.clone(),
+                ))
+            })
+            .collect()
+    }
+
+    fn any(&self, element: &XmlElement) -> AnyNode {
+        let mut node = AnyNode::new(element.name.clone());
+        node.module = element
+            .namespace
+            .as_deref()
+            .and_then(|ns| self.schema.namespace_module(ns))
+            .map(String::from);
+        if element.children.is_empty() {
+            node.value = Some(element.text.clone());
+        }
+        node.children = element.children.iter().map(|c| self.any(c)).collect();
+        node
+    }
+
+    fn value(
+        &self,
+        snode: &SchemaNode,
+        element: &XmlElement,
+        scope: &[(String, String)],
+        path: &str,
+    ) -> Result<LeafValue, DataError> {
+        let invalid =
+            |reason: String| DataError::InvalidValue(path.to_owned(), element.text.clone(), reason);
+        if !element.children.is_empty() {
+            return Err(invalid(String::from("not a leaf value")));
+        }
+        xml_value(self.schema, snode, &element.text, scope).map_err(invalid)
+    }
+
+    fn nodes(
+        &self,
+        elements: &[XmlElement],
+        candidates: &[Rc<SchemaNode>],
+        scope: &mut Vec<(String, String)>,
+        path: &str,
+    ) -> Result<Vec<DataNode>, DataError> {
+        let mut nodes = vec![];
+        for element in elements {
+            let depth = scope.len();
+            scope.extend(element.namespaces.iter().cloned());
+            let node = self.node(element, candidates, scope, path)?;
+            insert_ordered(&mut nodes, candidates, node);
+            scope.truncate(depth);
+        }
+        Ok(nodes)
+    }
+
+    fn node(
+        &self,
+        element: &XmlElement,
+        candidates: &[Rc<SchemaNode>],
+        scope: &mut Vec<(String, String)>,
+        path: &str,
+    ) -> Result<DataNode, DataError> {
+        let snode = element
+            .namespace
+            .as_deref()
+            .and_then(|ns| self.schema.namespace_module(ns))
+            .and_then(|module| find_schema(candidates, Some(module), &element.name))
+            .cloned()
+            .ok_or_else(|| DataError::UnknownNode(path.to_owned(), element.qname()))?;
+        let path = format!("{}/{}", path, element.name);
+        let content = match snode.kind {
+            SchemaKind::Container => DataContent::Container(self.nodes(
+                &element.children,
+                &snode.children,
+                scope,
+                &path,
+            )?),
+            SchemaKind::List => {
+                for (i, key) in snode.keys.iter().enumerate() {
+                    let is_key = |e: &XmlElement| {
+                        &e.name == key && e.namespace.as_deref() == Some(&snode.namespace)
+                    };
+                    if !element.children.iter().any(is_key) {
+                        return Err(DataError::MissingKey(path, key.clone()));
+                    }
+                    if !element.children.get(i).is_some_and(is_key) {
+                        return Err(DataError::KeyOrder(path));
+                    }
+                }
+                DataContent::ListEntry(self.nodes(
+                    &element.children,
+                    &snode.children,
+                    scope,
+                    &path,
+                )?)
+            }
+            // RFC6241 7.2.  A leaf to be deleted in <edit-config> may have
+            // no value.
+            SchemaKind::Leaf => match self.value(&snode, element, scope, &path) {
+                Ok(value) => DataContent::Leaf(value),
+                Err(_) if element.text.is_empty() && is_deletion(element) => {
+                    DataContent::Leaf(LeafValue::Empty)
+                }
+                Err(e) => return Err(e),
+            },
+            SchemaKind::LeafList => {
+                DataContent::LeafList(self.value(&snode, element, scope, &path)?)
+            }
+            SchemaKind::Anydata => {
+                DataContent::Anydata(element.children.iter().map(|c| self.any(c)).collect())
+            }
+        };
+        let mut node = DataNode::new(snode, content);
+        node.annotations = self.annotations(element);
+        Ok(node)
+    }
+}
+
+impl DataTree {
+    // RFC7950 XML encoding of the top level nodes.
+    pub fn to_xml(&self) -> Vec<XmlElement> {
+        let encoder = Encoder {
+            schema: &self.schema,
+        };
+        self.nodes
+            .iter()
+            .map(|node| encoder.element(node, None))
+            .collect()
+    }
+
+    pub fn from_xml(schema: Rc<Schema>, elements: &[XmlElement]) -> Result<DataTree, DataError> {
+        let decoder = Decoder { schema: &schema };
+        let nodes = decoder.nodes(elements, &schema.nodes, &mut vec![], "")?;
+        Ok(DataTree { schema, nodes })
+    }
+
+    // Pretty printed XML text of the top level nodes.
+    pub fn xml_encode(&self) -> String {
+        self.to_xml()
+            .iter()
+            .map(|e| e.to_string_pretty())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // Decode XML text.  Content of NETCONF <config> or <data> element is
+    // decoded when the text is the element.
+    pub fn xml_decode(schema: Rc<Schema>, text: &str) -> Result<DataTree, DataError> {
+        let elements = xml_parse(text)?;
+        match elements.as_slice() {
+            [root]
+                if (root.name == "config" || root.name == "data")
+                    && root.namespace.as_deref() == Some(NETCONF_NAMESPACE) =>
+            {
+                let decoder = Decoder { schema: &schema };
+                let mut scope = root.namespaces.clone();
+                let nodes = decoder.nodes(&root.children, &schema.nodes, &mut scope, "")?;
+                Ok(DataTree { schema, nodes })
+            }
+            _ => DataTree::from_xml(schema, &elements),
+        }
+    }
+}
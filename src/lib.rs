@@ -1,9 +1,14 @@
 #[macro_use]
 extern crate derive_new;
 
+pub mod data;
 pub mod modules;
 pub mod node;
 pub mod parser;
+pub mod xpath;
+pub use data::*;
 pub use modules::*;
 pub use node::*;
 pub use parser::*;
+// Submodules of the same names in "modules" and "parser".
+pub use modules::{deviation, extension, revision, types};
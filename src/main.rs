@@ -4,32 +4,25 @@ fn main() {
     // Allocate a new Yang.
     let mut yang = Yang::new();
     yang.add_path("/etc/openconfigd/yang:yang/...");
+    yang.add_env_path();
 
-    // Read a module.
+    // Load a module and its imports.
     let mut ms = Modules::new();
     let yang_name = "iana-if-type";
-    let data = yang.read(&ms, yang_name).unwrap();
 
-    match yang_parse(&data) {
-        Ok((_, module)) => {
-            ms.modules.insert(module.prefix.to_owned(), module);
-
-            let entry = ms.modules.get(&"ianaift".to_string());
-            if let Some(e) = entry {
-                println!("Module found");
-                println!("name: {}", e.name);
-                println!("namespace: {}", e.namespace);
-                println!("prefix: {}", e.prefix);
-                for (_, t) in &e.typedefs {
-                    println!("typedef: {}", t.name);
-                }
-                println!("Module dump: {:?}", e);
-            } else {
-                println!("Module not found")
+    match ms.load(&yang, yang_name) {
+        Ok(e) => {
+            println!("Module found");
+            println!("name: {}", e.name);
+            println!("namespace: {}", e.namespace);
+            println!("prefix: {}", e.prefix);
+            for t in e.typedefs.values() {
+                println!("typedef: {}", t.name);
             }
+            println!("Module dump: {:?}", e);
         }
         Err(e) => {
-            println!("module parse: {:?}", e);
+            println!("module load: {}", e);
         }
     }
 }
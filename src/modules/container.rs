@@ -1,24 +1,432 @@
-#[derive(Debug, PartialEq, new)]
+use crate::modules::*;
+use crate::Node;
+
+// RFC7950 7.5.2.  The container's Substatements
+//
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | action       | 7.15    | 0..n        |
+// | anydata      | 7.10    | 0..n        |
+// | anyxml       | 7.11    | 0..n        |
+// | choice       | 7.9     | 0..n        |
+// | config       | 7.21.1  | 0..1        |
+// | container    | 7.5     | 0..n        |
+// | description  | 7.21.3  | 0..1        |
+// | grouping     | 7.12    | 0..n        |
+// | if-feature   | 7.20.2  | 0..n        |
+// | leaf         | 7.6     | 0..n        |
+// | leaf-list    | 7.7     | 0..n        |
+// | list         | 7.8     | 0..n        |
+// | must         | 7.5.3   | 0..n        |
+// | notification | 7.16    | 0..n        |
+// | presence     | 7.5.5   | 0..1        |
+// | reference    | 7.21.4  | 0..1        |
+// | status       | 7.21.2  | 0..1        |
+// | typedef      | 7.3     | 0..n        |
+// | uses         | 7.13    | 0..n        |
+// | when         | 7.21.5  | 0..1        |
+// +--------------+---------+-------------+
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct ContainerNode {
     pub name: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub config: Option<bool>,
+    #[new(default)]
+    pub presence: Option<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub must: Vec<MustNode>,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub nodes: Vec<Node>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
 }
 
-#[derive(Debug, PartialEq, new)]
+// RFC7950 7.8.1.  The list's Substatements
+//
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | key          | 7.8.2   | 0..1        |
+// | max-elements | 7.7.6   | 0..1        |
+// | min-elements | 7.7.5   | 0..1        |
+// | ordered-by   | 7.7.7   | 0..1        |
+// | unique       | 7.8.3   | 0..n        |
+// +--------------+---------+-------------+
+//
+// Substatements shared with container are omitted from the table.
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct ListNode {
     pub name: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub key: Option<String>,
+    #[new(default)]
+    pub config: Option<bool>,
+    #[new(default)]
+    pub min_elements: Option<u64>,
+    #[new(default)]
+    pub max_elements: Option<u64>,
+    #[new(default)]
+    pub ordered_by: Option<String>,
+    #[new(default)]
+    pub unique: Vec<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub must: Vec<MustNode>,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub nodes: Vec<Node>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+impl ListNode {
+    // Key leaf names in "key" statement order.
+    pub fn keys(&self) -> Vec<&str> {
+        match &self.key {
+            Some(key) => key.split_whitespace().collect(),
+            None => vec![],
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct KeyNode {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+// RFC7950 7.6.2.  The leaf's Substatements
+//
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | config       | 7.21.1  | 0..1        |
+// | default      | 7.6.4   | 0..1        |
+// | description  | 7.21.3  | 0..1        |
+// | if-feature   | 7.20.2  | 0..n        |
+// | mandatory    | 7.6.5   | 0..1        |
+// | must         | 7.5.3   | 0..n        |
+// | reference    | 7.21.4  | 0..1        |
+// | status       | 7.21.2  | 0..1        |
+// | type         | 7.6.3   | 1           |
+// | units        | 7.3.3   | 0..1        |
+// | when         | 7.21.5  | 0..1        |
+// +--------------+---------+-------------+
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct LeafNode {
     pub name: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub typ: Option<TypeNode>,
+    #[new(default)]
+    pub default: Option<String>,
+    #[new(default)]
+    pub mandatory: Option<bool>,
+    #[new(default)]
+    pub config: Option<bool>,
+    #[new(default)]
+    pub units: Option<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub must: Vec<MustNode>,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
 }
 
-#[derive(Debug, PartialEq, new)]
+// RFC7950 7.7.2.  The leaf-list's Substatements
+//
+// In addition to the leaf's substatements except "mandatory", leaf-list
+// has "max-elements", "min-elements" and "ordered-by".  "default" may
+// appear multiple times.
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct LeafListNode {
     pub name: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub typ: Option<TypeNode>,
+    #[new(default)]
+    pub default: Vec<String>,
+    #[new(default)]
+    pub config: Option<bool>,
+    #[new(default)]
+    pub min_elements: Option<u64>,
+    #[new(default)]
+    pub max_elements: Option<u64>,
+    #[new(default)]
+    pub ordered_by: Option<String>,
+    #[new(default)]
+    pub units: Option<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub must: Vec<MustNode>,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+// RFC7950 7.10.1.  The anydata's Substatements
+//
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | config       | 7.21.1  | 0..1        |
+// | description  | 7.21.3  | 0..1        |
+// | if-feature   | 7.20.2  | 0..n        |
+// | mandatory    | 7.6.5   | 0..1        |
+// | must         | 7.5.3   | 0..n        |
+// | reference    | 7.21.4  | 0..1        |
+// | status       | 7.21.2  | 0..1        |
+// | when         | 7.21.5  | 0..1        |
+// +--------------+---------+-------------+
+//
+// "anyxml" has the same substatements and is stored with "anyxml" set.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct AnydataNode {
+    pub name: String,
+    #[new(default)]
+    pub anyxml: bool,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub config: Option<bool>,
+    #[new(default)]
+    pub mandatory: Option<bool>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub must: Vec<MustNode>,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+// RFC7950 7.9.1.  The choice's Substatements
+//
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | anydata      | 7.10    | 0..n        |
+// | anyxml       | 7.11    | 0..n        |
+// | case         | 7.9.2   | 0..n        |
+// | choice       | 7.9     | 0..n        |
+// | config       | 7.21.1  | 0..1        |
+// | container    | 7.5     | 0..n        |
+// | default      | 7.9.3   | 0..1        |
+// | description  | 7.21.3  | 0..1        |
+// | if-feature   | 7.20.2  | 0..n        |
+// | leaf         | 7.6     | 0..n        |
+// | leaf-list    | 7.7     | 0..n        |
+// | list         | 7.8     | 0..n        |
+// | mandatory    | 7.9.4   | 0..1        |
+// | reference    | 7.21.4  | 0..1        |
+// | status       | 7.21.2  | 0..1        |
+// | when         | 7.21.5  | 0..1        |
+// +--------------+---------+-------------+
+//
+// "nodes" has the cases and the data definitions of the shorthand cases
+// as they are written.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct ChoiceNode {
+    pub name: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub config: Option<bool>,
+    #[new(default)]
+    pub mandatory: Option<bool>,
+    #[new(default)]
+    pub default: Option<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub nodes: Vec<Node>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+// RFC7950 7.9.2.1.  The case's Substatements
+//
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | anydata      | 7.10    | 0..n        |
+// | anyxml       | 7.11    | 0..n        |
+// | choice       | 7.9     | 0..n        |
+// | container    | 7.5     | 0..n        |
+// | description  | 7.21.3  | 0..1        |
+// | if-feature   | 7.20.2  | 0..n        |
+// | leaf         | 7.6     | 0..n        |
+// | leaf-list    | 7.7     | 0..n        |
+// | list         | 7.8     | 0..n        |
+// | reference    | 7.21.4  | 0..1        |
+// | status       | 7.21.2  | 0..1        |
+// | uses         | 7.13    | 0..n        |
+// | when         | 7.21.5  | 0..1        |
+// +--------------+---------+-------------+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct CaseNode {
+    pub name: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub nodes: Vec<Node>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct PresenceNode {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct MinElementsNode {
+    pub name: String,
+    #[new(default)]
+    pub min_elements: u64,
+}
+
+// "unbounded" is stored as u64::MAX.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct MaxElementsNode {
+    pub name: String,
+    #[new(default)]
+    pub max_elements: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct OrderedByNode {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct UniqueNode {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct ErrorMessageNode {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct ErrorAppTagNode {
+    pub name: String,
+}
+
+impl Node {
+    // Name of data definition node.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Node::Container(n) => Some(&n.name),
+            Node::List(n) => Some(&n.name),
+            Node::Leaf(n) => Some(&n.name),
+            Node::LeafList(n) => Some(&n.name),
+            Node::Anydata(n) => Some(&n.name),
+            Node::Choice(n) => Some(&n.name),
+            Node::Case(n) => Some(&n.name),
+            _ => None,
+        }
+    }
+
+    // Child data definition nodes.
+    pub fn nodes(&self) -> Option<&Vec<Node>> {
+        match self {
+            Node::Container(n) => Some(&n.nodes),
+            Node::List(n) => Some(&n.nodes),
+            Node::Choice(n) => Some(&n.nodes),
+            Node::Case(n) => Some(&n.nodes),
+            Node::Grouping(n) => Some(&n.nodes),
+            Node::Uses(n) => Some(&n.nodes),
+            _ => None,
+        }
+    }
+
+    pub fn nodes_mut(&mut self) -> Option<&mut Vec<Node>> {
+        match self {
+            Node::Container(n) => Some(&mut n.nodes),
+            Node::List(n) => Some(&mut n.nodes),
+            Node::Choice(n) => Some(&mut n.nodes),
+            Node::Case(n) => Some(&mut n.nodes),
+            Node::Grouping(n) => Some(&mut n.nodes),
+            Node::Uses(n) => Some(&mut n.nodes),
+            _ => None,
+        }
+    }
+
+    pub fn config(&self) -> Option<bool> {
+        match self {
+            Node::Container(n) => n.config,
+            Node::List(n) => n.config,
+            Node::Leaf(n) => n.config,
+            Node::LeafList(n) => n.config,
+            Node::Anydata(n) => n.config,
+            Node::Choice(n) => n.config,
+            _ => None,
+        }
+    }
+}
+
+// Data node named "name" in "nodes".  Choices, cases and uses are not data
+// nodes and their data nodes are looked up as the siblings.
+pub fn find_data_node<'a>(nodes: &'a [Node], name: &str) -> Option<&'a Node> {
+    nodes.iter().find_map(|node| match node {
+        Node::Choice(n) => find_data_node(&n.nodes, name),
+        Node::Case(n) => find_data_node(&n.nodes, name),
+        Node::Uses(n) => find_data_node(&n.nodes, name),
+        _ if node.name() == Some(name) => Some(node),
+        _ => None,
+    })
 }
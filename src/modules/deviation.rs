@@ -0,0 +1,596 @@
+use crate::modules::*;
+use crate::Node;
+use std::collections::HashMap;
+use thiserror::Error;
+
+// RFC7950 7.20.3.  The "deviation" Statement
+//
+// +--------------+----------+-------------+
+// | substatement | section  | cardinality |
+// +--------------+----------+-------------+
+// | description  | 7.21.3   | 0..1        |
+// | deviate      | 7.20.3.2 | 1..n        |
+// | reference    | 7.21.4   | 0..1        |
+// +--------------+----------+-------------+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct DeviationNode {
+    pub name: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub deviates: Vec<DeviateNode>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviateKind {
+    NotSupported,
+    Add,
+    Replace,
+    Delete,
+}
+
+impl DeviateKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeviateKind::NotSupported => "not-supported",
+            DeviateKind::Add => "add",
+            DeviateKind::Replace => "replace",
+            DeviateKind::Delete => "delete",
+        }
+    }
+}
+
+// RFC7950 7.20.3.2.  The "deviate" Statement
+//
+// +--------------+--------------+-------------+
+// | substatement | section      | cardinality |
+// +--------------+--------------+-------------+
+// | config       | 7.21.1       | 0..1        |
+// | default      | 7.6.4, 7.7.4 | 0..n        |
+// | mandatory    | 7.6.5        | 0..1        |
+// | max-elements | 7.7.6        | 0..1        |
+// | min-elements | 7.7.5        | 0..1        |
+// | must         | 7.5.3        | 0..n        |
+// | type         | 7.4          | 0..1        |
+// | unique       | 7.8.3        | 0..n        |
+// | units        | 7.3.3        | 0..1        |
+// +--------------+--------------+-------------+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct DeviateNode {
+    pub kind: DeviateKind,
+    #[new(default)]
+    pub config: Option<bool>,
+    #[new(default)]
+    pub default: Vec<String>,
+    #[new(default)]
+    pub mandatory: Option<bool>,
+    #[new(default)]
+    pub max_elements: Option<u64>,
+    #[new(default)]
+    pub min_elements: Option<u64>,
+    #[new(default)]
+    pub must: Vec<MustNode>,
+    #[new(default)]
+    pub typ: Option<TypeNode>,
+    #[new(default)]
+    pub unique: Vec<String>,
+    #[new(default)]
+    pub units: Option<String>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum DeviationError {
+    #[error("deviation {0}: prefix {1} is not imported")]
+    UnknownPrefix(String, String),
+    #[error("deviation {0}: target module {1} is not loaded")]
+    ModuleNotFound(String, String),
+    #[error("deviation {0}: target node not found")]
+    TargetNotFound(String),
+    #[error("deviation {0}: module can't deviate its own node")]
+    SelfDeviation(String),
+    #[error("deviation {0}: \"not-supported\" can't be combined with other deviate")]
+    NotSupportedCombined(String),
+    #[error("deviation {0}: list key leaf can't be not-supported")]
+    KeyNotSupported(String),
+    #[error("deviation {0}: {1} is not a valid property of the target node")]
+    InvalidProperty(String, &'static str),
+    #[error("deviation {0}: deviate add {1} which already exists")]
+    AlreadyExists(String, &'static str),
+    #[error("deviation {0}: deviate {1} {2} which does not exist")]
+    NotExists(String, &'static str, &'static str),
+    #[error("deviation {0}: {1} can't be replaced")]
+    NotReplaceable(String, &'static str),
+    #[error("deviation {0}: mandatory node can't have default")]
+    MandatoryDefault(String),
+    #[error("deviation {0}: min-elements is greater than max-elements")]
+    MinMaxElements(String),
+    #[error("deviation {0}: config true node under config false node")]
+    ConfigTrueUnderFalse(String),
+}
+
+// Split absolute schema node identifier "/a:b/a:c" into (prefix, name)
+// pairs.
+fn schema_node_id(path: &str) -> Vec<(Option<&str>, &str)> {
+    path.split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| match s.find(':') {
+            Some(pos) => (Some(&s[..pos]), &s[pos + 1..]),
+            None => (None, s),
+        })
+        .collect()
+}
+
+fn deviate_single<T: Clone + PartialEq>(
+    target: &str,
+    kind: DeviateKind,
+    property: &'static str,
+    slot: Option<&mut Option<T>>,
+    value: &Option<T>,
+) -> Result<(), DeviationError> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(()),
+    };
+    let slot = match slot {
+        Some(slot) => slot,
+        None => return Err(DeviationError::InvalidProperty(target.to_owned(), property)),
+    };
+    match kind {
+        DeviateKind::Add => {
+            if slot.is_some() {
+                return Err(DeviationError::AlreadyExists(target.to_owned(), property));
+            }
+            *slot = Some(value.clone());
+        }
+        DeviateKind::Replace => {
+            if slot.is_none() {
+                return Err(DeviationError::NotExists(
+                    target.to_owned(),
+                    kind.as_str(),
+                    property,
+                ));
+            }
+            *slot = Some(value.clone());
+        }
+        DeviateKind::Delete => {
+            if slot.as_ref() != Some(value) {
+                return Err(DeviationError::NotExists(
+                    target.to_owned(),
+                    kind.as_str(),
+                    property,
+                ));
+            }
+            *slot = None;
+        }
+        DeviateKind::NotSupported => {}
+    }
+    Ok(())
+}
+
+// Properties which may appear multiple times.  "must" is matched by its
+// expression text since compiled expressions carry the prefixes of the
+// module where they are defined.
+trait DeviateValue: Clone {
+    fn same(&self, other: &Self) -> bool;
+}
+
+impl DeviateValue for String {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl DeviateValue for MustNode {
+    fn same(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+fn deviate_multi<T: DeviateValue>(
+    target: &str,
+    kind: DeviateKind,
+    property: &'static str,
+    slot: Option<&mut Vec<T>>,
+    values: &[T],
+) -> Result<(), DeviationError> {
+    if values.is_empty() {
+        return Ok(());
+    }
+    let slot = match slot {
+        Some(slot) => slot,
+        None => return Err(DeviationError::InvalidProperty(target.to_owned(), property)),
+    };
+    match kind {
+        DeviateKind::Add => {
+            slot.extend(values.iter().cloned());
+        }
+        DeviateKind::Replace => {
+            if property != "default" {
+                return Err(DeviationError::NotReplaceable(target.to_owned(), property));
+            }
+            if slot.is_empty() {
+                return Err(DeviationError::NotExists(
+                    target.to_owned(),
+                    kind.as_str(),
+                    property,
+                ));
+            }
+            *slot = values.to_vec();
+        }
+        DeviateKind::Delete => {
+            for value in values {
+                match slot.iter().position(|v| v.same(value)) {
+                    Some(index) => {
+                        slot.remove(index);
+                    }
+                    None => {
+                        return Err(DeviationError::NotExists(
+                            target.to_owned(),
+                            kind.as_str(),
+                            property,
+                        ))
+                    }
+                }
+            }
+        }
+        DeviateKind::NotSupported => {}
+    }
+    Ok(())
+}
+
+fn config_mut(node: &mut Node) -> Option<&mut Option<bool>> {
+    match node {
+        Node::Container(n) => Some(&mut n.config),
+        Node::List(n) => Some(&mut n.config),
+        Node::Leaf(n) => Some(&mut n.config),
+        Node::LeafList(n) => Some(&mut n.config),
+        Node::Anydata(n) => Some(&mut n.config),
+        Node::Choice(n) => Some(&mut n.config),
+        _ => None,
+    }
+}
+
+fn mandatory_mut(node: &mut Node) -> Option<&mut Option<bool>> {
+    match node {
+        Node::Leaf(n) => Some(&mut n.mandatory),
+        Node::Anydata(n) => Some(&mut n.mandatory),
+        Node::Choice(n) => Some(&mut n.mandatory),
+        _ => None,
+    }
+}
+
+fn default_mut(node: &mut Node) -> Option<&mut Option<String>> {
+    match node {
+        Node::Leaf(n) => Some(&mut n.default),
+        Node::Choice(n) => Some(&mut n.default),
+        _ => None,
+    }
+}
+
+fn min_elements_mut(node: &mut Node) -> Option<&mut Option<u64>> {
+    match node {
+        Node::List(n) => Some(&mut n.min_elements),
+        Node::LeafList(n) => Some(&mut n.min_elements),
+        _ => None,
+    }
+}
+
+fn max_elements_mut(node: &mut Node) -> Option<&mut Option<u64>> {
+    match node {
+        Node::List(n) => Some(&mut n.max_elements),
+        Node::LeafList(n) => Some(&mut n.max_elements),
+        _ => None,
+    }
+}
+
+fn must_mut(node: &mut Node) -> Option<&mut Vec<MustNode>> {
+    match node {
+        Node::Container(n) => Some(&mut n.must),
+        Node::List(n) => Some(&mut n.must),
+        Node::Leaf(n) => Some(&mut n.must),
+        Node::Anydata(n) => Some(&mut n.must),
+        Node::LeafList(n) => Some(&mut n.must),
+        _ => None,
+    }
+}
+
+fn type_mut(node: &mut Node) -> Option<&mut Option<TypeNode>> {
+    match node {
+        Node::Leaf(n) => Some(&mut n.typ),
+        Node::LeafList(n) => Some(&mut n.typ),
+        _ => None,
+    }
+}
+
+fn unique_mut(node: &mut Node) -> Option<&mut Vec<String>> {
+    match node {
+        Node::List(n) => Some(&mut n.unique),
+        _ => None,
+    }
+}
+
+fn units_mut(node: &mut Node) -> Option<&mut Option<String>> {
+    match node {
+        Node::Leaf(n) => Some(&mut n.units),
+        Node::LeafList(n) => Some(&mut n.units),
+        _ => None,
+    }
+}
+
+fn deviate_apply(target: &str, node: &mut Node, d: &DeviateNode) -> Result<(), DeviationError> {
+    let kind = d.kind;
+    deviate_single(target, kind, "config", config_mut(node), &d.config)?;
+    deviate_single(target, kind, "mandatory", mandatory_mut(node), &d.mandatory)?;
+    deviate_single(
+        target,
+        kind,
+        "min-elements",
+        min_elements_mut(node),
+        &d.min_elements,
+    )?;
+    deviate_single(
+        target,
+        kind,
+        "max-elements",
+        max_elements_mut(node),
+        &d.max_elements,
+    )?;
+    deviate_single(target, kind, "units", units_mut(node), &d.units)?;
+    deviate_multi(target, kind, "must", must_mut(node), &d.must)?;
+    deviate_multi(target, kind, "unique", unique_mut(node), &d.unique)?;
+
+    // "type" can only be replaced.
+    if d.typ.is_some() && kind != DeviateKind::Replace {
+        return Err(DeviationError::InvalidProperty(target.to_owned(), "type"));
+    }
+    deviate_single(target, kind, "type", type_mut(node), &d.typ)?;
+
+    // "default" is single for leaf and choice, and multiple for leaf-list.
+    match node {
+        Node::Leaf(_) | Node::Choice(_) => {
+            if d.default.len() > 1 {
+                return Err(DeviationError::InvalidProperty(
+                    target.to_owned(),
+                    "default",
+                ));
+            }
+            let default = d.default.first().cloned();
+            deviate_single(target, kind, "default", default_mut(node), &default)?;
+        }
+        Node::LeafList(n) => {
+            deviate_multi(target, kind, "default", Some(&mut n.default), &d.default)?;
+        }
+        _ => {
+            deviate_multi::<String>(target, kind, "default", None, &d.default)?;
+        }
+    }
+    Ok(())
+}
+
+// Validate the target node after deviations are applied.
+fn deviate_check(target: &str, node: &Node, parent_config: bool) -> Result<(), DeviationError> {
+    let (min, max) = match node {
+        Node::Leaf(n) if n.mandatory == Some(true) && n.default.is_some() => {
+            return Err(DeviationError::MandatoryDefault(target.to_owned()));
+        }
+        Node::Choice(n) if n.mandatory == Some(true) && n.default.is_some() => {
+            return Err(DeviationError::MandatoryDefault(target.to_owned()));
+        }
+        Node::List(n) => (n.min_elements, n.max_elements),
+        Node::LeafList(n) => (n.min_elements, n.max_elements),
+        _ => (None, None),
+    };
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(DeviationError::MinMaxElements(target.to_owned()));
+        }
+    }
+    if !parent_config && node.config() == Some(true) {
+        return Err(DeviationError::ConfigTrueUnderFalse(target.to_owned()));
+    }
+    Ok(())
+}
+
+impl Modules {
+    // Resolve deviation target to (module name, node names).
+    fn deviation_target(
+        &self,
+        module: &Module,
+        deviation: &DeviationNode,
+    ) -> Result<(String, Vec<String>), DeviationError> {
+        let path = schema_node_id(&deviation.name);
+        let prefix = match path.first() {
+            Some((Some(prefix), _)) => *prefix,
+            Some((None, _)) => module.prefix.as_str(),
+            None => return Err(DeviationError::TargetNotFound(deviation.name.to_owned())),
+        };
+        let target_module = match module.prefix_module(prefix) {
+            Some(name) => name.to_owned(),
+            None => {
+                return Err(DeviationError::UnknownPrefix(
+                    deviation.name.to_owned(),
+                    prefix.to_owned(),
+                ))
+            }
+        };
+        if target_module == module.name {
+            return Err(DeviationError::SelfDeviation(deviation.name.to_owned()));
+        }
+        if self.find_module(&target_module).is_none() {
+            return Err(DeviationError::ModuleNotFound(
+                deviation.name.to_owned(),
+                target_module,
+            ));
+        }
+        let names = path.iter().map(|(_, name)| name.to_string()).collect();
+        Ok((target_module, names))
+    }
+
+    // Names of the implemented modules which have deviations of the module
+    // "name".
+    pub fn deviation_modules(&self, name: &str) -> Vec<&str> {
+        self.implemented()
+            .into_iter()
+            .filter(|module| {
+                module.deviations.iter().any(|deviation| {
+                    self.deviation_target(module, deviation)
+                        .is_ok_and(|(target, _)| target == name)
+                })
+            })
+            .map(|module| module.name.as_str())
+            .collect()
+    }
+
+    // Apply all of deviations in the loaded modules to the target modules'
+    // schema tree.  "not-supported" deviations are applied after other
+    // deviations so that the result does not depend on the order of
+    // deviation statements.  Deviations are applied to copies of the target
+    // modules' schema trees, which replace the originals only when all of
+    // the deviations succeed.
+    pub fn apply_deviations(&mut self) -> Result<(), DeviationError> {
+        let mut targets = vec![];
+        for module in self.implemented() {
+            for deviation in &module.deviations {
+                let (target_module, names) = self.deviation_target(module, deviation)?;
+                targets.push((target_module, names, deviation.clone()));
+            }
+        }
+        let mut trees: HashMap<String, Vec<Node>> = HashMap::new();
+        for (target_module, _, _) in &targets {
+            if !trees.contains_key(target_module) {
+                let module = self.find_module(target_module).unwrap();
+                trees.insert(target_module.to_owned(), module.nodes.clone());
+            }
+        }
+        let mut removals = vec![];
+        for (target_module, names, deviation) in targets {
+            let not_supported = deviation
+                .deviates
+                .iter()
+                .any(|d| d.kind == DeviateKind::NotSupported);
+            if not_supported {
+                if deviation.deviates.len() > 1 {
+                    return Err(DeviationError::NotSupportedCombined(deviation.name));
+                }
+                removals.push((target_module, names, deviation.name));
+                continue;
+            }
+            let mut config = true;
+            let mut nodes = trees.get_mut(&target_module).unwrap();
+            let (last, parents) = names.split_last().unwrap();
+            for name in parents {
+                let node = nodes
+                    .iter_mut()
+                    .find(|n| n.name() == Some(name))
+                    .ok_or_else(|| DeviationError::TargetNotFound(deviation.name.to_owned()))?;
+                config = node.config().unwrap_or(config);
+                nodes = node
+                    .nodes_mut()
+                    .ok_or_else(|| DeviationError::TargetNotFound(deviation.name.to_owned()))?;
+            }
+            let node = nodes
+                .iter_mut()
+                .find(|n| n.name() == Some(last))
+                .ok_or_else(|| DeviationError::TargetNotFound(deviation.name.to_owned()))?;
+            for d in &deviation.deviates {
+                deviate_apply(&deviation.name, node, d)?;
+            }
+            deviate_check(&deviation.name, node, config)?;
+        }
+        for (target_module, names, target) in removals {
+            let mut nodes = trees.get_mut(&target_module).unwrap();
+            let mut keys: Vec<String> = vec![];
+            let (last, parents) = names.split_last().unwrap();
+            for name in parents {
+                let node = nodes
+                    .iter_mut()
+                    .find(|n| n.name() == Some(name))
+                    .ok_or_else(|| DeviationError::TargetNotFound(target.to_owned()))?;
+                keys = match node {
+                    Node::List(n) => n.keys().iter().map(|k| k.to_string()).collect(),
+                    _ => vec![],
+                };
+                nodes = node
+                    .nodes_mut()
+                    .ok_or_else(|| DeviationError::TargetNotFound(target.to_owned()))?;
+            }
+            if keys.iter().any(|k| k == last) {
+                return Err(DeviationError::KeyNotSupported(target));
+            }
+            let index = nodes
+                .iter()
+                .position(|n| n.name() == Some(last))
+                .ok_or_else(|| DeviationError::TargetNotFound(target.to_owned()))?;
+            nodes.remove(index);
+        }
+        for (target_module, nodes) in trees {
+            self.find_module_mut(&target_module).unwrap().nodes = nodes;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_node_id() {
+        let path = schema_node_id("/if:interfaces/if:interface/mtu");
+        assert_eq!(
+            path,
+            vec![
+                (Some("if"), "interfaces"),
+                (Some("if"), "interface"),
+                (None, "mtu")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deviate_single() {
+        let mut slot = None;
+        deviate_single("/t", DeviateKind::Add, "units", Some(&mut slot), &Some(1)).unwrap();
+        assert_eq!(slot, Some(1));
+        assert_eq!(
+            deviate_single("/t", DeviateKind::Add, "units", Some(&mut slot), &Some(2)),
+            Err(DeviationError::AlreadyExists("/t".to_owned(), "units"))
+        );
+        deviate_single(
+            "/t",
+            DeviateKind::Replace,
+            "units",
+            Some(&mut slot),
+            &Some(2),
+        )
+        .unwrap();
+        assert_eq!(slot, Some(2));
+        assert_eq!(
+            deviate_single(
+                "/t",
+                DeviateKind::Delete,
+                "units",
+                Some(&mut slot),
+                &Some(1)
+            ),
+            Err(DeviationError::NotExists(
+                "/t".to_owned(),
+                "delete",
+                "units"
+            ))
+        );
+        deviate_single(
+            "/t",
+            DeviateKind::Delete,
+            "units",
+            Some(&mut slot),
+            &Some(2),
+        )
+        .unwrap();
+        assert_eq!(slot, None);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::modules::*;
+use crate::Node;
+
+// RFC7950 7.19.  The "extension" Statement
+//
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | argument     | 7.19.2  | 0..1        |
+// | description  | 7.21.3  | 0..1        |
+// | reference    | 7.21.4  | 0..1        |
+// | status       | 7.21.2  | 0..1        |
+// +--------------+---------+-------------+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct ExtensionNode {
+    pub name: String,
+    #[new(default)]
+    pub argument: Option<ArgumentNode>,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+// RFC7950 7.19.2.  The "argument" Statement
+//
+// +--------------+----------+-------------+
+// | substatement | section  | cardinality |
+// +--------------+----------+-------------+
+// | yin-element  | 7.19.2.2 | 0..1        |
+// +--------------+----------+-------------+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct ArgumentNode {
+    pub name: String,
+    #[new(default)]
+    pub yin_element: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct YinElementNode {
+    pub name: String,
+    #[new(default)]
+    pub yin_element: bool,
+}
+
+// Extension instance such as 'nacm:default-deny-write;'.  "prefix" is
+// None for substatements of an extension instance which are YANG
+// statements.  "module" is the name of the module which defines the
+// extension, it is resolved by the prefix at the end of module parsing.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct ExtInstanceNode {
+    pub prefix: Option<String>,
+    pub keyword: String,
+    #[new(default)]
+    pub argument: Option<String>,
+    #[new(default)]
+    pub module: Option<String>,
+    #[new(default)]
+    pub nodes: Vec<ExtInstanceNode>,
+}
+
+impl ExtInstanceNode {
+    // Keyword as written in the module, e.g. "nacm:default-deny-write".
+    pub fn name(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.keyword),
+            None => self.keyword.to_owned(),
+        }
+    }
+
+    // Whether this is an instance of the extension "keyword" defined in
+    // "module".
+    pub fn is(&self, module: &str, keyword: &str) -> bool {
+        self.module.as_deref() == Some(module) && self.keyword == keyword
+    }
+}
+
+// Find extension instance by defining module name and keyword.
+pub fn find_ext_instance<'a>(
+    exts: &'a [ExtInstanceNode],
+    module: &str,
+    keyword: &str,
+) -> Option<&'a ExtInstanceNode> {
+    exts.iter().find(|e| e.is(module, keyword))
+}
+
+fn resolve_exts(module: &Module, exts: &mut [ExtInstanceNode]) {
+    for ext in exts.iter_mut() {
+        if let Some(prefix) = &ext.prefix {
+            ext.module = module.prefix_module(prefix).map(|m| m.to_owned());
+        }
+        resolve_exts(module, &mut ext.nodes);
+    }
+}
+
+fn resolve_type_exts(module: &Module, typ: &mut Option<TypeNode>) {
+    if let Some(typ) = typ {
+        resolve_exts(module, &mut typ.ext_instances);
+        for union in typ.union.iter_mut() {
+            resolve_exts(module, &mut union.ext_instances);
+        }
+    }
+}
+
+fn resolve_must_exts(module: &Module, must: &mut [MustNode]) {
+    for must in must.iter_mut() {
+        resolve_exts(module, &mut must.ext_instances);
+    }
+}
+
+fn resolve_when_exts(module: &Module, when: &mut Option<WhenNode>) {
+    if let Some(when) = when {
+        resolve_exts(module, &mut when.ext_instances);
+    }
+}
+
+fn resolve_node_exts(module: &Module, node: &mut Node) {
+    match node {
+        Node::Container(n) => {
+            resolve_exts(module, &mut n.ext_instances);
+            resolve_must_exts(module, &mut n.must);
+            resolve_when_exts(module, &mut n.when);
+        }
+        Node::List(n) => {
+            resolve_exts(module, &mut n.ext_instances);
+            resolve_must_exts(module, &mut n.must);
+            resolve_when_exts(module, &mut n.when);
+        }
+        Node::Leaf(n) => {
+            resolve_exts(module, &mut n.ext_instances);
+            resolve_must_exts(module, &mut n.must);
+            resolve_when_exts(module, &mut n.when);
+            resolve_type_exts(module, &mut n.typ);
+        }
+        Node::LeafList(n) => {
+            resolve_exts(module, &mut n.ext_instances);
+            resolve_must_exts(module, &mut n.must);
+            resolve_when_exts(module, &mut n.when);
+            resolve_type_exts(module, &mut n.typ);
+        }
+        Node::Anydata(n) => {
+            resolve_exts(module, &mut n.ext_instances);
+            resolve_must_exts(module, &mut n.must);
+            resolve_when_exts(module, &mut n.when);
+        }
+        Node::Choice(n) => {
+            resolve_exts(module, &mut n.ext_instances);
+            resolve_when_exts(module, &mut n.when);
+        }
+        Node::Case(n) => {
+            resolve_exts(module, &mut n.ext_instances);
+            resolve_when_exts(module, &mut n.when);
+        }
+        Node::Grouping(n) => resolve_exts(module, &mut n.ext_instances),
+        Node::Uses(n) => {
+            resolve_exts(module, &mut n.ext_instances);
+            resolve_when_exts(module, &mut n.when);
+            for refine in n.refines.iter_mut() {
+                resolve_exts(module, &mut refine.ext_instances);
+                resolve_must_exts(module, &mut refine.must);
+            }
+        }
+        _ => {}
+    }
+    if let Some(nodes) = node.nodes_mut() {
+        for n in nodes.iter_mut() {
+            resolve_node_exts(module, n);
+        }
+    }
+}
+
+impl Module {
+    // Resolve the defining module of all of extension instances in the
+    // module.
+    pub fn resolve_ext_instances(&mut self) {
+        let mut exts = std::mem::take(&mut self.ext_instances);
+        resolve_exts(self, &mut exts);
+        self.ext_instances = exts;
+
+        let mut imports = std::mem::take(&mut self.imports);
+        for import in imports.iter_mut() {
+            resolve_exts(self, &mut import.ext_instances);
+        }
+        self.imports = imports;
+
+        let mut revisions = std::mem::take(&mut self.revisions);
+        for revision in revisions.iter_mut() {
+            resolve_exts(self, &mut revision.ext_instances);
+        }
+        self.revisions = revisions;
+
+        let mut extensions = std::mem::take(&mut self.extensions);
+        for extension in extensions.values_mut() {
+            resolve_exts(self, &mut extension.ext_instances);
+        }
+        self.extensions = extensions;
+
+        let mut identities = std::mem::take(&mut self.identities);
+        for identity in identities.values_mut() {
+            resolve_exts(self, &mut identity.ext_instances);
+        }
+        self.identities = identities;
+
+        let mut features = std::mem::take(&mut self.features);
+        for feature in features.values_mut() {
+            resolve_exts(self, &mut feature.ext_instances);
+        }
+        self.features = features;
+
+        let mut typedefs = std::mem::take(&mut self.typedefs);
+        for typedef in typedefs.values_mut() {
+            resolve_exts(self, &mut typedef.ext_instances);
+            resolve_type_exts(self, &mut typedef.typ);
+        }
+        self.typedefs = typedefs;
+
+        let mut nodes = std::mem::take(&mut self.nodes);
+        for node in nodes.iter_mut() {
+            resolve_node_exts(self, node);
+        }
+        self.nodes = nodes;
+
+        let mut groupings = std::mem::take(&mut self.groupings);
+        for grouping in groupings.values_mut() {
+            resolve_exts(self, &mut grouping.ext_instances);
+            for node in grouping.nodes.iter_mut() {
+                resolve_node_exts(self, node);
+            }
+        }
+        self.groupings = groupings;
+
+        let mut deviations = std::mem::take(&mut self.deviations);
+        for deviation in deviations.iter_mut() {
+            resolve_exts(self, &mut deviation.ext_instances);
+        }
+        self.deviations = deviations;
+    }
+}
+
+impl Node {
+    // Extension instances of data definition node.
+    pub fn ext_instances(&self) -> &[ExtInstanceNode] {
+        match self {
+            Node::Container(n) => &n.ext_instances,
+            Node::List(n) => &n.ext_instances,
+            Node::Leaf(n) => &n.ext_instances,
+            Node::LeafList(n) => &n.ext_instances,
+            Node::Anydata(n) => &n.ext_instances,
+            Node::Choice(n) => &n.ext_instances,
+            Node::Case(n) => &n.ext_instances,
+            Node::Grouping(n) => &n.ext_instances,
+            Node::Uses(n) => &n.ext_instances,
+            _ => &[],
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::modules::*;
+use crate::Node;
+
+// RFC7950 7.12.1.  The grouping's Substatements
+//
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | action       | 7.15    | 0..n        |
+// | anydata      | 7.10    | 0..n        |
+// | anyxml       | 7.11    | 0..n        |
+// | choice       | 7.9     | 0..n        |
+// | container    | 7.5     | 0..n        |
+// | description  | 7.21.3  | 0..1        |
+// | grouping     | 7.12    | 0..n        |
+// | leaf         | 7.6     | 0..n        |
+// | leaf-list    | 7.7     | 0..n        |
+// | list         | 7.8     | 0..n        |
+// | notification | 7.16    | 0..n        |
+// | reference    | 7.21.4  | 0..1        |
+// | status       | 7.21.2  | 0..1        |
+// | typedef      | 7.3     | 0..n        |
+// | uses         | 7.13    | 0..n        |
+// +--------------+---------+-------------+
+//
+// "nodes" has the data definitions, and the nested groupings which are
+// in scope of the data definitions.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct GroupingNode {
+    pub name: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub nodes: Vec<Node>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+// RFC7950 7.13.1.  The uses's Substatements
+//
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | augment      | 7.17    | 0..n        |
+// | description  | 7.21.3  | 0..1        |
+// | if-feature   | 7.20.2  | 0..n        |
+// | refine       | 7.13.2  | 0..n        |
+// | reference    | 7.21.4  | 0..1        |
+// | status       | 7.21.2  | 0..1        |
+// | when         | 7.21.5  | 0..1        |
+// +--------------+---------+-------------+
+//
+// "augment" is not supported.  "nodes" is empty when parsed and has the
+// copy of the grouping's data definitions when the schema is compiled,
+// then "module" is the module where the grouping is defined.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct UsesNode {
+    pub name: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub when: Option<WhenNode>,
+    #[new(default)]
+    pub refines: Vec<RefineNode>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+    #[new(default)]
+    pub module: Option<ModuleId>,
+    #[new(default)]
+    pub nodes: Vec<Node>,
+}
+
+// RFC7950 7.13.2.  The "refine" Statement
+//
+// "name" is the descendant schema node identifier of the target node.
+// "must" and "if-feature" are added to the target and the others replace
+// the target's properties.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct RefineNode {
+    pub name: String,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub config: Option<bool>,
+    #[new(default)]
+    pub mandatory: Option<bool>,
+    #[new(default)]
+    pub presence: Option<String>,
+    #[new(default)]
+    pub default: Vec<String>,
+    #[new(default)]
+    pub min_elements: Option<u64>,
+    #[new(default)]
+    pub max_elements: Option<u64>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub must: Vec<MustNode>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+impl RefineNode {
+    // Apply the refinements to the target node.  False when the target
+    // can't have one of the refined properties.
+    pub fn apply(&self, node: &mut Node) -> bool {
+        // Whether "presence", "mandatory", "min-elements" and
+        // "max-elements", "must" and "config" apply, and the number of
+        // defaults.
+        let (presence, mandatory, elements, must, config, defaults) = match node {
+            Node::Container(_) => (true, false, false, true, true, 0),
+            Node::List(_) => (false, false, true, true, true, 0),
+            Node::Leaf(_) => (false, true, false, true, true, 1),
+            Node::LeafList(_) => (false, false, true, true, true, usize::MAX),
+            Node::Anydata(_) => (false, true, false, true, true, 0),
+            Node::Choice(_) => (false, true, false, false, true, 1),
+            Node::Case(_) => (false, false, false, false, false, 0),
+            _ => return false,
+        };
+        if (self.presence.is_some() && !presence)
+            || (self.mandatory.is_some() && !mandatory)
+            || ((self.min_elements.is_some() || self.max_elements.is_some()) && !elements)
+            || (!self.must.is_empty() && !must)
+            || (self.config.is_some() && !config)
+            || self.default.len() > defaults
+        {
+            return false;
+        }
+        let (description, reference) = match node {
+            Node::Container(n) => {
+                if let Some(presence) = &self.presence {
+                    n.presence = Some(presence.clone());
+                }
+                n.config = self.config.or(n.config);
+                n.if_features.extend(self.if_features.iter().cloned());
+                n.must.extend(self.must.iter().cloned());
+                (&mut n.description, &mut n.reference)
+            }
+            Node::List(n) => {
+                n.config = self.config.or(n.config);
+                n.min_elements = self.min_elements.or(n.min_elements);
+                n.max_elements = self.max_elements.or(n.max_elements);
+                n.if_features.extend(self.if_features.iter().cloned());
+                n.must.extend(self.must.iter().cloned());
+                (&mut n.description, &mut n.reference)
+            }
+            Node::Leaf(n) => {
+                if let Some(default) = self.default.first() {
+                    n.default = Some(default.clone());
+                }
+                n.config = self.config.or(n.config);
+                n.mandatory = self.mandatory.or(n.mandatory);
+                n.if_features.extend(self.if_features.iter().cloned());
+                n.must.extend(self.must.iter().cloned());
+                (&mut n.description, &mut n.reference)
+            }
+            Node::LeafList(n) => {
+                if !self.default.is_empty() {
+                    n.default = self.default.clone();
+                }
+                n.config = self.config.or(n.config);
+                n.min_elements = self.min_elements.or(n.min_elements);
+                n.max_elements = self.max_elements.or(n.max_elements);
+                n.if_features.extend(self.if_features.iter().cloned());
+                n.must.extend(self.must.iter().cloned());
+                (&mut n.description, &mut n.reference)
+            }
+            Node::Anydata(n) => {
+                n.config = self.config.or(n.config);
+                n.mandatory = self.mandatory.or(n.mandatory);
+                n.if_features.extend(self.if_features.iter().cloned());
+                n.must.extend(self.must.iter().cloned());
+                (&mut n.description, &mut n.reference)
+            }
+            Node::Choice(n) => {
+                if let Some(default) = self.default.first() {
+                    n.default = Some(default.clone());
+                }
+                n.config = self.config.or(n.config);
+                n.mandatory = self.mandatory.or(n.mandatory);
+                n.if_features.extend(self.if_features.iter().cloned());
+                (&mut n.description, &mut n.reference)
+            }
+            Node::Case(n) => {
+                n.if_features.extend(self.if_features.iter().cloned());
+                (&mut n.description, &mut n.reference)
+            }
+            _ => return false,
+        };
+        if self.description.is_some() {
+            *description = self.description.clone();
+        }
+        if self.reference.is_some() {
+            *reference = self.reference.clone();
+        }
+        true
+    }
+}
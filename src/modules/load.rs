@@ -0,0 +1,203 @@
+use super::yang::skip_comments;
+use super::*;
+use crate::parser::yang_parse;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ModuleError {
+    #[error("Module {0} not found: {1}")]
+    NotFound(String, String),
+    #[error("Module {0} parse error")]
+    Parse(String),
+    #[error("File of {0} has module {1}")]
+    NameMismatch(String, String),
+    #[error("Module {0} has revision {1}, {2} is required")]
+    RevisionMismatch(String, String, String),
+    #[error("{0} is a submodule, not a module")]
+    NotModule(String),
+    #[error("Submodule {0} does not belong to module {1}")]
+    BelongsTo(String, String),
+    #[error("Import cycle {}", .0.join(" -> "))]
+    ImportCycle(Vec<String>),
+    #[error(transparent)]
+    Revision(#[from] RevisionError),
+    #[error("Schema {0} not found in YANG library")]
+    UnknownSchema(String),
+    #[error("Module set {0} not found in YANG library")]
+    UnknownModuleSet(String),
+}
+
+// Problems which do not prevent the module from being loaded.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ModuleWarning {
+    #[error("File {} has revision {1}", .0.display())]
+    FileRevision(PathBuf, String),
+    #[error("File {} can't be parsed, its revision is taken from the file name", .0.display())]
+    Unparsed(PathBuf),
+}
+
+// Find, read and parse the module or submodule "name" in the source.  When
+// "revision" is specified, "name@revision" is looked up first and the
+// module's most recent revision must match to it.  Revisions must be listed
+// newest first.
+fn read_module(
+    source: &dyn ModuleSource,
+    name: &str,
+    revision: Option<&str>,
+    warnings: &mut Vec<ModuleWarning>,
+) -> Result<Module, ModuleError> {
+    let not_found = |e: FindError| ModuleError::NotFound(name.to_owned(), e.to_string());
+    let mut tried = vec![];
+    let found = match revision {
+        Some(revision) => match source.find(&format!("{}@{}", name, revision), &mut tried) {
+            Ok(None) => source.find(name, &mut tried),
+            result => result,
+        },
+        None => source.find(name, &mut tried),
+    }
+    .map_err(not_found)?;
+    let ModuleText {
+        location,
+        text,
+        warnings: found_warnings,
+    } = found.ok_or_else(|| not_found(FindError::NotFound(name.to_owned(), tried)))?;
+    warnings.extend(found_warnings);
+    let module = match yang_parse(skip_comments(&text)) {
+        Ok((_, module)) => module,
+        Err(_) => return Err(ModuleError::Parse(name.to_owned())),
+    };
+    if module.name != name {
+        return Err(ModuleError::NameMismatch(name.to_owned(), module.name));
+    }
+    module.check_revisions()?;
+    if let Some(date) = file_name_revision(&location) {
+        if module.revision_date() != Some(date) {
+            let revision = module.revision().unwrap_or("none").to_owned();
+            warnings.push(ModuleWarning::FileRevision(location, revision));
+        }
+    }
+    if let Some(revision) = revision {
+        if module.revision() != Some(revision) {
+            return Err(ModuleError::RevisionMismatch(
+                name.to_owned(),
+                module.revision().unwrap_or("none").to_owned(),
+                revision.to_owned(),
+            ));
+        }
+    }
+    Ok(module)
+}
+
+fn revision_date(date: &str) -> Option<&str> {
+    if date.is_empty() {
+        None
+    } else {
+        Some(date)
+    }
+}
+
+// Merge the definitions of the included submodules, including ones
+// included from the submodules, into "module".
+fn include_submodules(
+    source: &dyn ModuleSource,
+    module: &mut Module,
+    warnings: &mut Vec<ModuleWarning>,
+) -> Result<(), ModuleError> {
+    let mut includes = module.includes.clone();
+    let mut included: Vec<String> = vec![];
+    while let Some(include) = includes.pop() {
+        if included.contains(&include.name) {
+            continue;
+        }
+        let revision = revision_date(&include.revision_date);
+        let sub = read_module(source, &include.name, revision, warnings)?;
+        if sub.belongs_to.as_deref() != Some(module.name.as_str()) {
+            return Err(ModuleError::BelongsTo(sub.name, module.name.to_owned()));
+        }
+        included.push(sub.name);
+        includes.extend(sub.includes);
+        for import in sub.imports {
+            let imported = module
+                .imports
+                .iter()
+                .any(|i| i.name == import.name && i.prefix == import.prefix);
+            if !imported {
+                module.imports.push(import);
+            }
+        }
+        module.typedefs.extend(sub.typedefs);
+        module.groupings.extend(sub.groupings);
+        module.identities.extend(sub.identities);
+        module.features.extend(sub.features);
+        module.extensions.extend(sub.extensions);
+        module.nodes.extend(sub.nodes);
+        module.deviations.extend(sub.deviations);
+        module.ext_instances.extend(sub.ext_instances);
+    }
+    module.build_prefixes();
+    Ok(())
+}
+
+impl Modules {
+    // Load the module "name" and all of the modules it imports from the
+    // source, such as Yang for the load paths or ModuleSources to consult
+    // multiple sources in order.  Submodules are merged into the including
+    // module.  Modules are keyed by the name and revision.
+    //
+    // RFC7950 5.1.  Modules and Submodules
+    //     There MUST NOT be any circular chains of imports.
+    pub fn load(&mut self, source: &dyn ModuleSource, name: &str) -> Result<&Module, ModuleError> {
+        let id = self.load_module(source, name, None, &mut vec![])?;
+        Ok(&self.modules[&id])
+    }
+
+    // Load the revision of the module "name", any revision when "revision"
+    // is None.
+    pub fn load_revision(
+        &mut self,
+        source: &dyn ModuleSource,
+        name: &str,
+        revision: Option<&str>,
+    ) -> Result<&Module, ModuleError> {
+        let id = self.load_module(source, name, revision, &mut vec![])?;
+        Ok(&self.modules[&id])
+    }
+
+    fn load_module(
+        &mut self,
+        source: &dyn ModuleSource,
+        name: &str,
+        revision: Option<&str>,
+        stack: &mut Vec<String>,
+    ) -> Result<ModuleId, ModuleError> {
+        if let Some(pos) = stack.iter().position(|n| n == name) {
+            let mut cycle = stack[pos..].to_vec();
+            cycle.push(name.to_owned());
+            return Err(ModuleError::ImportCycle(cycle));
+        }
+        // RFC7950 7.1.5.  The "import" Statement
+        //     If no "revision-date" substatement is present, the module
+        //     may be imported from any revision.
+        if let Some(module) = self.find_revision(name, revision) {
+            return Ok(module.id());
+        }
+
+        let mut module = read_module(source, name, revision, &mut self.warnings)?;
+        if module.belongs_to.is_some() {
+            return Err(ModuleError::NotModule(name.to_owned()));
+        }
+        include_submodules(source, &mut module, &mut self.warnings)?;
+
+        stack.push(name.to_owned());
+        for import in module.imports.clone() {
+            let revision = revision_date(&import.revision_date);
+            self.load_module(source, &import.name, revision, stack)?;
+        }
+        stack.pop();
+
+        let id = module.id();
+        self.add(module);
+        Ok(id)
+    }
+}
@@ -1,8 +1,28 @@
+#![allow(clippy::module_inception)]
+
 pub mod container;
+pub mod deviation;
+pub mod extension;
+pub mod grouping;
+pub mod load;
 pub mod modules;
+pub mod must;
+pub mod pattern;
+pub mod printer;
+pub mod revision;
+pub mod source;
 pub mod types;
 pub mod yang;
 pub use container::*;
+pub use deviation::*;
+pub use extension::*;
+pub use grouping::*;
+pub use load::*;
 pub use modules::*;
+pub use must::*;
+pub use pattern::*;
+pub use printer::*;
+pub use revision::*;
+pub use source::*;
 pub use types::*;
 pub use yang::*;
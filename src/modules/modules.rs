@@ -1,5 +1,11 @@
+use super::deviation::DeviationNode;
+use super::extension::{ExtInstanceNode, ExtensionNode};
+use super::grouping::GroupingNode;
+use super::load::ModuleWarning;
 use super::types::TypedefNode;
-use std::collections::HashMap;
+use crate::Node;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 // RFC7950 7.1.1.  The module's Substatements
 //
@@ -37,31 +43,127 @@ use std::collections::HashMap;
 //
 // In RFC6020 (YANG 1), yang-version cardinality is 0..1.
 
+// Identity of a module in Modules, the module name and the most recent
+// revision.  Revision is None when the module has no revision statement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, new)]
+pub struct ModuleId {
+    pub name: String,
+    pub revision: Option<String>,
+}
+
+impl fmt::Display for ModuleId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.revision {
+            Some(revision) => write!(f, "{}@{}", self.name, revision),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+// Multiple revisions of a module can be loaded, for example when modules
+// import the different revisions by "revision-date".  The most recent
+// revision of each module which is not "import_only" is the implemented one
+// and is compiled into the schema.
 pub struct Modules {
-    pub modules: HashMap<String, Module>,
+    pub modules: HashMap<ModuleId, Module>,
+    pub import_only: HashSet<ModuleId>,
+    pub warnings: Vec<ModuleWarning>,
+}
+
+impl Default for Modules {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Modules {
     pub fn new() -> Self {
         Self {
             modules: HashMap::new(),
+            import_only: HashSet::new(),
+            warnings: vec![],
         }
     }
+
+    // Add the module, the module with the same name and revision is
+    // replaced and returned.
+    pub fn add(&mut self, module: Module) -> Option<Module> {
+        self.modules.insert(module.id(), module)
+    }
+
+    // Lookup module by module name.  The most recent revision is returned
+    // when multiple revisions are loaded.
+    pub fn find_module(&self, name: &str) -> Option<&Module> {
+        self.modules
+            .values()
+            .filter(|m| m.name == name)
+            .max_by(|a, b| a.revision().cmp(&b.revision()))
+    }
+
+    pub fn find_module_mut(&mut self, name: &str) -> Option<&mut Module> {
+        self.modules
+            .values_mut()
+            .filter(|m| m.name == name)
+            .max_by(|a, b| a.revision().cmp(&b.revision()))
+    }
+
+    // Lookup module by module name and revision.  Revision None is any
+    // revision, that is the most recent one.
+    pub fn find_revision(&self, name: &str, revision: Option<&str>) -> Option<&Module> {
+        match revision {
+            Some(revision) => self
+                .modules
+                .values()
+                .find(|m| m.name == name && m.revision() == Some(revision)),
+            None => self.find_module(name),
+        }
+    }
+
+    // Lookup module by namespace URI such as the namespace of XML elements.
+    pub fn namespace_module(&self, namespace: &str) -> Option<&Module> {
+        self.modules
+            .values()
+            .filter(|m| m.namespace == namespace)
+            .max_by(|a, b| a.revision().cmp(&b.revision()))
+    }
+
+    // Resolve the prefix in "module" to the imported module, the revision
+    // of "revision-date" or the most recent revision.
+    pub fn import_module(&self, module: &Module, prefix: &str) -> Option<&Module> {
+        let id = module.prefixes.get(prefix)?;
+        self.find_revision(&id.name, id.revision.as_deref())
+    }
+
+    pub fn is_implemented(&self, module: &Module) -> bool {
+        let id = module.id();
+        !self.import_only.contains(&id)
+            && self
+                .modules
+                .values()
+                .filter(|m| m.name == module.name && !self.import_only.contains(&m.id()))
+                .all(|m| m.revision() <= module.revision())
+    }
+
+    // The implemented modules sorted by name.
+    pub fn implemented(&self) -> Vec<&Module> {
+        let mut modules: Vec<&Module> = self
+            .modules
+            .values()
+            .filter(|m| self.is_implemented(m))
+            .collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+        modules
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub enum YangVersion {
+    #[default]
     V1,
     V1_1,
 }
 
-impl Default for YangVersion {
-    fn default() -> Self {
-        YangVersion::V1
-    }
-}
-
-#[derive(Debug, Default, PartialEq, new)]
+#[derive(Debug, Clone, Default, PartialEq, new)]
 pub struct YangVersionNode {
     pub name: String,
 }
@@ -76,61 +178,118 @@ pub struct Module {
     pub description: Option<String>,
     pub revisions: Vec<RevisionNode>,
     pub typedefs: HashMap<String, TypedefNode>,
+    pub groupings: HashMap<String, GroupingNode>,
     pub yang_version: YangVersion,
     pub imports: Vec<ImportNode>,
     pub includes: Vec<IncludeNode>,
+    pub belongs_to: Option<String>,
+    pub prefixes: HashMap<String, ModuleId>,
+    pub identities: HashMap<String, IdentityNode>,
+    pub features: HashMap<String, FeatureNode>,
+    pub nodes: Vec<Node>,
+    pub deviations: Vec<DeviationNode>,
+    pub extensions: HashMap<String, ExtensionNode>,
+    pub ext_instances: Vec<ExtInstanceNode>,
 }
 
 impl Module {
     pub fn new(name: String) -> Self {
         Self {
-            name: name,
+            name,
             typedefs: HashMap::new(),
             ..Default::default()
         }
     }
+
+    // Name of the module which the definitions belong to, that is the
+    // module itself or the module of the submodule.
+    pub fn module_name(&self) -> &str {
+        self.belongs_to.as_deref().unwrap_or(&self.name)
+    }
+
+    // The most recent revision by the dates of the revision statements.
+    pub fn revision(&self) -> Option<&str> {
+        self.revisions
+            .iter()
+            .filter_map(|r| r.date().map(|date| (date, r)))
+            .max_by_key(|(date, _)| *date)
+            .map(|(_, r)| r.name.as_str())
+    }
+
+    pub fn id(&self) -> ModuleId {
+        ModuleId::new(self.name.to_owned(), self.revision().map(String::from))
+    }
+
+    // Build the local prefix to module mapping of the module's own prefix
+    // and imports.  The module's own prefix maps to the module itself, or
+    // the module which the submodule belongs to.
+    pub fn build_prefixes(&mut self) {
+        self.prefixes = self
+            .imports
+            .iter()
+            .map(|i| {
+                let revision = Some(i.revision_date.to_owned()).filter(|r| !r.is_empty());
+                (
+                    i.prefix.to_owned(),
+                    ModuleId::new(i.name.to_owned(), revision),
+                )
+            })
+            .collect();
+        let id = match &self.belongs_to {
+            Some(name) => ModuleId::new(name.to_owned(), None),
+            None => self.id(),
+        };
+        self.prefixes.insert(self.prefix.to_owned(), id);
+    }
+
+    // Resolve prefix to module name by the local prefix mapping.
+    pub fn prefix_module(&self, prefix: &str) -> Option<&str> {
+        self.prefixes.get(prefix).map(|id| id.name.as_str())
+    }
 }
 
-#[derive(Debug, Default, PartialEq, new)]
+#[derive(Debug, Clone, Default, PartialEq, new)]
 pub struct NamespaceNode {
     pub name: String,
 }
 
-#[derive(Debug, Default, PartialEq, new)]
+#[derive(Debug, Clone, Default, PartialEq, new)]
 pub struct PrefixNode {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct OrganizationNode {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct ContactNode {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct DescriptionNode {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct ReferenceNode {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct RevisionNode {
     pub name: String,
     #[new(default)]
     pub description: Option<String>,
     #[new(default)]
     pub reference: Option<String>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct ImportNode {
     pub name: String,
     #[new(default)]
@@ -141,61 +300,90 @@ pub struct ImportNode {
     pub reference: Option<String>,
     #[new(default)]
     pub revision_date: String,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct IncludeNode {
     pub name: String,
     #[new(default)]
     pub description: Option<String>,
     #[new(default)]
     pub reference: Option<String>,
+    #[new(default)]
+    pub revision_date: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct BelongsToNode {
+    pub name: String,
+    #[new(default)]
+    pub prefix: String,
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct RevisionDateNode {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct IdentityNode {
     pub name: String,
     #[new(default)]
+    pub bases: Vec<String>,
+    #[new(default)]
     pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct FeatureNode {
     pub name: String,
     #[new(default)]
     pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub status: Option<String>,
+    #[new(default)]
+    pub if_features: Vec<String>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct MandatoryNode {
     pub name: String,
     #[new(default)]
     pub mandatory: bool,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct ConfigNode {
     pub name: String,
     #[new(default)]
     pub config: bool,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct IfFeatureNode {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct UnitsNode {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct StatusNode {
     pub name: String,
 }
@@ -0,0 +1,175 @@
+use crate::modules::*;
+use crate::xpath::XPath;
+use crate::Node;
+use std::collections::HashMap;
+
+// RFC7950 7.5.3.  The "must" Statement
+//
+// +---------------+---------+-------------+
+// | substatement  | section | cardinality |
+// +---------------+---------+-------------+
+// | description   | 7.21.3  | 0..1        |
+// | error-app-tag | 7.5.4.2 | 0..1        |
+// | error-message | 7.5.4.1 | 0..1        |
+// | reference     | 7.21.4  | 0..1        |
+// +---------------+---------+-------------+
+//
+// "name" is the XPath expression text and "xpath" is the compiled
+// expression.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct MustNode {
+    pub name: String,
+    #[new(default)]
+    pub xpath: Option<XPath>,
+    #[new(default)]
+    pub error_message: Option<String>,
+    #[new(default)]
+    pub error_app_tag: Option<String>,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+// RFC7950 7.21.5.  The "when" Statement
+//
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | description  | 7.21.3  | 0..1        |
+// | reference    | 7.21.4  | 0..1        |
+// +--------------+---------+-------------+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct WhenNode {
+    pub name: String,
+    #[new(default)]
+    pub xpath: Option<XPath>,
+    #[new(default)]
+    pub description: Option<String>,
+    #[new(default)]
+    pub reference: Option<String>,
+    #[new(default)]
+    pub ext_instances: Vec<ExtInstanceNode>,
+}
+
+// RFC7950 6.4.1.  XPath Context
+//     Names without a namespace prefix belong to the same namespace as the
+//     identifier of the current node.  Prefixes are resolved by the
+//     "prefix" and "import" statements of the module where the expression
+//     is defined.
+fn bind_xpath(prefixes: &HashMap<String, String>, xpath: &mut Option<XPath>) {
+    if let Some(xpath) = xpath {
+        xpath.prefixes = prefixes.clone();
+    }
+}
+
+fn bind_must(prefixes: &HashMap<String, String>, must: &mut [MustNode]) {
+    for must in must.iter_mut() {
+        bind_xpath(prefixes, &mut must.xpath);
+    }
+}
+
+fn bind_when(prefixes: &HashMap<String, String>, when: &mut Option<WhenNode>) {
+    if let Some(when) = when {
+        bind_xpath(prefixes, &mut when.xpath);
+    }
+}
+
+fn bind_node(prefixes: &HashMap<String, String>, node: &mut Node) {
+    match node {
+        Node::Container(n) => {
+            bind_must(prefixes, &mut n.must);
+            bind_when(prefixes, &mut n.when);
+        }
+        Node::List(n) => {
+            bind_must(prefixes, &mut n.must);
+            bind_when(prefixes, &mut n.when);
+        }
+        Node::Leaf(n) => {
+            bind_must(prefixes, &mut n.must);
+            bind_when(prefixes, &mut n.when);
+        }
+        Node::LeafList(n) => {
+            bind_must(prefixes, &mut n.must);
+            bind_when(prefixes, &mut n.when);
+        }
+        Node::Anydata(n) => {
+            bind_must(prefixes, &mut n.must);
+            bind_when(prefixes, &mut n.when);
+        }
+        Node::Choice(n) => bind_when(prefixes, &mut n.when),
+        Node::Case(n) => bind_when(prefixes, &mut n.when),
+        Node::Uses(n) => {
+            bind_when(prefixes, &mut n.when);
+            for refine in n.refines.iter_mut() {
+                bind_must(prefixes, &mut refine.must);
+            }
+        }
+        _ => {}
+    }
+    if let Some(nodes) = node.nodes_mut() {
+        for n in nodes.iter_mut() {
+            bind_node(prefixes, n);
+        }
+    }
+}
+
+impl Module {
+    // Prefix to module name mapping of the module's own prefix and
+    // imports.
+    pub fn prefix_map(&self) -> HashMap<String, String> {
+        self.prefixes
+            .iter()
+            .map(|(prefix, id)| (prefix.to_owned(), id.name.to_owned()))
+            .collect()
+    }
+
+    // Bind prefixes of all of "must" and "when" expressions in the module,
+    // including ones in deviations which keep the deviation module's
+    // context after they are applied to the target.
+    pub fn bind_xpath_prefixes(&mut self) {
+        let prefixes = self.prefix_map();
+        for node in self.nodes.iter_mut() {
+            bind_node(&prefixes, node);
+        }
+        for grouping in self.groupings.values_mut() {
+            for node in grouping.nodes.iter_mut() {
+                bind_node(&prefixes, node);
+            }
+        }
+        for deviation in self.deviations.iter_mut() {
+            for deviate in deviation.deviates.iter_mut() {
+                bind_must(&prefixes, &mut deviate.must);
+            }
+        }
+    }
+}
+
+impl Node {
+    pub fn must(&self) -> &[MustNode] {
+        match self {
+            Node::Container(n) => &n.must,
+            Node::List(n) => &n.must,
+            Node::Leaf(n) => &n.must,
+            Node::LeafList(n) => &n.must,
+            Node::Anydata(n) => &n.must,
+            _ => &[],
+        }
+    }
+
+    pub fn when(&self) -> Option<&WhenNode> {
+        match self {
+            Node::Container(n) => n.when.as_ref(),
+            Node::List(n) => n.when.as_ref(),
+            Node::Leaf(n) => n.when.as_ref(),
+            Node::LeafList(n) => n.when.as_ref(),
+            Node::Anydata(n) => n.when.as_ref(),
+            Node::Choice(n) => n.when.as_ref(),
+            Node::Case(n) => n.when.as_ref(),
+            Node::Uses(n) => n.when.as_ref(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use regex::Regex;
+
+// RFC7950 9.4.5.  The "pattern" Statement
+//     The "pattern" statement, which is an optional substatement to the
+//     "type" statement, takes as an argument a regular expression string,
+//     as defined in [XSD-TYPES].
+//
+// XSD regular expressions are implicitly anchored, "^" and "$" are normal
+// characters, "\i" and "\c" are XML name character classes and character
+// class subtraction is written as "[a-z-[aeiou]]".  They are translated
+// into the regex crate syntax.
+pub fn pattern_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let mut out = String::from("^(?:");
+    let mut chars = pattern.chars().peekable();
+    let mut depth = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('i') if depth > 0 => out.push_str("_:A-Za-z"),
+                Some('i') => out.push_str("[_:A-Za-z]"),
+                Some('I') => out.push_str("[^_:A-Za-z]"),
+                Some('c') if depth > 0 => out.push_str("\\-._:A-Za-z0-9"),
+                Some('c') => out.push_str("[\\-._:A-Za-z0-9]"),
+                Some('C') => out.push_str("[^\\-._:A-Za-z0-9]"),
+                Some(e) => {
+                    out.push('\\');
+                    out.push(e);
+                }
+                None => out.push_str("\\\\"),
+            },
+            '[' => {
+                depth += 1;
+                out.push('[');
+                if chars.peek() == Some(&'^') {
+                    out.push(chars.next().unwrap_or('^'));
+                }
+            }
+            ']' if depth > 0 => {
+                depth -= 1;
+                out.push(']');
+            }
+            '-' if depth > 0 && chars.peek() == Some(&'[') => out.push_str("--"),
+            '^' | '$' if depth == 0 => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out.push_str(")$");
+    Regex::new(&out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_regex_test() {
+        let re = pattern_regex("[0-9a-fA-F]*").unwrap();
+        assert!(re.is_match("0aF"));
+        assert!(!re.is_match("0aFx"));
+
+        // Anchored implicitly and "$" is a normal character.
+        let re = pattern_regex("a$").unwrap();
+        assert!(re.is_match("a$"));
+        assert!(!re.is_match("ba$"));
+
+        let re = pattern_regex("\\i\\c*").unwrap();
+        assert!(re.is_match("if-name.1"));
+        assert!(!re.is_match("1if"));
+
+        let re = pattern_regex("[a-z-[aeiou]]+").unwrap();
+        assert!(re.is_match("xyz"));
+        assert!(!re.is_match("xaz"));
+    }
+}
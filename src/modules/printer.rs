@@ -0,0 +1,1176 @@
+use crate::*;
+use std::collections::HashMap;
+use std::fmt;
+
+// Options of the YANG printer.  "indent" is the number of spaces per
+// nesting level and "line_width" the width to which long strings are
+// wrapped.  "canonical" reorders the substatements to the canonical order.
+//
+// RFC8407 4.3.  Line Length
+//     Modules SHOULD be formatted so that lines do not exceed 72
+//     characters in length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+    pub indent: usize,
+    pub line_width: usize,
+    pub canonical: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            line_width: 72,
+            canonical: true,
+        }
+    }
+}
+
+// RFC8407 4.6.  Statement Order
+//     The canonical order of statements in a module or submodule is the
+//     order in which they are defined in the ABNF of [RFC7950].
+//
+// Substatements of "keyword" in groups of the same rank.  Statements in
+// the same group, such as the data definition statements, keep their
+// relative order.
+const DATA_DEFS: &[&str] = &[
+    "container",
+    "leaf",
+    "leaf-list",
+    "list",
+    "choice",
+    "anydata",
+    "anyxml",
+    "uses",
+    "action",
+    "notification",
+];
+const TYPEDEFS: &[&str] = &["typedef", "grouping"];
+
+fn canonical_order(keyword: &str) -> Option<Vec<&'static [&'static str]>> {
+    let order: Vec<&[&str]> = match keyword {
+        "module" | "submodule" => vec![
+            &["yang-version"],
+            &["namespace"],
+            &["prefix"],
+            &["belongs-to"],
+            &["import"],
+            &["include"],
+            &["organization"],
+            &["contact"],
+            &["description"],
+            &["reference"],
+            &["revision"],
+            &[
+                "extension",
+                "feature",
+                "identity",
+                "typedef",
+                "grouping",
+                "container",
+                "leaf",
+                "leaf-list",
+                "list",
+                "choice",
+                "anydata",
+                "anyxml",
+                "uses",
+                "augment",
+                "rpc",
+                "notification",
+                "deviation",
+            ],
+        ],
+        "container" => vec![
+            &["when"],
+            &["if-feature"],
+            &["must"],
+            &["presence"],
+            &["config"],
+            &["status"],
+            &["description"],
+            &["reference"],
+            TYPEDEFS,
+            DATA_DEFS,
+        ],
+        "leaf" => vec![
+            &["when"],
+            &["if-feature"],
+            &["type"],
+            &["units"],
+            &["must"],
+            &["default"],
+            &["config"],
+            &["mandatory"],
+            &["status"],
+            &["description"],
+            &["reference"],
+        ],
+        "leaf-list" => vec![
+            &["when"],
+            &["if-feature"],
+            &["type"],
+            &["units"],
+            &["must"],
+            &["default"],
+            &["config"],
+            &["min-elements"],
+            &["max-elements"],
+            &["ordered-by"],
+            &["status"],
+            &["description"],
+            &["reference"],
+        ],
+        "list" => vec![
+            &["when"],
+            &["if-feature"],
+            &["must"],
+            &["key"],
+            &["unique"],
+            &["config"],
+            &["min-elements"],
+            &["max-elements"],
+            &["ordered-by"],
+            &["status"],
+            &["description"],
+            &["reference"],
+            TYPEDEFS,
+            DATA_DEFS,
+        ],
+        "choice" => vec![
+            &["when"],
+            &["if-feature"],
+            &["default"],
+            &["config"],
+            &["mandatory"],
+            &["status"],
+            &["description"],
+            &["reference"],
+            &[
+                "case",
+                "container",
+                "leaf",
+                "leaf-list",
+                "list",
+                "choice",
+                "anydata",
+                "anyxml",
+            ],
+        ],
+        "case" => vec![
+            &["when"],
+            &["if-feature"],
+            &["status"],
+            &["description"],
+            &["reference"],
+            DATA_DEFS,
+        ],
+        "anydata" | "anyxml" => vec![
+            &["when"],
+            &["if-feature"],
+            &["must"],
+            &["config"],
+            &["mandatory"],
+            &["status"],
+            &["description"],
+            &["reference"],
+        ],
+        "grouping" => vec![
+            &["status"],
+            &["description"],
+            &["reference"],
+            TYPEDEFS,
+            DATA_DEFS,
+        ],
+        "uses" => vec![
+            &["when"],
+            &["if-feature"],
+            &["status"],
+            &["description"],
+            &["reference"],
+            &["refine"],
+            &["augment"],
+        ],
+        "refine" => vec![
+            &["if-feature"],
+            &["must"],
+            &["presence"],
+            &["default"],
+            &["config"],
+            &["mandatory"],
+            &["min-elements"],
+            &["max-elements"],
+            &["description"],
+            &["reference"],
+        ],
+        "augment" => vec![
+            &["when"],
+            &["if-feature"],
+            &["status"],
+            &["description"],
+            &["reference"],
+            &[
+                "container",
+                "leaf",
+                "leaf-list",
+                "list",
+                "choice",
+                "anydata",
+                "anyxml",
+                "uses",
+                "case",
+                "action",
+                "notification",
+            ],
+        ],
+        "rpc" | "action" => vec![
+            &["if-feature"],
+            &["status"],
+            &["description"],
+            &["reference"],
+            TYPEDEFS,
+            &["input"],
+            &["output"],
+        ],
+        "input" | "output" => vec![&["must"], TYPEDEFS, DATA_DEFS],
+        "notification" => vec![
+            &["if-feature"],
+            &["must"],
+            &["status"],
+            &["description"],
+            &["reference"],
+            TYPEDEFS,
+            DATA_DEFS,
+        ],
+        "typedef" => vec![
+            &["type"],
+            &["units"],
+            &["default"],
+            &["status"],
+            &["description"],
+            &["reference"],
+        ],
+        "type" => vec![
+            &["fraction-digits"],
+            &["range"],
+            &["length"],
+            &["pattern"],
+            &["enum"],
+            &["bit"],
+            &["path"],
+            &["require-instance"],
+            &["base"],
+            &["type"],
+        ],
+        "range" | "length" | "must" => vec![
+            &["error-message"],
+            &["error-app-tag"],
+            &["description"],
+            &["reference"],
+        ],
+        "pattern" => vec![
+            &["modifier"],
+            &["error-message"],
+            &["error-app-tag"],
+            &["description"],
+            &["reference"],
+        ],
+        "when" | "revision" => vec![&["description"], &["reference"]],
+        "enum" => vec![
+            &["if-feature"],
+            &["value"],
+            &["status"],
+            &["description"],
+            &["reference"],
+        ],
+        "bit" => vec![
+            &["if-feature"],
+            &["position"],
+            &["status"],
+            &["description"],
+            &["reference"],
+        ],
+        "import" => vec![
+            &["prefix"],
+            &["revision-date"],
+            &["description"],
+            &["reference"],
+        ],
+        "include" => vec![&["revision-date"], &["description"], &["reference"]],
+        "extension" => vec![&["argument"], &["status"], &["description"], &["reference"]],
+        "feature" => vec![
+            &["if-feature"],
+            &["status"],
+            &["description"],
+            &["reference"],
+        ],
+        "identity" => vec![
+            &["if-feature"],
+            &["base"],
+            &["status"],
+            &["description"],
+            &["reference"],
+        ],
+        "deviation" => vec![&["description"], &["reference"], &["deviate"]],
+        "deviate" => vec![
+            &["type"],
+            &["units"],
+            &["must"],
+            &["unique"],
+            &["default"],
+            &["config"],
+            &["mandatory"],
+            &["min-elements"],
+            &["max-elements"],
+        ],
+        _ => return None,
+    };
+    Some(order)
+}
+
+// Statements whose argument is text and always quoted.
+const QUOTED_KEYWORDS: &[&str] = &[
+    "contact",
+    "description",
+    "error-message",
+    "must",
+    "namespace",
+    "organization",
+    "pattern",
+    "presence",
+    "reference",
+    "when",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Quote {
+    None,
+    Single,
+    Double,
+}
+
+fn quote_style(keyword: &str, value: &str) -> Quote {
+    let needs_quote = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || ";{}\"'".contains(c))
+        || value.contains("//")
+        || value.contains("/*")
+        || value.contains("*/");
+    if !needs_quote && !QUOTED_KEYWORDS.contains(&keyword) {
+        Quote::None
+    } else if !value.contains('\'')
+        && !value.contains('\n')
+        && (value.contains('\\') || value.contains('"'))
+    {
+        // Patterns are easier to read without escaping.
+        Quote::Single
+    } else {
+        Quote::Double
+    }
+}
+
+// Line breaks are kept in the text unless a line has trailing whitespace,
+// which is stripped from double-quoted strings.
+fn literal_newlines(value: &str) -> bool {
+    let lines: Vec<&str> = value.split('\n').collect();
+    lines.len() > 1
+        && lines[..lines.len() - 1]
+            .iter()
+            .all(|line| !line.ends_with([' ', '\t']))
+}
+
+fn escape(value: &str, newlines: bool) -> String {
+    let mut out = String::new();
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\t' => out.push_str("\\t"),
+            '\n' if !newlines => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Split the text at spaces into chunks of at most "width" characters.
+fn split_chunks(text: &str, width: usize) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut chunk = String::new();
+    for word in text.split_inclusive(' ') {
+        if !chunk.is_empty() && chunk.len() + word.len() > width {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        chunk.push_str(word);
+    }
+    chunks.push(chunk);
+    chunks
+}
+
+fn write_comment(out: &mut String, comment: &str, indent: &str) {
+    for line in comment.split('\n') {
+        if !line.is_empty() {
+            out.push_str(indent);
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+}
+
+// Groups of the statements of a module which are separated by blank lines
+// when the group changes.
+fn module_group(keyword: &str) -> usize {
+    match keyword {
+        "yang-version" | "namespace" | "prefix" | "belongs-to" => 0,
+        "import" | "include" => 1,
+        "organization" | "contact" | "description" | "reference" => 2,
+        "revision" => 3,
+        _ => 4,
+    }
+}
+
+fn blank_line(prev: &Stmt, stmt: &Stmt) -> bool {
+    let group = module_group(&stmt.keyword);
+    group != module_group(&prev.keyword)
+        || group >= 2
+        || !prev.substmts.is_empty()
+        || !stmt.substmts.is_empty()
+}
+
+impl Stmt {
+    // Reorder the substatements to the canonical order.  Extension
+    // instances stay after the statement they follow.
+    pub fn canonicalize(&mut self) {
+        if let Some(order) = canonical_order(&self.keyword) {
+            let rank = |keyword: &str| {
+                order
+                    .iter()
+                    .position(|group| group.contains(&keyword))
+                    .unwrap_or(order.len())
+            };
+            let mut prev = 0;
+            let mut ranked: Vec<(usize, Stmt)> = self
+                .substmts
+                .drain(..)
+                .map(|s| {
+                    if !s.is_ext_instance() {
+                        prev = rank(&s.keyword);
+                    }
+                    (prev, s)
+                })
+                .collect();
+            ranked.sort_by_key(|(rank, _)| *rank);
+            self.substmts = ranked.into_iter().map(|(_, s)| s).collect();
+        }
+        for s in self.substmts.iter_mut() {
+            s.canonicalize();
+        }
+    }
+
+    // YANG text of the statement.
+    pub fn to_yang(&self, opts: &FormatOptions) -> String {
+        let mut out = String::new();
+        self.write(&mut out, 0, opts);
+        out
+    }
+
+    fn write(&self, out: &mut String, depth: usize, opts: &FormatOptions) {
+        let indent = " ".repeat(depth * opts.indent);
+        let inner = " ".repeat((depth + 1) * opts.indent);
+        for comment in self.comments.iter() {
+            write_comment(out, comment, &indent);
+        }
+        let block = !self.substmts.is_empty()
+            || !self.end_comments.is_empty()
+            || self.open_comment.is_some();
+        let mut end = if block { " {" } else { ";" };
+
+        out.push_str(&indent);
+        out.push_str(&self.keyword);
+        match &self.argument {
+            None => {}
+            // The strings of a concatenation with comments are not joined,
+            // each of them is on its own line followed by its comments.
+            Some(_) if !self.argument_parts.is_empty() => {
+                let mut line_start = false;
+                for (i, (part, comments)) in self.argument_parts.iter().enumerate() {
+                    if !line_start {
+                        out.push('\n');
+                    }
+                    out.push_str(&inner);
+                    if i > 0 {
+                        out.push_str("+ ");
+                    }
+                    match quote_style(&self.keyword, part) {
+                        Quote::Single => out.push_str(&format!("'{}'", part)),
+                        _ => out.push_str(&format!("\"{}\"", escape(part, false))),
+                    }
+                    line_start = !comments.is_empty();
+                    if line_start {
+                        out.push('\n');
+                        for comment in comments.iter() {
+                            write_comment(out, comment, &inner);
+                        }
+                    }
+                }
+                if line_start {
+                    out.push_str(&inner);
+                    end = end.trim_start();
+                }
+            }
+            Some(value) => match quote_style(&self.keyword, value) {
+                Quote::None => {
+                    out.push(' ');
+                    out.push_str(value);
+                }
+                _ if literal_newlines(value) => {
+                    // Continuation lines are indented to the column after
+                    // the opening quote, which is stripped when parsed.
+                    out.push('\n');
+                    out.push_str(&inner);
+                    out.push('"');
+                    for (i, line) in escape(value, true).split('\n').enumerate() {
+                        if i > 0 {
+                            out.push('\n');
+                            if !line.is_empty() {
+                                out.push_str(&inner);
+                                out.push(' ');
+                            }
+                        }
+                        out.push_str(line);
+                    }
+                    out.push('"');
+                }
+                quote => {
+                    let (q, text) = match quote {
+                        Quote::Single => ('\'', value.to_owned()),
+                        _ => ('"', escape(value, false)),
+                    };
+                    let quoted = format!("{}{}{}", q, text, q);
+                    if indent.len() + self.keyword.len() + quoted.len() + 1 + end.len()
+                        <= opts.line_width
+                    {
+                        out.push(' ');
+                        out.push_str(&quoted);
+                    } else if inner.len() + quoted.len() + end.len() <= opts.line_width {
+                        out.push('\n');
+                        out.push_str(&inner);
+                        out.push_str(&quoted);
+                    } else {
+                        // RFC7950 6.1.3.  Quoting
+                        //     A string can be split into multiple quoted
+                        //     strings concatenated with "+".
+                        let width = opts.line_width.saturating_sub(inner.len() + 5).max(20);
+                        for (i, chunk) in split_chunks(&text, width).iter().enumerate() {
+                            out.push('\n');
+                            out.push_str(&inner);
+                            if i > 0 {
+                                out.push_str("+ ");
+                            }
+                            out.push(q);
+                            out.push_str(chunk);
+                            out.push(q);
+                        }
+                    }
+                }
+            },
+        }
+        out.push_str(end);
+        let first = if block {
+            &self.open_comment
+        } else {
+            &self.line_comment
+        };
+        if let Some(comment) = first {
+            out.push(' ');
+            out.push_str(comment);
+        }
+        out.push('\n');
+        if !block {
+            return;
+        }
+
+        for (i, s) in self.substmts.iter().enumerate() {
+            if depth == 0 && i > 0 && blank_line(&self.substmts[i - 1], s) {
+                out.push('\n');
+            }
+            s.write(out, depth + 1, opts);
+        }
+        for comment in self.end_comments.iter() {
+            write_comment(out, comment, &inner);
+        }
+        out.push_str(&indent);
+        out.push('}');
+        if let Some(comment) = &self.line_comment {
+            out.push(' ');
+            out.push_str(comment);
+        }
+        out.push('\n');
+    }
+}
+
+// Format YANG text.  Comments are kept with the statements they precede,
+// or follow on the same line.
+pub fn format_yang(text: &str, opts: &FormatOptions) -> Result<String, StmtError> {
+    let mut stmt = stmt_parse(text)?;
+    if opts.canonical {
+        stmt.canonicalize();
+    }
+    Ok(stmt.to_yang(opts))
+}
+
+// Lines of the text which differ from the formatted text.  "line" is the
+// line number of the first line of "original", or of the line before
+// which "formatted" is inserted.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct FormatDiff {
+    pub line: usize,
+    pub original: Vec<String>,
+    pub formatted: Vec<String>,
+}
+
+impl fmt::Display for FormatDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "@@ line {} @@", self.line)?;
+        for line in self.original.iter() {
+            write!(f, "\n-{}", line)?;
+        }
+        for line in self.formatted.iter() {
+            write!(f, "\n+{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+// Line diff by the longest common subsequence.
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<FormatDiff> {
+    let prefix = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a = &a[prefix..a.len() - suffix];
+    let b = &b[prefix..b.len() - suffix];
+
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diffs: Vec<FormatDiff> = vec![];
+    let (mut i, mut j) = (0, 0);
+    let mut hunk: Option<FormatDiff> = None;
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            diffs.extend(hunk.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let h = hunk.get_or_insert_with(|| FormatDiff::new(prefix + i + 1, vec![], vec![]));
+        if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            h.original.push(a[i].to_owned());
+            i += 1;
+        } else {
+            h.formatted.push(b[j].to_owned());
+            j += 1;
+        }
+    }
+    diffs.extend(hunk);
+    diffs
+}
+
+// Check mode of the formatter.  Empty when the text is formatted.
+pub fn check_format(text: &str, opts: &FormatOptions) -> Result<Vec<FormatDiff>, StmtError> {
+    let formatted = format_yang(text, opts)?;
+    let original: Vec<&str> = text.lines().collect();
+    let formatted: Vec<&str> = formatted.lines().collect();
+    Ok(diff_lines(&original, &formatted))
+}
+
+// Text of multi-line strings of the parsed model keeps the indentation of
+// the module file.  Strip the common indentation of the lines after the
+// first one and the trailing whitespace of the lines.
+fn dedent(value: &str) -> String {
+    let lines: Vec<&str> = value.split('\n').map(|l| l.trim_end()).collect();
+    if lines.len() == 1 {
+        return value.to_owned();
+    }
+    let indent = lines[1..]
+        .iter()
+        .filter(|l| !l.is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut out = lines[0].to_owned();
+    for line in lines[1..].iter() {
+        out.push('\n');
+        out.push_str(line.get(indent..).unwrap_or_default());
+    }
+    out
+}
+
+fn stmt(keyword: &str, value: &str) -> Stmt {
+    Stmt::arg(keyword, &dedent(value))
+}
+
+fn push_opt<T: AsRef<str>>(stmts: &mut Vec<Stmt>, keyword: &str, value: &Option<T>) {
+    if let Some(v) = value {
+        stmts.push(stmt(keyword, v.as_ref()));
+    }
+}
+
+fn push_bool(stmts: &mut Vec<Stmt>, keyword: &str, value: Option<bool>) {
+    if let Some(v) = value {
+        stmts.push(Stmt::arg(keyword, if v { "true" } else { "false" }));
+    }
+}
+
+fn push_elements(stmts: &mut Vec<Stmt>, min: Option<u64>, max: Option<u64>) {
+    if let Some(v) = min {
+        stmts.push(Stmt::arg("min-elements", &v.to_string()));
+    }
+    if let Some(v) = max {
+        let v = if v == u64::MAX {
+            String::from("unbounded")
+        } else {
+            v.to_string()
+        };
+        stmts.push(Stmt::arg("max-elements", &v));
+    }
+}
+
+fn push_all(stmts: &mut Vec<Stmt>, keyword: &str, values: &[String]) {
+    stmts.extend(values.iter().map(|v| stmt(keyword, v)));
+}
+
+fn ext_stmt(ext: &ExtInstanceNode) -> Stmt {
+    let mut s = Stmt::new(ext.name(), ext.argument.as_deref().map(dedent));
+    s.substmts = ext.nodes.iter().map(ext_stmt).collect();
+    s
+}
+
+fn push_exts(stmts: &mut Vec<Stmt>, exts: &[ExtInstanceNode]) {
+    stmts.extend(exts.iter().map(ext_stmt));
+}
+
+fn block(keyword: &str, name: &str, substmts: Vec<Stmt>) -> Stmt {
+    let mut s = stmt(keyword, name);
+    s.substmts = substmts;
+    s
+}
+
+fn must_stmt(must: &MustNode) -> Stmt {
+    let mut subs = vec![];
+    push_opt(&mut subs, "error-message", &must.error_message);
+    push_opt(&mut subs, "error-app-tag", &must.error_app_tag);
+    push_opt(&mut subs, "description", &must.description);
+    push_opt(&mut subs, "reference", &must.reference);
+    push_exts(&mut subs, &must.ext_instances);
+    block("must", &must.name, subs)
+}
+
+fn when_stmt(when: &WhenNode) -> Stmt {
+    let mut subs = vec![];
+    push_opt(&mut subs, "description", &when.description);
+    push_opt(&mut subs, "reference", &when.reference);
+    push_exts(&mut subs, &when.ext_instances);
+    block("when", &when.name, subs)
+}
+
+// Common statements of the data definition statements.
+fn push_data_def(
+    stmts: &mut Vec<Stmt>,
+    when: &Option<WhenNode>,
+    if_features: &[String],
+    must: &[MustNode],
+) {
+    stmts.extend(when.iter().map(when_stmt));
+    push_all(stmts, "if-feature", if_features);
+    stmts.extend(must.iter().map(must_stmt));
+}
+
+fn push_meta(
+    stmts: &mut Vec<Stmt>,
+    status: &Option<String>,
+    description: &Option<String>,
+    reference: &Option<String>,
+) {
+    push_opt(stmts, "status", status);
+    push_opt(stmts, "description", description);
+    push_opt(stmts, "reference", reference);
+}
+
+fn enum_stmt(e: &EnumNode) -> Stmt {
+    let mut subs = vec![];
+    for node in e.nodes.0.iter() {
+        match node {
+            Node::IfFeature(n) => subs.push(stmt("if-feature", &n.name)),
+            Node::ValueNode(n) => subs.push(stmt("value", &n.name)),
+            Node::Status(n) => subs.push(stmt("status", &n.name)),
+            Node::Description(n) => subs.push(stmt("description", &n.name)),
+            Node::Reference(n) => subs.push(stmt("reference", &n.name)),
+            Node::ExtInstance(n) => subs.push(ext_stmt(n)),
+            _ => {}
+        }
+    }
+    block("enum", &e.name, subs)
+}
+
+fn type_stmt(typ: &TypeNode) -> Stmt {
+    let mut subs = vec![];
+    if let Some(v) = typ.fraction_digits {
+        subs.push(Stmt::arg("fraction-digits", &v.to_string()));
+    }
+    push_opt(&mut subs, "range", &typ.range);
+    push_opt(&mut subs, "length", &typ.length);
+    for pattern in typ.patterns.iter() {
+        let mut modifier = vec![];
+        if pattern.invert_match {
+            modifier.push(Stmt::arg("modifier", "invert-match"));
+        }
+        subs.push(block("pattern", &pattern.pattern, modifier));
+    }
+    subs.extend(typ.enums.iter().map(enum_stmt));
+    for bit in typ.bits.iter() {
+        let mut bit_subs = vec![];
+        if let Some(v) = bit.position {
+            bit_subs.push(Stmt::arg("position", &v.to_string()));
+        }
+        push_opt(&mut bit_subs, "description", &bit.description);
+        subs.push(block("bit", &bit.name, bit_subs));
+    }
+    push_opt(&mut subs, "path", &typ.path);
+    push_bool(&mut subs, "require-instance", typ.require_instance);
+    push_all(&mut subs, "base", &typ.bases);
+    subs.extend(typ.union.iter().map(type_stmt));
+    push_exts(&mut subs, &typ.ext_instances);
+    block("type", &typ.name, subs)
+}
+
+fn node_stmt(node: &Node) -> Option<Stmt> {
+    let mut subs = vec![];
+    let s = match node {
+        Node::Container(n) => {
+            push_data_def(&mut subs, &n.when, &n.if_features, &n.must);
+            push_opt(&mut subs, "presence", &n.presence);
+            push_bool(&mut subs, "config", n.config);
+            push_meta(&mut subs, &n.status, &n.description, &n.reference);
+            push_exts(&mut subs, &n.ext_instances);
+            subs.extend(n.nodes.iter().filter_map(node_stmt));
+            block("container", &n.name, subs)
+        }
+        Node::List(n) => {
+            push_data_def(&mut subs, &n.when, &n.if_features, &n.must);
+            push_opt(&mut subs, "key", &n.key);
+            push_all(&mut subs, "unique", &n.unique);
+            push_bool(&mut subs, "config", n.config);
+            push_elements(&mut subs, n.min_elements, n.max_elements);
+            push_opt(&mut subs, "ordered-by", &n.ordered_by);
+            push_meta(&mut subs, &n.status, &n.description, &n.reference);
+            push_exts(&mut subs, &n.ext_instances);
+            subs.extend(n.nodes.iter().filter_map(node_stmt));
+            block("list", &n.name, subs)
+        }
+        Node::Leaf(n) => {
+            push_data_def(&mut subs, &n.when, &n.if_features, &n.must);
+            subs.extend(n.typ.iter().map(type_stmt));
+            push_opt(&mut subs, "units", &n.units);
+            push_opt(&mut subs, "default", &n.default);
+            push_bool(&mut subs, "config", n.config);
+            push_bool(&mut subs, "mandatory", n.mandatory);
+            push_meta(&mut subs, &n.status, &n.description, &n.reference);
+            push_exts(&mut subs, &n.ext_instances);
+            block("leaf", &n.name, subs)
+        }
+        Node::LeafList(n) => {
+            push_data_def(&mut subs, &n.when, &n.if_features, &n.must);
+            subs.extend(n.typ.iter().map(type_stmt));
+            push_opt(&mut subs, "units", &n.units);
+            push_all(&mut subs, "default", &n.default);
+            push_bool(&mut subs, "config", n.config);
+            push_elements(&mut subs, n.min_elements, n.max_elements);
+            push_opt(&mut subs, "ordered-by", &n.ordered_by);
+            push_meta(&mut subs, &n.status, &n.description, &n.reference);
+            push_exts(&mut subs, &n.ext_instances);
+            block("leaf-list", &n.name, subs)
+        }
+        Node::Anydata(n) => {
+            push_data_def(&mut subs, &n.when, &n.if_features, &n.must);
+            push_bool(&mut subs, "config", n.config);
+            push_bool(&mut subs, "mandatory", n.mandatory);
+            push_meta(&mut subs, &n.status, &n.description, &n.reference);
+            push_exts(&mut subs, &n.ext_instances);
+            let keyword = if n.anyxml { "anyxml" } else { "anydata" };
+            block(keyword, &n.name, subs)
+        }
+        Node::Choice(n) => {
+            push_data_def(&mut subs, &n.when, &n.if_features, &[]);
+            push_opt(&mut subs, "default", &n.default);
+            push_bool(&mut subs, "config", n.config);
+            push_bool(&mut subs, "mandatory", n.mandatory);
+            push_meta(&mut subs, &n.status, &n.description, &n.reference);
+            push_exts(&mut subs, &n.ext_instances);
+            subs.extend(n.nodes.iter().filter_map(node_stmt));
+            block("choice", &n.name, subs)
+        }
+        Node::Case(n) => {
+            push_data_def(&mut subs, &n.when, &n.if_features, &[]);
+            push_meta(&mut subs, &n.status, &n.description, &n.reference);
+            push_exts(&mut subs, &n.ext_instances);
+            subs.extend(n.nodes.iter().filter_map(node_stmt));
+            block("case", &n.name, subs)
+        }
+        Node::Grouping(n) => grouping_stmt(n),
+        Node::Uses(n) => {
+            push_data_def(&mut subs, &n.when, &n.if_features, &[]);
+            push_meta(&mut subs, &n.status, &n.description, &n.reference);
+            subs.extend(n.refines.iter().map(refine_stmt));
+            push_exts(&mut subs, &n.ext_instances);
+            block("uses", &n.name, subs)
+        }
+        _ => return None,
+    };
+    Some(s)
+}
+
+fn grouping_stmt(grouping: &GroupingNode) -> Stmt {
+    let mut subs = vec![];
+    push_meta(
+        &mut subs,
+        &grouping.status,
+        &grouping.description,
+        &grouping.reference,
+    );
+    push_exts(&mut subs, &grouping.ext_instances);
+    subs.extend(grouping.nodes.iter().filter_map(node_stmt));
+    block("grouping", &grouping.name, subs)
+}
+
+fn refine_stmt(refine: &RefineNode) -> Stmt {
+    let mut subs = vec![];
+    push_all(&mut subs, "if-feature", &refine.if_features);
+    subs.extend(refine.must.iter().map(must_stmt));
+    push_opt(&mut subs, "presence", &refine.presence);
+    push_all(&mut subs, "default", &refine.default);
+    push_bool(&mut subs, "config", refine.config);
+    push_bool(&mut subs, "mandatory", refine.mandatory);
+    push_elements(&mut subs, refine.min_elements, refine.max_elements);
+    push_opt(&mut subs, "description", &refine.description);
+    push_opt(&mut subs, "reference", &refine.reference);
+    push_exts(&mut subs, &refine.ext_instances);
+    block("refine", &refine.name, subs)
+}
+
+fn deviation_stmt(deviation: &DeviationNode) -> Stmt {
+    let mut subs = vec![];
+    push_opt(&mut subs, "description", &deviation.description);
+    push_opt(&mut subs, "reference", &deviation.reference);
+    for deviate in deviation.deviates.iter() {
+        let mut d = vec![];
+        d.extend(deviate.typ.iter().map(type_stmt));
+        push_opt(&mut d, "units", &deviate.units);
+        d.extend(deviate.must.iter().map(must_stmt));
+        push_all(&mut d, "unique", &deviate.unique);
+        push_all(&mut d, "default", &deviate.default);
+        push_bool(&mut d, "config", deviate.config);
+        push_bool(&mut d, "mandatory", deviate.mandatory);
+        push_elements(&mut d, deviate.min_elements, deviate.max_elements);
+        subs.push(block("deviate", deviate.kind.as_str(), d));
+    }
+    push_exts(&mut subs, &deviation.ext_instances);
+    block("deviation", &deviation.name, subs)
+}
+
+fn sorted<T>(map: &HashMap<String, T>) -> Vec<&T> {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    keys.into_iter().map(|k| &map[k]).collect()
+}
+
+impl Module {
+    // Statement tree of the parsed module.  Definitions kept in maps are
+    // sorted by name.
+    pub fn to_stmt(&self) -> Stmt {
+        let mut subs = vec![];
+        if let YangVersion::V1_1 = self.yang_version {
+            subs.push(Stmt::arg("yang-version", "1.1"));
+        }
+        let keyword = match &self.belongs_to {
+            Some(module) => {
+                let prefix = vec![Stmt::arg("prefix", &self.prefix)];
+                subs.push(block("belongs-to", module, prefix));
+                "submodule"
+            }
+            None => {
+                subs.push(stmt("namespace", &self.namespace));
+                subs.push(Stmt::arg("prefix", &self.prefix));
+                "module"
+            }
+        };
+        for import in self.imports.iter() {
+            let mut s = vec![Stmt::arg("prefix", &import.prefix)];
+            if !import.revision_date.is_empty() {
+                s.push(Stmt::arg("revision-date", &import.revision_date));
+            }
+            push_opt(&mut s, "description", &import.description);
+            push_opt(&mut s, "reference", &import.reference);
+            push_exts(&mut s, &import.ext_instances);
+            subs.push(block("import", &import.name, s));
+        }
+        for include in self.includes.iter() {
+            let mut s = vec![];
+            if !include.revision_date.is_empty() {
+                s.push(Stmt::arg("revision-date", &include.revision_date));
+            }
+            push_opt(&mut s, "description", &include.description);
+            push_opt(&mut s, "reference", &include.reference);
+            subs.push(block("include", &include.name, s));
+        }
+        push_opt(&mut subs, "organization", &self.organization);
+        push_opt(&mut subs, "contact", &self.contact);
+        push_opt(&mut subs, "description", &self.description);
+        for revision in self.revisions.iter() {
+            let mut s = vec![];
+            push_opt(&mut s, "description", &revision.description);
+            push_opt(&mut s, "reference", &revision.reference);
+            push_exts(&mut s, &revision.ext_instances);
+            subs.push(block("revision", &revision.name, s));
+        }
+        push_exts(&mut subs, &self.ext_instances);
+
+        for ext in sorted(&self.extensions) {
+            let mut s = vec![];
+            if let Some(arg) = &ext.argument {
+                let mut yin = vec![];
+                push_bool(&mut yin, "yin-element", arg.yin_element);
+                s.push(block("argument", &arg.name, yin));
+            }
+            push_meta(&mut s, &ext.status, &ext.description, &ext.reference);
+            push_exts(&mut s, &ext.ext_instances);
+            subs.push(block("extension", &ext.name, s));
+        }
+        for feature in sorted(&self.features) {
+            let mut s = vec![];
+            push_all(&mut s, "if-feature", &feature.if_features);
+            push_meta(
+                &mut s,
+                &feature.status,
+                &feature.description,
+                &feature.reference,
+            );
+            push_exts(&mut s, &feature.ext_instances);
+            subs.push(block("feature", &feature.name, s));
+        }
+        for identity in sorted(&self.identities) {
+            let mut s = vec![];
+            push_all(&mut s, "if-feature", &identity.if_features);
+            push_all(&mut s, "base", &identity.bases);
+            push_meta(
+                &mut s,
+                &identity.status,
+                &identity.description,
+                &identity.reference,
+            );
+            push_exts(&mut s, &identity.ext_instances);
+            subs.push(block("identity", &identity.name, s));
+        }
+        for typedef in sorted(&self.typedefs) {
+            let mut s = vec![];
+            s.extend(typedef.typ.iter().map(type_stmt));
+            push_opt(&mut s, "units", &typedef.units);
+            push_opt(&mut s, "default", &typedef.default);
+            push_meta(
+                &mut s,
+                &typedef.status,
+                &typedef.description,
+                &typedef.reference,
+            );
+            push_exts(&mut s, &typedef.ext_instances);
+            subs.push(block("typedef", &typedef.name, s));
+        }
+        subs.extend(sorted(&self.groupings).into_iter().map(grouping_stmt));
+        subs.extend(self.nodes.iter().filter_map(node_stmt));
+        subs.extend(self.deviations.iter().map(deviation_stmt));
+        block(keyword, &self.name, subs)
+    }
+
+    // YANG text of the parsed module.
+    pub fn to_yang(&self, opts: &FormatOptions) -> String {
+        let mut stmt = self.to_stmt();
+        if opts.canonical {
+            stmt.canonicalize();
+        }
+        stmt.to_yang(opts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_yang_test() {
+        let literal = "module example { prefix ex;
+namespace \"urn:example\";
+  // Leaf.
+  leaf a { description \"A\n   leaf\"; type string { pattern '\\d+'; } }
+  /* End. */ }";
+        let opts = FormatOptions::default();
+        let formatted = format_yang(literal, &opts).unwrap();
+        assert_eq!(
+            formatted,
+            "module example {
+  namespace \"urn:example\";
+  prefix ex;
+
+  // Leaf.
+  leaf a {
+    type string {
+      pattern '\\d+';
+    }
+    description
+      \"A
+       leaf\";
+  }
+  /* End. */
+}
+"
+        );
+        assert_eq!(format_yang(&formatted, &opts).unwrap(), formatted);
+        assert_eq!(check_format(&formatted, &opts), Ok(vec![]));
+
+        // Long strings are wrapped.
+        let opts = FormatOptions {
+            line_width: 30,
+            ..Default::default()
+        };
+        let literal = "module a { description \"one two three four five six seven\"; }";
+        assert_eq!(
+            format_yang(literal, &opts).unwrap(),
+            "module a {\n  description\n    \"one two three four \"\n    + \"five six seven\";\n}\n"
+        );
+    }
+
+    #[test]
+    fn diff_lines_test() {
+        let diffs = diff_lines(&["a", "b", "c", "d"], &["a", "x", "c", "d", "e"]);
+        assert_eq!(
+            diffs,
+            vec![
+                FormatDiff::new(2, vec![String::from("b")], vec![String::from("x")]),
+                FormatDiff::new(5, vec![], vec![String::from("e")]),
+            ]
+        );
+        assert_eq!(diffs[0].to_string(), "@@ line 2 @@\n-b\n+x");
+    }
+}
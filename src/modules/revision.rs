@@ -0,0 +1,163 @@
+use super::*;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+// RFC7950 7.1.9.  The "revision" Statement
+//     The argument is a date string in the format "YYYY-MM-DD".
+//
+// Dates are ordered chronologically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RevisionDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum RevisionError {
+    #[error("Invalid revision date {0}")]
+    InvalidDate(String),
+    #[error("Revision {1} is listed before newer revision {2} in {0}")]
+    Order(String, String, String),
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl RevisionDate {
+    // Date of the calendar, None for a date such as 2021-02-29.
+    pub fn from_ymd(year: u16, month: u8, day: u8) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+}
+
+impl FromStr for RevisionDate {
+    type Err = RevisionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RevisionError::InvalidDate(s.to_owned());
+        let bytes = s.as_bytes();
+        let digits = |range: std::ops::Range<usize>| bytes[range].iter().all(u8::is_ascii_digit);
+        if bytes.len() != 10
+            || bytes[4] != b'-'
+            || bytes[7] != b'-'
+            || !digits(0..4)
+            || !digits(5..7)
+            || !digits(8..10)
+        {
+            return Err(invalid());
+        }
+        let year = s[0..4].parse().map_err(|_| invalid())?;
+        let month = s[5..7].parse().map_err(|_| invalid())?;
+        let day = s[8..10].parse().map_err(|_| invalid())?;
+        RevisionDate::from_ymd(year, month, day).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for RevisionDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl RevisionNode {
+    pub fn date(&self) -> Option<RevisionDate> {
+        self.name.parse().ok()
+    }
+}
+
+impl Module {
+    // The most recent revision by the dates of the revision statements.
+    pub fn revision_date(&self) -> Option<RevisionDate> {
+        self.revisions.iter().filter_map(|r| r.date()).max()
+    }
+
+    // RFC7950 7.1.9.  The "revision" Statement
+    //     The revision statements SHOULD be listed in reverse chronological
+    //     order with the most recent revision listed first.
+    pub fn check_revisions(&self) -> Result<(), RevisionError> {
+        let mut dates = vec![];
+        for revision in self.revisions.iter() {
+            let date = revision
+                .date()
+                .ok_or_else(|| RevisionError::InvalidDate(revision.name.to_owned()))?;
+            dates.push(date);
+        }
+        for pair in dates.windows(2) {
+            if pair[0] < pair[1] {
+                return Err(RevisionError::Order(
+                    self.name.to_owned(),
+                    pair[0].to_string(),
+                    pair[1].to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revision_date_test() {
+        let date: RevisionDate = "2020-02-29".parse().unwrap();
+        assert_eq!(date, RevisionDate::from_ymd(2020, 2, 29).unwrap());
+        assert_eq!(date.to_string(), "2020-02-29");
+        assert!(date < "2020-03-01".parse().unwrap());
+        assert!("2000-02-29".parse::<RevisionDate>().is_ok());
+        for s in &[
+            "2021-02-29",
+            "1900-02-29",
+            "2020-04-31",
+            "2020-13-01",
+            "2020-00-10",
+            "2020-01-00",
+            "2020-1-01",
+            "20200101",
+            "2020-01-01x",
+        ] {
+            assert_eq!(
+                s.parse::<RevisionDate>(),
+                Err(RevisionError::InvalidDate(s.to_string())),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn check_revisions_test() {
+        let mut module = Module::new(String::from("m"));
+        module.revisions = vec![
+            RevisionNode::new(String::from("2021-01-01")),
+            RevisionNode::new(String::from("2020-06-01")),
+        ];
+        assert!(module.check_revisions().is_ok());
+        module
+            .revisions
+            .push(RevisionNode::new(String::from("2022-01-01")));
+        assert_eq!(
+            module.check_revisions(),
+            Err(RevisionError::Order(
+                String::from("m"),
+                String::from("2020-06-01"),
+                String::from("2022-01-01")
+            ))
+        );
+        assert_eq!(module.revision(), Some("2022-01-01"));
+    }
+}
@@ -0,0 +1,236 @@
+use super::yang::{header_module_name, text_revision};
+use super::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Text of the module found in a source.  "location" is the file path, or
+// the file name in the memory source.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct ModuleText {
+    pub location: PathBuf,
+    pub text: String,
+    // Warnings about the files looked up to find the text.
+    #[new(default)]
+    pub warnings: Vec<ModuleWarning>,
+}
+
+// Source of YANG module text.  "name" is the module name or
+// "name@revision-date".  Locations looked up without success are appended
+// to "tried" for the error message.
+pub trait ModuleSource {
+    fn find(&self, name: &str, tried: &mut Vec<PathBuf>) -> Result<Option<ModuleText>, FindError>;
+}
+
+// Filesystem source of the load paths.
+impl ModuleSource for Yang {
+    fn find(&self, name: &str, tried: &mut Vec<PathBuf>) -> Result<Option<ModuleText>, FindError> {
+        let mut warnings = vec![];
+        match self.locate(name, &mut warnings) {
+            Ok(path) => match fs::read_to_string(&path) {
+                Ok(text) => Ok(Some(ModuleText {
+                    location: path,
+                    text,
+                    warnings,
+                })),
+                Err(e) => Err(FindError::Io(path, e)),
+            },
+            Err(FindError::NotFound(_, paths)) => {
+                tried.extend(paths);
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+// Callback source which returns the text of "name".
+impl<F> ModuleSource for F
+where
+    F: Fn(&str) -> Option<String>,
+{
+    fn find(&self, name: &str, tried: &mut Vec<PathBuf>) -> Result<Option<ModuleText>, FindError> {
+        match self(name) {
+            Some(text) => Ok(Some(ModuleText::new(PathBuf::from(name), text))),
+            None => {
+                tried.push(PathBuf::from(name));
+                Ok(None)
+            }
+        }
+    }
+}
+
+// In-memory source of YANG files such as ones embedded by include_str!()
+// or read from an archive.  Files are keyed by the file name
+// "name.yang" or "name@revision-date.yang".
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemorySource {
+    files: BTreeMap<String, String>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Insert the text of the file, ".yang" is appended to the file name
+    // when it doesn't have it.
+    pub fn insert(&mut self, file_name: &str, text: &str) {
+        let base = file_name.trim_end_matches(".yang");
+        self.files.insert(format!("{}.yang", base), text.to_owned());
+    }
+
+    // Insert the text by the module name and the most recent revision in
+    // it.  Returns the file name, None when the text is not a module or
+    // submodule.
+    pub fn add(&mut self, text: &str) -> Option<String> {
+        let name = header_module_name(text)?;
+        let file_name = match text_revision(Path::new(name), Some(text), &mut vec![]) {
+            Some(revision) => format!("{}@{}.yang", name, revision),
+            None => format!("{}.yang", name),
+        };
+        self.files.insert(file_name.clone(), text.to_owned());
+        Some(file_name)
+    }
+
+    pub fn file_names(&self) -> impl Iterator<Item = &String> {
+        self.files.keys()
+    }
+
+    // Read all of ".yang" files in the tar archive.  Directories of the
+    // files are ignored.
+    #[cfg(feature = "tar")]
+    pub fn from_tar<R: std::io::Read>(reader: R) -> Result<Self, std::io::Error> {
+        let mut source = Self::new();
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.into_owned();
+            if let Some(file_name) = yang_file_name(&path) {
+                let mut text = String::new();
+                std::io::Read::read_to_string(&mut entry, &mut text)?;
+                source.files.insert(file_name, text);
+            }
+        }
+        Ok(source)
+    }
+
+    // Read all of ".yang" files in the zip archive.  Directories of the
+    // files are ignored.
+    #[cfg(feature = "zip")]
+    pub fn from_zip<R: std::io::Read + std::io::Seek>(reader: R) -> Result<Self, std::io::Error> {
+        let mut source = Self::new();
+        let mut archive = zip::ZipArchive::new(reader)?;
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            let file_name = match file.enclosed_name().and_then(yang_file_name) {
+                Some(file_name) => file_name,
+                None => continue,
+            };
+            let mut text = String::new();
+            std::io::Read::read_to_string(&mut file, &mut text)?;
+            source.files.insert(file_name, text);
+        }
+        Ok(source)
+    }
+}
+
+#[cfg(any(feature = "tar", feature = "zip"))]
+fn yang_file_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    if file_name.ends_with(".yang") {
+        Some(file_name.to_owned())
+    } else {
+        None
+    }
+}
+
+impl ModuleSource for MemorySource {
+    // Exact match, then the latest of "name@revision-date.yang" files by
+    // their revision statements.
+    fn find(&self, name: &str, tried: &mut Vec<PathBuf>) -> Result<Option<ModuleText>, FindError> {
+        let base = name.trim_end_matches(".yang");
+        let file_name = format!("{}.yang", base);
+        if let Some(text) = self.files.get(&file_name) {
+            return Ok(Some(ModuleText::new(
+                PathBuf::from(file_name),
+                text.clone(),
+            )));
+        }
+        tried.push(PathBuf::from(file_name));
+        if base.contains('@') {
+            return Ok(None);
+        }
+        let prefix = format!("{}@", base);
+        let mut warnings = vec![];
+        let latest = self
+            .files
+            .iter()
+            .filter(|(f, _)| f.starts_with(&prefix))
+            .max_by_key(|(f, text)| text_revision(Path::new(f), Some(text), &mut warnings));
+        Ok(latest.map(|(f, text)| ModuleText {
+            location: PathBuf::from(f),
+            text: text.clone(),
+            warnings,
+        }))
+    }
+}
+
+// Sources consulted in the order they are added.  The first source which
+// has the module is used.
+#[derive(Default)]
+pub struct ModuleSources {
+    sources: Vec<Box<dyn ModuleSource>>,
+}
+
+impl ModuleSources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<S: ModuleSource + 'static>(&mut self, source: S) {
+        self.sources.push(Box::new(source));
+    }
+}
+
+impl ModuleSource for ModuleSources {
+    fn find(&self, name: &str, tried: &mut Vec<PathBuf>) -> Result<Option<ModuleText>, FindError> {
+        for source in self.sources.iter() {
+            if let Some(text) = source.find(name, tried)? {
+                return Ok(Some(text));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_source_test() {
+        let mut source = MemorySource::new();
+        source.insert("a", "module a { }");
+        assert_eq!(
+            source.add("/* b */ module b { revision 2021-01-01; }"),
+            Some(String::from("b@2021-01-01.yang"))
+        );
+        // File name is older than the revision statement.
+        source.insert("b@2020-06-01.yang", "module b { revision 2022-01-01; }");
+        assert_eq!(source.add("container c;"), None);
+
+        let mut tried = vec![];
+        let found = source.find("a", &mut tried).unwrap().unwrap();
+        assert_eq!(found.location, PathBuf::from("a.yang"));
+        let found = source.find("b", &mut tried).unwrap().unwrap();
+        assert_eq!(found.location, PathBuf::from("b@2020-06-01.yang"));
+        let found = source.find("b@2021-01-01", &mut tried).unwrap().unwrap();
+        assert_eq!(found.text, "/* b */ module b { revision 2021-01-01; }");
+        assert!(source.find("b@2019-01-01", &mut tried).unwrap().is_none());
+        assert_eq!(
+            tried,
+            vec![PathBuf::from("b.yang"), PathBuf::from("b@2019-01-01.yang")]
+        );
+    }
+}
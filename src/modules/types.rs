@@ -1,6 +1,8 @@
+use crate::modules::ExtInstanceNode;
 use crate::Node;
+use std::fmt;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TypeKind {
     Ynone,
     Yint8,
@@ -11,15 +13,79 @@ pub enum TypeKind {
     Yuint16,
     Yuint32,
     Yuint64,
-    // Yenum,
+    Ydecimal64,
+    Ystring,
+    Yboolean,
+    Yenumeration,
+    Ybits,
+    Ybinary,
+    Yleafref,
+    Yidentityref,
+    Yempty,
+    Yunion,
+    YinstanceIdentifier,
 }
 
-#[derive(Debug, PartialEq)]
+impl TypeKind {
+    // Built-in type kind of type name.  Names which are not built-in type
+    // (typedef reference) return Ynone.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "int8" => TypeKind::Yint8,
+            "int16" => TypeKind::Yint16,
+            "int32" => TypeKind::Yint32,
+            "int64" => TypeKind::Yint64,
+            "uint8" => TypeKind::Yuint8,
+            "uint16" => TypeKind::Yuint16,
+            "uint32" => TypeKind::Yuint32,
+            "uint64" => TypeKind::Yuint64,
+            "decimal64" => TypeKind::Ydecimal64,
+            "string" => TypeKind::Ystring,
+            "boolean" => TypeKind::Yboolean,
+            "enumeration" => TypeKind::Yenumeration,
+            "bits" => TypeKind::Ybits,
+            "binary" => TypeKind::Ybinary,
+            "leafref" => TypeKind::Yleafref,
+            "identityref" => TypeKind::Yidentityref,
+            "empty" => TypeKind::Yempty,
+            "union" => TypeKind::Yunion,
+            "instance-identifier" => TypeKind::YinstanceIdentifier,
+            _ => TypeKind::Ynone,
+        }
+    }
+
+    pub fn is_int(&self) -> bool {
+        matches!(
+            self,
+            TypeKind::Yint8 | TypeKind::Yint16 | TypeKind::Yint32 | TypeKind::Yint64
+        )
+    }
+
+    pub fn is_uint(&self) -> bool {
+        matches!(
+            self,
+            TypeKind::Yuint8 | TypeKind::Yuint16 | TypeKind::Yuint32 | TypeKind::Yuint64
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct TypeNode {
     pub kind: TypeKind,
     pub name: String,
     pub range_int: Option<Vec<RangeInt>>,
     pub range_uint: Option<Vec<RangeUint>>,
+    pub range: Option<String>,
+    pub length: Option<String>,
+    pub patterns: Vec<PatternNode>,
+    pub path: Option<String>,
+    pub enums: Vec<EnumNode>,
+    pub bits: Vec<BitNode>,
+    pub bases: Vec<String>,
+    pub union: Vec<TypeNode>,
+    pub fraction_digits: Option<u8>,
+    pub require_instance: Option<bool>,
+    pub ext_instances: Vec<ExtInstanceNode>,
 }
 
 impl Default for TypeNode {
@@ -29,6 +95,17 @@ impl Default for TypeNode {
             kind: TypeKind::Ynone,
             range_int: None,
             range_uint: None,
+            range: None,
+            length: None,
+            patterns: vec![],
+            path: None,
+            enums: vec![],
+            bits: vec![],
+            bases: vec![],
+            union: vec![],
+            fraction_digits: None,
+            require_instance: None,
+            ext_instances: vec![],
         }
     }
 }
@@ -36,7 +113,7 @@ impl Default for TypeNode {
 impl TypeNode {
     pub fn new(kind: TypeKind) -> Self {
         TypeNode {
-            kind: kind,
+            kind,
             ..Default::default()
         }
     }
@@ -44,22 +121,54 @@ impl TypeNode {
     pub fn match_with(_str: &str) {}
 }
 
-#[derive(Debug, Default, PartialEq)]
+// RFC7950 9.4.6.  The "modifier" Statement
+//     If a pattern has the "invert-match" modifier present, the type is
+//     restricted to values that do not match the pattern.
+#[derive(Debug, Clone, Default, PartialEq, new)]
+pub struct PatternNode {
+    pub pattern: String,
+    #[new(default)]
+    pub invert_match: bool,
+}
+
+impl fmt::Display for PatternNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.invert_match {
+            write!(f, "{} (invert-match)", self.pattern)
+        } else {
+            write!(f, "{}", self.pattern)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct ModifierNode {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct TypedefNode {
     pub name: String,
-    pub typ: Option<Node>,
+    pub typ: Option<TypeNode>,
+    pub default: Option<String>,
+    pub units: Option<String>,
+    pub description: Option<String>,
+    pub reference: Option<String>,
+    pub status: Option<String>,
+    pub ext_instances: Vec<ExtInstanceNode>,
 }
 
 impl TypedefNode {
-    pub fn new(name: String, typ: Option<Node>) -> Self {
+    pub fn new(name: String, typ: Option<TypeNode>) -> Self {
         TypedefNode {
-            name: name,
-            typ: typ,
+            name,
+            typ,
+            ..Default::default()
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ValueNode {
     pub name: String,
     pub nodes: (),
@@ -70,27 +179,46 @@ pub struct Uint8Node {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EnumNode {
     pub name: String,
     pub nodes: (Vec<Node>,),
 }
 
-#[derive(Debug, PartialEq)]
+impl EnumNode {
+    // Value of "value" substatement when it exists.
+    pub fn value(&self) -> Option<i64> {
+        self.nodes.0.iter().find_map(|n| match n {
+            Node::ValueNode(v) => v.name.parse::<i64>().ok(),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct BitNode {
+    pub name: String,
+    #[new(default)]
+    pub position: Option<u32>,
+    #[new(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum IntVal {
     Min,
     Max,
     Val(i64),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum UintVal {
     Min,
     Max,
     Val(u64),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RangeVal<T> {
     Min,
     Max,
@@ -119,7 +247,7 @@ mod tests {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Range<T> {
     pub start: RangeVal<T>,
     pub end: RangeVal<T>,
@@ -128,19 +256,19 @@ pub struct Range<T> {
 pub type RangeInt = Range<i64>;
 pub type RangeUint = Range<u64>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RangeIntNode {
     pub name: String,
     pub nodes: (Vec<RangeInt>,),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RangeUintNode {
     pub name: String,
     pub nodes: (Vec<RangeUint>,),
 }
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct EnumerationNode {
     pub name: String,
     pub nodes: (Vec<Node>,),
@@ -159,12 +287,12 @@ impl EnumerationNode {
     }
 }
 
-#[derive(Debug, Default, PartialEq, new)]
+#[derive(Debug, Clone, Default, PartialEq, new)]
 pub struct DefaultNode {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, new)]
+#[derive(Debug, Clone, PartialEq, new)]
 pub struct BaseNode {
     pub name: String,
 }
@@ -1,130 +1,269 @@
 use crate::modules::*;
+use crate::parser::{identifier, yang_parse};
+use std::env;
 use std::ffi::OsStr;
 use std::fs::{self, File};
 use std::io::prelude::*;
-use std::io::{BufReader, Error, ErrorKind};
-use std::path::PathBuf;
+use std::io::{BufReader, Error};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+// Environment variables of colon ':' separated YANG file load paths.
+pub const YANG_PATH_ENVS: [&str; 2] = ["YANGPATH", "YANG_MODPATH"];
+
+#[derive(Debug, Error)]
+pub enum FindError {
+    #[error("Can't find {0}, tried {}", display_paths(.1))]
+    NotFound(String, Vec<PathBuf>),
+    #[error("Can't open {}: {1}", .0.display())]
+    Io(PathBuf, Error),
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    paths.join(", ")
+}
+
+// Skip leading whitespaces and comments of YANG text.
+pub(crate) fn skip_comments(text: &str) -> &str {
+    let mut s = text.trim_start();
+    loop {
+        if let Some(rest) = s.strip_prefix("//") {
+            s = rest
+                .split_once('\n')
+                .map_or("", |(_, rest)| rest)
+                .trim_start();
+        } else if let Some((_, rest)) = s.strip_prefix("/*").and_then(|r| r.split_once("*/")) {
+            s = rest.trim_start();
+        } else {
+            return s;
+        }
+    }
+}
+
+// Name of the module or submodule defined in YANG text.
+pub(crate) fn header_module_name(text: &str) -> Option<&str> {
+    let s = skip_comments(text);
+    let s = s
+        .strip_prefix("module")
+        .or_else(|| s.strip_prefix("submodule"))?;
+    let s = s.trim_start_matches(char::is_whitespace);
+    let s = s.trim_start_matches(['"', '\'']);
+    identifier(s).ok().map(|(_, name)| name)
+}
+
+// Revision date of the file name "name@revision-date.yang".
+pub fn file_name_revision(path: &Path) -> Option<RevisionDate> {
+    let name = path.file_name()?.to_str()?.strip_suffix(".yang")?;
+    name.split_once('@')?.1.parse().ok()
+}
+
+// The most recent revision of the module in the text by the revision
+// statements, or by the file name with a warning when the text can't be
+// parsed.
+pub(crate) fn text_revision(
+    path: &Path,
+    text: Option<&str>,
+    warnings: &mut Vec<ModuleWarning>,
+) -> Option<RevisionDate> {
+    let parsed = text.and_then(|text| match yang_parse(skip_comments(text)) {
+        Ok((_, module)) => Some(module.revision_date()),
+        Err(_) => None,
+    });
+    match parsed {
+        Some(revision) => revision,
+        None => {
+            warnings.push(ModuleWarning::Unparsed(path.to_owned()));
+            file_name_revision(path)
+        }
+    }
+}
+
+// The most recent revision of the module in the file.
+pub fn file_revision(path: &Path, warnings: &mut Vec<ModuleWarning>) -> Option<RevisionDate> {
+    text_revision(path, fs::read_to_string(path).ok().as_deref(), warnings)
+}
 
 pub struct Yang {
     paths: Vec<PathBuf>,
+    validate: bool,
+}
+
+impl Default for Yang {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Yang {
     pub fn new() -> Self {
-        Yang { paths: vec![] }
+        Yang {
+            paths: vec![],
+            validate: false,
+        }
     }
 
-    // Add colon ':' separated path to YANG file load paths.
+    // Add colon ':' separated path to YANG file load paths.  Path ending
+    // with "..." is searched recursively.
     pub fn add_path(&mut self, paths: &str) {
-        for path in paths.split(":") {
+        for path in paths.split(":").filter(|p| !p.is_empty()) {
             self.paths.push(PathBuf::from(path));
         }
     }
 
+    // Add load paths of "YANGPATH" and "YANG_MODPATH" environment variables
+    // in this order.
+    pub fn add_env_path(&mut self) {
+        for name in YANG_PATH_ENVS.iter() {
+            if let Ok(paths) = env::var(name) {
+                self.add_path(&paths);
+            }
+        }
+    }
+
     pub fn paths(&self) -> &Vec<PathBuf> {
         &self.paths
     }
 
-    pub fn scan_dir(&self, dir: &str, name: &str, recursive: bool) -> Result<PathBuf, Error> {
-        let mut candidate = vec![];
-
-        let mut file_name = String::from(name);
-        if !file_name.ends_with(".yang") {
-            file_name.push_str(".yang");
-        }
+    // When enabled, files are accepted only when the module or submodule
+    // name in the file matches to the file name.
+    pub fn set_validate(&mut self, validate: bool) {
+        self.validate = validate;
+    }
 
-        let mut basename = String::from(name.trim_end_matches(".yang"));
-        basename.push_str("@");
-
-        let dirent = fs::read_dir(dir)?;
-        for entry in dirent {
-            if let Ok(entry) = entry {
-                if let Ok(file_type) = entry.file_type() {
-                    if file_type.is_file() {
-                        // File.
-                        if let Some(os_str) = entry.path().file_name() {
-                            if let Some(file_str) = os_str.to_str() {
-                                if file_str == file_name {
-                                    return Ok(entry.path());
-                                }
-                                if let None = name.find('@') {
-                                    // Try revision match such as 'ietf-dhcp@2016-08-25.yang'.
-                                    if file_str.starts_with(&basename)
-                                        && file_str.ends_with(".yang")
-                                    {
-                                        candidate.push(entry.path());
-                                    }
-                                }
-                            }
-                        }
-                    } else if file_type.is_dir() && recursive {
-                        // Directory.
-                        if let Some(dir_str) = entry.path().to_str() {
-                            if let Ok(pathbuf) = self.scan_dir(dir_str, name, recursive) {
-                                return Ok(pathbuf);
-                            }
-                        }
-                    }
-                }
-            }
+    // Whether the file defines the module "name".
+    fn valid_file(&self, path: &Path, name: &str) -> bool {
+        if !self.validate {
+            return true;
         }
-        if candidate.len() == 0 {
-            return Err(Error::new(
-                ErrorKind::Other,
-                "can't find candidate YANG file",
-            ));
+        match fs::read_to_string(path) {
+            Ok(text) => header_module_name(&text) == Some(name),
+            Err(_) => false,
         }
-
-        // When the specified file is not found by exact match, directories are
-        // scanned for "name@revision-date.yang" files, the latest (sorted by
-        // YYYY-MM-DD revision-date) of candidates will be selected.
-        candidate.sort();
-
-        Ok(candidate.pop().unwrap())
     }
 
-    pub fn find_file(&mut self, file_name: &str) -> Result<File, Error> {
-        let mut file_path = PathBuf::from(file_name);
+    fn scan(
+        &self,
+        dir: &Path,
+        name: &str,
+        recursive: bool,
+        tried: &mut Vec<PathBuf>,
+        warnings: &mut Vec<ModuleWarning>,
+    ) -> Option<PathBuf> {
+        let base = name.trim_end_matches(".yang");
+        let module_name = base.split('@').next().unwrap_or(base);
+        let file_name = format!("{}.yang", base);
+        let revision_prefix = format!("{}@", base);
 
-        // When file does not have path, scan current dir.
-        if let None = file_name.find('/') {
-            if let Ok(fp) = self.scan_dir(".", file_name, false) {
-                file_path = fp;
+        let mut entries: Vec<PathBuf> = match fs::read_dir(dir) {
+            Ok(dirent) => dirent.flatten().map(|e| e.path()).collect(),
+            Err(_) => vec![],
+        };
+        entries.sort();
+
+        // Exact match, then the latest of "name@revision-date.yang" files
+        // (sorted by YYYY-MM-DD revision-date).
+        let exact = dir.join(&file_name);
+        tried.push(exact.clone());
+        let mut candidates = vec![];
+        if exact.is_file() {
+            candidates.push(exact);
+        }
+        if !base.contains('@') {
+            let mut revisions: Vec<PathBuf> = entries
+                .iter()
+                .filter(|p| p.is_file())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(OsStr::to_str)
+                        .is_some_and(|f| f.starts_with(&revision_prefix) && f.ends_with(".yang"))
+                })
+                .cloned()
+                .collect();
+            // The newest by the revision statements in the files, then by
+            // the file names when the statements are the same.  Each file is
+            // read once.
+            revisions.sort_by_cached_key(|p| {
+                std::cmp::Reverse((file_revision(p, warnings), file_name_revision(p)))
+            });
+            candidates.extend(revisions);
+        }
+        for candidate in candidates {
+            if self.valid_file(&candidate, module_name) {
+                return Some(candidate);
+            }
+            if !tried.contains(&candidate) {
+                tried.push(candidate);
             }
         }
 
-        match File::open(&file_path) {
-            Ok(file) => {
-                // When file can be opened and has a path, add the path to paths.
-                if file_path.pop() {
-                    self.paths.push(file_path);
-                }
-                return Ok(file);
-            }
-            Err(_) => {
-                if let Some(_) = file_name.find('/') {
-                    return Err(Error::new(ErrorKind::Other, "can't find file"));
+        if recursive {
+            for entry in entries.iter().filter(|p| p.is_dir()) {
+                if let Some(path) = self.scan(entry, name, recursive, tried, warnings) {
+                    return Some(path);
                 }
             }
         }
+        None
+    }
 
+    // Scan the directory for "name.yang", or the latest revision of
+    // "name@revision-date.yang" files by their revision statements when
+    // the name has no revision.  Files in
+    // the directory are preferred to ones in subdirectories, which are
+    // scanned in the sorted order.
+    pub fn scan_dir(&self, dir: &str, name: &str, recursive: bool) -> Result<PathBuf, Error> {
+        self.scan(Path::new(dir), name, recursive, &mut vec![], &mut vec![])
+            .ok_or_else(|| Error::other("can't find candidate YANG file"))
+    }
+
+    // Find the file of the module "name" such as "ietf-interfaces" or
+    // "ietf-interfaces@2018-02-20".  A name with a path is opened as is.
+    // Otherwise the current directory, then the load paths are searched in
+    // the order and the first match is used.
+    pub fn find_path(&self, name: &str) -> Result<PathBuf, FindError> {
+        self.locate(name, &mut vec![])
+    }
+
+    // Find the file as find_path(), warnings about the candidate files are
+    // appended to "warnings".
+    pub(crate) fn locate(
+        &self,
+        name: &str,
+        warnings: &mut Vec<ModuleWarning>,
+    ) -> Result<PathBuf, FindError> {
+        let mut tried = vec![];
+        if name.contains('/') {
+            let path = PathBuf::from(name);
+            if path.is_file() {
+                return Ok(path);
+            }
+            tried.push(path);
+            return Err(FindError::NotFound(name.to_owned(), tried));
+        }
+        if let Some(path) = self.scan(Path::new("."), name, false, &mut tried, warnings) {
+            return Ok(path);
+        }
         for path in self.paths() {
-            if path.file_name() == Some(OsStr::new("...")) {
-                let mut dir = path.clone();
-                if dir.pop() {
-                    if let Some(dir_str) = dir.to_str() {
-                        if let Ok(fp) = self.scan_dir(dir_str, file_name, true) {
-                            file_path = fp;
-                        }
-                    }
-                }
+            let found = if path.file_name() == Some(OsStr::new("...")) {
+                let dir = path
+                    .parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."));
+                self.scan(dir, name, true, &mut tried, warnings)
             } else {
-                let mut dir = path.clone();
-                dir.push(file_name);
-                file_path = dir;
+                self.scan(path, name, false, &mut tried, warnings)
+            };
+            if let Some(path) = found {
+                return Ok(path);
             }
         }
-        return File::open(&file_path);
+        Err(FindError::NotFound(name.to_owned(), tried))
+    }
+
+    pub fn find_file(&self, name: &str) -> Result<File, FindError> {
+        let path = self.find_path(name)?;
+        File::open(&path).map_err(|e| FindError::Io(path, e))
     }
 
     pub fn read_file(&self, file: File) -> Result<String, Error> {
@@ -134,17 +273,10 @@ impl Yang {
         Ok(contents)
     }
 
-    pub fn read(&mut self, _ms: &Modules, name: &str) -> Result<String, Error> {
-        // Find and open file.
-        let file = self.find_file(name)?;
-
-        // Read file contents.
-        let data = self.read_file(file)?;
-
-        // Parse file.
-        // let ast = parse_data(data)?;
-
-        Ok(data)
+    pub fn read(&mut self, _ms: &Modules, name: &str) -> Result<String, FindError> {
+        // Find and read file.
+        let path = self.find_path(name)?;
+        fs::read_to_string(&path).map_err(|e| FindError::Io(path, e))
     }
 }
 
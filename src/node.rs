@@ -1,6 +1,6 @@
 use crate::modules::*;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Node {
     EmptyNode,
     Namespace(Box<NamespaceNode>),
@@ -18,6 +18,7 @@ pub enum Node {
     YangVersion(Box<YangVersionNode>),
     Import(Box<ImportNode>),
     Include(Box<IncludeNode>),
+    BelongsTo(Box<BelongsToNode>),
     RevisionDate(Box<RevisionDateNode>),
     Identity(Box<IdentityNode>),
     Feature(Box<FeatureNode>),
@@ -26,6 +27,12 @@ pub enum Node {
     Key(Box<KeyNode>),
     Leaf(Box<LeafNode>),
     LeafList(Box<LeafListNode>),
+    Anydata(Box<AnydataNode>),
+    Choice(Box<ChoiceNode>),
+    Case(Box<CaseNode>),
+    Grouping(Box<GroupingNode>),
+    Uses(Box<UsesNode>),
+    Refine(Box<RefineNode>),
     Base(Box<BaseNode>),
     Type(Box<TypeNode>),
     Mandatory(Box<MandatoryNode>),
@@ -35,4 +42,21 @@ pub enum Node {
     Status(Box<StatusNode>),
     RangeInt(Box<Vec<RangeInt>>),
     RangeUint(Box<Vec<RangeUint>>),
+    Must(Box<MustNode>),
+    When(Box<WhenNode>),
+    Presence(Box<PresenceNode>),
+    MinElements(Box<MinElementsNode>),
+    MaxElements(Box<MaxElementsNode>),
+    OrderedBy(Box<OrderedByNode>),
+    Unique(Box<UniqueNode>),
+    ErrorMessage(Box<ErrorMessageNode>),
+    ErrorAppTag(Box<ErrorAppTagNode>),
+    Modifier(Box<ModifierNode>),
+    Bit(Box<BitNode>),
+    Deviation(Box<DeviationNode>),
+    Deviate(Box<DeviateNode>),
+    Extension(Box<ExtensionNode>),
+    Argument(Box<ArgumentNode>),
+    YinElement(Box<YinElementNode>),
+    ExtInstance(Box<ExtInstanceNode>),
 }
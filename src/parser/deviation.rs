@@ -0,0 +1,153 @@
+use crate::modules::*;
+use crate::parser::*;
+use crate::Node;
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{char, multispace0, multispace1};
+use nom::multi::many0;
+use nom::sequence::delimited;
+use nom::IResult;
+
+fn deviate_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        config_parse,
+        default_parse,
+        mandatory_parse,
+        max_elements_parse,
+        min_elements_parse,
+        must_parse,
+        types_parse,
+        unique_parse,
+        units_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+fn deviate_kind_parse(s: &str) -> IResult<&str, DeviateKind> {
+    let (s, v) = alt((
+        tag("not-supported"),
+        tag("add"),
+        tag("replace"),
+        tag("delete"),
+    ))(s)?;
+    let kind = match v {
+        "not-supported" => DeviateKind::NotSupported,
+        "add" => DeviateKind::Add,
+        "replace" => DeviateKind::Replace,
+        _ => DeviateKind::Delete,
+    };
+    Ok((s, kind))
+}
+
+pub fn deviate_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("deviate")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, kind) = alt((
+        deviate_kind_parse,
+        delimited(char('"'), deviate_kind_parse, char('"')),
+    ))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((deviate_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = DeviateNode::new(kind);
+    for sub in subs {
+        match sub {
+            Node::Config(n) => node.config = Some(n.config),
+            Node::Default(n) => node.default.push(n.name),
+            Node::Mandatory(n) => node.mandatory = Some(n.mandatory),
+            Node::MaxElements(n) => node.max_elements = Some(n.max_elements),
+            Node::MinElements(n) => node.min_elements = Some(n.min_elements),
+            Node::Must(n) => node.must.push(*n),
+            Node::Type(n) => node.typ = Some(*n),
+            Node::Unique(n) => node.unique.push(n.name),
+            Node::Units(n) => node.units = Some(n.name),
+            _ => {}
+        }
+    }
+    Ok((s, Node::Deviate(Box::new(node))))
+}
+
+fn deviation_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        description_parse,
+        reference_parse,
+        deviate_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+// Module:top
+pub fn deviation_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("deviation")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = string_arg_parse(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = deviation_sub_parse(s)?;
+    let mut node = DeviationNode::new(v);
+    for sub in subs {
+        match sub {
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Deviate(n) => node.deviates.push(*n),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            _ => {}
+        }
+    }
+    Ok((s, Node::Deviation(Box::new(node))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deviation_parse_test() {
+        let literal = r#"
+        deviation /if:interfaces/if:interface/if:mtu {
+          description "MTU is limited.";
+          deviate replace {
+            type uint16 {
+              range "68..9000";
+            }
+          }
+          deviate add {
+            default 1500;
+            units "octets";
+          }
+        }"#;
+        let (_, v) = deviation_parse(literal).unwrap();
+        if let Node::Deviation(d) = v {
+            assert_eq!(d.name, "/if:interfaces/if:interface/if:mtu");
+            assert_eq!(d.description, Some(String::from("MTU is limited.")));
+            assert_eq!(d.deviates.len(), 2);
+            assert_eq!(d.deviates[0].kind, DeviateKind::Replace);
+            let typ = d.deviates[0].typ.as_ref().unwrap();
+            assert_eq!(typ.kind, TypeKind::Yuint16);
+            assert_eq!(typ.range, Some(String::from("68..9000")));
+            assert_eq!(d.deviates[1].kind, DeviateKind::Add);
+            assert_eq!(d.deviates[1].default, vec![String::from("1500")]);
+            assert_eq!(d.deviates[1].units, Some(String::from("octets")));
+        } else {
+            panic!("deviation_parse returns {:?}", v);
+        }
+    }
+
+    #[test]
+    fn deviate_not_supported_parse_test() {
+        let literal = r#"deviate not-supported;"#;
+        let (_, v) = deviate_parse(literal).unwrap();
+        assert_eq!(
+            v,
+            Node::Deviate(Box::new(DeviateNode::new(DeviateKind::NotSupported)))
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::modules::*;
+use crate::parser::*;
+use crate::Node;
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{char, multispace0, multispace1};
+use nom::combinator::opt;
+use nom::multi::many0;
+use nom::sequence::preceded;
+use nom::IResult;
+
+fn yin_element_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("yin-element")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = boolean_parse(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char(';')(s)?;
+    let mut node = YinElementNode::new(String::from("yin-element"));
+    node.yin_element = v;
+    Ok((s, Node::YinElement(Box::new(node))))
+}
+
+fn argument_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((yin_element_parse, ext_instance_parse)))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+fn argument_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("argument")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = string_arg_parse(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((argument_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = ArgumentNode::new(v);
+    for sub in subs {
+        if let Node::YinElement(n) = sub {
+            node.yin_element = Some(n.yin_element);
+        }
+    }
+    Ok((s, Node::Argument(Box::new(node))))
+}
+
+fn extension_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        argument_parse,
+        description_parse,
+        reference_parse,
+        status_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+// Module:top
+pub fn extension_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("extension")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = identifier(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((extension_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = ExtensionNode::new(String::from(v));
+    for sub in subs {
+        match sub {
+            Node::Argument(n) => node.argument = Some(*n),
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            _ => {}
+        }
+    }
+    Ok((s, Node::Extension(Box::new(node))))
+}
+
+// Keyword parser which returns optional prefix and keyword.
+type KeywordParser<'a> = fn(&'a str) -> IResult<&'a str, (Option<&'a str>, &'a str)>;
+
+// Statement of any keyword with optional argument and substatements.
+fn ext_stmt_parse<'a>(s: &'a str, keyword: KeywordParser<'a>) -> IResult<&'a str, ExtInstanceNode> {
+    let (s, _) = multispace0(s)?;
+    let (s, (prefix, name)) = keyword(s)?;
+    let (s, argument) = opt(preceded(multispace1, string_arg_parse))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, nodes) = alt((ext_sub_parse, |s| {
+        let (s, _) = char(';')(s)?;
+        Ok((s, vec![]))
+    }))(s)?;
+    let mut node = ExtInstanceNode::new(prefix.map(String::from), String::from(name));
+    node.argument = argument;
+    node.nodes = nodes;
+    Ok((s, node))
+}
+
+fn prefixed_keyword(s: &str) -> IResult<&str, (Option<&str>, &str)> {
+    let (s, prefix) = identifier(s)?;
+    let (s, _) = char(':')(s)?;
+    let (s, keyword) = identifier(s)?;
+    Ok((s, (Some(prefix), keyword)))
+}
+
+fn any_keyword(s: &str) -> IResult<&str, (Option<&str>, &str)> {
+    alt((prefixed_keyword, |s| {
+        let (s, keyword) = identifier(s)?;
+        Ok((s, (None, keyword)))
+    }))(s)
+}
+
+// Substatements of extension instance may be any YANG statements.
+fn ext_sub_parse(s: &str) -> IResult<&str, Vec<ExtInstanceNode>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(|s| ext_stmt_parse(s, any_keyword))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+// RFC7950 6.3.1.  Extension Usage
+//     An extension can be used anywhere a statement is allowed, its
+//     keyword is the extension name prefixed by the prefix of the module
+//     which defines the extension.
+pub fn ext_instance_parse(s: &str) -> IResult<&str, Node> {
+    let (s, node) = ext_stmt_parse(s, prefixed_keyword)?;
+    Ok((s, Node::ExtInstance(Box::new(node))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extension_parse_test() {
+        let literal = r#"
+        extension default-deny-write {
+          description "Used to indicate that the data model node
+             represents a sensitive security system parameter.";
+        }"#;
+        let (_, v) = extension_parse(literal).unwrap();
+        let mut node = ExtensionNode::new(String::from("default-deny-write"));
+        node.description = Some(String::from(
+            "Used to indicate that the data model node
+             represents a sensitive security system parameter.",
+        ));
+        assert_eq!(v, Node::Extension(Box::new(node)));
+    }
+
+    #[test]
+    fn extension_argument_parse_test() {
+        let literal = r#"
+        extension openconfig-version {
+          argument "semver" {
+            yin-element false;
+          }
+        }"#;
+        let (_, v) = extension_parse(literal).unwrap();
+        if let Node::Extension(n) = v {
+            let argument = n.argument.unwrap();
+            assert_eq!(argument.name, "semver");
+            assert_eq!(argument.yin_element, Some(false));
+        } else {
+            panic!("extension_parse returns {:?}", v);
+        }
+    }
+
+    #[test]
+    fn ext_instance_parse_test() {
+        let literal = r#"nacm:default-deny-write;"#;
+        let (_, v) = ext_instance_parse(literal).unwrap();
+        let node = ExtInstanceNode::new(
+            Some(String::from("nacm")),
+            String::from("default-deny-write"),
+        );
+        assert_eq!(v, Node::ExtInstance(Box::new(node)));
+
+        let literal = r#"oc-ext:openconfig-version "1.0.0";"#;
+        let (_, v) = ext_instance_parse(literal).unwrap();
+        let mut node = ExtInstanceNode::new(
+            Some(String::from("oc-ext")),
+            String::from("openconfig-version"),
+        );
+        node.argument = Some(String::from("1.0.0"));
+        assert_eq!(v, Node::ExtInstance(Box::new(node)));
+    }
+
+    #[test]
+    fn ext_instance_sub_parse_test() {
+        let literal = r#"
+        ex:annotation last-modified {
+          type yang:date-and-time;
+          ex:flag;
+        }"#;
+        let (s, v) = ext_instance_parse(literal).unwrap();
+        assert_eq!(s, "");
+        if let Node::ExtInstance(n) = v {
+            assert_eq!(n.name(), "ex:annotation");
+            assert_eq!(n.argument, Some(String::from("last-modified")));
+            assert_eq!(n.nodes.len(), 2);
+            assert_eq!(n.nodes[0].name(), "type");
+            assert_eq!(
+                n.nodes[0].argument,
+                Some(String::from("yang:date-and-time"))
+            );
+            assert_eq!(n.nodes[1].name(), "ex:flag");
+        } else {
+            panic!("ext_instance_parse returns {:?}", v);
+        }
+    }
+}
@@ -1,10 +1,20 @@
+#![allow(clippy::module_inception)]
+
+pub mod deviation;
 pub mod error;
+pub mod extension;
 pub mod parser;
 pub mod range;
 pub mod revision;
+pub mod stmt;
 pub mod types;
+pub mod yin;
+pub use deviation::*;
 pub use error::*;
+pub use extension::*;
 pub use parser::*;
 pub use range::*;
 pub use revision::*;
+pub use stmt::*;
 pub use types::*;
+pub use yin::*;
@@ -1,12 +1,13 @@
 use super::*;
 use crate::modules::*;
+use crate::xpath::XPath;
 use crate::Node;
 use nom::branch::{alt, permutation};
 use nom::bytes::complete::{tag, take_until, take_while, take_while1};
 use nom::character::complete::{anychar, char, multispace0, multispace1, none_of};
-use nom::combinator::{recognize, verify};
+use nom::combinator::{map, recognize, verify};
 use nom::error::{make_error, ErrorKind};
-use nom::multi::{many0, separated_list};
+use nom::multi::{many0, separated_list, separated_nonempty_list};
 use nom::sequence::{delimited, pair};
 use nom::Err;
 use nom::IResult;
@@ -97,12 +98,70 @@ pub fn quoted_string_list(s: &str) -> IResult<&str, String> {
     Ok((s, v.into_iter().collect()))
 }
 
+fn is_unquoted_string_char(c: char) -> bool {
+    !c.is_whitespace() && c != ';' && c != '{' && c != '}' && c != '"' && c != '\''
+}
+
+pub fn unquoted_string(s: &str) -> IResult<&str, &str> {
+    take_while1(is_unquoted_string_char)(s)
+}
+
+// Replace the escape sequences of double-quoted string.
+pub fn unescape(s: &str) -> String {
+    let mut value = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some(c) => value.push(c),
+            None => {}
+        }
+    }
+    value
+}
+
+pub fn double_quoted_string_list(s: &str) -> IResult<&str, String> {
+    let (s, v) = separated_nonempty_list(
+        permutation((multispace0, char('+'), multispace0)),
+        double_quoted_string,
+    )(s)?;
+    Ok((s, v.into_iter().map(unescape).collect()))
+}
+
+pub fn single_quoted_string_list(s: &str) -> IResult<&str, String> {
+    let (s, v) = separated_nonempty_list(
+        permutation((multispace0, char('+'), multispace0)),
+        quoted_string,
+    )(s)?;
+    Ok((s, v.concat()))
+}
+
+// RFC7950 6.1.3.  Quoting
+//     If a string contains any space, tab, or newline characters, a
+//     semicolon (";"), braces ("{" or "}"), or comment sequences ("//",
+//     "/*", or "*/"), then it MUST be enclosed within double or single
+//     quotes.  A string can be split into multiple quoted strings
+//     concatenated with "+".
+pub fn string_arg_parse(s: &str) -> IResult<&str, String> {
+    alt((
+        double_quoted_string_list,
+        single_quoted_string_list,
+        map(unquoted_string, String::from),
+    ))(s)
+}
+
+// The argument may be quoted as any other, e.g. 'config "false";'.
 pub fn boolean_parse(s: &str) -> IResult<&str, bool> {
-    let (s, v) = alt((tag("true"), tag("false")))(s)?;
-    match v {
-        "true" => Ok((s, true)),
-        "false" => Ok((s, false)),
-        _ => Err(Err::Error(make_error(s, ErrorKind::Fix))),
+    let (rest, v) = string_arg_parse(s)?;
+    match v.as_str() {
+        "true" => Ok((rest, true)),
+        "false" => Ok((rest, false)),
+        _ => Err(Err::Error(make_error(s, ErrorKind::Tag))),
     }
 }
 
@@ -116,11 +175,11 @@ pub fn c_comment_parse(s: &str) -> IResult<&str, Node> {
 }
 
 // Single statement 'keyword: "double quoted string";'
-pub fn single_statement_parse(s: &str, key: String) -> IResult<&str, &str> {
+pub fn single_statement_parse(s: &str, key: String) -> IResult<&str, String> {
     let (s, _) = multispace0(s)?;
     let (s, _) = tag(key.as_str())(s)?;
     let (s, _) = multispace1(s)?;
-    let (s, v) = double_quoted_string(s)?;
+    let (s, v) = string_arg_parse(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char(';')(s)?;
     Ok((s, v))
@@ -128,13 +187,13 @@ pub fn single_statement_parse(s: &str, key: String) -> IResult<&str, &str> {
 
 pub fn description_parse(s: &str) -> IResult<&str, Node> {
     let (s, v) = single_statement_parse(s, String::from("description"))?;
-    let n = DescriptionNode::new(v.to_owned());
+    let n = DescriptionNode::new(v);
     Ok((s, Node::Description(Box::new(n))))
 }
 
 pub fn reference_parse(s: &str) -> IResult<&str, Node> {
     let (s, v) = single_statement_parse(s, String::from("reference"))?;
-    let node = ReferenceNode::new(v.to_owned());
+    let node = ReferenceNode::new(v);
     Ok((s, Node::Reference(Box::new(node))))
 }
 
@@ -162,15 +221,150 @@ pub fn config_parse(s: &str) -> IResult<&str, Node> {
     Ok((s, Node::Config(Box::new(node))))
 }
 
+// YANG 1.1 "if-feature" argument is if-feature-expr.
 pub fn if_feature_parse(s: &str) -> IResult<&str, Node> {
+    let (s, v) = single_statement_parse(s, String::from("if-feature"))?;
+    let node = IfFeatureNode::new(v);
+    Ok((s, Node::IfFeature(Box::new(node))))
+}
+
+pub fn presence_parse(s: &str) -> IResult<&str, Node> {
+    let (s, v) = single_statement_parse(s, String::from("presence"))?;
+    let node = PresenceNode::new(v);
+    Ok((s, Node::Presence(Box::new(node))))
+}
+
+pub fn error_message_parse(s: &str) -> IResult<&str, Node> {
+    let (s, v) = single_statement_parse(s, String::from("error-message"))?;
+    let node = ErrorMessageNode::new(v);
+    Ok((s, Node::ErrorMessage(Box::new(node))))
+}
+
+pub fn error_app_tag_parse(s: &str) -> IResult<&str, Node> {
+    let (s, v) = single_statement_parse(s, String::from("error-app-tag"))?;
+    let node = ErrorAppTagNode::new(v);
+    Ok((s, Node::ErrorAppTag(Box::new(node))))
+}
+
+pub fn must_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        error_message_parse,
+        error_app_tag_parse,
+        description_parse,
+        reference_parse,
+        ext_instance_parse,
+    )))(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, _) = tag("if-feature")(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+// Compile XPath argument of "must" and "when".
+fn xpath_arg_parse(s: &str) -> IResult<&str, (String, XPath)> {
+    let (s, v) = string_arg_parse(s)?;
+    match XPath::compile(&v) {
+        Ok(xpath) => Ok((s, (v, xpath))),
+        Err(_) => Err(Err::Error(make_error(s, ErrorKind::Verify))),
+    }
+}
+
+pub fn must_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("must")(s)?;
     let (s, _) = multispace1(s)?;
-    let (s, v) = identifier(s)?;
+    let (s, (v, xpath)) = xpath_arg_parse(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((must_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = MustNode::new(v);
+    node.xpath = Some(xpath);
+    for sub in subs {
+        match sub {
+            Node::ErrorMessage(n) => node.error_message = Some(n.name),
+            Node::ErrorAppTag(n) => node.error_app_tag = Some(n.name),
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            _ => {}
+        }
+    }
+    Ok((s, Node::Must(Box::new(node))))
+}
+
+pub fn when_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        description_parse,
+        reference_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+pub fn when_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("when")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, (v, xpath)) = xpath_arg_parse(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((when_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = WhenNode::new(v);
+    node.xpath = Some(xpath);
+    for sub in subs {
+        match sub {
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            _ => {}
+        }
+    }
+    Ok((s, Node::When(Box::new(node))))
+}
+
+pub fn min_elements_parse(s: &str) -> IResult<&str, Node> {
+    let (s, v) = single_statement_parse(s, String::from("min-elements"))?;
+    match v.parse::<u64>() {
+        Ok(min) => {
+            let mut node = MinElementsNode::new(v);
+            node.min_elements = min;
+            Ok((s, Node::MinElements(Box::new(node))))
+        }
+        Err(_) => Err(Err::Error(make_error(s, ErrorKind::Digit))),
+    }
+}
+
+pub fn max_elements_parse(s: &str) -> IResult<&str, Node> {
+    let (s, v) = single_statement_parse(s, String::from("max-elements"))?;
+    let max = if v == "unbounded" {
+        u64::MAX
+    } else {
+        match v.parse::<u64>() {
+            Ok(max) => max,
+            Err(_) => return Err(Err::Error(make_error(s, ErrorKind::Digit))),
+        }
+    };
+    let mut node = MaxElementsNode::new(v);
+    node.max_elements = max;
+    Ok((s, Node::MaxElements(Box::new(node))))
+}
+
+pub fn ordered_by_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("ordered-by")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = alt((tag("user"), tag("system")))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char(';')(s)?;
-    let node = MandatoryNode::new(v.to_owned());
-    Ok((s, Node::Mandatory(Box::new(node))))
+    let node = OrderedByNode::new(v.to_owned());
+    Ok((s, Node::OrderedBy(Box::new(node))))
+}
+
+pub fn unique_parse(s: &str) -> IResult<&str, Node> {
+    let (s, v) = single_statement_parse(s, String::from("unique"))?;
+    let node = UniqueNode::new(v);
+    Ok((s, Node::Unique(Box::new(node))))
 }
 
 pub fn semicolon_end_parse(s: &str) -> IResult<&str, Vec<Node>> {
@@ -225,7 +419,7 @@ fn module_parse(s: &str) -> IResult<&str, Node> {
         tag("description"),
     ))(s)?;
     let (s, _) = multispace1(s)?;
-    let (s, v) = alt((double_quoted_string, identifier))(s)?;
+    let (s, v) = string_arg_parse(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char(';')(s)?;
     let node = match k {
@@ -273,6 +467,7 @@ pub fn import_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
         revision_date_stmt_parse,
         base_parse,
         status_parse,
+        ext_instance_parse,
     )))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char('}')(s)?;
@@ -310,12 +505,64 @@ pub fn import_parse(s: &str) -> IResult<&str, Node> {
             Node::RevisionDate(n) => {
                 node.revision_date = n.name.to_owned();
             }
+            Node::ExtInstance(n) => {
+                node.ext_instances.push(*n);
+            }
             _ => {}
         }
     }
     Ok((s, Node::Import(Box::new(node))))
 }
 
+// The include's Substatements
+// +---------------+---------+-------------+
+// | substatement  | section | cardinality |
+// +---------------+---------+-------------+
+// | description   | 7.21.3  | 0..1        |
+// | reference     | 7.21.4  | 0..1        |
+// | revision-date | 7.1.5.1 | 0..1        |
+// +---------------+---------+-------------+
+pub fn include_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("include")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = identifier(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((import_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = IncludeNode::new(String::from(v));
+    for sub in subs {
+        match sub {
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::RevisionDate(n) => node.revision_date = n.name,
+            _ => {}
+        }
+    }
+    Ok((s, Node::Include(Box::new(node))))
+}
+
+// The belongs-to's Substatements
+// +--------------+---------+-------------+
+// | substatement | section | cardinality |
+// +--------------+---------+-------------+
+// | prefix       | 7.1.4   | 1           |
+// +--------------+---------+-------------+
+fn belongs_to_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("belongs-to")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = identifier(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = import_sub_parse(s)?;
+    let mut node = BelongsToNode::new(String::from(v));
+    for sub in subs {
+        if let Node::Prefix(n) = sub {
+            node.prefix = n.name;
+        }
+    }
+    Ok((s, Node::BelongsTo(Box::new(node))))
+}
+
 // +--------------+---------+-------------+
 // | substatement | section | cardinality |
 // +--------------+---------+-------------+
@@ -325,32 +572,41 @@ pub fn import_parse(s: &str) -> IResult<&str, Node> {
 // | reference    | 7.21.4  | 0..1        |
 // | status       | 7.21.2  | 0..1        |
 // +--------------+---------+-------------+
+pub fn identity_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        base_parse,
+        description_parse,
+        if_feature_parse,
+        reference_parse,
+        status_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
 pub fn identity_parse(s: &str) -> IResult<&str, Node> {
     let (s, _) = multispace0(s)?;
     let (s, _) = tag("identity")(s)?;
     let (s, _) = multispace1(s)?;
     let (s, v) = identifier(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, mut subs) = alt((import_sub_parse, semicolon_end_parse))(s)?;
-    let mut node = ImportNode::new(String::from(v));
-    while let Some(sub) = subs.pop() {
+    let (s, subs) = alt((identity_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = IdentityNode::new(String::from(v));
+    for sub in subs {
         match sub {
-            Node::Prefix(n) => {
-                node.prefix = n.name.to_owned();
-            }
-            Node::Description(n) => {
-                node.description = Some(n.name.to_owned());
-            }
-            Node::Reference(n) => {
-                node.reference = Some(n.name.to_owned());
-            }
-            Node::RevisionDate(n) => {
-                node.revision_date = n.name.to_owned();
-            }
+            Node::Base(n) => node.bases.push(n.name),
+            Node::Description(n) => node.description = Some(n.name),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
             _ => {}
         }
     }
-    Ok((s, Node::Import(Box::new(node))))
+    Ok((s, Node::Identity(Box::new(node))))
 }
 
 // +--------------+---------+-------------+
@@ -361,37 +617,44 @@ pub fn identity_parse(s: &str) -> IResult<&str, Node> {
 // | reference    | 7.21.4  | 0..1        |
 // | status       | 7.21.2  | 0..1        |
 // +--------------+---------+-------------+
+pub fn feature_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        description_parse,
+        if_feature_parse,
+        reference_parse,
+        status_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
 pub fn feature_parse(s: &str) -> IResult<&str, Node> {
     let (s, _) = multispace0(s)?;
     let (s, _) = tag("feature")(s)?;
     let (s, _) = multispace1(s)?;
     let (s, v) = identifier(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, mut subs) = alt((import_sub_parse, semicolon_end_parse))(s)?;
-    let mut node = ImportNode::new(String::from(v));
-    while let Some(sub) = subs.pop() {
+    let (s, subs) = alt((feature_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = FeatureNode::new(String::from(v));
+    for sub in subs {
         match sub {
-            Node::Prefix(n) => {
-                node.prefix = n.name.to_owned();
-            }
-            Node::Description(n) => {
-                node.description = Some(n.name.to_owned());
-            }
-            Node::Reference(n) => {
-                node.reference = Some(n.name.to_owned());
-            }
-            Node::RevisionDate(n) => {
-                node.revision_date = n.name.to_owned();
-            }
+            Node::Description(n) => node.description = Some(n.name),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
             _ => {}
         }
     }
-    Ok((s, Node::Import(Box::new(node))))
+    Ok((s, Node::Feature(Box::new(node))))
 }
 
 pub fn units_parse(s: &str) -> IResult<&str, Node> {
     let (s, v) = single_statement_parse(s, String::from("units"))?;
-    let n = UnitsNode::new(v.to_owned());
+    let n = UnitsNode::new(v);
     Ok((s, Node::Units(Box::new(n))))
 }
 
@@ -407,6 +670,12 @@ pub fn leaf_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
         if_feature_parse,
         units_parse,
         status_parse,
+        must_parse,
+        when_parse,
+        min_elements_parse,
+        max_elements_parse,
+        ordered_by_parse,
+        ext_instance_parse,
     )))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char('}')(s)?;
@@ -419,8 +688,25 @@ pub fn leaf_parse(s: &str) -> IResult<&str, Node> {
     let (s, _) = multispace1(s)?;
     let (s, v) = identifier(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, _subs) = alt((leaf_sub_parse, semicolon_end_parse))(s)?;
-    let node = LeafNode::new(String::from(v));
+    let (s, subs) = alt((leaf_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = LeafNode::new(String::from(v));
+    for sub in subs {
+        match sub {
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Type(n) => node.typ = Some(*n),
+            Node::Mandatory(n) => node.mandatory = Some(n.mandatory),
+            Node::Config(n) => node.config = Some(n.config),
+            Node::Default(n) => node.default = Some(n.name),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Units(n) => node.units = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::Must(n) => node.must.push(*n),
+            Node::When(n) => node.when = Some(*n),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            _ => {}
+        }
+    }
     Ok((s, Node::Leaf(Box::new(node))))
 }
 
@@ -430,26 +716,342 @@ pub fn leaf_list_parse(s: &str) -> IResult<&str, Node> {
     let (s, _) = multispace1(s)?;
     let (s, v) = identifier(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, _subs) = alt((leaf_sub_parse, semicolon_end_parse))(s)?;
-    let node = LeafListNode::new(String::from(v));
+    let (s, subs) = alt((leaf_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = LeafListNode::new(String::from(v));
+    for sub in subs {
+        match sub {
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Type(n) => node.typ = Some(*n),
+            Node::Config(n) => node.config = Some(n.config),
+            Node::Default(n) => node.default.push(n.name),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Units(n) => node.units = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::Must(n) => node.must.push(*n),
+            Node::When(n) => node.when = Some(*n),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            Node::MinElements(n) => node.min_elements = Some(n.min_elements),
+            Node::MaxElements(n) => node.max_elements = Some(n.max_elements),
+            Node::OrderedBy(n) => node.ordered_by = Some(n.name),
+            _ => {}
+        }
+    }
     Ok((s, Node::LeafList(Box::new(node))))
 }
 
 pub fn key_parse(s: &str) -> IResult<&str, Node> {
     let (s, v) = single_statement_parse(s, String::from("key"))?;
-    let node = KeyNode::new(v.to_owned());
+    let node = KeyNode::new(v);
     Ok((s, Node::Key(Box::new(node))))
 }
 
-pub fn list_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+pub fn anydata_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
     let (s, _) = char('{')(s)?;
     let (s, nodes) = many0(alt((
+        config_parse,
         description_parse,
-        key_parse,
+        if_feature_parse,
+        mandatory_parse,
+        must_parse,
+        reference_parse,
+        status_parse,
+        when_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+// "anyxml" shares the substatements with "anydata".
+pub fn anydata_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, keyword) = alt((tag("anydata"), tag("anyxml")))(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = identifier(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((anydata_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = AnydataNode::new(String::from(v));
+    node.anyxml = keyword == "anyxml";
+    for sub in subs {
+        match sub {
+            Node::Config(n) => node.config = Some(n.config),
+            Node::Description(n) => node.description = Some(n.name),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Mandatory(n) => node.mandatory = Some(n.mandatory),
+            Node::Must(n) => node.must.push(*n),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::When(n) => node.when = Some(*n),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            _ => {}
+        }
+    }
+    Ok((s, Node::Anydata(Box::new(node))))
+}
+
+pub fn case_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        data_def_parse,
+        description_parse,
+        if_feature_parse,
+        reference_parse,
+        status_parse,
+        when_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+pub fn case_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("case")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = identifier(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((case_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = CaseNode::new(String::from(v));
+    for sub in subs {
+        match sub {
+            Node::Description(n) => node.description = Some(n.name),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::When(n) => node.when = Some(*n),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            Node::Container(_)
+            | Node::Leaf(_)
+            | Node::LeafList(_)
+            | Node::List(_)
+            | Node::Anydata(_)
+            | Node::Choice(_)
+            | Node::Uses(_) => node.nodes.push(sub),
+            _ => {}
+        }
+    }
+    Ok((s, Node::Case(Box::new(node))))
+}
+
+pub fn choice_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        case_parse,
+        data_def_parse,
+        config_parse,
+        default_parse,
+        description_parse,
+        if_feature_parse,
+        mandatory_parse,
+        reference_parse,
+        status_parse,
+        when_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+// RFC7950 7.9.2.  The choice's "case" Statement
+//     As a shorthand, the "case" statement can be omitted if the branch
+//     contains a single "anydata", "anyxml", "choice", "container", "leaf",
+//     "list", or "leaf-list" statement.
+pub fn choice_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("choice")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = identifier(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((choice_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = ChoiceNode::new(String::from(v));
+    for sub in subs {
+        match sub {
+            Node::Config(n) => node.config = Some(n.config),
+            Node::Default(n) => node.default = Some(n.name),
+            Node::Description(n) => node.description = Some(n.name),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Mandatory(n) => node.mandatory = Some(n.mandatory),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::When(n) => node.when = Some(*n),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            Node::Container(_)
+            | Node::Leaf(_)
+            | Node::LeafList(_)
+            | Node::List(_)
+            | Node::Anydata(_)
+            | Node::Choice(_)
+            | Node::Case(_) => node.nodes.push(sub),
+            _ => {}
+        }
+    }
+    Ok((s, Node::Choice(Box::new(node))))
+}
+
+pub fn refine_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        config_parse,
+        default_parse,
+        description_parse,
+        if_feature_parse,
+        mandatory_parse,
+        max_elements_parse,
+        min_elements_parse,
+        must_parse,
+        presence_parse,
+        reference_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+pub fn refine_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("refine")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = string_arg_parse(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((refine_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = RefineNode::new(v);
+    for sub in subs {
+        match sub {
+            Node::Config(n) => node.config = Some(n.config),
+            Node::Default(n) => node.default.push(n.name),
+            Node::Description(n) => node.description = Some(n.name),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Mandatory(n) => node.mandatory = Some(n.mandatory),
+            Node::MaxElements(n) => node.max_elements = Some(n.max_elements),
+            Node::MinElements(n) => node.min_elements = Some(n.min_elements),
+            Node::Must(n) => node.must.push(*n),
+            Node::Presence(n) => node.presence = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            _ => {}
+        }
+    }
+    Ok((s, Node::Refine(Box::new(node))))
+}
+
+pub fn uses_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        description_parse,
+        if_feature_parse,
+        refine_parse,
+        reference_parse,
+        status_parse,
+        when_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+pub fn uses_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("uses")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = string_arg_parse(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((uses_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = UsesNode::new(v);
+    for sub in subs {
+        match sub {
+            Node::Description(n) => node.description = Some(n.name),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Refine(n) => node.refines.push(*n),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::When(n) => node.when = Some(*n),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            _ => {}
+        }
+    }
+    Ok((s, Node::Uses(Box::new(node))))
+}
+
+pub fn grouping_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        data_def_parse,
+        grouping_parse,
+        description_parse,
+        reference_parse,
+        status_parse,
+        ext_instance_parse,
+    )))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, nodes))
+}
+
+pub fn grouping_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("grouping")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = identifier(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, subs) = alt((grouping_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = GroupingNode::new(String::from(v));
+    for sub in subs {
+        match sub {
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            Node::Container(_)
+            | Node::Leaf(_)
+            | Node::LeafList(_)
+            | Node::List(_)
+            | Node::Anydata(_)
+            | Node::Choice(_)
+            | Node::Uses(_)
+            | Node::Grouping(_) => node.nodes.push(sub),
+            _ => {}
+        }
+    }
+    Ok((s, Node::Grouping(Box::new(node))))
+}
+
+// Data definition statements.
+pub fn data_def_parse(s: &str) -> IResult<&str, Node> {
+    alt((
+        container_parse,
         leaf_parse,
         leaf_list_parse,
-        container_parse,
+        list_parse,
+        anydata_parse,
+        choice_parse,
+        uses_parse,
+    ))(s)
+}
+
+pub fn list_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+    let (s, _) = char('{')(s)?;
+    let (s, nodes) = many0(alt((
+        description_parse,
+        reference_parse,
+        key_parse,
+        data_def_parse,
+        grouping_parse,
         status_parse,
+        config_parse,
+        if_feature_parse,
+        must_parse,
+        when_parse,
+        min_elements_parse,
+        max_elements_parse,
+        ordered_by_parse,
+        unique_parse,
+        ext_instance_parse,
     )))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char('}')(s)?;
@@ -462,8 +1064,34 @@ pub fn list_parse(s: &str) -> IResult<&str, Node> {
     let (s, _) = multispace1(s)?;
     let (s, v) = identifier(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, _subs) = alt((list_sub_parse, semicolon_end_parse))(s)?;
-    let node = ListNode::new(String::from(v));
+    let (s, subs) = alt((list_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = ListNode::new(String::from(v));
+    for sub in subs {
+        match sub {
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Key(n) => node.key = Some(n.name),
+            Node::Config(n) => node.config = Some(n.config),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::Must(n) => node.must.push(*n),
+            Node::When(n) => node.when = Some(*n),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            Node::MinElements(n) => node.min_elements = Some(n.min_elements),
+            Node::MaxElements(n) => node.max_elements = Some(n.max_elements),
+            Node::OrderedBy(n) => node.ordered_by = Some(n.name),
+            Node::Unique(n) => node.unique.push(n.name),
+            Node::Container(_)
+            | Node::Leaf(_)
+            | Node::LeafList(_)
+            | Node::List(_)
+            | Node::Anydata(_)
+            | Node::Choice(_)
+            | Node::Uses(_)
+            | Node::Grouping(_) => node.nodes.push(sub),
+            _ => {}
+        }
+    }
     Ok((s, Node::List(Box::new(node))))
 }
 
@@ -483,10 +1111,16 @@ pub fn container_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
     let (s, _) = char('{')(s)?;
     let (s, nodes) = many0(alt((
         description_parse,
-        list_parse,
+        reference_parse,
+        data_def_parse,
+        grouping_parse,
         config_parse,
-        leaf_parse,
         status_parse,
+        presence_parse,
+        if_feature_parse,
+        must_parse,
+        when_parse,
+        ext_instance_parse,
     )))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char('}')(s)?;
@@ -499,39 +1133,50 @@ pub fn container_parse(s: &str) -> IResult<&str, Node> {
     let (s, _) = multispace1(s)?;
     let (s, v) = identifier(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, _subs) = alt((container_sub_parse, semicolon_end_parse))(s)?;
-    let node = ContainerNode::new(String::from(v));
-
-    // while let Some(sub) = subs.pop() {
-    //     match sub {
-    //         Node::Prefix(n) => {
-    //             node.prefix = n.name.to_owned();
-    //         }
-    //         Node::Description(n) => {
-    //             node.description = Some(n.name.to_owned());
-    //         }
-    //         Node::Reference(n) => {
-    //             node.reference = Some(n.name.to_owned());
-    //         }
-    //         Node::RevisionDate(n) => {
-    //             node.revision_date = n.name.to_owned();
-    //         }
-    //         _ => {}
-    //     }
-    // }
+    let (s, subs) = alt((container_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = ContainerNode::new(String::from(v));
+    for sub in subs {
+        match sub {
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Config(n) => node.config = Some(n.config),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::Presence(n) => node.presence = Some(n.name),
+            Node::IfFeature(n) => node.if_features.push(n.name),
+            Node::Must(n) => node.must.push(*n),
+            Node::When(n) => node.when = Some(*n),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            Node::Container(_)
+            | Node::Leaf(_)
+            | Node::LeafList(_)
+            | Node::List(_)
+            | Node::Anydata(_)
+            | Node::Choice(_)
+            | Node::Uses(_)
+            | Node::Grouping(_) => node.nodes.push(sub),
+            _ => {}
+        }
+    }
     Ok((s, Node::Container(Box::new(node))))
 }
 
+// RFC7950 7.2.  The "submodule" Statement
+//
+// A submodule is parsed into a Module with "belongs_to" set to the name of
+// the module.  The prefix of the "belongs-to" statement is the prefix of
+// the submodule.
 pub fn yang_parse(s: &str) -> IResult<&str, Module> {
-    let (s, _) = tag("module")(s)?;
+    let (s, keyword) = alt((tag("module"), tag("submodule")))(s)?;
     let (s, _) = multispace1(s)?;
     let (s, name) = identifier(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char('{')(s)?;
-    let (s, mut nodes) = many0(alt((
+    let (s, nodes) = many0(alt((
         yang_version_parse,
         module_parse,
         prefix_parse,
+        belongs_to_parse,
+        include_parse,
         reference_parse,
         revision_parse,
         c_comment_parse,
@@ -539,14 +1184,21 @@ pub fn yang_parse(s: &str) -> IResult<&str, Module> {
         import_parse,
         identity_parse,
         feature_parse,
-        container_parse,
+        data_def_parse,
+        grouping_parse,
+        deviation_parse,
+        extension_parse,
+        ext_instance_parse,
     )))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char('}')(s)?;
+    if keyword == "submodule" && !nodes.iter().any(|n| matches!(n, Node::BelongsTo(_))) {
+        return Err(Err::Error(make_error(s, ErrorKind::Tag)));
+    }
 
     let mut module = Module::new(name.to_owned());
 
-    while let Some(node) = nodes.pop() {
+    for node in nodes {
         match node {
             Node::Namespace(n) => {
                 module.namespace = n.name.to_owned();
@@ -569,9 +1221,49 @@ pub fn yang_parse(s: &str) -> IResult<&str, Module> {
             Node::Typedef(n) => {
                 module.typedefs.insert(n.name.to_owned(), *n);
             }
+            Node::Grouping(n) => {
+                module.groupings.insert(n.name.to_owned(), *n);
+            }
+            Node::Import(n) => {
+                module.imports.push(*n);
+            }
+            Node::Include(n) => {
+                module.includes.push(*n);
+            }
+            Node::BelongsTo(n) => {
+                module.belongs_to = Some(n.name);
+                module.prefix = n.prefix;
+            }
+            Node::Identity(n) => {
+                module.identities.insert(n.name.to_owned(), *n);
+            }
+            Node::Feature(n) => {
+                module.features.insert(n.name.to_owned(), *n);
+            }
+            Node::Deviation(n) => {
+                module.deviations.push(*n);
+            }
+            Node::Extension(n) => {
+                module.extensions.insert(n.name.to_owned(), *n);
+            }
+            Node::ExtInstance(n) => {
+                module.ext_instances.push(*n);
+            }
+            Node::Container(_)
+            | Node::Leaf(_)
+            | Node::LeafList(_)
+            | Node::List(_)
+            | Node::Anydata(_)
+            | Node::Choice(_)
+            | Node::Uses(_) => {
+                module.nodes.push(node);
+            }
             _ => {}
         }
     }
+    module.build_prefixes();
+    module.resolve_ext_instances();
+    module.bind_xpath_prefixes();
     Ok((s, module))
 }
 
@@ -581,7 +1273,7 @@ mod tests {
 
     #[test]
     fn test_yang_version_arg_parse() {
-        for literal in vec!["1", "1.1"] {
+        for literal in ["1", "1.1"] {
             match yang_version_arg_parse(literal) {
                 Ok((_, v)) => {
                     assert_eq!(v, literal);
@@ -595,7 +1287,7 @@ mod tests {
 
     #[test]
     fn test_yang_version_token_parse() {
-        for (literal, output) in vec![("1", "1"), ("1.1", "1.1"), (r#""1""#, "1")] {
+        for (literal, output) in [("1", "1"), ("1.1", "1.1"), (r#""1""#, "1")] {
             match yang_version_arg_str_parse(literal) {
                 Ok((_, v)) => {
                     assert_eq!(v, output);
@@ -628,6 +1320,27 @@ mod tests {
         assert_eq!(v, "collection abchogehoge");
     }
 
+    #[test]
+    fn test_submodule_parse() {
+        let literal = r#"submodule a-sub {
+          belongs-to a {
+            prefix a;
+          }
+          include a-common {
+            revision-date 2020-01-01;
+          }
+        }"#;
+        let (_, module) = yang_parse(literal).unwrap();
+        assert_eq!(module.belongs_to.as_deref(), Some("a"));
+        assert_eq!(module.prefix, "a");
+        assert_eq!(module.prefix_module("a"), Some("a"));
+        let mut include = IncludeNode::new(String::from("a-common"));
+        include.revision_date = String::from("2020-01-01");
+        assert_eq!(module.includes, vec![include]);
+
+        assert!(yang_parse("submodule a-sub { prefix a; }").is_err());
+    }
+
     #[test]
     fn test_prefix_parse() {
         let literal = r#"prefix if;"#;
@@ -658,6 +1371,31 @@ mod tests {
         assert_eq!(result.1, "\\n");
     }
 
+    #[test]
+    fn test_uses_parse() {
+        let literal =
+            "uses exg:endpoint {\n  refine port { default 443; }\n  refine \"server/address\";\n}";
+        let (rest, node) = uses_parse(literal).unwrap();
+        assert_eq!(rest, "");
+        let uses = match node {
+            Node::Uses(uses) => uses,
+            _ => panic!("uses should be parsed"),
+        };
+        assert_eq!(uses.name, "exg:endpoint");
+        let refines: Vec<(&str, &[String])> = uses
+            .refines
+            .iter()
+            .map(|r| (r.name.as_str(), &r.default[..]))
+            .collect();
+        assert_eq!(
+            refines,
+            vec![
+                ("port", &[String::from("443")][..]),
+                ("server/address", &[][..])
+            ]
+        );
+    }
+
     #[test]
     fn test_boolean_parse() {
         let literal = "true";
@@ -668,6 +1406,10 @@ mod tests {
         let result = boolean_parse(literal);
         assert_eq!(result, Ok(("", false)));
 
+        let literal = "\"false\"";
+        let result = boolean_parse(literal);
+        assert_eq!(result, Ok(("", false)));
+
         let literal = "hoge";
         let result = boolean_parse(literal);
         assert_eq!(result, Err(Err::Error((literal, ErrorKind::Tag))));
@@ -1,4 +1,4 @@
-use crate::modules::{RevisionDateNode, RevisionNode};
+use crate::modules::{RevisionDate, RevisionDateNode, RevisionNode};
 use crate::parser::*;
 use crate::Node;
 
@@ -18,7 +18,11 @@ fn revision_date_parse(s: &str) -> IResult<&str, Node> {
     let (s, month) = take_while_m_n(2, 2, |c: char| c.is_ascii_digit())(s)?;
     let (s, _) = char('-')(s)?;
     let (s, day) = take_while_m_n(2, 2, |c: char| c.is_ascii_digit())(s)?;
-    let n = RevisionNode::new(std::format!("{}-{}-{}", year, month, day));
+    let date = std::format!("{}-{}-{}", year, month, day);
+    if date.parse::<RevisionDate>().is_err() {
+        return Err(Err::Error(make_error(s, ErrorKind::Verify)));
+    }
+    let n = RevisionNode::new(date);
     Ok((s, Node::Revision(Box::new(n))))
 }
 
@@ -34,7 +38,11 @@ fn revision_date_token_parse(s: &str) -> IResult<&str, Node> {
 
 fn revision_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
     let (s, _) = char('{')(s)?;
-    let (s, nodes) = many0(alt((description_parse, reference_parse)))(s)?;
+    let (s, nodes) = many0(alt((
+        description_parse,
+        reference_parse,
+        ext_instance_parse,
+    )))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char('}')(s)?;
     Ok((s, nodes))
@@ -49,7 +57,7 @@ pub fn revision_parse(s: &str) -> IResult<&str, Node> {
     let (s, subs) = alt((revision_sub_parse, semicolon_end_parse))(s)?;
 
     if let Node::Revision(mut node) = v {
-        for sub in &subs {
+        for sub in subs {
             match sub {
                 Node::Description(n) => {
                     node.description = Some(n.name.to_owned());
@@ -57,6 +65,9 @@ pub fn revision_parse(s: &str) -> IResult<&str, Node> {
                 Node::Reference(n) => {
                     node.reference = Some(n.name.to_owned());
                 }
+                Node::ExtInstance(n) => {
+                    node.ext_instances.push(*n);
+                }
                 _ => {}
             }
         }
@@ -72,6 +83,7 @@ pub fn revision_date_stmt_parse(s: &str) -> IResult<&str, Node> {
     let (s, _) = multispace1(s)?;
     let (s, v) = revision_date_token_parse(s)?;
     let (s, _) = multispace0(s)?;
+    let (s, _) = char(';')(s)?;
     if let Node::Revision(n) = v {
         let node = RevisionDateNode::new(n.name);
         Ok((s, Node::RevisionDate(Box::new(node))))
@@ -91,6 +103,7 @@ mod tests {
             name: String::from("2020-08-10"),
             description: None,
             reference: None,
+            ext_instances: vec![],
         };
         let node = Node::Revision(Box::new(n));
 
@@ -107,6 +120,7 @@ mod tests {
             name: String::from("2020-08-11"),
             description: None,
             reference: None,
+            ext_instances: vec![],
         };
         let node = Node::Revision(Box::new(n));
 
@@ -133,6 +147,7 @@ mod tests {
             reference: Some(String::from(
                 "RFC 8343: A YANG Data Model for Interface Management",
             )),
+            ext_instances: vec![],
         };
         let node = Node::Revision(Box::new(n));
 
@@ -150,6 +165,7 @@ mod tests {
             name: String::from("2018-02-20"),
             description: None,
             reference: None,
+            ext_instances: vec![],
         };
         let node = Node::Revision(Box::new(n));
 
@@ -0,0 +1,386 @@
+use super::*;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_until, take_while, take_while1};
+use nom::character::complete::{char, multispace0, multispace1, none_of};
+use nom::combinator::{opt, recognize};
+use nom::error::{make_error, ErrorKind};
+use nom::multi::many0;
+use nom::sequence::pair;
+use nom::{Err, IResult};
+use thiserror::Error;
+
+// RFC7950 6.3.  Statements
+//     A YANG module contains a sequence of statements.  Each statement
+//     starts with a keyword, followed by zero or one argument, followed
+//     either by a semicolon (";") or a block of substatements enclosed
+//     within braces ("{ }").
+//
+// Generic statement tree of YANG text.  "argument" is the value after
+// quoting, concatenation and escape processing.  "comments" are the
+// comments before the statement and "end_comments" are the ones after the
+// last substatement.  "line_comment" is the comment on the same line after
+// the ";" or "}" of the statement, and "open_comment" the one after the
+// "{".  The text of a comment includes the "//" or "/* */" delimiters.
+//
+// "argument_parts" are the quoted strings of a concatenated argument with
+// the comments after each of them.  They are kept only when the
+// concatenation has comments, so that the strings are not joined.
+#[derive(Debug, Clone, PartialEq, new)]
+pub struct Stmt {
+    pub keyword: String,
+    pub argument: Option<String>,
+    #[new(default)]
+    pub substmts: Vec<Stmt>,
+    #[new(default)]
+    pub comments: Vec<String>,
+    #[new(default)]
+    pub end_comments: Vec<String>,
+    #[new(default)]
+    pub line_comment: Option<String>,
+    #[new(default)]
+    pub open_comment: Option<String>,
+    #[new(default)]
+    pub argument_parts: Vec<(String, Vec<String>)>,
+}
+
+impl Stmt {
+    pub fn arg(keyword: &str, argument: &str) -> Self {
+        Self::new(keyword.to_owned(), Some(argument.to_owned()))
+    }
+
+    // First substatement of "keyword".
+    pub fn substmt(&self, keyword: &str) -> Option<&Stmt> {
+        self.substmts.iter().find(|s| s.keyword == keyword)
+    }
+
+    // Extension instance such as "nacm:default-deny-write".
+    pub fn is_ext_instance(&self) -> bool {
+        self.keyword.contains(':')
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum StmtError {
+    #[error("syntax error at line {0} column {1}")]
+    Syntax(usize, usize),
+}
+
+// Tab characters are counted as 8 spaces.
+const TAB_WIDTH: usize = 8;
+
+fn text_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+// Strip the indentation of the lines after the first one up to "column".
+fn strip_indent(text: &str, column: usize) -> String {
+    let mut lines = text.split('\n');
+    let mut out = String::from(lines.next().unwrap_or_default());
+    for line in lines {
+        out.push('\n');
+        let mut width = 0;
+        let mut start = line.len();
+        for (i, c) in line.char_indices() {
+            if width >= column {
+                start = i;
+                break;
+            }
+            match c {
+                ' ' => width += 1,
+                '\t' => width += TAB_WIDTH,
+                _ => {
+                    start = i;
+                    break;
+                }
+            }
+        }
+        // Part of the tab beyond the column remains as spaces.
+        if width > column {
+            out.push_str(&" ".repeat(width - column));
+        }
+        out.push_str(&line[start..]);
+    }
+    out
+}
+
+// RFC7950 6.1.3.  Quoting
+//     If a double-quoted string contains a line break followed by space or
+//     tab characters that are used to indent the text according to the
+//     layout in the YANG file, this leading whitespace is stripped from
+//     the string, up to and including the column of the starting double
+//     quote character, or to the first non-whitespace character, whichever
+//     occurs first.  Any tab character in a succeeding line that must be
+//     examined for stripping is first converted into 8 space characters.
+//
+//     If a double-quoted string contains space or tab characters before a
+//     line break, this trailing whitespace is stripped from the string.
+fn double_quoted_value(body: &str, column: usize) -> String {
+    let last = body.matches('\n').count();
+    let trimmed: Vec<&str> = body
+        .split('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            if i < last {
+                line.trim_end_matches([' ', '\t'])
+            } else {
+                line
+            }
+        })
+        .collect();
+    unescape(&strip_indent(&trimmed.join("\n"), column + 1))
+}
+
+struct StmtParser<'a> {
+    input: &'a str,
+}
+
+impl<'a> StmtParser<'a> {
+    // Line and column of "s" in the input, both start at 1.
+    fn position(&self, s: &str) -> (usize, usize) {
+        let offset = self.input.len() - s.len();
+        let before = &self.input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (
+            before.matches('\n').count() + 1,
+            text_width(&before[line_start..]) + 1,
+        )
+    }
+
+    // Whitespace and comments.  Continuation lines of block comments are
+    // stripped of the indentation up to the column of the comment.
+    fn space(&self, s: &'a str) -> IResult<&'a str, Vec<String>> {
+        let mut comments = vec![];
+        let (mut s, _) = multispace0(s)?;
+        loop {
+            if s.starts_with("//") {
+                let (rest, comment) = take_while(|c| c != '\n')(s)?;
+                comments.push(comment.trim_end().to_owned());
+                s = rest;
+            } else if s.starts_with("/*") {
+                let column = self.position(s).1 - 1;
+                let (rest, comment) =
+                    recognize(pair(tag("/*"), pair(take_until("*/"), tag("*/"))))(s)?;
+                comments.push(strip_indent(comment, column));
+                s = rest;
+            } else {
+                break;
+            }
+            s = multispace0(s)?.0;
+        }
+        Ok((s, comments))
+    }
+
+    // Comment which ends the line after the ";", "{" or "}".
+    fn line_comment(&self, s: &'a str) -> IResult<&'a str, Option<String>> {
+        let (rest, _) = take_while(|c| c == ' ' || c == '\t')(s)?;
+        if rest.starts_with("//") {
+            let (rest, comment) = take_while(|c| c != '\n')(rest)?;
+            return Ok((rest, Some(comment.trim_end().to_owned())));
+        }
+        if rest.starts_with("/*") {
+            let (rest, comment) =
+                recognize(pair(tag("/*"), pair(take_until("*/"), tag("*/"))))(rest)?;
+            let (rest, _) = take_while(|c| c == ' ' || c == '\t' || c == '\r')(rest)?;
+            if !comment.contains('\n') && (rest.is_empty() || rest.starts_with('\n')) {
+                return Ok((rest, Some(comment.to_owned())));
+            }
+        }
+        Ok((s, None))
+    }
+
+    fn keyword(&self, s: &'a str) -> IResult<&'a str, &'a str> {
+        recognize(pair(identifier, opt(pair(char(':'), identifier))))(s)
+    }
+
+    fn quoted(&self, s: &'a str) -> IResult<&'a str, String> {
+        if s.starts_with('"') {
+            let column = self.position(s).1 - 1;
+            let (s, _) = char('"')(s)?;
+            let (s, body) = recognize(many0(alt((
+                take_while1(|c| c != '"' && c != '\\'),
+                recognize(pair(char('\\'), none_of(""))),
+            ))))(s)?;
+            let (s, _) = char('"')(s)?;
+            Ok((s, double_quoted_value(body, column)))
+        } else {
+            let (s, _) = char('\'')(s)?;
+            let (s, body) = take_while(|c| c != '\'')(s)?;
+            let (s, _) = char('\'')(s)?;
+            Ok((s, body.to_owned()))
+        }
+    }
+
+    // RFC7950 6.1.3.  Quoting
+    //     A string can be split into multiple quoted strings concatenated
+    //     with "+".
+    //
+    // The strings with the comments after each of them.
+    fn argument(&self, s: &'a str) -> IResult<&'a str, Vec<(String, Vec<String>)>> {
+        if !s.starts_with('"') && !s.starts_with('\'') {
+            let (s, v) =
+                take_while1(|c: char| !c.is_whitespace() && c != ';' && c != '{' && c != '}')(s)?;
+            if v.contains("//") || v.contains("/*") || v.contains('"') || v.contains('\'') {
+                return Err(Err::Error(make_error(s, ErrorKind::Verify)));
+            }
+            return Ok((s, vec![(v.to_owned(), vec![])]));
+        }
+        let (mut s, value) = self.quoted(s)?;
+        let mut parts = vec![(value, vec![])];
+        loop {
+            let (rest, mut comments) = self.space(s)?;
+            if !rest.starts_with('+') {
+                return Ok((s, parts));
+            }
+            let (rest, more) = self.space(&rest[1..])?;
+            let (rest, v) = self.quoted(rest)?;
+            comments.extend(more);
+            if let Some(last) = parts.last_mut() {
+                last.1 = comments;
+            }
+            parts.push((v, vec![]));
+            s = rest;
+        }
+    }
+
+    fn stmt(&self, s: &'a str, comments: Vec<String>) -> IResult<&'a str, Stmt> {
+        let (s, keyword) = self.keyword(s)?;
+        let mut stmt = Stmt::new(keyword.to_owned(), None);
+        stmt.comments = comments;
+        let (s, argument) = opt(pair(multispace1, |s| self.argument(s)))(s)?;
+        // Comments before the ";" or "{" follow the argument.
+        let (s, comments) = self.space(s)?;
+        match argument {
+            Some((_, mut parts)) => {
+                if let Some(last) = parts.last_mut() {
+                    last.1.extend(comments);
+                }
+                stmt.argument = Some(parts.iter().map(|(v, _)| v.as_str()).collect());
+                if parts.iter().any(|(_, c)| !c.is_empty()) {
+                    stmt.argument_parts = parts;
+                }
+            }
+            None => stmt.comments.extend(comments),
+        }
+        if let Ok((s, _)) = char::<_, (&str, ErrorKind)>(';')(s) {
+            let (s, comment) = self.line_comment(s)?;
+            stmt.line_comment = comment;
+            return Ok((s, stmt));
+        }
+        let (s, _) = char('{')(s)?;
+        let (mut s, comment) = self.line_comment(s)?;
+        stmt.open_comment = comment;
+        loop {
+            let (rest, comments) = self.space(s)?;
+            if let Ok((rest, _)) = char::<_, (&str, ErrorKind)>('}')(rest) {
+                stmt.end_comments = comments;
+                let (rest, comment) = self.line_comment(rest)?;
+                stmt.line_comment = comment;
+                return Ok((rest, stmt));
+            }
+            let (rest, sub) = self.stmt(rest, comments)?;
+            stmt.substmts.push(sub);
+            s = rest;
+        }
+    }
+}
+
+// Parse YANG text into the statement tree of the "module" or "submodule"
+// statement.  Comments after the statement are kept in "end_comments".
+pub fn stmt_parse(s: &str) -> Result<Stmt, StmtError> {
+    let parser = StmtParser { input: s };
+    let error = |rest: &str| {
+        let (line, column) = parser.position(rest);
+        StmtError::Syntax(line, column)
+    };
+    let result = parser
+        .space(s)
+        .and_then(|(rest, comments)| parser.stmt(rest, comments))
+        .and_then(|(rest, stmt)| Ok((parser.space(rest)?, stmt)));
+    match result {
+        Ok(((rest, comments), mut stmt)) => {
+            if !rest.is_empty() {
+                return Err(error(rest));
+            }
+            stmt.end_comments.extend(comments);
+            Ok(stmt)
+        }
+        Err(Err::Error((rest, _))) | Err(Err::Failure((rest, _))) => Err(error(rest)),
+        Err(Err::Incomplete(_)) => Err(error("")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stmt_parse_test() {
+        let literal = "// Example.
+module example {
+  prefix ex;
+  description
+    \"First line
+     second line
+       indented\\t\\\"quoted\\\"\";
+  /* Block
+     comment. */
+  leaf a { type string { pattern '[a-z]+' + \"\\\\d\"; } }
+  ex:ext;
+  // End.
+}";
+        let stmt = stmt_parse(literal).unwrap();
+        assert_eq!(stmt.keyword, "module");
+        assert_eq!(stmt.argument.as_deref(), Some("example"));
+        assert_eq!(stmt.comments, vec!["// Example."]);
+        assert_eq!(stmt.end_comments, vec!["// End."]);
+        assert_eq!(
+            stmt.substmt("description").unwrap().argument.as_deref(),
+            Some("First line\nsecond line\n  indented\t\"quoted\"")
+        );
+        let leaf = stmt.substmt("leaf").unwrap();
+        assert_eq!(leaf.comments, vec!["/* Block\n   comment. */"]);
+        let pattern = leaf.substmts[0].substmt("pattern").unwrap();
+        assert_eq!(pattern.argument.as_deref(), Some("[a-z]+\\d"));
+        assert!(stmt.substmts[3].is_ext_instance());
+
+        assert_eq!(
+            stmt_parse("module example {\n  prefix ex\n}"),
+            Err(StmtError::Syntax(3, 1))
+        );
+        assert_eq!(stmt_parse("module a { } }"), Err(StmtError::Syntax(1, 14)));
+    }
+
+    #[test]
+    fn stmt_comments_test() {
+        let literal = "module example { // Open.
+  leaf a { type string; } // A.
+  leaf b { type string; /* B. */ }
+  pattern '[a-z]+' + /* Mid. */ '[0-9]+' // End.
+    ;
+}";
+        let stmt = stmt_parse(literal).unwrap();
+        assert_eq!(stmt.open_comment.as_deref(), Some("// Open."));
+        let a = &stmt.substmts[0];
+        assert_eq!(a.line_comment.as_deref(), Some("// A."));
+        assert_eq!(a.substmts[0].line_comment, None);
+
+        // A block comment is on the same line only when it ends the line.
+        let b = &stmt.substmts[1];
+        assert_eq!(b.substmts[0].line_comment, None);
+        assert_eq!(b.end_comments, vec!["/* B. */"]);
+
+        let pattern = &stmt.substmts[2];
+        assert_eq!(pattern.argument.as_deref(), Some("[a-z]+[0-9]+"));
+        assert_eq!(
+            pattern.argument_parts,
+            vec![
+                (String::from("[a-z]+"), vec![String::from("/* Mid. */")]),
+                (String::from("[0-9]+"), vec![String::from("// End.")]),
+            ]
+        );
+        let leaf = stmt_parse("module a { leaf \"b\" + \"c\"; }").unwrap();
+        assert_eq!(leaf.substmts[0].argument_parts, vec![]);
+    }
+}
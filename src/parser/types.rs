@@ -4,7 +4,11 @@ use crate::Node;
 use nom::branch::alt;
 use nom::bytes::complete::{tag, take_while1};
 use nom::character::complete::{char, multispace0, multispace1};
+use nom::combinator::recognize;
+use nom::error::{make_error, ErrorKind};
 use nom::multi::many0;
+use nom::sequence::delimited;
+use nom::Err;
 use nom::IResult;
 
 // 4.2.4.  Built-In Types
@@ -85,7 +89,7 @@ use nom::IResult;
 // }
 
 fn is_digit_value(c: char) -> bool {
-    c.is_digit(10)
+    c.is_ascii_digit()
 }
 
 fn digit_parse(s: &str) -> IResult<&str, &str> {
@@ -106,9 +110,29 @@ pub fn value_parse(s: &str) -> IResult<&str, Node> {
     Ok((s, Node::ValueNode(Box::new(node))))
 }
 
+fn position_parse(s: &str) -> IResult<&str, Node> {
+    let (s, _) = multispace0(s)?;
+    let (s, _) = tag("position")(s)?;
+    let (s, _) = multispace1(s)?;
+    let (s, v) = alt((double_quoted_string, digit_parse))(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char(';')(s)?;
+    let mut node = BitNode::new(String::from(""));
+    node.position = v.parse::<u32>().ok();
+    Ok((s, Node::Bit(Box::new(node))))
+}
+
 fn enum_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
     let (s, _) = char('{')(s)?;
-    let (s, nodes) = many0(alt((description_parse, value_parse)))(s)?;
+    let (s, nodes) = many0(alt((
+        description_parse,
+        reference_parse,
+        value_parse,
+        position_parse,
+        status_parse,
+        if_feature_parse,
+        ext_instance_parse,
+    )))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char('}')(s)?;
     Ok((s, nodes))
@@ -123,190 +147,238 @@ fn enum_parse(s: &str) -> IResult<&str, Node> {
     let (s, _) = multispace0(s)?;
     let (s, _) = tag("enum")(s)?;
     let (s, _) = multispace1(s)?;
-    let (s, ident) = identifier(s)?;
+    let (s, ident) = string_arg_parse(s)?;
     let (s, _) = multispace0(s)?;
     let (s, sub) = alt((enum_sub_parse, semicolon_end_parse))(s)?;
     let node = EnumNode {
-        name: String::from(ident),
+        name: ident,
         nodes: (sub,),
     };
     Ok((s, Node::EnumNode(Box::new(node))))
 }
 
-fn int_range_parse(s: &str) -> IResult<&str, (&str, &str)> {
+fn bit_parse(s: &str) -> IResult<&str, Node> {
     let (s, _) = multispace0(s)?;
-    let (s, k) = tag("range")(s)?;
+    let (s, _) = tag("bit")(s)?;
     let (s, _) = multispace1(s)?;
-    let (s, v) = double_quoted_string(s)?;
-
+    let (s, ident) = identifier(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, _) = char(';')(s)?;
+    let (s, subs) = alt((enum_sub_parse, semicolon_end_parse))(s)?;
+    let mut node = BitNode::new(String::from(ident));
+    for sub in subs {
+        match sub {
+            Node::Bit(n) => {
+                node.position = n.position;
+            }
+            Node::Description(n) => {
+                node.description = Some(n.name);
+            }
+            _ => {}
+        }
+    }
+    Ok((s, Node::Bit(Box::new(node))))
+}
 
-    Ok((s, (k, v)))
+// Substatements of "type" statement.
+enum TypeSub {
+    Range(String),
+    Length(String),
+    Pattern(PatternNode),
+    Path(String),
+    Enum(EnumNode),
+    Bit(BitNode),
+    Base(String),
+    Type(Box<TypeNode>),
+    FractionDigits(u8),
+    RequireInstance(bool),
+    ExtInstance(ExtInstanceNode),
 }
 
-fn uint_range_parse(s: &str) -> IResult<&str, Node> {
+// Statement with string argument, such as 'range "1..10";', and its
+// substatements.
+fn type_arg_parse<'a>(s: &'a str, key: &str) -> IResult<&'a str, (String, Vec<Node>)> {
     let (s, _) = multispace0(s)?;
-    let (s, _) = tag("range")(s)?;
+    let (s, _) = tag(key)(s)?;
     let (s, _) = multispace1(s)?;
-    let (s, v) = double_quoted_string(s)?;
-    let (_, r) = range_uint_parse(v)?;
+    let (s, v) = string_arg_parse(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, _) = char(';')(s)?;
-    Ok((s, Node::RangeUint(Box::new(r))))
+    let (s, subs) = alt((restriction_sub_parse, semicolon_end_parse))(s)?;
+    Ok((s, (v, subs)))
 }
 
-fn int_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
+// "range", "length" and "pattern" may have description, reference,
+// error-message, error-app-tag and modifier substatements.  Only the
+// modifier of "pattern" is kept in the type, the others are not used for
+// validation.
+fn restriction_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
     let (s, _) = char('{')(s)?;
-    let (s, _) = many0(int_range_parse)(s)?;
+    let (s, nodes) = many0(alt((
+        description_parse,
+        reference_parse,
+        error_message_parse,
+        error_app_tag_parse,
+        modifier_parse,
+        ext_instance_parse,
+    )))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char('}')(s)?;
-    Ok((s, vec![]))
+    Ok((s, nodes))
 }
 
-fn uint_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
-    let (s, _) = char('{')(s)?;
-    let (s, n) = many0(uint_range_parse)(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = char('}')(s)?;
-    Ok((s, n))
+// RFC7950 9.4.6.  The "modifier" Statement
+//     The "modifier" statement, which is an optional substatement to the
+//     "pattern" statement, takes as an argument the string "invert-match".
+fn modifier_parse(s: &str) -> IResult<&str, Node> {
+    let (s, v) = single_statement_parse(s, String::from("modifier"))?;
+    if v != "invert-match" {
+        return Err(Err::Error(make_error(s, ErrorKind::Tag)));
+    }
+    Ok((s, Node::Modifier(Box::new(ModifierNode::new(v)))))
 }
 
-fn pattern_parse(s: &str) -> IResult<&str, (&str, &str)> {
-    let (s, _) = multispace0(s)?;
-    let (s, k) = tag("pattern")(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, _v) = quoted_string_list(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = char(';')(s)?;
-    Ok((s, (k, "")))
+fn range_parse(s: &str) -> IResult<&str, TypeSub> {
+    let (s, (v, _)) = type_arg_parse(s, "range")?;
+    Ok((s, TypeSub::Range(v)))
 }
 
-fn length_parse(s: &str) -> IResult<&str, (&str, &str)> {
-    let (s, _) = multispace0(s)?;
-    let (s, k) = tag("length")(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, v) = double_quoted_string(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = char(';')(s)?;
-    Ok((s, (k, v)))
+fn length_parse(s: &str) -> IResult<&str, TypeSub> {
+    let (s, (v, _)) = type_arg_parse(s, "length")?;
+    Ok((s, TypeSub::Length(v)))
 }
 
-fn path_parse(s: &str) -> IResult<&str, (&str, &str)> {
-    let (s, _) = multispace0(s)?;
-    let (s, k) = tag("path")(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, v) = double_quoted_string(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = char(';')(s)?;
-    Ok((s, (k, v)))
+fn pattern_parse(s: &str) -> IResult<&str, TypeSub> {
+    let (s, (v, subs)) = type_arg_parse(s, "pattern")?;
+    let mut node = PatternNode::new(v);
+    node.invert_match = subs.iter().any(|n| matches!(n, Node::Modifier(_)));
+    Ok((s, TypeSub::Pattern(node)))
 }
 
-fn type_sub_parse(s: &str) -> IResult<&str, Vec<Node>> {
-    let (s, _) = char('{')(s)?;
-    let (s, _) = many0(alt((pattern_parse, length_parse, path_parse)))(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = char('}')(s)?;
-    Ok((s, vec![]))
+fn path_parse(s: &str) -> IResult<&str, TypeSub> {
+    let (s, (v, _)) = type_arg_parse(s, "path")?;
+    Ok((s, TypeSub::Path(v)))
 }
 
-fn type_int_parse(s: &str, type_string: String, type_kind: TypeKind) -> IResult<&str, Node> {
+fn fraction_digits_parse(s: &str) -> IResult<&str, TypeSub> {
     let (s, _) = multispace0(s)?;
-    let (s, _) = tag("type")(s)?;
+    let (s, _) = tag("fraction-digits")(s)?;
     let (s, _) = multispace1(s)?;
-    let (s, _) = tag(type_string.as_str())(s)?;
+    let (s, v) = alt((double_quoted_string, digit_parse))(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, _) = alt((int_sub_parse, semicolon_end_parse))(s)?;
-
-    let node = TypeNode::new(type_kind);
-    Ok((s, Node::Type(Box::new(node))))
+    let (s, _) = char(';')(s)?;
+    match v.parse::<u8>() {
+        Ok(v) => Ok((s, TypeSub::FractionDigits(v))),
+        Err(_) => Err(Err::Error(make_error(s, ErrorKind::Digit))),
+    }
 }
 
-fn type_uint_parse(s: &str, type_string: String, type_kind: TypeKind) -> IResult<&str, Node> {
+fn require_instance_parse(s: &str) -> IResult<&str, TypeSub> {
     let (s, _) = multispace0(s)?;
-    let (s, _) = tag("type")(s)?;
+    let (s, _) = tag("require-instance")(s)?;
     let (s, _) = multispace1(s)?;
-    let (s, _) = tag(type_string.as_str())(s)?;
+    let (s, v) = boolean_parse(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, mut subs) = alt((uint_sub_parse, semicolon_end_parse))(s)?;
-    let mut node = TypeNode::new(type_kind);
-    while let Some(sub) = subs.pop() {
-        match sub {
-            Node::RangeUint(n) => {
-                node.range_uint = Some(*n);
-            }
-            _ => {}
-        }
-    }
-    Ok((s, Node::Type(Box::new(node))))
-}
-
-fn type_int8_parse(s: &str) -> IResult<&str, Node> {
-    type_int_parse(s, String::from("int8"), TypeKind::Yint8)
-}
-
-fn type_int16_parse(s: &str) -> IResult<&str, Node> {
-    type_int_parse(s, String::from("int16"), TypeKind::Yint16)
-}
-
-fn type_int32_parse(s: &str) -> IResult<&str, Node> {
-    type_int_parse(s, String::from("int32"), TypeKind::Yint32)
-}
-
-fn type_int64_parse(s: &str) -> IResult<&str, Node> {
-    type_int_parse(s, String::from("int64"), TypeKind::Yint64)
-}
-
-fn type_uint8_parse(s: &str) -> IResult<&str, Node> {
-    type_uint_parse(s, String::from("uint8"), TypeKind::Yuint8)
-}
-
-fn type_uint16_parse(s: &str) -> IResult<&str, Node> {
-    type_uint_parse(s, String::from("uint16"), TypeKind::Yuint16)
+    let (s, _) = char(';')(s)?;
+    Ok((s, TypeSub::RequireInstance(v)))
 }
 
-fn type_uint32_parse(s: &str) -> IResult<&str, Node> {
-    type_uint_parse(s, String::from("uint32"), TypeKind::Yuint32)
+fn type_sub_stmt_parse(s: &str) -> IResult<&str, TypeSub> {
+    alt((
+        range_parse,
+        length_parse,
+        pattern_parse,
+        path_parse,
+        fraction_digits_parse,
+        require_instance_parse,
+        |s| {
+            let (s, n) = enum_parse(s)?;
+            match n {
+                Node::EnumNode(n) => Ok((s, TypeSub::Enum(*n))),
+                _ => Err(Err::Error(make_error(s, ErrorKind::Fix))),
+            }
+        },
+        |s| {
+            let (s, n) = bit_parse(s)?;
+            match n {
+                Node::Bit(n) => Ok((s, TypeSub::Bit(*n))),
+                _ => Err(Err::Error(make_error(s, ErrorKind::Fix))),
+            }
+        },
+        |s| {
+            let (s, n) = base_parse(s)?;
+            match n {
+                Node::Base(n) => Ok((s, TypeSub::Base(n.name))),
+                _ => Err(Err::Error(make_error(s, ErrorKind::Fix))),
+            }
+        },
+        |s| {
+            let (s, n) = ext_instance_parse(s)?;
+            match n {
+                Node::ExtInstance(n) => Ok((s, TypeSub::ExtInstance(*n))),
+                _ => Err(Err::Error(make_error(s, ErrorKind::Fix))),
+            }
+        },
+        |s| {
+            let (s, n) = type_stmt_parse(s)?;
+            Ok((s, TypeSub::Type(Box::new(n))))
+        },
+    ))(s)
 }
 
-fn type_uint64_parse(s: &str) -> IResult<&str, Node> {
-    type_uint_parse(s, String::from("uint64"), TypeKind::Yuint64)
+fn type_sub_parse(s: &str) -> IResult<&str, Vec<TypeSub>> {
+    let (s, _) = char('{')(s)?;
+    let (s, subs) = many0(type_sub_stmt_parse)(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, _) = char('}')(s)?;
+    Ok((s, subs))
 }
 
-fn type_string_parse(s: &str) -> IResult<&str, Node> {
-    let (s, _) = multispace0(s)?;
-    let (s, _) = tag("type")(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, _) = tag("string")(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = alt((type_sub_parse, semicolon_end_parse))(s)?;
-    Ok((s, Node::EmptyNode))
+fn type_sub_end_parse(s: &str) -> IResult<&str, Vec<TypeSub>> {
+    let (s, _) = tag(";")(s)?;
+    Ok((s, vec![]))
 }
 
-fn type_boolean_parse(s: &str) -> IResult<&str, Node> {
-    let (s, _) = multispace0(s)?;
-    let (s, _) = tag("type")(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, _) = tag("boolean")(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = alt((type_sub_parse, semicolon_end_parse))(s)?;
-    Ok((s, Node::EmptyNode))
+fn type_name_parse(s: &str) -> IResult<&str, &str> {
+    alt((recognize(path_identifier), identifier))(s)
 }
 
-fn type_enumeration_parse(s: &str) -> IResult<&str, Node> {
+// Generic "type" statement parser.
+pub fn type_stmt_parse(s: &str) -> IResult<&str, TypeNode> {
     let (s, _) = multispace0(s)?;
     let (s, _) = tag("type")(s)?;
     let (s, _) = multispace1(s)?;
-    let (s, _) = tag("enumeration")(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = char('{')(s)?;
-    let (s, enums) = many0(enum_parse)(s)?;
+    let (s, name) = alt((
+        delimited(char('"'), type_name_parse, char('"')),
+        type_name_parse,
+    ))(s)?;
     let (s, _) = multispace0(s)?;
-    let (s, _) = char('}')(s)?;
-
-    let node = EnumerationNode::new(enums);
+    let (s, subs) = alt((type_sub_parse, type_sub_end_parse))(s)?;
 
-    Ok((s, Node::EnumerationNode(Box::new(node))))
+    let mut node = TypeNode::new(TypeKind::from_name(name));
+    node.name = name.to_owned();
+    for sub in subs {
+        match sub {
+            TypeSub::Range(v) => {
+                if node.kind.is_int() {
+                    node.range_int = range_int_parse(&v).ok().map(|(_, r)| r);
+                }
+                if node.kind.is_uint() {
+                    node.range_uint = range_uint_parse(&v).ok().map(|(_, r)| r);
+                }
+                node.range = Some(v);
+            }
+            TypeSub::Length(v) => node.length = Some(v),
+            TypeSub::Pattern(v) => node.patterns.push(v),
+            TypeSub::Path(v) => node.path = Some(v),
+            TypeSub::Enum(v) => node.enums.push(v),
+            TypeSub::Bit(v) => node.bits.push(v),
+            TypeSub::Base(v) => node.bases.push(v),
+            TypeSub::Type(v) => node.union.push(*v),
+            TypeSub::FractionDigits(v) => node.fraction_digits = Some(v),
+            TypeSub::RequireInstance(v) => node.require_instance = Some(v),
+            TypeSub::ExtInstance(v) => node.ext_instances.push(v),
+        }
+    }
+    Ok((s, node))
 }
 
 // Single statement 'keyword: identity;'
@@ -314,7 +386,7 @@ fn single_identity_parse(s: &str, key: String) -> IResult<&str, &str> {
     let (s, _) = multispace0(s)?;
     let (s, _) = tag(key.as_str())(s)?;
     let (s, _) = multispace1(s)?;
-    let (s, v) = alt((path_identifier, identifier))(s)?;
+    let (s, v) = alt((recognize(path_identifier), identifier))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char(';')(s)?;
     Ok((s, v))
@@ -326,79 +398,17 @@ pub fn base_parse(s: &str) -> IResult<&str, Node> {
     Ok((s, Node::Base(Box::new(node))))
 }
 
-fn type_identityref_parse(s: &str) -> IResult<&str, Node> {
-    let (s, _) = multispace0(s)?;
-    let (s, _) = tag("type")(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, _) = tag("identityref")(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = char('{')(s)?;
-    let (s, enums) = many0(base_parse)(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = char('}')(s)?;
-
-    let node = EnumerationNode::new(enums);
-
-    Ok((s, Node::EnumerationNode(Box::new(node))))
-}
-
-fn type_parse(s: &str) -> IResult<&str, Node> {
-    let (s, _) = multispace0(s)?;
-    let (s, _) = tag("type")(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, _) = path_identifier(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = tag(";")(s)?;
-    Ok((s, Node::EmptyNode))
-}
-
-fn type_union_parse(s: &str) -> IResult<&str, Node> {
-    let (s, _) = multispace0(s)?;
-    let (s, _) = tag("type")(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, _) = tag("union")(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = char('{')(s)?;
-    let (s, enums) = many0(type_parse)(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = char('}')(s)?;
-    let node = EnumerationNode::new(enums);
-    Ok((s, Node::EnumerationNode(Box::new(node))))
-}
-
-fn type_path_identifier_parse(s: &str) -> IResult<&str, Node> {
-    let (s, _) = multispace0(s)?;
-    let (s, _) = tag("type")(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, _) = path_identifier(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = alt((type_sub_parse, semicolon_end_parse))(s)?;
-
-    Ok((s, Node::EmptyNode))
-}
-
-fn type_identifier_parse(s: &str) -> IResult<&str, Node> {
-    let (s, _) = multispace0(s)?;
-    let (s, _) = tag("type")(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, _) = identifier(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = alt((type_sub_parse, semicolon_end_parse))(s)?;
-
-    Ok((s, Node::EmptyNode))
-}
-
-pub fn find_type_node(nodes: &mut Vec<Node>) -> Option<Node> {
-    let index = nodes.iter().position(|x| match x {
-        Node::EnumerationNode(_) => true,
-        _ => false,
-    })?;
-    Some(nodes.swap_remove(index))
+pub fn find_type_node(nodes: &mut Vec<Node>) -> Option<TypeNode> {
+    let index = nodes.iter().position(|x| matches!(x, Node::Type(_)))?;
+    match nodes.swap_remove(index) {
+        Node::Type(n) => Some(*n),
+        _ => None,
+    }
 }
 
 pub fn default_parse(s: &str) -> IResult<&str, Node> {
     let (s, v) = single_statement_parse(s, String::from("default"))?;
-    let n = DefaultNode::new(v.to_owned());
+    let n = DefaultNode::new(v);
     Ok((s, Node::Default(Box::new(n))))
 }
 
@@ -416,68 +426,47 @@ pub fn typedef_parse(s: &str) -> IResult<&str, Node> {
         reference_parse,
         types_parse,
         status_parse,
+        units_parse,
+        ext_instance_parse,
     )))(s)?;
     let (s, _) = multispace0(s)?;
     let (s, _) = char('}')(s)?;
 
-    let node = TypedefNode::new(String::from(ident), find_type_node(&mut nodes));
+    let mut node = TypedefNode::new(String::from(ident), find_type_node(&mut nodes));
+    for n in nodes {
+        match n {
+            Node::Default(n) => node.default = Some(n.name),
+            Node::Description(n) => node.description = Some(n.name),
+            Node::Reference(n) => node.reference = Some(n.name),
+            Node::Status(n) => node.status = Some(n.name),
+            Node::Units(n) => node.units = Some(n.name),
+            Node::ExtInstance(n) => node.ext_instances.push(*n),
+            _ => {}
+        }
+    }
     Ok((s, Node::Typedef(Box::new(node))))
 }
 
 pub fn types_parse(s: &str) -> IResult<&str, Node> {
-    alt((
-        type_int8_parse,
-        type_int16_parse,
-        type_int32_parse,
-        type_int64_parse,
-        type_uint8_parse,
-        type_uint16_parse,
-        type_uint32_parse,
-        type_uint64_parse,
-        type_string_parse,
-        type_boolean_parse,
-        type_enumeration_parse,
-        type_union_parse,
-        type_identifier_parse,
-        type_path_identifier_parse,
-        type_identityref_parse,
-    ))(s)
+    let (s, node) = type_stmt_parse(s)?;
+    Ok((s, Node::Type(Box::new(node))))
 }
 
 // WIP for range match function.
-fn match_uint_range_node(range: &Vec<RangeUint>, n: u64) -> bool {
+fn match_uint_range_node(range: &[RangeUint], n: u64) -> bool {
+    let value = |v: &RangeVal<u64>| match v {
+        RangeVal::<u64>::Val(v) => *v,
+        RangeVal::<u64>::Max => u64::MAX,
+        _ => 0,
+    };
     for r in range {
         if r.end == RangeVal::<u64>::None {
             // Only start exists.
-            let start = match r.start {
-                RangeVal::<u64>::Val(v) => v,
-                RangeVal::<u64>::Min => 0,
-                RangeVal::<u64>::Max => 0,
-                _ => 0,
-            };
-            if n == start {
-                return true;
-            } else {
-                return false;
-            }
-        } else {
-            let start = match r.start {
-                RangeVal::<u64>::Val(v) => v,
-                RangeVal::<u64>::Min => 0,
-                RangeVal::<u64>::Max => 0,
-                _ => 0,
-            };
-            let end = match r.end {
-                RangeVal::<u64>::Val(v) => v,
-                RangeVal::<u64>::Min => 0,
-                RangeVal::<u64>::Max => 0,
-                _ => 0,
-            };
-            if start <= n && n <= end {
+            if n == value(&r.start) {
                 return true;
-            } else {
-                return false;
             }
+        } else if value(&r.start) <= n && n <= value(&r.end) {
+            return true;
         }
     }
     false
@@ -490,19 +479,33 @@ pub fn match_node(node: &TypeNode, s: &str) -> bool {
     }
     if let Ok(v) = s.parse::<u64>() {
         if let Some(range) = &node.range_uint {
-            return match_uint_range_node(&range, v);
+            match_uint_range_node(range, v)
         } else {
             // TODO Type value range check.
-            return false;
+            false
         }
     } else {
-        return false;
+        false
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nom::combinator::verify;
+
+    fn type_kind_parse(s: &str, type_kind: TypeKind) -> IResult<&str, Node> {
+        let (s, node) = verify(type_stmt_parse, |n: &TypeNode| n.kind == type_kind)(s)?;
+        Ok((s, Node::Type(Box::new(node))))
+    }
+
+    fn type_uint8_parse(s: &str) -> IResult<&str, Node> {
+        type_kind_parse(s, TypeKind::Yuint8)
+    }
+
+    fn type_identityref_parse(s: &str) -> IResult<&str, Node> {
+        type_kind_parse(s, TypeKind::Yidentityref)
+    }
 
     #[test]
     fn yang_type() {
@@ -543,12 +546,9 @@ mod tests {
             range "0..63";
         }"#;
         let (_, result) = type_uint8_parse(literal).unwrap();
-        match result {
-            Node::Type(t) => {
-                println!("test_uint8_parse {:?}", t);
-                assert_eq!(match_node(&t, "10"), true);
-            }
-            _ => {}
+        if let Node::Type(t) = result {
+            println!("test_uint8_parse {:?}", t);
+            assert!(match_node(&t, "10"));
         }
     }
 }
@@ -0,0 +1,446 @@
+use super::*;
+use crate::data::{xml_parse, DataError, XmlAttribute, XmlElement};
+use crate::modules::*;
+use std::collections::HashMap;
+use thiserror::Error;
+
+// RFC7950 13.  YIN
+//     A YANG module can be translated into an alternative XML-based syntax
+//     called YIN.
+//
+//     The YIN namespace is "urn:ietf:params:xml:ns:yang:yin:1".
+pub const YIN_NAMESPACE: &str = "urn:ietf:params:xml:ns:yang:yin:1";
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum YinError {
+    #[error("XML: {0}")]
+    Xml(#[from] DataError),
+    #[error("not a YIN module")]
+    NotYin,
+    #[error("unknown statement \"{0}\"")]
+    UnknownStatement(String),
+    #[error("missing argument \"{1}\" of \"{0}\"")]
+    MissingArgument(String, String),
+    #[error("unknown prefix \"{0}\"")]
+    UnknownPrefix(String),
+    #[error("YANG syntax error at \"{0}\"")]
+    Yang(String),
+}
+
+// RFC7950 13.1.  Formal YIN Definition
+//
+// +------------------+---------------+-------------+
+// | keyword          | argument name | yin-element |
+// +------------------+---------------+-------------+
+//
+// Argument name and yin-element of the keyword, None for the statements
+// without argument.
+fn yin_argument(keyword: &str) -> Option<Option<(&'static str, bool)>> {
+    let arg = match keyword {
+        "input" | "output" => return Some(None),
+        "action" | "anydata" | "anyxml" | "argument" | "base" | "bit" | "case" | "choice"
+        | "container" | "enum" | "extension" | "feature" | "grouping" | "identity"
+        | "if-feature" | "leaf" | "leaf-list" | "list" | "module" | "notification" | "rpc"
+        | "submodule" | "type" | "typedef" | "units" | "uses" => ("name", false),
+        "augment" | "deviation" | "refine" => ("target-node", false),
+        "belongs-to" | "import" | "include" => ("module", false),
+        "config" | "default" | "deviate" | "error-app-tag" | "fraction-digits" | "key"
+        | "length" | "mandatory" | "max-elements" | "min-elements" | "modifier" | "ordered-by"
+        | "path" | "pattern" | "position" | "prefix" | "presence" | "range"
+        | "require-instance" | "status" | "value" | "yang-version" | "yin-element" => {
+            ("value", false)
+        }
+        "contact" | "description" | "organization" | "reference" => ("text", true),
+        "error-message" => ("value", true),
+        "must" | "when" => ("condition", false),
+        "namespace" => ("uri", false),
+        "revision" | "revision-date" => ("date", false),
+        "unique" => ("tag", false),
+        _ => return None,
+    };
+    Some(Some(arg))
+}
+
+// Namespaces of the modules and arguments of the extensions which are
+// needed to map extension instances.  Extensions defined in the converted
+// module itself are always known.
+//
+// RFC7950 13.1.  Formal YIN Definition
+//     If the extension statement has an argument, the argument is mapped
+//     to an XML attribute or element by the "argument" and "yin-element"
+//     statements of the extension definition.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct YinContext {
+    pub namespaces: HashMap<String, String>,
+    pub extensions: HashMap<(String, String), Option<ArgumentNode>>,
+}
+
+impl YinContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_modules(ms: &Modules) -> Self {
+        let mut ctx = Self::new();
+        for module in ms.modules.values() {
+            ctx.add_module(module);
+        }
+        ctx
+    }
+
+    pub fn add_module(&mut self, module: &Module) {
+        if module.belongs_to.is_none() {
+            self.namespaces
+                .insert(module.name.clone(), module.namespace.clone());
+        }
+        for ext in module.extensions.values() {
+            self.extensions.insert(
+                (module.module_name().to_owned(), ext.name.clone()),
+                ext.argument.clone(),
+            );
+        }
+    }
+
+    fn module_of(&self, namespace: &str) -> Option<&str> {
+        self.namespaces
+            .iter()
+            .find(|(_, ns)| *ns == namespace)
+            .map(|(name, _)| name.as_str())
+    }
+
+    // Argument of an extension, None when the extension is unknown.
+    fn extension(&self, module: &str, keyword: &str) -> Option<Option<(&str, bool)>> {
+        self.extensions
+            .get(&(module.to_owned(), keyword.to_owned()))
+            .map(|arg| {
+                arg.as_ref()
+                    .map(|a| (a.name.as_str(), a.yin_element == Some(true)))
+            })
+    }
+}
+
+// Module and prefix declarations of the module statement.
+#[derive(Default)]
+struct Prefixes {
+    module: String,
+    namespace: Option<String>,
+    // Prefix to module name.
+    modules: HashMap<String, String>,
+}
+
+impl Prefixes {
+    fn prefix_of(&self, module: &str) -> Option<&str> {
+        self.modules
+            .iter()
+            .find(|(_, m)| *m == module)
+            .map(|(p, _)| p.as_str())
+    }
+}
+
+impl Stmt {
+    fn prefixes(&self) -> Prefixes {
+        let mut prefixes = Prefixes::default();
+        let arg = |s: Option<&Stmt>| s.and_then(|s| s.argument.clone()).unwrap_or_default();
+        prefixes.module = match self.substmt("belongs-to") {
+            Some(b) => arg(Some(b)),
+            None => self.argument.clone().unwrap_or_default(),
+        };
+        prefixes.namespace = self.substmt("namespace").and_then(|s| s.argument.clone());
+        let own = match self.substmt("belongs-to") {
+            Some(b) => b.substmt("prefix"),
+            None => self.substmt("prefix"),
+        };
+        prefixes.modules.insert(arg(own), prefixes.module.clone());
+        for import in self.substmts.iter().filter(|s| s.keyword == "import") {
+            prefixes
+                .modules
+                .insert(arg(import.substmt("prefix")), arg(Some(import)));
+        }
+        prefixes
+    }
+}
+
+struct YinWriter<'a> {
+    ctx: &'a YinContext,
+    prefixes: Prefixes,
+}
+
+impl YinWriter<'_> {
+    fn namespace(&self, module: &str) -> Option<&str> {
+        if module == self.prefixes.module {
+            if let Some(ns) = &self.prefixes.namespace {
+                return Some(ns);
+            }
+        }
+        self.ctx.namespaces.get(module).map(|ns| ns.as_str())
+    }
+
+    fn element(&self, stmt: &Stmt) -> Result<XmlElement, YinError> {
+        let (mut element, arg) = match stmt.keyword.split_once(':') {
+            Some((prefix, keyword)) => {
+                let module = self
+                    .prefixes
+                    .modules
+                    .get(prefix)
+                    .ok_or_else(|| YinError::UnknownPrefix(prefix.to_owned()))?;
+                if self.namespace(module).is_none() {
+                    return Err(YinError::UnknownPrefix(prefix.to_owned()));
+                }
+                let mut element = XmlElement::new(keyword.to_owned());
+                element.prefix = Some(prefix.to_owned());
+                // The argument of an unknown extension is kept in the
+                // "value" attribute.
+                let arg = self
+                    .ctx
+                    .extension(module, keyword)
+                    .map(|a| a.map(|(n, y)| (n.to_owned(), y)))
+                    .unwrap_or_else(|| {
+                        stmt.argument
+                            .as_ref()
+                            .map(|_| (String::from("value"), false))
+                    });
+                (element, arg)
+            }
+            None => {
+                let arg = yin_argument(&stmt.keyword)
+                    .ok_or_else(|| YinError::UnknownStatement(stmt.keyword.clone()))?;
+                let element = XmlElement::new(stmt.keyword.clone());
+                (element, arg.map(|(n, y)| (n.to_owned(), y)))
+            }
+        };
+        match (arg, &stmt.argument) {
+            (Some((name, true)), Some(value)) => {
+                let mut child = XmlElement::new(name);
+                child.prefix = element.prefix.clone();
+                child.text = value.clone();
+                element.children.push(child);
+            }
+            (Some((name, false)), Some(value)) => {
+                let attribute = XmlAttribute::new(name, None, value.clone());
+                element.attributes.push(attribute);
+            }
+            (Some((name, _)), None) => {
+                return Err(YinError::MissingArgument(stmt.keyword.clone(), name));
+            }
+            (None, _) => {}
+        }
+        for sub in stmt.substmts.iter() {
+            element.children.push(self.element(sub)?);
+        }
+        Ok(element)
+    }
+}
+
+// YIN document of the statement tree.  Comments are not kept.
+pub fn yin_write(stmt: &Stmt, ctx: &YinContext) -> Result<String, YinError> {
+    let mut ctx = ctx.clone();
+    let prefixes = stmt.prefixes();
+    for ext in stmt.substmts.iter().filter(|s| s.keyword == "extension") {
+        let arg = ext.substmt("argument").map(|a| {
+            let mut arg = ArgumentNode::new(a.argument.clone().unwrap_or_default());
+            arg.yin_element = a
+                .substmt("yin-element")
+                .map(|y| y.argument.as_deref() == Some("true"));
+            arg
+        });
+        let name = ext.argument.clone().unwrap_or_default();
+        ctx.extensions.insert((prefixes.module.clone(), name), arg);
+    }
+
+    let writer = YinWriter {
+        ctx: &ctx,
+        prefixes,
+    };
+    let mut element = writer.element(stmt)?;
+
+    // RFC7950 13.  YIN
+    //     The YIN version of the module SHOULD declare namespace prefixes
+    //     for the module and the imported modules.
+    element
+        .namespaces
+        .push((String::new(), YIN_NAMESPACE.to_owned()));
+    let mut prefixes: Vec<(&String, &String)> = writer.prefixes.modules.iter().collect();
+    prefixes.sort_by_key(|(prefix, module)| (**module != writer.prefixes.module, *prefix));
+    for (prefix, module) in prefixes {
+        if let Some(ns) = writer.namespace(module) {
+            element.namespaces.push((prefix.clone(), ns.to_owned()));
+        }
+    }
+    Ok(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}\n",
+        element.to_string_pretty()
+    ))
+}
+
+fn yin_attribute<'a>(element: &'a XmlElement, name: &str) -> Option<&'a str> {
+    element
+        .attributes
+        .iter()
+        .find(|a| a.prefix.is_none() && a.name == name)
+        .map(|a| a.value.as_str())
+}
+
+struct YinReader<'a> {
+    ctx: &'a YinContext,
+    prefixes: Prefixes,
+}
+
+impl YinReader<'_> {
+    fn stmt(&self, element: &XmlElement) -> Result<Stmt, YinError> {
+        let namespace = element.namespace.as_deref().unwrap_or_default();
+        let (keyword, arg) = if namespace == YIN_NAMESPACE {
+            let arg = yin_argument(&element.name)
+                .ok_or_else(|| YinError::UnknownStatement(element.name.clone()))?;
+            (element.name.clone(), arg.map(|(n, y)| (n.to_owned(), y)))
+        } else {
+            // Extension instance.  The prefix is the one of the module in
+            // YANG, which may differ from the XML prefix.
+            let module = match &self.prefixes.namespace {
+                Some(ns) if ns == namespace => Some(self.prefixes.module.as_str()),
+                _ => self.ctx.module_of(namespace),
+            };
+            let prefix = module
+                .and_then(|m| self.prefixes.prefix_of(m))
+                .or(element.prefix.as_deref())
+                .ok_or_else(|| YinError::UnknownStatement(element.name.clone()))?;
+            let arg = match module.and_then(|m| self.ctx.extension(m, &element.name)) {
+                Some(arg) => arg.map(|(n, y)| (n.to_owned(), y)),
+                None => element
+                    .attributes
+                    .iter()
+                    .find(|a| a.prefix.is_none())
+                    .map(|a| (a.name.clone(), false)),
+            };
+            (format!("{}:{}", prefix, element.name), arg)
+        };
+
+        let mut stmt = Stmt::new(keyword, None);
+        let mut arg_element = None;
+        if let Some((name, yin_element)) = arg {
+            stmt.argument = if yin_element {
+                arg_element = element
+                    .children
+                    .iter()
+                    .position(|c| c.name == name && c.namespace == element.namespace);
+                arg_element.map(|i| element.children[i].text.clone())
+            } else {
+                yin_attribute(element, &name).map(String::from)
+            };
+            if stmt.argument.is_none() {
+                return Err(YinError::MissingArgument(stmt.keyword, name));
+            }
+        }
+        for (i, child) in element.children.iter().enumerate() {
+            if Some(i) != arg_element {
+                stmt.substmts.push(self.stmt(child)?);
+            }
+        }
+        Ok(stmt)
+    }
+}
+
+// Prefixes and extension definitions of the YIN module element.
+fn yin_prefixes(root: &XmlElement, ctx: &mut YinContext) -> Prefixes {
+    let mut prefixes = Prefixes::default();
+    let yin = |e: &XmlElement| -> Option<String> {
+        match yin_argument(&e.name).flatten()? {
+            (name, true) => e.child(name).map(|c| c.text.clone()),
+            (name, false) => yin_attribute(e, name).map(String::from),
+        }
+    };
+    let children = |name: &'static str| {
+        root.children
+            .iter()
+            .filter(move |c| c.name == name && c.namespace.as_deref() == Some(YIN_NAMESPACE))
+    };
+    let belongs_to = children("belongs-to").next();
+    prefixes.module = match belongs_to {
+        Some(b) => yin(b),
+        None => yin(root),
+    }
+    .unwrap_or_default();
+    prefixes.namespace = children("namespace").find_map(yin);
+    let own = match belongs_to {
+        Some(b) => b.child("prefix").and_then(yin),
+        None => children("prefix").find_map(yin),
+    };
+    prefixes
+        .modules
+        .insert(own.unwrap_or_default(), prefixes.module.clone());
+    for import in children("import") {
+        let prefix = import.child("prefix").and_then(yin);
+        if let (Some(prefix), Some(module)) = (prefix, yin(import)) {
+            prefixes.modules.insert(prefix, module);
+        }
+    }
+    for ext in children("extension") {
+        let arg = ext.child("argument").and_then(|a| {
+            let mut arg = ArgumentNode::new(yin(a)?);
+            arg.yin_element = a.child("yin-element").and_then(yin).map(|v| v == "true");
+            Some(arg)
+        });
+        if let Some(name) = yin(ext) {
+            ctx.extensions.insert((prefixes.module.clone(), name), arg);
+        }
+    }
+    prefixes
+}
+
+// Parse a YIN document into the statement tree of the "module" or
+// "submodule" statement.
+pub fn yin_parse(s: &str, ctx: &YinContext) -> Result<Stmt, YinError> {
+    let root = match xml_parse(s)?.into_iter().next() {
+        Some(root) => root,
+        None => return Err(YinError::NotYin),
+    };
+    if root.namespace.as_deref() != Some(YIN_NAMESPACE)
+        || (root.name != "module" && root.name != "submodule")
+    {
+        return Err(YinError::NotYin);
+    }
+    let mut ctx = ctx.clone();
+    let prefixes = yin_prefixes(&root, &mut ctx);
+    let reader = YinReader {
+        ctx: &ctx,
+        prefixes,
+    };
+    reader.stmt(&root)
+}
+
+// Parse a YIN document into the module, which is the same as the one of
+// "yang_parse" for the YANG version of the module.
+pub fn yin_parse_module(s: &str, ctx: &YinContext) -> Result<Module, YinError> {
+    let stmt = yin_parse(s, ctx)?;
+    let opts = FormatOptions {
+        canonical: false,
+        ..Default::default()
+    };
+    let text = stmt.to_yang(&opts);
+    match yang_parse(&text) {
+        Ok((_, module)) => Ok(module),
+        Err(nom::Err::Error((rest, _))) | Err(nom::Err::Failure((rest, _))) => {
+            Err(YinError::Yang(rest.chars().take(32).collect()))
+        }
+        Err(nom::Err::Incomplete(_)) => Err(YinError::Yang(String::new())),
+    }
+}
+
+impl Module {
+    // YIN document of the parsed module.
+    pub fn to_yin(&self, ctx: &YinContext) -> Result<String, YinError> {
+        yin_write(&self.to_stmt(), ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yin_argument_test() {
+        assert_eq!(yin_argument("description"), Some(Some(("text", true))));
+        assert_eq!(yin_argument("augment"), Some(Some(("target-node", false))));
+        assert_eq!(yin_argument("input"), Some(None));
+        assert_eq!(yin_argument("ex:ext"), None);
+    }
+}
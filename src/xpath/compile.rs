@@ -0,0 +1,498 @@
+use super::*;
+use crate::parser::{identifier, is_identifier};
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_until};
+use nom::character::complete::{anychar, char, digit0, digit1, multispace0};
+use nom::combinator::{map, not, opt, peek, recognize, verify};
+use nom::multi::{many0, separated_list};
+use nom::sequence::{delimited, pair, preceded};
+use nom::IResult;
+use std::collections::HashMap;
+
+// XPath 1.0 3.7.  Lexical Structure
+//     If there is a preceding token and the preceding token is not one of
+//     @, ::, (, [, , or an Operator, then a * must be recognized as a
+//     MultiplyOperator and an NCName must be recognized as an
+//     OperatorName.
+//
+// The recursive descent parser below only looks for operators after an
+// operand, so the rule above holds without a separate tokenizer.
+
+fn token<'a>(t: &'static str) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    preceded(multispace0, tag(t))
+}
+
+// Operator name such as "and" which must not be a prefix of a longer name.
+fn operator_name<'a>(t: &'static str) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    move |s: &'a str| {
+        let (s, v) = token(t)(s)?;
+        let (s, _) = not(peek(verify(anychar, |c: &char| is_identifier(*c))))(s)?;
+        Ok((s, v))
+    }
+}
+
+fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+    Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+}
+
+// Left associative binary operators.
+fn binary_parse<'a>(
+    s: &'a str,
+    operand: fn(&'a str) -> IResult<&'a str, Expr>,
+    operator: fn(&'a str) -> IResult<&'a str, BinaryOp>,
+) -> IResult<&'a str, Expr> {
+    let (mut s, mut lhs) = operand(s)?;
+    while let Ok((rest, op)) = operator(s) {
+        let (rest, rhs) = operand(rest)?;
+        lhs = binary(op, lhs, rhs);
+        s = rest;
+    }
+    Ok((s, lhs))
+}
+
+fn qname_parse(s: &str) -> IResult<&str, QName> {
+    let (s, first) = identifier(s)?;
+    let (s, second) = opt(preceded(char(':'), identifier))(s)?;
+    match second {
+        Some(name) => Ok((s, QName::new(Some(first), name))),
+        None => Ok((s, QName::new(None, first))),
+    }
+}
+
+fn literal_parse(s: &str) -> IResult<&str, String> {
+    let (s, _) = multispace0(s)?;
+    let (s, v) = alt((
+        delimited(char('"'), take_until("\""), char('"')),
+        delimited(char('\''), take_until("'"), char('\'')),
+    ))(s)?;
+    Ok((s, String::from(v)))
+}
+
+fn number_parse(s: &str) -> IResult<&str, Expr> {
+    let (s, _) = multispace0(s)?;
+    let (s, v) = alt((
+        recognize(pair(digit1, opt(pair(char('.'), digit0)))),
+        recognize(pair(char('.'), digit1)),
+    ))(s)?;
+    // Digits are always a valid f64.
+    Ok((s, Expr::Number(v.parse::<f64>().unwrap_or(f64::NAN))))
+}
+
+fn variable_parse(s: &str) -> IResult<&str, Expr> {
+    let (s, _) = token("$")(s)?;
+    let (s, name) = qname_parse(s)?;
+    Ok((s, Expr::Variable(name)))
+}
+
+fn is_node_type(name: &QName) -> bool {
+    name.prefix.is_none()
+        && matches!(
+            name.name.as_str(),
+            "node" | "text" | "comment" | "processing-instruction"
+        )
+}
+
+fn function_call_parse(s: &str) -> IResult<&str, Expr> {
+    let (s, _) = multispace0(s)?;
+    let (s, name) = verify(qname_parse, |n: &QName| !is_node_type(n))(s)?;
+    let (s, _) = token("(")(s)?;
+    let (s, args) = separated_list(token(","), expr_parse)(s)?;
+    let (s, _) = token(")")(s)?;
+    Ok((s, Expr::Function(name, args)))
+}
+
+fn primary_parse(s: &str) -> IResult<&str, Expr> {
+    alt((
+        variable_parse,
+        delimited(token("("), expr_parse, token(")")),
+        map(literal_parse, Expr::Literal),
+        number_parse,
+        function_call_parse,
+    ))(s)
+}
+
+fn predicate_parse(s: &str) -> IResult<&str, Expr> {
+    delimited(token("["), expr_parse, token("]"))(s)
+}
+
+fn axis_parse(s: &str) -> IResult<&str, Axis> {
+    let (s, _) = multispace0(s)?;
+    if let Ok((s, _)) = char::<_, (&str, nom::error::ErrorKind)>('@')(s) {
+        return Ok((s, Axis::Attribute));
+    }
+    let named = |s| -> IResult<&str, Axis> {
+        let (s, name) = identifier(s)?;
+        let (s, _) = token("::")(s)?;
+        match Axis::from_name(name) {
+            Some(axis) => Ok((s, axis)),
+            None => Err(nom::Err::Error((s, nom::error::ErrorKind::Verify))),
+        }
+    };
+    match named(s) {
+        Ok((s, axis)) => Ok((s, axis)),
+        Err(_) => Ok((s, Axis::Child)),
+    }
+}
+
+fn node_type_parse(s: &str) -> IResult<&str, NodeTest> {
+    let (s, name) = alt((
+        tag("node"),
+        tag("text"),
+        tag("comment"),
+        tag("processing-instruction"),
+    ))(s)?;
+    let (s, _) = token("(")(s)?;
+    let (s, target) = opt(literal_parse)(s)?;
+    let (s, _) = token(")")(s)?;
+    let test = match name {
+        "node" => NodeTest::Node,
+        "text" => NodeTest::Text,
+        "comment" => NodeTest::Comment,
+        _ => NodeTest::ProcessingInstruction(target),
+    };
+    Ok((s, test))
+}
+
+fn name_test_parse(s: &str) -> IResult<&str, NodeTest> {
+    if let Ok((s, _)) = char::<_, (&str, nom::error::ErrorKind)>('*')(s) {
+        return Ok((s, NodeTest::Wildcard(None)));
+    }
+    let prefixed_wildcard = |s| -> IResult<&str, NodeTest> {
+        let (s, prefix) = identifier(s)?;
+        let (s, _) = tag(":*")(s)?;
+        Ok((s, NodeTest::Wildcard(Some(String::from(prefix)))))
+    };
+    alt((prefixed_wildcard, map(qname_parse, NodeTest::Name)))(s)
+}
+
+fn step_parse(s: &str) -> IResult<&str, Step> {
+    let (s, _) = multispace0(s)?;
+    if let Ok((s, _)) = tag::<_, _, (&str, nom::error::ErrorKind)>("..")(s) {
+        return Ok((s, Step::new(Axis::Parent, NodeTest::Node)));
+    }
+    if let Ok((s, _)) = tag::<_, _, (&str, nom::error::ErrorKind)>(".")(s) {
+        return Ok((s, Step::new(Axis::SelfAxis, NodeTest::Node)));
+    }
+    let (s, axis) = axis_parse(s)?;
+    let (s, _) = multispace0(s)?;
+    let (s, test) = alt((node_type_parse, name_test_parse))(s)?;
+    let (s, predicates) = many0(predicate_parse)(s)?;
+    let step = Step {
+        axis,
+        test,
+        predicates,
+    };
+    Ok((s, step))
+}
+
+// "//" is short for "/descendant-or-self::node()/".
+fn descendant_or_self() -> Step {
+    Step::new(Axis::DescendantOrSelf, NodeTest::Node)
+}
+
+// Steps following "/" or "//" after the first step.
+fn relative_steps_parse(s: &str) -> IResult<&str, Vec<Step>> {
+    let (mut s, mut steps) = (s, vec![]);
+    loop {
+        let (rest, abbreviated) = match token("//")(s) {
+            Ok((rest, _)) => (rest, true),
+            Err(_) => match token("/")(s) {
+                Ok((rest, _)) => (rest, false),
+                Err(_) => return Ok((s, steps)),
+            },
+        };
+        let (rest, step) = step_parse(rest)?;
+        if abbreviated {
+            steps.push(descendant_or_self());
+        }
+        steps.push(step);
+        s = rest;
+    }
+}
+
+fn relative_path_parse(s: &str) -> IResult<&str, Vec<Step>> {
+    let (s, first) = step_parse(s)?;
+    let (s, mut rest) = relative_steps_parse(s)?;
+    rest.insert(0, first);
+    Ok((s, rest))
+}
+
+fn location_path_parse(s: &str) -> IResult<&str, Expr> {
+    if let Ok((s, mut steps)) = preceded(token("//"), relative_path_parse)(s) {
+        steps.insert(0, descendant_or_self());
+        let path = LocationPath {
+            absolute: true,
+            steps,
+        };
+        return Ok((s, Expr::Path(path)));
+    }
+    if let Ok((s, _)) = token("/")(s) {
+        let (s, steps) = opt(relative_path_parse)(s)?;
+        let path = LocationPath {
+            absolute: true,
+            steps: steps.unwrap_or_default(),
+        };
+        return Ok((s, Expr::Path(path)));
+    }
+    let (s, steps) = relative_path_parse(s)?;
+    let path = LocationPath {
+        absolute: false,
+        steps,
+    };
+    Ok((s, Expr::Path(path)))
+}
+
+fn filter_path_parse(s: &str) -> IResult<&str, Expr> {
+    let (s, primary) = primary_parse(s)?;
+    let (s, predicates) = many0(predicate_parse)(s)?;
+    let (s, steps) = relative_steps_parse(s)?;
+    if predicates.is_empty() && steps.is_empty() {
+        Ok((s, primary))
+    } else {
+        Ok((s, Expr::Filter(Box::new(primary), predicates, steps)))
+    }
+}
+
+fn path_expr_parse(s: &str) -> IResult<&str, Expr> {
+    alt((filter_path_parse, location_path_parse))(s)
+}
+
+fn union_parse(s: &str) -> IResult<&str, Expr> {
+    binary_parse(s, path_expr_parse, |s| {
+        map(token("|"), |_| BinaryOp::Union)(s)
+    })
+}
+
+fn unary_parse(s: &str) -> IResult<&str, Expr> {
+    match token("-")(s) {
+        Ok((s, _)) => {
+            let (s, expr) = unary_parse(s)?;
+            Ok((s, Expr::Negate(Box::new(expr))))
+        }
+        Err(_) => union_parse(s),
+    }
+}
+
+fn multiplicative_parse(s: &str) -> IResult<&str, Expr> {
+    binary_parse(s, unary_parse, |s| {
+        alt((
+            map(token("*"), |_| BinaryOp::Multiply),
+            map(operator_name("div"), |_| BinaryOp::Divide),
+            map(operator_name("mod"), |_| BinaryOp::Modulo),
+        ))(s)
+    })
+}
+
+fn additive_parse(s: &str) -> IResult<&str, Expr> {
+    binary_parse(s, multiplicative_parse, |s| {
+        alt((
+            map(token("+"), |_| BinaryOp::Add),
+            map(token("-"), |_| BinaryOp::Subtract),
+        ))(s)
+    })
+}
+
+fn relational_parse(s: &str) -> IResult<&str, Expr> {
+    binary_parse(s, additive_parse, |s| {
+        alt((
+            map(token("<="), |_| BinaryOp::LessEqual),
+            map(token("<"), |_| BinaryOp::Less),
+            map(token(">="), |_| BinaryOp::GreaterEqual),
+            map(token(">"), |_| BinaryOp::Greater),
+        ))(s)
+    })
+}
+
+fn equality_parse(s: &str) -> IResult<&str, Expr> {
+    binary_parse(s, relational_parse, |s| {
+        alt((
+            map(token("="), |_| BinaryOp::Equal),
+            map(token("!="), |_| BinaryOp::NotEqual),
+        ))(s)
+    })
+}
+
+fn and_parse(s: &str) -> IResult<&str, Expr> {
+    binary_parse(s, equality_parse, |s| {
+        map(operator_name("and"), |_| BinaryOp::And)(s)
+    })
+}
+
+fn or_parse(s: &str) -> IResult<&str, Expr> {
+    binary_parse(s, and_parse, |s| {
+        map(operator_name("or"), |_| BinaryOp::Or)(s)
+    })
+}
+
+pub fn expr_parse(s: &str) -> IResult<&str, Expr> {
+    or_parse(s)
+}
+
+impl XPath {
+    // Compile XPath expression.  Prefixes in the expression are bound to
+    // modules when the expression is attached to a module.
+    pub fn compile(text: &str) -> Result<XPath, XPathError> {
+        match expr_parse(text) {
+            Ok((rest, expr)) if rest.trim().is_empty() => Ok(XPath {
+                text: String::from(text),
+                expr,
+                prefixes: HashMap::new(),
+            }),
+            Ok((rest, _)) => Err(XPathError::Syntax(
+                String::from(text),
+                String::from(rest.trim_start()),
+            )),
+            Err(_) => Err(XPathError::Syntax(String::from(text), String::from(text))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(name: &str) -> NodeTest {
+        NodeTest::Name(QName::new(None, name))
+    }
+
+    fn child(n: &str) -> Step {
+        Step::new(Axis::Child, name(n))
+    }
+
+    fn path(absolute: bool, steps: Vec<Step>) -> Expr {
+        Expr::Path(LocationPath { absolute, steps })
+    }
+
+    #[test]
+    fn xpath_path_test() {
+        let xpath = XPath::compile("/if:interfaces/if:interface").unwrap();
+        let steps = vec![
+            Step::new(
+                Axis::Child,
+                NodeTest::Name(QName::new(Some("if"), "interfaces")),
+            ),
+            Step::new(
+                Axis::Child,
+                NodeTest::Name(QName::new(Some("if"), "interface")),
+            ),
+        ];
+        assert_eq!(xpath.expr, path(true, steps));
+
+        let xpath = XPath::compile("../type").unwrap();
+        let steps = vec![Step::new(Axis::Parent, NodeTest::Node), child("type")];
+        assert_eq!(xpath.expr, path(false, steps));
+
+        let xpath = XPath::compile("//name").unwrap();
+        let steps = vec![descendant_or_self(), child("name")];
+        assert_eq!(xpath.expr, path(true, steps));
+
+        let xpath = XPath::compile("ancestor-or-self::node()/@foo").unwrap();
+        let steps = vec![
+            Step::new(Axis::AncestorOrSelf, NodeTest::Node),
+            Step::new(Axis::Attribute, name("foo")),
+        ];
+        assert_eq!(xpath.expr, path(false, steps));
+
+        let xpath = XPath::compile("/").unwrap();
+        assert_eq!(xpath.expr, path(true, vec![]));
+
+        let xpath = XPath::compile("ex:*").unwrap();
+        let steps = vec![Step::new(
+            Axis::Child,
+            NodeTest::Wildcard(Some(String::from("ex"))),
+        )];
+        assert_eq!(xpath.expr, path(false, steps));
+    }
+
+    #[test]
+    fn xpath_predicate_test() {
+        let xpath = XPath::compile("interface[name = current()/../ifname]/mtu").unwrap();
+        let mut step = child("interface");
+        step.predicates.push(binary(
+            BinaryOp::Equal,
+            path(false, vec![child("name")]),
+            Expr::Filter(
+                Box::new(Expr::Function(QName::new(None, "current"), vec![])),
+                vec![],
+                vec![Step::new(Axis::Parent, NodeTest::Node), child("ifname")],
+            ),
+        ));
+        assert_eq!(xpath.expr, path(false, vec![step, child("mtu")]));
+    }
+
+    #[test]
+    fn xpath_operator_test() {
+        // "*" and "div" after an operand are operators, otherwise names.
+        let xpath = XPath::compile("* * div div 2").unwrap();
+        let wildcard = path(
+            false,
+            vec![Step::new(Axis::Child, NodeTest::Wildcard(None))],
+        );
+        let expected = binary(
+            BinaryOp::Divide,
+            binary(
+                BinaryOp::Multiply,
+                wildcard,
+                path(false, vec![child("div")]),
+            ),
+            Expr::Number(2.0),
+        );
+        assert_eq!(xpath.expr, expected);
+
+        // Precedence and associativity.
+        let xpath = XPath::compile("1 - 2 - 3 = -4 or a and b-c").unwrap();
+        let expected = binary(
+            BinaryOp::Or,
+            binary(
+                BinaryOp::Equal,
+                binary(
+                    BinaryOp::Subtract,
+                    binary(BinaryOp::Subtract, Expr::Number(1.0), Expr::Number(2.0)),
+                    Expr::Number(3.0),
+                ),
+                Expr::Negate(Box::new(Expr::Number(4.0))),
+            ),
+            binary(
+                BinaryOp::And,
+                path(false, vec![child("a")]),
+                path(false, vec![child("b-c")]),
+            ),
+        );
+        assert_eq!(xpath.expr, expected);
+
+        let xpath = XPath::compile("count(a | b) <= 'x' != $v").unwrap();
+        let expected = binary(
+            BinaryOp::NotEqual,
+            binary(
+                BinaryOp::LessEqual,
+                Expr::Function(
+                    QName::new(None, "count"),
+                    vec![binary(
+                        BinaryOp::Union,
+                        path(false, vec![child("a")]),
+                        path(false, vec![child("b")]),
+                    )],
+                ),
+                Expr::Literal(String::from("x")),
+            ),
+            Expr::Variable(QName::new(None, "v")),
+        );
+        assert_eq!(xpath.expr, expected);
+    }
+
+    #[test]
+    fn xpath_node_type_name_test() {
+        // Node type names are node names unless followed by "(".
+        let xpath = XPath::compile("text/node()").unwrap();
+        let steps = vec![child("text"), Step::new(Axis::Child, NodeTest::Node)];
+        assert_eq!(xpath.expr, path(false, steps));
+    }
+
+    #[test]
+    fn xpath_syntax_error_test() {
+        assert!(XPath::compile("a[b").is_err());
+        assert!(XPath::compile("a b").is_err());
+        assert!(XPath::compile("foo(").is_err());
+        assert!(XPath::compile("").is_err());
+    }
+}
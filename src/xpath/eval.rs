@@ -0,0 +1,1092 @@
+use super::*;
+use crate::modules::pattern_regex;
+
+// Instance data tree which XPath expressions are evaluated over.  "Node"
+// is a handle of a node in the tree and its ordering must be document
+// order.  The root node is the parent of top level data nodes and has no
+// name.
+//
+// The YANG specific functions use the optional methods.  The default
+// implementations behave as if the data has no such value.
+pub trait Document {
+    type Node: Clone + Ord;
+
+    fn root(&self) -> Self::Node;
+
+    fn parent(&self, node: &Self::Node) -> Option<Self::Node>;
+
+    fn children(&self, node: &Self::Node) -> Vec<Self::Node>;
+
+    // Module name and local name of the node.
+    fn name(&self, node: &Self::Node) -> Option<(&str, &str)>;
+
+    // Value of leaf and leaf-list node in the canonical string form.
+    fn value(&self, node: &Self::Node) -> Option<String>;
+
+    fn namespace(&self, _node: &Self::Node) -> Option<&str> {
+        None
+    }
+
+    // Target nodes of leafref or instance-identifier leaf.
+    fn deref(&self, _node: &Self::Node) -> Vec<Self::Node> {
+        vec![]
+    }
+
+    // Module name and identity name of identityref leaf.
+    fn identity(&self, _node: &Self::Node) -> Option<(String, String)> {
+        None
+    }
+
+    // Whether the identity is derived from the base identity.  Both are
+    // pairs of module name and identity name.
+    fn derived_from(&self, _identity: (&str, &str), _base: (&str, &str)) -> bool {
+        false
+    }
+
+    // Assigned value of enumeration leaf.
+    fn enum_value(&self, _node: &Self::Node) -> Option<i64> {
+        None
+    }
+}
+
+// XPath 1.0 1.  Introduction
+//     Expressions evaluate to yield an object, which has one of the
+//     following four basic types: node-set, boolean, number, string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<N> {
+    Nodes(Vec<N>),
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+// Evaluate the expression with the context node and the "current()" node
+// set to "node".
+pub fn evaluate<D: Document>(
+    doc: &D,
+    xpath: &XPath,
+    node: &D::Node,
+) -> Result<Value<D::Node>, XPathError> {
+    let context = Context {
+        doc,
+        xpath,
+        current: node.clone(),
+    };
+    context.eval(&xpath.expr, node, 1, 1)
+}
+
+// Evaluate the expression and convert the result to boolean as "must" and
+// "when" do.
+pub fn evaluate_bool<D: Document>(
+    doc: &D,
+    xpath: &XPath,
+    node: &D::Node,
+) -> Result<bool, XPathError> {
+    let context = Context {
+        doc,
+        xpath,
+        current: node.clone(),
+    };
+    let value = context.eval(&xpath.expr, node, 1, 1)?;
+    Ok(context.boolean(&value))
+}
+
+struct Context<'a, D: Document> {
+    doc: &'a D,
+    xpath: &'a XPath,
+    current: D::Node,
+}
+
+fn sort_nodes<N: Ord>(nodes: &mut Vec<N>) {
+    nodes.sort();
+    nodes.dedup();
+}
+
+// XPath 1.0 4.4  Number Functions
+//     The number function converts its argument to a number as follows:
+//     a string that consists of optional whitespace followed by an optional
+//     minus sign followed by a Number followed by whitespace is converted
+//     to the IEEE 754 number that is nearest; any other string is
+//     converted to NaN.
+pub fn string_to_number(s: &str) -> f64 {
+    let s = s.trim();
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    let valid = !digits.is_empty()
+        && digits != "."
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.matches('.').count() <= 1;
+    if valid {
+        s.parse::<f64>().unwrap_or(f64::NAN)
+    } else {
+        f64::NAN
+    }
+}
+
+// XPath 1.0 4.2  String Functions
+//     A number is converted to a string as follows: NaN is converted to
+//     the string NaN, positive zero and negative zero are converted to 0,
+//     infinity is converted to Infinity, an integer is represented in
+//     decimal form with no decimal point and other numbers have at least
+//     one digit before and after the decimal point.
+pub fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        String::from("NaN")
+    } else if n.is_infinite() {
+        String::from(if n > 0.0 { "Infinity" } else { "-Infinity" })
+    } else if n == 0.0 {
+        String::from("0")
+    } else {
+        format!("{}", n)
+    }
+}
+
+fn round(n: f64) -> f64 {
+    if n.is_nan() || n.is_infinite() {
+        n
+    } else if (-0.5..0.0).contains(&n) {
+        -0.0
+    } else {
+        (n + 0.5).floor()
+    }
+}
+
+fn compare_numbers(op: BinaryOp, lhs: f64, rhs: f64) -> bool {
+    match op {
+        BinaryOp::Equal => lhs == rhs,
+        BinaryOp::NotEqual => lhs != rhs,
+        BinaryOp::Less => lhs < rhs,
+        BinaryOp::LessEqual => lhs <= rhs,
+        BinaryOp::Greater => lhs > rhs,
+        BinaryOp::GreaterEqual => lhs >= rhs,
+        _ => false,
+    }
+}
+
+fn is_relational(op: BinaryOp) -> bool {
+    matches!(
+        op,
+        BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual
+    )
+}
+
+impl<'a, D: Document> Context<'a, D> {
+    fn string_value(&self, node: &D::Node) -> String {
+        if let Some(value) = self.doc.value(node) {
+            return value;
+        }
+        self.doc
+            .children(node)
+            .iter()
+            .map(|c| self.string_value(c))
+            .collect()
+    }
+
+    fn string(&self, value: &Value<D::Node>) -> String {
+        match value {
+            Value::Nodes(nodes) => match nodes.first() {
+                Some(node) => self.string_value(node),
+                None => String::new(),
+            },
+            Value::Boolean(b) => String::from(if *b { "true" } else { "false" }),
+            Value::Number(n) => number_to_string(*n),
+            Value::String(s) => s.to_owned(),
+        }
+    }
+
+    fn number(&self, value: &Value<D::Node>) -> f64 {
+        match value {
+            Value::Boolean(b) => {
+                if *b {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Value::Number(n) => *n,
+            _ => string_to_number(&self.string(value)),
+        }
+    }
+
+    fn boolean(&self, value: &Value<D::Node>) -> bool {
+        match value {
+            Value::Nodes(nodes) => !nodes.is_empty(),
+            Value::Boolean(b) => *b,
+            Value::Number(n) => *n != 0.0 && !n.is_nan(),
+            Value::String(s) => !s.is_empty(),
+        }
+    }
+
+    fn nodes(&self, value: Value<D::Node>, function: &str) -> Result<Vec<D::Node>, XPathError> {
+        match value {
+            Value::Nodes(nodes) => Ok(nodes),
+            _ => Err(XPathError::NotNodeSet(String::from(function))),
+        }
+    }
+
+    // Module of unprefixed names, which is the module of the current node.
+    fn default_module(&self) -> Option<&str> {
+        self.doc.name(&self.current).map(|(module, _)| module)
+    }
+
+    fn prefix_module(&self, prefix: &str) -> Result<&str, XPathError> {
+        self.xpath
+            .prefix_module(prefix)
+            .ok_or_else(|| XPathError::UnknownPrefix(String::from(prefix)))
+    }
+
+    fn module_match(&self, prefix: &Option<String>, module: &str) -> Result<bool, XPathError> {
+        match prefix {
+            Some(prefix) => Ok(self.prefix_module(prefix)? == module),
+            None => match self.default_module() {
+                Some(default) => Ok(default == module),
+                None => Ok(true),
+            },
+        }
+    }
+
+    fn node_test(&self, test: &NodeTest, node: &D::Node) -> Result<bool, XPathError> {
+        let name = self.doc.name(node);
+        match test {
+            NodeTest::Node => Ok(true),
+            NodeTest::Name(qname) => match name {
+                Some((module, name)) if name == qname.name => {
+                    self.module_match(&qname.prefix, module)
+                }
+                _ => Ok(false),
+            },
+            NodeTest::Wildcard(None) => Ok(name.is_some()),
+            NodeTest::Wildcard(prefix) => match name {
+                Some((module, _)) => self.module_match(prefix, module),
+                None => Ok(false),
+            },
+            // Text, comment and processing instruction nodes are not part of
+            // YANG data tree.
+            _ => Ok(false),
+        }
+    }
+
+    fn descendants(&self, node: &D::Node, nodes: &mut Vec<D::Node>) {
+        for child in self.doc.children(node) {
+            nodes.push(child.clone());
+            self.descendants(&child, nodes);
+        }
+    }
+
+    fn ancestors(&self, node: &D::Node) -> Vec<D::Node> {
+        let mut nodes = vec![];
+        let mut node = self.doc.parent(node);
+        while let Some(n) = node {
+            node = self.doc.parent(&n);
+            nodes.push(n);
+        }
+        nodes
+    }
+
+    // Siblings before and after the node.
+    fn siblings(&self, node: &D::Node) -> (Vec<D::Node>, Vec<D::Node>) {
+        let parent = match self.doc.parent(node) {
+            Some(parent) => parent,
+            None => return (vec![], vec![]),
+        };
+        let mut children = self.doc.children(&parent);
+        match children.iter().position(|c| c == node) {
+            Some(index) => {
+                let following = children.split_off(index + 1);
+                children.pop();
+                (children, following)
+            }
+            None => (vec![], vec![]),
+        }
+    }
+
+    // Nodes on the axis in the axis order, reverse axes return the nearest
+    // node first.
+    fn axis(&self, axis: Axis, node: &D::Node) -> Vec<D::Node> {
+        let mut nodes = vec![];
+        match axis {
+            Axis::Child => nodes = self.doc.children(node),
+            Axis::Descendant => self.descendants(node, &mut nodes),
+            Axis::DescendantOrSelf => {
+                nodes.push(node.clone());
+                self.descendants(node, &mut nodes);
+            }
+            Axis::Parent => nodes.extend(self.doc.parent(node)),
+            Axis::Ancestor => nodes = self.ancestors(node),
+            Axis::AncestorOrSelf => {
+                nodes.push(node.clone());
+                nodes.extend(self.ancestors(node));
+            }
+            Axis::FollowingSibling => nodes = self.siblings(node).1,
+            Axis::PrecedingSibling => {
+                nodes = self.siblings(node).0;
+                nodes.reverse();
+            }
+            Axis::Following => {
+                let mut n = Some(node.clone());
+                while let Some(current) = n {
+                    for sibling in self.siblings(&current).1 {
+                        nodes.push(sibling.clone());
+                        self.descendants(&sibling, &mut nodes);
+                    }
+                    n = self.doc.parent(&current);
+                }
+                sort_nodes(&mut nodes);
+            }
+            Axis::Preceding => {
+                let mut n = Some(node.clone());
+                while let Some(current) = n {
+                    for sibling in self.siblings(&current).0 {
+                        nodes.push(sibling.clone());
+                        self.descendants(&sibling, &mut nodes);
+                    }
+                    n = self.doc.parent(&current);
+                }
+                sort_nodes(&mut nodes);
+                nodes.reverse();
+            }
+            Axis::SelfAxis => nodes.push(node.clone()),
+            // No attribute and namespace nodes in YANG data tree.
+            Axis::Attribute | Axis::Namespace => {}
+        }
+        nodes
+    }
+
+    // Filter nodes by predicates.  Proximity position is the index in
+    // "nodes" which is in the axis order.
+    fn predicates(
+        &self,
+        mut nodes: Vec<D::Node>,
+        predicates: &[Expr],
+    ) -> Result<Vec<D::Node>, XPathError> {
+        for predicate in predicates {
+            let size = nodes.len();
+            let mut filtered = vec![];
+            for (index, node) in nodes.into_iter().enumerate() {
+                let value = self.eval(predicate, &node, index + 1, size)?;
+                let matched = match value {
+                    Value::Number(n) => n == (index + 1) as f64,
+                    _ => self.boolean(&value),
+                };
+                if matched {
+                    filtered.push(node);
+                }
+            }
+            nodes = filtered;
+        }
+        Ok(nodes)
+    }
+
+    fn steps(&self, mut nodes: Vec<D::Node>, steps: &[Step]) -> Result<Vec<D::Node>, XPathError> {
+        for step in steps {
+            let mut result = vec![];
+            for node in nodes.iter() {
+                let mut candidates = vec![];
+                for candidate in self.axis(step.axis, node) {
+                    if self.node_test(&step.test, &candidate)? {
+                        candidates.push(candidate);
+                    }
+                }
+                result.extend(self.predicates(candidates, &step.predicates)?);
+            }
+            sort_nodes(&mut result);
+            nodes = result;
+        }
+        Ok(nodes)
+    }
+
+    fn compare(
+        &self,
+        op: BinaryOp,
+        lhs: &Value<D::Node>,
+        rhs: &Value<D::Node>,
+    ) -> Result<bool, XPathError> {
+        // XPath 1.0 3.4  Booleans
+        //     If both objects to be compared are node-sets, then the
+        //     comparison will be true if and only if there is a node in the
+        //     first node-set and a node in the second node-set such that the
+        //     result of performing the comparison on the string-values of the
+        //     two nodes is true.
+        match (lhs, rhs) {
+            (Value::Nodes(lnodes), Value::Nodes(rnodes)) => {
+                for l in lnodes.iter() {
+                    let lvalue = Value::String(self.string_value(l));
+                    for r in rnodes.iter() {
+                        let rvalue = Value::String(self.string_value(r));
+                        if self.compare(op, &lvalue, &rvalue)? {
+                            return Ok(true);
+                        }
+                    }
+                }
+                Ok(false)
+            }
+            (Value::Nodes(nodes), other) | (other, Value::Nodes(nodes)) => {
+                let swapped = matches!(rhs, Value::Nodes(_));
+                if let Value::Boolean(_) = other {
+                    let value = Value::Boolean(!nodes.is_empty());
+                    return if swapped {
+                        self.compare(op, other, &value)
+                    } else {
+                        self.compare(op, &value, other)
+                    };
+                }
+                for node in nodes.iter() {
+                    let value = match other {
+                        Value::Number(_) => {
+                            Value::Number(string_to_number(&self.string_value(node)))
+                        }
+                        _ => Value::String(self.string_value(node)),
+                    };
+                    let result = if swapped {
+                        self.compare(op, other, &value)?
+                    } else {
+                        self.compare(op, &value, other)?
+                    };
+                    if result {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            _ if is_relational(op) => Ok(compare_numbers(op, self.number(lhs), self.number(rhs))),
+            (Value::Boolean(_), _) | (_, Value::Boolean(_)) => {
+                let equal = self.boolean(lhs) == self.boolean(rhs);
+                Ok(if op == BinaryOp::Equal { equal } else { !equal })
+            }
+            (Value::Number(_), _) | (_, Value::Number(_)) => {
+                Ok(compare_numbers(op, self.number(lhs), self.number(rhs)))
+            }
+            _ => {
+                let equal = self.string(lhs) == self.string(rhs);
+                Ok(if op == BinaryOp::Equal { equal } else { !equal })
+            }
+        }
+    }
+
+    fn binary(
+        &self,
+        op: BinaryOp,
+        lhs: &Expr,
+        rhs: &Expr,
+        node: &D::Node,
+        position: usize,
+        size: usize,
+    ) -> Result<Value<D::Node>, XPathError> {
+        let lvalue = self.eval(lhs, node, position, size)?;
+        match op {
+            BinaryOp::Or => {
+                if self.boolean(&lvalue) {
+                    return Ok(Value::Boolean(true));
+                }
+                let rvalue = self.eval(rhs, node, position, size)?;
+                return Ok(Value::Boolean(self.boolean(&rvalue)));
+            }
+            BinaryOp::And => {
+                if !self.boolean(&lvalue) {
+                    return Ok(Value::Boolean(false));
+                }
+                let rvalue = self.eval(rhs, node, position, size)?;
+                return Ok(Value::Boolean(self.boolean(&rvalue)));
+            }
+            _ => {}
+        }
+        let rvalue = self.eval(rhs, node, position, size)?;
+        let value = match op {
+            BinaryOp::Union => {
+                let mut nodes = self.nodes(lvalue, "|")?;
+                nodes.extend(self.nodes(rvalue, "|")?);
+                sort_nodes(&mut nodes);
+                Value::Nodes(nodes)
+            }
+            BinaryOp::Add => Value::Number(self.number(&lvalue) + self.number(&rvalue)),
+            BinaryOp::Subtract => Value::Number(self.number(&lvalue) - self.number(&rvalue)),
+            BinaryOp::Multiply => Value::Number(self.number(&lvalue) * self.number(&rvalue)),
+            BinaryOp::Divide => Value::Number(self.number(&lvalue) / self.number(&rvalue)),
+            BinaryOp::Modulo => Value::Number(self.number(&lvalue) % self.number(&rvalue)),
+            _ => Value::Boolean(self.compare(op, &lvalue, &rvalue)?),
+        };
+        Ok(value)
+    }
+
+    fn eval(
+        &self,
+        expr: &Expr,
+        node: &D::Node,
+        position: usize,
+        size: usize,
+    ) -> Result<Value<D::Node>, XPathError> {
+        match expr {
+            Expr::Binary(op, lhs, rhs) => self.binary(*op, lhs, rhs, node, position, size),
+            Expr::Negate(expr) => {
+                let value = self.eval(expr, node, position, size)?;
+                Ok(Value::Number(-self.number(&value)))
+            }
+            Expr::Literal(s) => Ok(Value::String(s.to_owned())),
+            Expr::Number(n) => Ok(Value::Number(*n)),
+            // RFC7950 6.4.1.  XPath Context
+            //     The set of variable bindings is empty.
+            Expr::Variable(name) => Err(XPathError::UnknownVariable(name.to_string())),
+            Expr::Function(name, args) => self.function(name, args, node, position, size),
+            Expr::Path(path) => {
+                let start = if path.absolute {
+                    self.doc.root()
+                } else {
+                    node.clone()
+                };
+                Ok(Value::Nodes(self.steps(vec![start], &path.steps)?))
+            }
+            Expr::Filter(primary, predicates, steps) => {
+                let value = self.eval(primary, node, position, size)?;
+                let mut nodes = self.nodes(value, "filter")?;
+                sort_nodes(&mut nodes);
+                let nodes = self.predicates(nodes, predicates)?;
+                Ok(Value::Nodes(self.steps(nodes, steps)?))
+            }
+        }
+    }
+
+    // Resolve identity name in the expression to module name and identity
+    // name.
+    fn identity_arg(&self, name: &str) -> Result<(String, String), XPathError> {
+        match name.split_once(':') {
+            Some((prefix, ident)) => Ok((self.prefix_module(prefix)?.to_owned(), ident.to_owned())),
+            None => {
+                let module = self.default_module().unwrap_or_default();
+                Ok((module.to_owned(), name.to_owned()))
+            }
+        }
+    }
+
+    fn function(
+        &self,
+        name: &QName,
+        args: &[Expr],
+        node: &D::Node,
+        position: usize,
+        size: usize,
+    ) -> Result<Value<D::Node>, XPathError> {
+        let fname = name.to_string();
+        let (min, max) = match fname.as_str() {
+            "last" | "position" | "true" | "false" | "current" => (0, 0),
+            "count" | "id" | "boolean" | "not" | "lang" | "sum" | "floor" | "ceiling" | "round"
+            | "deref" | "enum-value" => (1, 1),
+            "local-name" | "namespace-uri" | "name" | "string" | "string-length"
+            | "normalize-space" | "number" => (0, 1),
+            "starts-with"
+            | "contains"
+            | "substring-before"
+            | "substring-after"
+            | "re-match"
+            | "derived-from"
+            | "derived-from-or-self"
+            | "bit-is-set" => (2, 2),
+            "substring" => (2, 3),
+            "translate" => (3, 3),
+            "concat" => (2, usize::MAX),
+            _ => return Err(XPathError::UnknownFunction(fname)),
+        };
+        if args.len() < min || args.len() > max {
+            return Err(XPathError::Arity(fname));
+        }
+        let mut values = vec![];
+        for arg in args {
+            values.push(self.eval(arg, node, position, size)?);
+        }
+        let context = || Value::Nodes(vec![node.clone()]);
+        let arg_string = |index: usize| match values.get(index) {
+            Some(value) => self.string(value),
+            None => self.string(&context()),
+        };
+        let first_node = |value: Value<D::Node>| -> Result<Option<D::Node>, XPathError> {
+            let mut nodes = self.nodes(value, &fname)?;
+            sort_nodes(&mut nodes);
+            Ok(nodes.into_iter().next())
+        };
+
+        let value = match fname.as_str() {
+            // XPath 1.0 4.1  Node Set Functions
+            "last" => Value::Number(size as f64),
+            "position" => Value::Number(position as f64),
+            "count" => Value::Number(self.nodes(values.remove(0), &fname)?.len() as f64),
+            "id" => Value::Nodes(vec![]),
+            "local-name" | "name" | "namespace-uri" => {
+                let target = match values.pop() {
+                    Some(value) => first_node(value)?,
+                    None => Some(node.clone()),
+                };
+                let s = match target {
+                    Some(target) => match (fname.as_str(), self.doc.name(&target)) {
+                        ("local-name", Some((_, local))) => local.to_owned(),
+                        ("name", Some((module, local))) => format!("{}:{}", module, local),
+                        ("namespace-uri", Some(_)) => {
+                            self.doc.namespace(&target).unwrap_or_default().to_owned()
+                        }
+                        _ => String::new(),
+                    },
+                    None => String::new(),
+                };
+                Value::String(s)
+            }
+
+            // XPath 1.0 4.2  String Functions
+            "string" => Value::String(arg_string(0)),
+            "concat" => Value::String(values.iter().map(|v| self.string(v)).collect()),
+            "starts-with" => Value::Boolean(arg_string(0).starts_with(&arg_string(1))),
+            "contains" => Value::Boolean(arg_string(0).contains(&arg_string(1))),
+            "substring-before" => {
+                let (s, pattern) = (arg_string(0), arg_string(1));
+                Value::String(match s.find(&pattern) {
+                    Some(index) => s[..index].to_owned(),
+                    None => String::new(),
+                })
+            }
+            "substring-after" => {
+                let (s, pattern) = (arg_string(0), arg_string(1));
+                Value::String(match s.find(&pattern) {
+                    Some(index) => s[index + pattern.len()..].to_owned(),
+                    None => String::new(),
+                })
+            }
+            "substring" => {
+                let s = arg_string(0);
+                let start = round(self.number(&values[1]));
+                let end = match values.get(2) {
+                    Some(len) => start + round(self.number(len)),
+                    None => f64::INFINITY,
+                };
+                let sub = s
+                    .chars()
+                    .enumerate()
+                    .filter(|(i, _)| {
+                        let p = (*i + 1) as f64;
+                        p >= start && p < end
+                    })
+                    .map(|(_, c)| c)
+                    .collect();
+                Value::String(sub)
+            }
+            "string-length" => Value::Number(arg_string(0).chars().count() as f64),
+            "normalize-space" => {
+                let s = arg_string(0);
+                Value::String(s.split_whitespace().collect::<Vec<_>>().join(" "))
+            }
+            "translate" => {
+                let from: Vec<char> = arg_string(1).chars().collect();
+                let to: Vec<char> = arg_string(2).chars().collect();
+                let s = arg_string(0)
+                    .chars()
+                    .filter_map(|c| match from.iter().position(|f| *f == c) {
+                        Some(index) => to.get(index).cloned(),
+                        None => Some(c),
+                    })
+                    .collect();
+                Value::String(s)
+            }
+
+            // XPath 1.0 4.3  Boolean Functions
+            "boolean" => Value::Boolean(self.boolean(&values[0])),
+            "not" => Value::Boolean(!self.boolean(&values[0])),
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            "lang" => Value::Boolean(false),
+
+            // XPath 1.0 4.4  Number Functions
+            "number" => match values.first() {
+                Some(value) => Value::Number(self.number(value)),
+                None => Value::Number(self.number(&context())),
+            },
+            "sum" => {
+                let nodes = self.nodes(values.remove(0), &fname)?;
+                let sum = nodes
+                    .iter()
+                    .map(|n| string_to_number(&self.string_value(n)))
+                    .sum();
+                Value::Number(sum)
+            }
+            "floor" => Value::Number(self.number(&values[0]).floor()),
+            "ceiling" => Value::Number(self.number(&values[0]).ceil()),
+            "round" => Value::Number(round(self.number(&values[0]))),
+
+            // RFC7950 10.1.1.  current()
+            "current" => Value::Nodes(vec![self.current.clone()]),
+
+            // RFC7950 10.2.1.  re-match()
+            "re-match" => {
+                let pattern = arg_string(1);
+                let re = pattern_regex(&pattern).map_err(|_| XPathError::Pattern(pattern))?;
+                Value::Boolean(re.is_match(&arg_string(0)))
+            }
+
+            // RFC7950 10.3.1.  deref()
+            "deref" => match first_node(values.remove(0))? {
+                Some(target) => {
+                    let mut nodes = self.doc.deref(&target);
+                    sort_nodes(&mut nodes);
+                    Value::Nodes(nodes)
+                }
+                None => Value::Nodes(vec![]),
+            },
+
+            // RFC7950 10.4.1.  derived-from()
+            // RFC7950 10.4.2.  derived-from-or-self()
+            "derived-from" | "derived-from-or-self" => {
+                let base = self.identity_arg(&arg_string(1))?;
+                let nodes = self.nodes(values.remove(0), &fname)?;
+                let or_self = fname == "derived-from-or-self";
+                let matched = nodes.iter().any(|n| match self.doc.identity(n) {
+                    Some(identity) => {
+                        (or_self && identity == base)
+                            || self
+                                .doc
+                                .derived_from((&identity.0, &identity.1), (&base.0, &base.1))
+                    }
+                    None => false,
+                });
+                Value::Boolean(matched)
+            }
+
+            // RFC7950 10.5.1.  enum-value()
+            "enum-value" => {
+                let value = match first_node(values.remove(0))? {
+                    Some(target) => self.doc.enum_value(&target),
+                    None => None,
+                };
+                Value::Number(value.map(|v| v as f64).unwrap_or(f64::NAN))
+            }
+
+            // RFC7950 10.6.1.  bit-is-set()
+            "bit-is-set" => {
+                let bit = arg_string(1);
+                let set = match first_node(values.remove(0))? {
+                    Some(target) => self
+                        .string_value(&target)
+                        .split_whitespace()
+                        .any(|b| b == bit),
+                    None => false,
+                };
+                Value::Boolean(set)
+            }
+            _ => return Err(XPathError::UnknownFunction(fname)),
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    // Data tree of (parent, module, name, value) in document order.
+    struct TestDoc {
+        nodes: Vec<(
+            Option<usize>,
+            &'static str,
+            &'static str,
+            Option<&'static str>,
+        )>,
+    }
+
+    impl TestDoc {
+        fn find(&self, name: &str) -> usize {
+            self.nodes.iter().position(|n| n.2 == name).unwrap() + 1
+        }
+    }
+
+    // Node 0 is the root and node n is nodes[n - 1].
+    impl Document for TestDoc {
+        type Node = usize;
+
+        fn root(&self) -> usize {
+            0
+        }
+
+        fn parent(&self, node: &usize) -> Option<usize> {
+            match node {
+                0 => None,
+                n => Some(self.nodes[n - 1].0.unwrap_or(0)),
+            }
+        }
+
+        fn children(&self, node: &usize) -> Vec<usize> {
+            (1..=self.nodes.len())
+                .filter(|n| self.nodes[n - 1].0.unwrap_or(0) == *node)
+                .collect()
+        }
+
+        fn name(&self, node: &usize) -> Option<(&str, &str)> {
+            match node {
+                0 => None,
+                n => Some((self.nodes[n - 1].1, self.nodes[n - 1].2)),
+            }
+        }
+
+        fn value(&self, node: &usize) -> Option<String> {
+            match node {
+                0 => None,
+                n => self.nodes[n - 1].3.map(String::from),
+            }
+        }
+
+        fn deref(&self, node: &usize) -> Vec<usize> {
+            match self.name(node) {
+                Some((_, "ref")) => {
+                    let value = self.value(node).unwrap();
+                    self.children(&1)
+                        .into_iter()
+                        .map(|n| n + 1)
+                        .filter(|n| self.value(n).as_deref() == Some(value.as_str()))
+                        .collect()
+                }
+                _ => vec![],
+            }
+        }
+
+        fn identity(&self, node: &usize) -> Option<(String, String)> {
+            match self.name(node) {
+                Some((_, "type")) => {
+                    let value = self.value(node).unwrap();
+                    let (module, name) = value.split_once(':').unwrap();
+                    Some((module.to_owned(), name.to_owned()))
+                }
+                _ => None,
+            }
+        }
+
+        fn derived_from(&self, identity: (&str, &str), base: (&str, &str)) -> bool {
+            identity == ("iana-if-type", "ethernetCsmacd")
+                && base == ("ietf-interfaces", "interface-type")
+        }
+
+        fn enum_value(&self, node: &usize) -> Option<i64> {
+            match self.value(node).as_deref() {
+                Some("up") => Some(1),
+                Some("down") => Some(2),
+                _ => None,
+            }
+        }
+    }
+
+    // <interfaces>
+    //   <interface> <name>eth0</name> <type>.. <mtu>1500</mtu> ... </interface>
+    //   <interface> <name>eth1</name> ... <mtu>9000</mtu> ... </interface>
+    // </interfaces>
+    // <ref>eth1</ref>
+    fn doc() -> TestDoc {
+        let m = "ex";
+        TestDoc {
+            nodes: vec![
+                (None, m, "interfaces", None),                             // 1
+                (Some(1), m, "interface", None),                           // 2
+                (Some(2), m, "name", Some("eth0")),                        // 3
+                (Some(2), m, "type", Some("iana-if-type:ethernetCsmacd")), // 4
+                (Some(2), m, "mtu", Some("1500")),                         // 5
+                (Some(2), m, "oper-status", Some("up")),                   // 6
+                (Some(2), m, "flags", Some("up running")),                 // 7
+                (Some(1), m, "interface", None),                           // 8
+                (Some(8), m, "name", Some("eth1")),                        // 9
+                (Some(8), m, "type", Some("iana-if-type:other")),          // 10
+                (Some(8), m, "mtu", Some("9000")),                         // 11
+                (Some(8), m, "oper-status", Some("down")),                 // 12
+                (Some(8), m, "flags", Some("")),                           // 13
+                (None, m, "ref", Some("eth1")),                            // 14
+                (None, "other", "interfaces", None),                       // 15
+            ],
+        }
+    }
+
+    fn xpath(text: &str) -> XPath {
+        let mut xpath = XPath::compile(text).unwrap();
+        let mut prefixes = HashMap::new();
+        prefixes.insert(String::from("ex"), String::from("ex"));
+        prefixes.insert(String::from("if"), String::from("ietf-interfaces"));
+        prefixes.insert(String::from("ianaift"), String::from("iana-if-type"));
+        xpath.prefixes = prefixes;
+        xpath
+    }
+
+    fn eval(doc: &TestDoc, text: &str, node: usize) -> Value<usize> {
+        evaluate(doc, &xpath(text), &node).unwrap()
+    }
+
+    #[test]
+    fn eval_path_test() {
+        let doc = doc();
+        let tests = [
+            ("/interfaces/interface", vec![2, 8]),
+            ("/ex:interfaces/interface/name", vec![3, 9]),
+            ("//mtu", vec![5, 11]),
+            ("../interface[2]/name", vec![9]),
+            ("../interface[last()]/name", vec![9]),
+            ("../interface[name = 'eth0']/mtu", vec![5]),
+            ("../interface[mtu > 2000]", vec![8]),
+            ("ancestor::*", vec![1]),
+            ("ancestor-or-self::node()", vec![0, 1, 2]),
+            ("following-sibling::interface", vec![8]),
+            ("following::name", vec![9]),
+            ("preceding::*[1]", vec![]),
+            ("name | ../interface/mtu", vec![3, 5, 11]),
+            ("(../interface/name)[2]", vec![9]),
+            ("/*", vec![1, 14, 15]),
+            ("descendant::*[position() = 2]", vec![4]),
+        ];
+        for (text, expected) in tests.iter() {
+            assert_eq!(
+                eval(&doc, text, 2),
+                Value::Nodes(expected.clone()),
+                "{}",
+                text
+            );
+        }
+        // Names without prefix belong to the module of the current node.
+        assert_eq!(eval(&doc, "/interfaces", 14), Value::Nodes(vec![1]));
+        assert_eq!(
+            eval(&doc, "preceding-sibling::*[1]", 8),
+            Value::Nodes(vec![2])
+        );
+        assert_eq!(eval(&doc, "preceding::mtu", 9), Value::Nodes(vec![5]));
+    }
+
+    #[test]
+    fn eval_operator_test() {
+        let doc = doc();
+        let tests = [
+            ("1 + 2 * 3", Value::Number(7.0)),
+            ("7 mod 3 - 5 div 2", Value::Number(-1.5)),
+            ("-mtu", Value::Number(-1500.0)),
+            ("mtu = 1500", Value::Boolean(true)),
+            ("mtu != 1500", Value::Boolean(false)),
+            ("../interface/mtu = 9000", Value::Boolean(true)),
+            ("../interface/mtu != 9000", Value::Boolean(true)),
+            ("../interface/mtu < ../interface/mtu", Value::Boolean(true)),
+            ("nothing = 'x' or not(nothing)", Value::Boolean(true)),
+            ("name = 'eth0' and true() = 1", Value::Boolean(true)),
+            ("'abc' = 'abd'", Value::Boolean(false)),
+            ("mtu > '1000'", Value::Boolean(true)),
+            ("name = true()", Value::Boolean(true)),
+        ];
+        for (text, expected) in tests.iter() {
+            assert_eq!(&eval(&doc, text, 2), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn eval_function_test() {
+        let doc = doc();
+        let tests = [
+            ("count(../interface)", Value::Number(2.0)),
+            ("sum(../interface/mtu)", Value::Number(10500.0)),
+            ("string(mtu)", Value::String(String::from("1500"))),
+            ("string(1 div 0)", Value::String(String::from("Infinity"))),
+            ("string(0.5)", Value::String(String::from("0.5"))),
+            ("number('x')", Value::Number(f64::NAN)),
+            (
+                "concat(name, '/', mtu)",
+                Value::String(String::from("eth0/1500")),
+            ),
+            ("starts-with(name, 'eth')", Value::Boolean(true)),
+            ("contains(type, 'Csma')", Value::Boolean(true)),
+            (
+                "substring-before(type, ':')",
+                Value::String(String::from("iana-if-type")),
+            ),
+            (
+                "substring-after(type, ':')",
+                Value::String(String::from("ethernetCsmacd")),
+            ),
+            (
+                "substring('12345', 1.5, 2.6)",
+                Value::String(String::from("234")),
+            ),
+            ("substring('12345', 2)", Value::String(String::from("2345"))),
+            ("string-length(name)", Value::Number(4.0)),
+            (
+                "normalize-space('  a  b ')",
+                Value::String(String::from("a b")),
+            ),
+            (
+                "translate('bar', 'abc', 'AB')",
+                Value::String(String::from("BAr")),
+            ),
+            (
+                "floor(-1.5) + ceiling(1.2) + round(2.5)",
+                Value::Number(3.0),
+            ),
+            ("local-name(..)", Value::String(String::from("interfaces"))),
+            ("name(mtu)", Value::String(String::from("ex:mtu"))),
+            ("boolean(mtu) and not(false())", Value::Boolean(true)),
+        ];
+        for (text, expected) in tests.iter() {
+            let value = eval(&doc, text, 2);
+            match (&value, expected) {
+                (Value::Number(a), Value::Number(b)) if a.is_nan() && b.is_nan() => {}
+                _ => assert_eq!(&value, expected, "{}", text),
+            }
+        }
+    }
+
+    #[test]
+    fn eval_yang_function_test() {
+        let doc = doc();
+        let tests = [
+            ("current()/name", 2, Value::Nodes(vec![3])),
+            (
+                "../interface[name = current()/name]",
+                2,
+                Value::Nodes(vec![2]),
+            ),
+            ("re-match(name, 'eth[0-9]+')", 2, Value::Boolean(true)),
+            ("re-match(name, 'eth')", 2, Value::Boolean(false)),
+            ("deref(/ref)/../mtu", 2, Value::Nodes(vec![11])),
+            (
+                "derived-from(type, 'if:interface-type')",
+                2,
+                Value::Boolean(true),
+            ),
+            (
+                "derived-from(type, 'ianaift:ethernetCsmacd')",
+                2,
+                Value::Boolean(false),
+            ),
+            (
+                "derived-from-or-self(type, 'ianaift:ethernetCsmacd')",
+                2,
+                Value::Boolean(true),
+            ),
+            (
+                "derived-from(type, 'if:interface-type')",
+                8,
+                Value::Boolean(false),
+            ),
+            ("enum-value(oper-status)", 8, Value::Number(2.0)),
+            ("bit-is-set(flags, 'running')", 2, Value::Boolean(true)),
+            ("bit-is-set(flags, 'running')", 8, Value::Boolean(false)),
+        ];
+        for (text, node, expected) in tests.iter() {
+            assert_eq!(&eval(&doc, text, *node), expected, "{}", text);
+        }
+
+        // must on /interfaces/interface/mtu.
+        let mtu = doc.find("mtu");
+        let must = xpath("current() >= 1280 and ../name != 'lo'");
+        assert!(evaluate_bool(&doc, &must, &mtu).unwrap());
+    }
+
+    #[test]
+    fn eval_error_test() {
+        let doc = doc();
+        let tests = [
+            ("foo()", XPathError::UnknownFunction(String::from("foo"))),
+            ("count()", XPathError::Arity(String::from("count"))),
+            ("count(1)", XPathError::NotNodeSet(String::from("count"))),
+            ("$var", XPathError::UnknownVariable(String::from("var"))),
+            (
+                "/x:interfaces",
+                XPathError::UnknownPrefix(String::from("x")),
+            ),
+            ("re-match('a', '[')", XPathError::Pattern(String::from("["))),
+        ];
+        for (text, expected) in tests.iter() {
+            assert_eq!(&evaluate(&doc, &xpath(text), &2).unwrap_err(), expected);
+        }
+    }
+}
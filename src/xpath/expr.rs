@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::fmt;
+
+// XPath 1.0 expression tree.  The grammar is defined in
+// https://www.w3.org/TR/1999/REC-xpath-19991116/.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Literal(String),
+    Number(f64),
+    Variable(QName),
+    Function(QName, Vec<Expr>),
+    Path(LocationPath),
+    // FilterExpr Predicate* followed by optional relative location path.
+    Filter(Box<Expr>, Vec<Expr>, Vec<Step>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Union,
+}
+
+impl BinaryOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinaryOp::Or => "or",
+            BinaryOp::And => "and",
+            BinaryOp::Equal => "=",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEqual => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEqual => ">=",
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "div",
+            BinaryOp::Modulo => "mod",
+            BinaryOp::Union => "|",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QName {
+    pub prefix: Option<String>,
+    pub name: String,
+}
+
+impl QName {
+    pub fn new(prefix: Option<&str>, name: &str) -> Self {
+        QName {
+            prefix: prefix.map(String::from),
+            name: String::from(name),
+        }
+    }
+}
+
+impl fmt::Display for QName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.prefix {
+            Some(prefix) => write!(f, "{}:{}", prefix, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocationPath {
+    pub absolute: bool,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub axis: Axis,
+    pub test: NodeTest,
+    pub predicates: Vec<Expr>,
+}
+
+impl Step {
+    pub fn new(axis: Axis, test: NodeTest) -> Self {
+        Step {
+            axis,
+            test,
+            predicates: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Namespace,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    SelfAxis,
+}
+
+impl Axis {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let axis = match name {
+            "ancestor" => Axis::Ancestor,
+            "ancestor-or-self" => Axis::AncestorOrSelf,
+            "attribute" => Axis::Attribute,
+            "child" => Axis::Child,
+            "descendant" => Axis::Descendant,
+            "descendant-or-self" => Axis::DescendantOrSelf,
+            "following" => Axis::Following,
+            "following-sibling" => Axis::FollowingSibling,
+            "namespace" => Axis::Namespace,
+            "parent" => Axis::Parent,
+            "preceding" => Axis::Preceding,
+            "preceding-sibling" => Axis::PrecedingSibling,
+            "self" => Axis::SelfAxis,
+            _ => return None,
+        };
+        Some(axis)
+    }
+
+    // Reverse axes order the nodes in reverse document order for proximity
+    // position in predicates.
+    pub fn is_reverse(&self) -> bool {
+        matches!(
+            self,
+            Axis::Ancestor | Axis::AncestorOrSelf | Axis::Preceding | Axis::PrecedingSibling
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeTest {
+    Name(QName),
+    // "*" or "prefix:*".
+    Wildcard(Option<String>),
+    Node,
+    Text,
+    Comment,
+    ProcessingInstruction(Option<String>),
+}
+
+// Compiled XPath expression with the prefix to module name mapping of the
+// module where the expression is defined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XPath {
+    pub text: String,
+    pub expr: Expr,
+    pub prefixes: HashMap<String, String>,
+}
+
+impl XPath {
+    // Module name of the prefix in the expression.
+    pub fn prefix_module(&self, prefix: &str) -> Option<&str> {
+        self.prefixes.get(prefix).map(|m| m.as_str())
+    }
+}
@@ -0,0 +1,25 @@
+pub mod compile;
+pub mod eval;
+pub mod expr;
+pub use compile::*;
+pub use eval::*;
+pub use expr::*;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum XPathError {
+    #[error("XPath \"{0}\" syntax error at \"{1}\"")]
+    Syntax(String, String),
+    #[error("unknown prefix \"{0}\"")]
+    UnknownPrefix(String),
+    #[error("unknown function \"{0}\"")]
+    UnknownFunction(String),
+    #[error("wrong number of arguments to \"{0}\"")]
+    Arity(String),
+    #[error("\"{0}\" requires node-set argument")]
+    NotNodeSet(String),
+    #[error("unknown variable \"{0}\"")]
+    UnknownVariable(String),
+    #[error("invalid pattern \"{0}\"")]
+    Pattern(String),
+}
@@ -0,0 +1,63 @@
+// Helpers shared by the integration tests.  Each helper is used by more
+// than one test crate, and the crates declare "pub mod common" since a
+// crate uses only a part of them.
+
+use libyang::*;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+// IETF modules in yang/third_party and the modules of the YANG texts.
+pub fn third_party_modules(extra: &[&str]) -> Modules {
+    let mut ms = Modules::new();
+    for name in &[
+        "ietf-yang-types",
+        "ietf-inet-types",
+        "iana-if-type",
+        "ietf-interfaces",
+    ] {
+        let literal = fs::read_to_string(format!("yang/third_party/ietf/{}.yang", name)).unwrap();
+        let (_, module) = yang_parse(&literal).unwrap();
+        ms.add(module);
+    }
+    for literal in extra {
+        let (_, module) = yang_parse(literal).unwrap();
+        ms.add(module);
+    }
+    ms
+}
+
+pub fn third_party_schema(extra: &[&str]) -> Rc<Schema> {
+    third_party_modules(extra).schema().unwrap()
+}
+
+// Schema of a single module which doesn't import other modules.
+pub fn schema(literal: &str) -> Rc<Schema> {
+    let mut ms = Modules::new();
+    let (_, module) = yang_parse(literal).unwrap();
+    ms.add(module);
+    ms.schema().unwrap()
+}
+
+// Load module "name" from the YANG texts.
+pub fn load_modules(texts: &[&str], name: &str) -> Modules {
+    let mut source = MemorySource::new();
+    for text in texts {
+        source.add(text).unwrap();
+    }
+    let mut ms = Modules::new();
+    ms.load(&source, name).unwrap();
+    ms
+}
+
+// Create directories with YANG files under a temporary directory.
+pub fn yang_dirs(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("libyang-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (file, literal) in files {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, literal).unwrap();
+    }
+    root
+}
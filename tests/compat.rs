@@ -0,0 +1,373 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const OLD: &str = r#"module example {
+  namespace "urn:example";
+  prefix ex;
+  revision 2020-01-01;
+  feature tls;
+  identity protocol;
+  typedef port {
+    type uint16 {
+      range "1..65535";
+    }
+  }
+  container system {
+    leaf hostname {
+      type string {
+        length "1..64";
+      }
+    }
+    leaf port {
+      type port;
+      default 22;
+    }
+    leaf mode {
+      type enumeration {
+        enum auto;
+        enum manual;
+        enum disabled;
+      }
+    }
+    leaf timeout {
+      type uint32;
+    }
+    leaf location {
+      type string;
+    }
+    leaf-list dns {
+      type string;
+      max-elements 4;
+    }
+    list user {
+      key name;
+      leaf name {
+        type string;
+      }
+      leaf uid {
+        type uint32;
+      }
+    }
+    container state {
+      config false;
+      leaf uptime {
+        type uint64;
+      }
+    }
+  }
+}"#;
+
+const NEW: &str = r#"module example {
+  namespace "urn:example";
+  prefix ex;
+  revision 2021-01-01;
+  revision 2020-01-01;
+  identity protocol;
+  typedef port {
+    type uint16 {
+      range "1024..65535";
+    }
+  }
+  container system {
+    leaf hostname {
+      type string {
+        length "1..32";
+        pattern "[a-z]+";
+      }
+    }
+    leaf port {
+      type port;
+      default 2022;
+    }
+    leaf mode {
+      type enumeration {
+        enum manual;
+        enum auto;
+      }
+    }
+    leaf timeout {
+      type string;
+    }
+    leaf-list dns {
+      type string;
+      max-elements 2;
+    }
+    list user {
+      key uid;
+      leaf name {
+        type string;
+      }
+      leaf uid {
+        type uint32;
+      }
+    }
+    container state {
+      leaf uptime {
+        type uint64;
+      }
+    }
+    leaf domain {
+      type string;
+      mandatory true;
+    }
+  }
+}"#;
+
+// Only compatible changes, a new revision, a wider range and length, a new
+// enum and a new optional node.
+const COMPATIBLE: &str = r#"module example {
+  namespace "urn:example";
+  prefix ex;
+  revision 2022-01-01;
+  revision 2020-01-01;
+  feature tls;
+  feature ssh;
+  identity protocol;
+  typedef port {
+    type uint16;
+  }
+  container system {
+    leaf hostname {
+      type string {
+        length "1..32 | 33..128";
+      }
+    }
+    leaf port {
+      type port;
+      default 22;
+    }
+    leaf mode {
+      type enumeration {
+        enum auto;
+        enum manual;
+        enum disabled;
+        enum testing;
+      }
+    }
+    leaf timeout {
+      type uint32;
+    }
+    leaf location {
+      type string;
+    }
+    leaf-list dns {
+      type string;
+      max-elements 8;
+    }
+    list user {
+      key name;
+      leaf name {
+        type string;
+      }
+      leaf uid {
+        type uint32;
+      }
+    }
+    container state {
+      config false;
+      leaf uptime {
+        type uint64;
+      }
+    }
+    leaf description {
+      type string;
+    }
+  }
+}"#;
+
+#[test]
+fn check_update_test() {
+    let old = load_modules(&[OLD], "example");
+    let new = load_modules(&[NEW], "example");
+    let report = old.check_update(&new, "example").unwrap();
+    assert_eq!(report.old_revision.as_deref(), Some("2020-01-01"));
+    assert_eq!(report.new_revision.as_deref(), Some("2021-01-01"));
+    assert!(!report.is_compatible());
+
+    let issues: Vec<String> = report.issues.iter().map(|i| i.to_string()).collect();
+    assert_eq!(
+        issues,
+        vec![
+            "/: feature \"tls\" removed",
+            "/example:system/hostname: length narrowed from \"1..64\" to \"1..32\"",
+            "/example:system/hostname: pattern \"[a-z]+\" added",
+            "/example:system/port: default changed from \"22\" to \"2022\"",
+            "/example:system/port: range narrowed from \"1..65535\" to \"1024..65535\"",
+            "/example:system/mode: value of enum \"auto\" changed from 0 to 1",
+            "/example:system/mode: value of enum \"manual\" changed from 1 to 0",
+            "/example:system/mode: enum \"disabled\" removed",
+            "/example:system/timeout: type changed from \"uint32\" to \"string\"",
+            "/example:system/location: node removed",
+            "/example:system/dns: max-elements decreased from \"4\" to \"2\"",
+            "/example:system/user: key changed from \"name\" to \"uid\"",
+            "/example:system/state: config changed from false to true",
+            "/example:system/domain: mandatory node added",
+        ]
+    );
+    let json = report.to_json();
+    assert_eq!(json.get("compatible"), Some(&JsonValue::Bool(false)));
+    match json.get("issues") {
+        Some(JsonValue::Array(issues)) => {
+            assert_eq!(issues.len(), 14);
+            assert_eq!(
+                issues[9].get("change").and_then(|c| c.as_str()),
+                Some("node-removed")
+            );
+        }
+        _ => panic!("issues should be an array"),
+    }
+
+    let compatible = load_modules(&[COMPATIBLE], "example");
+    let report = old.check_update(&compatible, "example").unwrap();
+    assert_eq!(report.issues, vec![]);
+
+    // Same revision again.
+    let report = old.check_update(&old, "example").unwrap();
+    assert_eq!(
+        report.issues,
+        vec![CompatIssue::new(
+            String::from("/"),
+            CompatChange::RevisionNotAdded(
+                Some(String::from("2020-01-01")),
+                Some(String::from("2020-01-01"))
+            )
+        )]
+    );
+    assert_eq!(
+        old.check_update(&Modules::new(), "example"),
+        Err(CompatError::ModuleNotFound(String::from("example")))
+    );
+}
+
+const CHOICE_OLD: &str = r#"module example-choice {
+  namespace "urn:example:choice";
+  prefix exc;
+  revision 2020-01-01;
+  container system {
+    choice transport {
+      case tcp {
+        leaf tcp-port {
+          type uint16;
+        }
+      }
+      leaf udp-port {
+        type uint16;
+      }
+    }
+  }
+  choice top {
+    leaf state {
+      type string;
+    }
+  }
+}"#;
+
+// "transport" becomes mandatory and a mandatory node is added in the
+// existing case "tcp", the new case "sctp" may have one.  The new
+// container "limits" is mandatory by its mandatory choice, and "options"
+// is not by the mandatory leaf in a case.
+const CHOICE_NEW: &str = r#"module example-choice {
+  namespace "urn:example:choice";
+  prefix exc;
+  revision 2021-01-01;
+  revision 2020-01-01;
+  container system {
+    choice transport {
+      mandatory true;
+      case tcp {
+        leaf tcp-port {
+          type uint16;
+        }
+        leaf keepalive {
+          type uint32;
+          mandatory true;
+        }
+      }
+      leaf udp-port {
+        type uint16;
+      }
+      case sctp {
+        leaf sctp-port {
+          type uint16;
+          mandatory true;
+        }
+      }
+    }
+  }
+  container limits {
+    choice unit {
+      mandatory true;
+      leaf seconds {
+        type uint32;
+      }
+      leaf minutes {
+        type uint32;
+      }
+    }
+  }
+  container options {
+    choice format {
+      leaf width {
+        type uint8;
+        mandatory true;
+      }
+    }
+  }
+  choice top {
+    leaf state {
+      type string;
+    }
+  }
+  choice mode {
+    mandatory true;
+    leaf fast {
+      type empty;
+    }
+    leaf slow {
+      type empty;
+    }
+  }
+}"#;
+
+#[test]
+fn check_update_choice_test() {
+    let old = load_modules(&[CHOICE_OLD], "example-choice");
+    let new = load_modules(&[CHOICE_NEW], "example-choice");
+    let report = old.check_update(&new, "example-choice").unwrap();
+    let issues: Vec<String> = report.issues.iter().map(|i| i.to_string()).collect();
+    assert_eq!(
+        issues,
+        vec![
+            "/example-choice:system/transport: node became mandatory",
+            "/example-choice:system/keepalive: mandatory node added",
+            "/example-choice:limits: mandatory node added",
+            "/example-choice:mode: mandatory node added",
+        ]
+    );
+}
+
+// The files are the same module and revision, with groupings, "uses" and
+// quoted boolean arguments.
+#[test]
+fn ietf_dhcp_update_test() {
+    let old = "tests/ietf-dhcp@2016-08-25.yang";
+    let new = "tests/ietf-dhcp@2017-03-02.yang";
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_yang-compat"))
+        .args(["-p", "yang/third_party/ietf", old, new])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.contains("\"change\": \"revision-not-added\""));
+    assert_eq!(report.matches("\"change\"").count(), 1);
+
+    let literal = std::fs::read_to_string(new).unwrap();
+    let schema = third_party_schema(&[&literal]);
+    let node = schema
+        .find_path("/dhcp:dhcp/dhcp:server/dhcp:option/dhcp:domain-name")
+        .unwrap();
+    assert_eq!(node.name, "domain-name");
+    assert_eq!(node.namespace, "urn:ietf:params:xml:ns:yang:ietf-dhcp");
+}
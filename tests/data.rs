@@ -0,0 +1,431 @@
+pub mod common;
+
+use common::*;
+use libyang::xpath::*;
+use libyang::*;
+use std::rc::Rc;
+
+const EXAMPLE: &str = r#"module example-data {
+  namespace "urn:example:data";
+  prefix ex;
+  import ietf-interfaces {
+    prefix if;
+  }
+  import iana-if-type {
+    prefix ianaift;
+  }
+  typedef percent {
+    type uint8 {
+      range "0 .. 100";
+    }
+  }
+  container system {
+    leaf hostname {
+      type string {
+        length "1..64";
+      }
+    }
+    leaf load {
+      type percent;
+    }
+    leaf ratio {
+      type decimal64 {
+        fraction-digits 2;
+      }
+    }
+    leaf mode {
+      type enumeration {
+        enum active;
+        enum standby {
+          value 10;
+        }
+      }
+    }
+    leaf kind {
+      type identityref {
+        base if:interface-type;
+      }
+    }
+    leaf uplink {
+      type leafref {
+        path "/if:interfaces/if:interface/if:name";
+      }
+    }
+    leaf-list tags {
+      type string;
+    }
+    list server {
+      key "address port";
+      leaf address {
+        type string;
+      }
+      leaf port {
+        type uint16;
+      }
+      leaf weight {
+        type int8;
+      }
+    }
+    anydata extra;
+  }
+}"#;
+
+fn example_tree() -> DataTree {
+    DataTree::new(third_party_schema(&[EXAMPLE]))
+}
+
+#[test]
+fn data_schema_test() {
+    let tree = example_tree();
+    let schema = &tree.schema;
+    let interfaces = schema.child(Some("ietf-interfaces"), "interfaces").unwrap();
+    let interface = interfaces.child(None, "interface").unwrap();
+    assert_eq!(interface.kind, SchemaKind::List);
+    assert_eq!(interface.keys, vec!["name"]);
+    assert_eq!(interface.children[0].name, "name");
+    assert!(interface.config);
+
+    let state = schema.child(None, "interfaces-state").unwrap();
+    assert!(!state.config);
+    assert!(!state.children[0].children[0].config);
+
+    let system = schema.child(Some("example-data"), "system").unwrap();
+    let uplink = system.child(None, "uplink").unwrap();
+    let leafref = uplink.leafref.as_ref().unwrap();
+    assert_eq!(leafref.typ.as_ref().unwrap().kind, TypeKind::Ystring);
+    assert!(schema.derived_from(
+        ("iana-if-type", "ethernetCsmacd"),
+        ("ietf-interfaces", "interface-type")
+    ));
+    assert_eq!(schema.resolve_module("if"), Some("ietf-interfaces"));
+}
+
+#[test]
+fn data_tree_set_get_delete_test() {
+    let mut tree = example_tree();
+
+    tree.set("/example-data:system/hostname", "router1")
+        .unwrap();
+    tree.set("/ex:system/load", "42").unwrap();
+    tree.set("/ex:system/ratio", "1.50").unwrap();
+    tree.set("/ex:system/mode", "standby").unwrap();
+    tree.set("/ex:system/kind", "ianaift:ethernetCsmacd")
+        .unwrap();
+    tree.set("/ex:system/tags", "b").unwrap();
+    tree.create("/ex:system/tags[.='a']").unwrap();
+    tree.set(
+        "/ex:system/server[address='10.0.0.1'][port='53']/weight",
+        "5",
+    )
+    .unwrap();
+    tree.create("/ex:system/server[address='10.0.0.2'][port='053']")
+        .unwrap();
+
+    let system = tree.get("/ex:system").unwrap();
+    let names: Vec<&str> = system.children().iter().map(|c| c.name()).collect();
+    assert_eq!(
+        names,
+        vec!["hostname", "load", "ratio", "mode", "kind", "tags", "tags", "server", "server"]
+    );
+    assert_eq!(
+        tree.get("/ex:system/load").unwrap().value(),
+        Some(&LeafValue::Uint(42))
+    );
+    let ratio = tree.get("/ex:system/ratio").unwrap().value().unwrap();
+    assert_eq!(ratio, &LeafValue::Decimal64(150, 2));
+    assert_eq!(ratio.to_string(), "1.5");
+    assert_eq!(
+        tree.get("/ex:system/mode").unwrap().value(),
+        Some(&LeafValue::Enum(String::from("standby"), 10))
+    );
+    assert_eq!(
+        tree.get("/ex:system/kind")
+            .unwrap()
+            .value()
+            .unwrap()
+            .to_string(),
+        "iana-if-type:ethernetCsmacd"
+    );
+    assert!(tree.get("/ex:system/tags[.='a']").is_ok());
+
+    // Keyed list lookup.
+    let entry = system.entry("server", &["10.0.0.2", "53"]).unwrap();
+    assert_eq!(entry.children().len(), 2);
+    let entry = tree
+        .get("/ex:system/server[port='53'][address='10.0.0.1']")
+        .unwrap();
+    assert_eq!(
+        entry.child("weight").unwrap().value(),
+        Some(&LeafValue::Int(5))
+    );
+
+    // Invalid values and paths.
+    assert!(matches!(
+        tree.set("/ex:system/load", "256"),
+        Err(DataError::InvalidValue(..))
+    ));
+    assert!(matches!(
+        tree.set("/ex:system/mode", "unknown"),
+        Err(DataError::InvalidValue(..))
+    ));
+    assert!(matches!(
+        tree.set("/ex:system/server[address='x']/weight", "1"),
+        Err(DataError::MissingKey(_, _))
+    ));
+    assert!(matches!(
+        tree.set("/ex:system/unknown", "1"),
+        Err(DataError::UnknownNode(_, _))
+    ));
+    assert!(matches!(
+        tree.set(
+            "/ex:system/server[address='10.0.0.1'][port='53']/port",
+            "54"
+        ),
+        Err(DataError::KeyModify(_))
+    ));
+    assert!(matches!(
+        tree.get("/ex:system/server[address='10.0.0.3'][port='53']"),
+        Err(DataError::NotFound(_))
+    ));
+
+    // Delete.
+    let deleted = tree.delete("/ex:system/tags[.='b']").unwrap();
+    assert_eq!(deleted.value(), Some(&LeafValue::String(String::from("b"))));
+    tree.delete("/ex:system/server[address='10.0.0.2'][port='53']")
+        .unwrap();
+    assert!(tree
+        .get("/ex:system/server[address='10.0.0.2'][port='53']")
+        .is_err());
+    assert!(tree.delete("/ex:system/hostname").is_ok());
+    assert!(tree.delete("/ex:system/hostname").is_err());
+}
+
+#[test]
+fn data_tree_xpath_test() {
+    let mut tree = example_tree();
+    tree.set(
+        "/ietf-interfaces:interfaces/interface[name='eth0']/type",
+        "ianaift:ethernetCsmacd",
+    )
+    .unwrap();
+    tree.set(
+        "/ietf-interfaces:interfaces/interface[name='eth1']/enabled",
+        "false",
+    )
+    .unwrap();
+    tree.set("/ex:system/uplink", "eth1").unwrap();
+    tree.set("/ex:system/mode", "standby").unwrap();
+
+    let mut xpath = XPath::compile("count(/if:interfaces/if:interface)").unwrap();
+    xpath
+        .prefixes
+        .insert(String::from("if"), String::from("ietf-interfaces"));
+    xpath
+        .prefixes
+        .insert(String::from("ex"), String::from("example-data"));
+    assert_eq!(
+        evaluate(&tree, &xpath, &tree.root()).unwrap(),
+        Value::Number(2.0)
+    );
+
+    let eval = |text: &str| {
+        let mut x = XPath::compile(text).unwrap();
+        x.prefixes = xpath.prefixes.clone();
+        evaluate(&tree, &x, &tree.root()).unwrap()
+    };
+    assert_eq!(
+        eval("string(deref(/ex:system/ex:uplink)/../if:enabled)"),
+        Value::String(String::from("false"))
+    );
+    assert_eq!(eval("enum-value(/ex:system/ex:mode)"), Value::Number(10.0));
+    assert_eq!(
+        eval("derived-from-or-self(/if:interfaces/if:interface[if:name='eth0']/if:type, 'if:interface-type')"),
+        Value::Boolean(true)
+    );
+
+    // Leafref value is checked against the target type.
+    let schema = Rc::clone(&tree.schema);
+    let uplink = schema
+        .child(None, "system")
+        .and_then(|s| s.child(None, "uplink"))
+        .unwrap();
+    assert!(LeafValue::parse(&schema, uplink, "eth9").is_ok());
+}
+
+const CHOICE: &str = r#"module example-data-choice {
+  namespace "urn:example:data-choice";
+  prefix exc;
+  container system {
+    leaf hostname {
+      type string;
+    }
+    choice transport {
+      default tcp;
+      case tcp {
+        leaf tcp-port {
+          type uint16;
+        }
+        choice tls {
+          leaf cert {
+            type string;
+          }
+        }
+      }
+      leaf udp-port {
+        type uint16;
+      }
+    }
+  }
+  choice top {
+    config false;
+    leaf state {
+      type string;
+    }
+  }
+}"#;
+
+#[test]
+fn data_choice_test() {
+    let schema = schema(CHOICE);
+    let system = schema.find_path("/exc:system").unwrap();
+    let names: Vec<&str> = system.children.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, vec!["hostname", "tcp-port", "cert", "udp-port"]);
+
+    // The data nodes of a case are children of the choice's parent, and
+    // the shorthand case is named after its data node.
+    let transport = &system.choices[0];
+    assert_eq!(transport.name, "transport");
+    assert_eq!(transport.default_case().unwrap().name, "tcp");
+    let cases: Vec<(&str, usize)> = transport
+        .cases
+        .iter()
+        .map(|c| (c.name.as_str(), c.nodes.len()))
+        .collect();
+    assert_eq!(cases, vec![("tcp", 2), ("udp-port", 1)]);
+    assert_eq!(transport.cases[0].choices[0].cases[0].name, "cert");
+    assert!(!schema.choices[0].config);
+    assert!(!schema.find_path("/exc:state").unwrap().config);
+
+    let mut tree = DataTree::new(schema);
+    tree.set("/example-data-choice:system/cert", "c").unwrap();
+    assert_eq!(
+        tree.to_json().to_string(),
+        r#"{"example-data-choice:system":{"cert":"c"}}"#
+    );
+
+    let mut ms = Modules::new();
+    let (_, module) = yang_parse(&CHOICE.replace("default tcp;", "default sctp;")).unwrap();
+    ms.add(module);
+    assert_eq!(
+        ms.schema(),
+        Err(SchemaError::DefaultCaseNotFound(
+            String::from("transport"),
+            String::from("sctp")
+        ))
+    );
+}
+
+const GROUPINGS: &str = r#"module example-groupings {
+  namespace "urn:example:groupings";
+  prefix exg;
+  typedef port {
+    type uint16;
+  }
+  grouping endpoint {
+    leaf address {
+      type string;
+    }
+    leaf port {
+      type port;
+      default 80;
+    }
+  }
+}"#;
+
+const USES: &str = r#"module example-uses {
+  namespace "urn:example:uses";
+  prefix exu;
+  import example-groupings {
+    prefix exg;
+  }
+  grouping server {
+    grouping tls {
+      leaf cert {
+        type string;
+      }
+    }
+    list server {
+      key "name";
+      leaf name {
+        type string;
+      }
+      uses exg:endpoint {
+        refine port {
+          default 443;
+          description "Server port";
+        }
+        refine address {
+          mandatory true;
+        }
+      }
+      uses tls;
+    }
+  }
+  container system {
+    uses server;
+  }
+}"#;
+
+#[test]
+fn data_grouping_test() {
+    let schema = third_party_schema(&[GROUPINGS, USES]);
+    let server = schema.find_path("/exu:system/exu:server").unwrap();
+    let names: Vec<&str> = server.children.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, vec!["name", "address", "port", "cert"]);
+
+    // Nodes of the grouping are in the namespace of the module which uses
+    // it, and the refinements replace the grouping's properties.
+    let port = server.child(None, "port").unwrap();
+    assert_eq!(port.module, "example-uses");
+    assert_eq!(port.namespace, "urn:example:uses");
+    assert_eq!(port.default, vec!["443"]);
+    assert_eq!(port.description.as_deref(), Some("Server port"));
+    assert_eq!(port.typ.as_ref().unwrap().kind, TypeKind::Yuint16);
+    assert!(server.child(None, "address").unwrap().mandatory);
+
+    let mut tree = DataTree::new(schema);
+    tree.set("/example-uses:system/server[name='s1']/cert", "c")
+        .unwrap();
+    assert_eq!(
+        tree.to_json().to_string(),
+        r#"{"example-uses:system":{"server":[{"name":"s1","cert":"c"}]}}"#
+    );
+
+    let unknown = USES.replace("uses tls;", "uses ssh;");
+    let mut ms = third_party_modules(&[GROUPINGS, &unknown]);
+    assert_eq!(
+        ms.schema(),
+        Err(SchemaError::UnknownGrouping(
+            String::from("example-uses"),
+            String::from("ssh")
+        ))
+    );
+    let refine = USES.replace("refine address {", "refine address {\n  presence \"p\";");
+    ms = third_party_modules(&[GROUPINGS, &refine]);
+    assert_eq!(
+        ms.schema(),
+        Err(SchemaError::InvalidRefine(
+            String::from("exg:endpoint"),
+            String::from("address")
+        ))
+    );
+    let looped = USES.replace("uses tls;", "uses server;");
+    ms = third_party_modules(&[GROUPINGS, &looped]);
+    assert_eq!(
+        ms.schema(),
+        Err(SchemaError::GroupingLoop(
+            String::from("example-uses"),
+            String::from("server")
+        ))
+    );
+}
@@ -0,0 +1,313 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const EXAMPLE: &str = r#"module example-default {
+  namespace "urn:example:default";
+  prefix exd;
+  import ietf-interfaces {
+    prefix if;
+  }
+  import iana-if-type {
+    prefix ianaift;
+  }
+  typedef port {
+    type uint16;
+    default 8080;
+  }
+  typedef admin-port {
+    type port;
+  }
+  typedef ratio {
+    type decimal64 {
+      fraction-digits 2;
+    }
+    default "0.5";
+  }
+  container server {
+    leaf port {
+      type admin-port;
+    }
+    leaf ratio {
+      type ratio;
+    }
+    leaf kind {
+      type identityref {
+        base if:interface-type;
+      }
+      default "ianaift:ethernetCsmacd";
+    }
+    leaf secure {
+      type boolean;
+      default false;
+    }
+    leaf cert {
+      when "../secure = 'true'";
+      type string;
+      default "server.pem";
+    }
+    leaf-list dns {
+      type string;
+      default "a";
+      default "b";
+    }
+    container log {
+      leaf level {
+        type uint8;
+        default 3;
+      }
+    }
+    container tls {
+      presence "TLS";
+      leaf version {
+        type string;
+        default "1.3";
+      }
+    }
+    list peer {
+      key "port";
+      leaf port {
+        type port;
+      }
+      leaf weight {
+        type port;
+        mandatory true;
+      }
+      leaf priority {
+        type uint8;
+        default 1;
+      }
+    }
+  }
+}"#;
+
+#[test]
+fn default_schema_test() {
+    let ms = third_party_modules(&[EXAMPLE]);
+    let schema = ms.schema().unwrap();
+    let server = schema.child(None, "server").unwrap();
+
+    // Defaults of the typedef chain, which are not used by keys and
+    // mandatory leaves.
+    assert_eq!(server.child(None, "port").unwrap().default, vec!["8080"]);
+    let peer = server.child(None, "peer").unwrap();
+    assert!(peer.child(None, "port").unwrap().default.is_empty());
+    assert!(peer.child(None, "weight").unwrap().default.is_empty());
+    assert_eq!(
+        server.child(None, "kind").unwrap().default_values(&schema),
+        vec![LeafValue::Identity(
+            String::from("iana-if-type"),
+            String::from("ethernetCsmacd")
+        )]
+    );
+
+    // Defaults must be valid for the type.
+    for (typ, default) in &[
+        ("uint8;", "256"),
+        ("uint8 { range \"1..10\"; }", "11"),
+        ("string { pattern \"[a-z]+\"; }", "A"),
+        ("enumeration { enum a; }", "b"),
+    ] {
+        let literal = format!(
+            r#"module example-invalid {{
+  namespace "urn:example:invalid";
+  prefix exi;
+  leaf value {{
+    type {}
+    default "{}";
+  }}
+}}"#,
+            typ, default
+        );
+        let ms = third_party_modules(&[&literal]);
+        assert!(matches!(
+            ms.schema(),
+            Err(SchemaError::InvalidDefault(name, value, _)) if name == "value" && value == *default
+        ));
+    }
+}
+
+#[test]
+fn default_fill_trim_test() {
+    let ms = third_party_modules(&[EXAMPLE]);
+    let schema = ms.schema().unwrap();
+    let mut tree = DataTree::json_decode(
+        schema.clone(),
+        r#"{"example-default:server": {
+             "ratio": "0.75",
+             "peer": [{"port": 1, "weight": 2}]}}"#,
+    )
+    .unwrap();
+    tree.fill_defaults();
+    assert_eq!(
+        tree.json_encode(),
+        r#"{
+  "example-default:server": {
+    "port": 8080,
+    "ratio": "0.75",
+    "kind": "iana-if-type:ethernetCsmacd",
+    "secure": false,
+    "dns": [
+      "a",
+      "b"
+    ],
+    "log": {
+      "level": 3
+    },
+    "peer": [
+      {
+        "port": 1,
+        "weight": 2,
+        "priority": 1
+      }
+    ]
+  }
+}"#
+    );
+    // Filling is idempotent.
+    let filled = tree.clone();
+    tree.fill_defaults();
+    assert_eq!(tree, filled);
+
+    // Explicit values equal to the defaults are removed.
+    tree.trim_defaults();
+    assert_eq!(
+        tree.json_encode(),
+        r#"{
+  "example-default:server": {
+    "ratio": "0.75",
+    "peer": [
+      {
+        "port": 1,
+        "weight": 2
+      }
+    ]
+  }
+}"#
+    );
+
+    // "when" condition of the default, presence container is not created.
+    tree.set("/example-default:server/secure", "true").unwrap();
+    tree.create("/example-default:server/tls").unwrap();
+    tree.fill_defaults();
+    assert_eq!(
+        tree.get("/example-default:server/cert")
+            .unwrap()
+            .value()
+            .unwrap()
+            .to_string(),
+        "server.pem"
+    );
+    assert_eq!(
+        tree.get("/example-default:server/tls/version")
+            .unwrap()
+            .value()
+            .unwrap()
+            .to_string(),
+        "1.3"
+    );
+    assert!(validate(&tree, &ms).is_ok());
+
+    // Defaults are only filled under existing nodes.
+    let mut tree = DataTree::new(schema);
+    tree.set(
+        "/ietf-interfaces:interfaces/interface[name='eth0']/type",
+        "iana-if-type:ethernetCsmacd",
+    )
+    .unwrap();
+    tree.fill_defaults();
+    assert_eq!(
+        tree.get("/ietf-interfaces:interfaces/interface[name='eth0']/enabled")
+            .unwrap()
+            .value(),
+        Some(&LeafValue::Boolean(true))
+    );
+    assert!(tree.get("/example-default:server/tls").is_err());
+    assert!(tree.get("/example-default:server/cert").is_err());
+    assert_eq!(
+        tree.get("/example-default:server/log/level")
+            .unwrap()
+            .value(),
+        Some(&LeafValue::Uint(3))
+    );
+    tree.trim_defaults();
+    assert!(tree.get("/example-default:server").is_err());
+    assert!(tree
+        .get("/ietf-interfaces:interfaces/interface[name='eth0']/enabled")
+        .is_err());
+}
+
+const CHOICE: &str = r#"module example-default-choice {
+  namespace "urn:example:default-choice";
+  prefix exc;
+  container transfer {
+    choice how {
+      default interval;
+      case interval {
+        leaf interval {
+          type uint16;
+          default 30;
+        }
+      }
+      case daily {
+        leaf daily {
+          type empty;
+        }
+        leaf time-of-day {
+          type string;
+          default "1am";
+        }
+      }
+      case manual {
+        leaf manual {
+          type empty;
+        }
+      }
+    }
+  }
+}"#;
+
+#[test]
+fn default_choice_test() {
+    let ms = third_party_modules(&[CHOICE]);
+    let schema = ms.schema().unwrap();
+    let filled = |json: &str| {
+        let mut tree = DataTree::json_decode(schema.clone(), json).unwrap();
+        tree.fill_defaults();
+        tree.to_json().to_string()
+    };
+
+    // The default case when no case is active.
+    assert_eq!(
+        filled(r#"{"example-default-choice:transfer": {}}"#),
+        r#"{"example-default-choice:transfer":{"interval":30}}"#
+    );
+    // Defaults of the active case only.
+    assert_eq!(
+        filled(r#"{"example-default-choice:transfer": {"daily": [null]}}"#),
+        r#"{"example-default-choice:transfer":{"daily":[null],"time-of-day":"1am"}}"#
+    );
+    assert_eq!(
+        filled(r#"{"example-default-choice:transfer": {"manual": [null]}}"#),
+        r#"{"example-default-choice:transfer":{"manual":[null]}}"#
+    );
+
+    // Defaults of the other cases are kept, which would make the default
+    // case active.
+    let mut tree = DataTree::json_decode(
+        schema.clone(),
+        r#"{"example-default-choice:transfer": {"time-of-day": "1am"}}"#,
+    )
+    .unwrap();
+    let source = tree.clone();
+    tree.trim_defaults();
+    assert_eq!(tree, source);
+    let mut tree = DataTree::json_decode(
+        schema,
+        r#"{"example-default-choice:transfer": {"interval": 30}}"#,
+    )
+    .unwrap();
+    tree.trim_defaults();
+    assert_eq!(tree.to_json().to_string(), "{}");
+}
@@ -0,0 +1,217 @@
+use libyang::*;
+
+fn modules_with_deviation(deviation: &str) -> Modules {
+    let mut yang = Yang::new();
+    yang.add_path("/etc/openconfigd/yang:yang/...");
+
+    let mut ms = Modules::new();
+    let data = yang.read(&ms, "ietf-interfaces").unwrap();
+    let (_, module) = yang_parse(&data).unwrap();
+    ms.add(module);
+
+    let (_, module) = yang_parse(deviation).unwrap();
+    ms.add(module);
+    ms
+}
+
+fn interface_node<'a>(ms: &'a Modules, name: &str) -> Option<&'a Node> {
+    let module = ms.find_module("ietf-interfaces").unwrap();
+    let interfaces = module
+        .nodes
+        .iter()
+        .find(|n| n.name() == Some("interfaces"))?;
+    let interface = interfaces
+        .nodes()?
+        .iter()
+        .find(|n| n.name() == Some("interface"))?;
+    interface.nodes()?.iter().find(|n| n.name() == Some(name))
+}
+
+#[test]
+fn deviation_not_supported() {
+    let mut ms = modules_with_deviation(
+        r#"module example-deviations {
+  namespace "urn:example:deviations";
+  prefix md;
+  import ietf-interfaces {
+    prefix if;
+  }
+  deviation /if:interfaces/if:interface/if:link-up-down-trap-enable {
+    deviate not-supported;
+  }
+}"#,
+    );
+    assert!(interface_node(&ms, "link-up-down-trap-enable").is_some());
+    ms.apply_deviations().unwrap();
+    assert!(interface_node(&ms, "link-up-down-trap-enable").is_none());
+}
+
+#[test]
+fn deviation_add_replace_delete() {
+    let mut ms = modules_with_deviation(
+        r#"module example-deviations {
+  namespace "urn:example:deviations";
+  prefix md;
+  import ietf-interfaces {
+    prefix if;
+  }
+  deviation /if:interfaces/if:interface/if:enabled {
+    deviate replace {
+      default false;
+    }
+    deviate add {
+      must "../if:type";
+    }
+  }
+  deviation /if:interfaces/if:interface/if:type {
+    deviate delete {
+      mandatory true;
+    }
+  }
+  deviation /if:interfaces/if:interface/if:description {
+    deviate replace {
+      type string {
+        length "0..64";
+      }
+    }
+    deviate add {
+      units "chars";
+    }
+  }
+}"#,
+    );
+    ms.apply_deviations().unwrap();
+
+    if let Some(Node::Leaf(leaf)) = interface_node(&ms, "enabled") {
+        assert_eq!(leaf.default, Some(String::from("false")));
+        assert_eq!(leaf.must.len(), 1);
+        assert_eq!(leaf.must[0].name, "../if:type");
+        // The added "must" keeps the deviation module's prefixes.
+        let xpath = leaf.must[0].xpath.as_ref().unwrap();
+        assert_eq!(xpath.prefix_module("md"), Some("example-deviations"));
+        assert_eq!(xpath.prefix_module("if"), Some("ietf-interfaces"));
+    } else {
+        panic!("enabled leaf not found");
+    }
+    if let Some(Node::Leaf(leaf)) = interface_node(&ms, "type") {
+        assert_eq!(leaf.mandatory, None);
+    } else {
+        panic!("type leaf not found");
+    }
+    if let Some(Node::Leaf(leaf)) = interface_node(&ms, "description") {
+        let typ = leaf.typ.as_ref().unwrap();
+        assert_eq!(typ.kind, TypeKind::Ystring);
+        assert_eq!(typ.length, Some(String::from("0..64")));
+        assert_eq!(leaf.units, Some(String::from("chars")));
+    } else {
+        panic!("description leaf not found");
+    }
+}
+
+#[test]
+fn deviation_illegal() {
+    let tests = [
+        (
+            "deviate add { default true; }",
+            "/if:interfaces/if:interface/if:enabled",
+            DeviationError::AlreadyExists(
+                String::from("/if:interfaces/if:interface/if:enabled"),
+                "default",
+            ),
+        ),
+        (
+            "deviate replace { units \"bps\"; }",
+            "/if:interfaces/if:interface/if:enabled",
+            DeviationError::NotExists(
+                String::from("/if:interfaces/if:interface/if:enabled"),
+                "replace",
+                "units",
+            ),
+        ),
+        (
+            "deviate add { max-elements 10; }",
+            "/if:interfaces/if:interface/if:enabled",
+            DeviationError::InvalidProperty(
+                String::from("/if:interfaces/if:interface/if:enabled"),
+                "max-elements",
+            ),
+        ),
+        (
+            "deviate add { default \"ethernetCsmacd\"; }",
+            "/if:interfaces/if:interface/if:type",
+            DeviationError::MandatoryDefault(String::from("/if:interfaces/if:interface/if:type")),
+        ),
+        (
+            "deviate not-supported;",
+            "/if:interfaces/if:interface/if:name",
+            DeviationError::KeyNotSupported(String::from("/if:interfaces/if:interface/if:name")),
+        ),
+        (
+            "deviate not-supported;",
+            "/if:interfaces/if:interface/if:unknown",
+            DeviationError::TargetNotFound(String::from("/if:interfaces/if:interface/if:unknown")),
+        ),
+        (
+            "deviate not-supported;",
+            "/ni:interfaces",
+            DeviationError::UnknownPrefix(String::from("/ni:interfaces"), String::from("ni")),
+        ),
+    ];
+    for (deviate, target, err) in tests.iter() {
+        let module = format!(
+            r#"module example-deviations {{
+  namespace "urn:example:deviations";
+  prefix md;
+  import ietf-interfaces {{
+    prefix if;
+  }}
+  deviation {} {{
+    {}
+  }}
+}}"#,
+            target, deviate
+        );
+        let mut ms = modules_with_deviation(&module);
+        assert_eq!(ms.apply_deviations(), Err(err.clone()));
+    }
+}
+
+#[test]
+fn deviation_atomic() {
+    let mut ms = modules_with_deviation(
+        r#"module example-deviations {
+  namespace "urn:example:deviations";
+  prefix md;
+  import ietf-interfaces {
+    prefix if;
+  }
+  deviation /if:interfaces/if:interface/if:enabled {
+    deviate replace {
+      default false;
+    }
+  }
+  deviation /if:interfaces/if:interface/if:link-up-down-trap-enable {
+    deviate not-supported;
+  }
+  deviation /if:interfaces/if:interface/if:unknown {
+    deviate add {
+      units "bps";
+    }
+  }
+}"#,
+    );
+    assert_eq!(
+        ms.apply_deviations(),
+        Err(DeviationError::TargetNotFound(String::from(
+            "/if:interfaces/if:interface/if:unknown"
+        )))
+    );
+
+    // The failed deviation leaves the schema tree unchanged.
+    if let Some(Node::Leaf(leaf)) = interface_node(&ms, "enabled") {
+        assert_eq!(leaf.default, Some(String::from("true")));
+    } else {
+        panic!("enabled leaf not found");
+    }
+    assert!(interface_node(&ms, "link-up-down-trap-enable").is_some());
+}
@@ -0,0 +1,254 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const EXAMPLE: &str = r#"module example-diff {
+  namespace "urn:example:diff";
+  prefix exd;
+  container system {
+    leaf hostname {
+      type string;
+    }
+    leaf-list dns {
+      type string;
+    }
+    list user {
+      key "name";
+      leaf name {
+        type string;
+      }
+      leaf uid {
+        type uint32;
+      }
+    }
+    list rule {
+      key "name";
+      ordered-by user;
+      leaf name {
+        type string;
+      }
+      leaf action {
+        type string;
+      }
+    }
+    leaf-list search {
+      type string;
+      ordered-by user;
+    }
+  }
+}"#;
+
+const SOURCE: &str = r#"{"example-diff:system": {
+  "hostname": "r1",
+  "dns": ["10.0.0.1", "10.0.0.2"],
+  "user": [{"name": "alice", "uid": 1}, {"name": "bob", "uid": 2}],
+  "rule": [{"name": "a"}, {"name": "b"}, {"name": "c", "action": "drop"}],
+  "search": ["x", "y", "z"]
+}}"#;
+
+const TARGET: &str = r#"{"example-diff:system": {
+  "hostname": "r2",
+  "dns": ["10.0.0.2", "10.0.0.3"],
+  "user": [{"name": "alice", "uid": 10}, {"name": "carol"}],
+  "rule": [{"name": "c", "action": "accept"}, {"name": "a"}, {"name": "d"}],
+  "search": ["z", "x", "y"]
+}}"#;
+
+fn op_name(op: &DiffOp) -> String {
+    match op {
+        DiffOp::Create(_, after) => format!("create after {:?}", after),
+        DiffOp::Delete(_) => String::from("delete"),
+        DiffOp::Replace(old, new) => format!(
+            "replace {} -> {}",
+            old.value().unwrap(),
+            new.value().unwrap()
+        ),
+        DiffOp::Move(after) => format!("move after {:?}", after),
+    }
+}
+
+#[test]
+fn diff_apply_test() {
+    let schema = schema(EXAMPLE);
+    let source = DataTree::json_decode(schema.clone(), SOURCE).unwrap();
+    let target = DataTree::json_decode(schema, TARGET).unwrap();
+
+    let diff = source.diff(&target);
+    let ops: Vec<(&str, String)> = diff
+        .iter()
+        .map(|e| (e.path.as_str(), op_name(&e.op)))
+        .collect();
+    let rule = |name: &str| Some(format!("/example-diff:system/rule[name='{}']", name));
+    assert_eq!(
+        ops,
+        vec![
+            (
+                "/example-diff:system/dns[.='10.0.0.1']",
+                String::from("delete")
+            ),
+            (
+                "/example-diff:system/user[name='bob']",
+                String::from("delete")
+            ),
+            (
+                "/example-diff:system/rule[name='b']",
+                String::from("delete")
+            ),
+            (
+                "/example-diff:system/hostname",
+                String::from("replace r1 -> r2")
+            ),
+            (
+                "/example-diff:system/dns[.='10.0.0.3']",
+                String::from("create after None")
+            ),
+            (
+                "/example-diff:system/user[name='alice']/uid",
+                String::from("replace 1 -> 10")
+            ),
+            (
+                "/example-diff:system/user[name='carol']",
+                String::from("create after None")
+            ),
+            (
+                "/example-diff:system/rule[name='c']",
+                String::from("move after None")
+            ),
+            (
+                "/example-diff:system/rule[name='c']/action",
+                String::from("replace drop -> accept")
+            ),
+            (
+                "/example-diff:system/rule[name='d']",
+                format!("create after {:?}", rule("a"))
+            ),
+            (
+                "/example-diff:system/search[.='z']",
+                String::from("move after None")
+            ),
+        ]
+    );
+
+    // Applying the diff gives the target, in the user order.
+    let mut tree = source.clone();
+    tree.apply(&diff).unwrap();
+    assert_eq!(tree, target);
+    assert_eq!(tree.json_encode(), target.json_encode());
+    assert!(tree.diff(&target).is_empty());
+
+    // Rollback by the reverse diff.  Order of the system ordered
+    // instances is not significant.
+    tree.apply(&target.diff(&source)).unwrap();
+    assert!(tree.diff(&source).is_empty());
+    assert_eq!(
+        tree.get("/example-diff:system").unwrap().children()[1..3],
+        source.get("/example-diff:system").unwrap().children()[1..3]
+            .iter()
+            .rev()
+            .cloned()
+            .collect::<Vec<_>>()[..]
+    );
+
+    // Diff which doesn't fit the tree is not applied at all.
+    let mut tree = source.clone();
+    let created = diff
+        .iter()
+        .find(|e| e.path == "/example-diff:system/user[name='carol']")
+        .unwrap()
+        .clone();
+    let mut invalid = vec![created.clone(), created];
+    assert!(matches!(tree.apply(&invalid), Err(DataError::Exists(_))));
+    invalid[1] = DiffEntry::new(
+        String::from("/example-diff:system/user[name='dave']"),
+        DiffOp::Move(None),
+    );
+    assert!(matches!(tree.apply(&invalid), Err(DataError::NotFound(_))));
+    assert_eq!(tree, source);
+
+    // Empty trees.
+    let empty = DataTree::new(tree.schema.clone());
+    let diff = empty.diff(&source);
+    assert_eq!(diff.len(), 1);
+    assert!(matches!(&diff[0].op, DiffOp::Create(_, None)));
+    let mut tree = empty.clone();
+    tree.apply(&diff).unwrap();
+    assert_eq!(tree, source);
+}
+
+#[test]
+fn merge_test() {
+    let schema = schema(EXAMPLE);
+    let mut tree = DataTree::json_decode(schema.clone(), SOURCE).unwrap();
+    let other = DataTree::json_decode(
+        schema,
+        r#"{"example-diff:system": {
+             "hostname": "r2",
+             "dns": ["10.0.0.2", "10.0.0.3"],
+             "user": [{"name": "bob", "uid": 20}, {"name": "carol"}],
+             "rule": [{"name": "a", "action": "log"}, {"name": "e"}],
+             "search": ["w"]
+           }}"#,
+    )
+    .unwrap();
+    tree.merge(&other).unwrap();
+    assert_eq!(
+        tree.to_json().to_string(),
+        r#"{"example-diff:system":{"hostname":"r2","dns":["10.0.0.1","10.0.0.2","10.0.0.3"],"user":[{"name":"alice","uid":1},{"name":"bob","uid":20},{"name":"carol"}],"rule":[{"name":"a","action":"log"},{"name":"b"},{"name":"c","action":"drop"},{"name":"e"}],"search":["x","y","z","w"]}}"#
+    );
+}
+
+#[test]
+fn separate_schema_test() {
+    // Trees of the same module compiled twice.
+    let schema_a = schema(EXAMPLE);
+    let schema_b = schema(EXAMPLE);
+    let base = DataTree::json_decode(
+        schema_a.clone(),
+        r#"{"example-diff:system": {"hostname": "r1", "user": [{"name": "alice"}]}}"#,
+    )
+    .unwrap();
+    let other = DataTree::json_decode(
+        schema_b.clone(),
+        r#"{"example-diff:system": {"user": [{"name": "x", "uid": 1}], "dns": ["10.0.0.1"]}}"#,
+    )
+    .unwrap();
+    let merged = r#"{"example-diff:system":{"hostname":"r1","dns":["10.0.0.1"],"user":[{"name":"alice"},{"name":"x","uid":1}]}}"#;
+
+    let mut tree = base.clone();
+    tree.merge(&other).unwrap();
+    assert_eq!(tree.to_json().to_string(), merged);
+    let uid = tree.get("/example-diff:system/user[name='x']/uid").unwrap();
+    assert_eq!(uid.value(), Some(&LeafValue::Uint(1)));
+
+    let mut tree = base.clone();
+    let mut target = base.clone();
+    target.merge(&other).unwrap();
+    let target = DataTree::json_decode(schema_b, &target.to_json().to_string()).unwrap();
+    tree.apply(&base.diff(&target)).unwrap();
+    assert_eq!(tree.to_json().to_string(), merged);
+    let uid = tree.get("/example-diff:system/user[name='x']/uid").unwrap();
+    assert_eq!(uid.value(), Some(&LeafValue::Uint(1)));
+
+    // Nodes which the schema doesn't have are not merged.
+    let schema_c = schema(&EXAMPLE.replace("leaf-list dns", "leaf-list domain"));
+    let other = DataTree::json_decode(
+        schema_c,
+        r#"{"example-diff:system": {"hostname": "r2", "domain": ["example.com"]}}"#,
+    )
+    .unwrap();
+    let mut tree = base.clone();
+    assert_eq!(
+        tree.merge(&other),
+        Err(DataError::UnknownNode(
+            String::from("/example-diff:system"),
+            String::from("domain")
+        ))
+    );
+    assert_eq!(tree, base);
+    assert!(matches!(
+        tree.apply(&base.diff(&other)),
+        Err(DataError::UnknownNode(..))
+    ));
+    assert_eq!(tree, base);
+}
@@ -0,0 +1,347 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const EXAMPLE: &str = r#"module example-edit {
+  namespace "urn:example:edit";
+  prefix exe;
+  container system {
+    leaf hostname {
+      type string;
+    }
+    leaf mtu {
+      type uint16;
+    }
+    container ntp {
+      leaf enabled {
+        type boolean;
+      }
+      leaf-list server {
+        type string;
+      }
+    }
+    list user {
+      key "name";
+      leaf name {
+        type string;
+      }
+      leaf uid {
+        type uint32;
+      }
+    }
+    list rule {
+      key "name";
+      ordered-by user;
+      leaf name {
+        type string;
+      }
+    }
+    leaf-list search {
+      type string;
+      ordered-by user;
+    }
+  }
+}"#;
+
+const BASE: &str = r#"{"example-edit:system": {
+  "hostname": "r1",
+  "mtu": 1500,
+  "ntp": {"enabled": true, "server": ["a", "b"]},
+  "user": [{"name": "alice", "uid": 1}],
+  "rule": [{"name": "a"}, {"name": "b"}],
+  "search": ["x", "y"]
+}}"#;
+
+fn edit(tree: &mut DataTree, config: &str, default: EditOperation) -> Result<(), EditError> {
+    let text = format!(
+        r#"<config xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"
+                  xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0"
+                  xmlns:yang="urn:ietf:params:xml:ns:yang:1">
+             <system xmlns="urn:example:edit">{}</system>
+           </config>"#,
+        config
+    );
+    let edit = DataTree::xml_decode(tree.schema.clone(), &text).unwrap();
+    tree.edit(&edit, default)
+}
+
+fn json(tree: &DataTree) -> String {
+    tree.get("/example-edit:system")
+        .unwrap()
+        .to_json()
+        .to_string()
+}
+
+#[test]
+fn edit_operation_test() {
+    let schema = schema(EXAMPLE);
+    let base = DataTree::json_decode(schema, BASE).unwrap();
+
+    // Merge is the default operation.
+    let mut tree = base.clone();
+    edit(
+        &mut tree,
+        r#"<hostname>r2</hostname>
+           <ntp><server>c</server></ntp>
+           <user><name>bob</name><uid>2</uid></user>"#,
+        EditOperation::Merge,
+    )
+    .unwrap();
+    assert_eq!(
+        json(&tree),
+        r#"{"hostname":"r2","mtu":1500,"ntp":{"enabled":true,"server":["a","b","c"]},"user":[{"name":"alice","uid":1},{"name":"bob","uid":2}],"rule":[{"name":"a"},{"name":"b"}],"search":["x","y"]}"#
+    );
+
+    // Replace the subtree, delete a leaf without value and remove a
+    // missing node.
+    let mut tree = base.clone();
+    edit(
+        &mut tree,
+        r#"<mtu nc:operation="delete"/>
+           <ntp nc:operation="replace"><server>d</server></ntp>
+           <user nc:operation="remove"><name>carol</name></user>
+           <user><name>alice</name><uid nc:operation="delete"/></user>"#,
+        EditOperation::Merge,
+    )
+    .unwrap();
+    assert_eq!(
+        json(&tree),
+        r#"{"hostname":"r1","ntp":{"server":["d"]},"user":[{"name":"alice"}],"rule":[{"name":"a"},{"name":"b"}],"search":["x","y"]}"#
+    );
+    // Annotations of edit-config are not kept.
+    assert!(tree
+        .get("/example-edit:system/ntp")
+        .unwrap()
+        .annotations
+        .is_empty());
+
+    // Errors, the tree is not modified.
+    let mut tree = base.clone();
+    let err = edit(
+        &mut tree,
+        r#"<hostname>r2</hostname>
+           <user nc:operation="create"><name>alice</name></user>"#,
+        EditOperation::Merge,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        EditError::DataExists(String::from("/example-edit:system/user[name='alice']"))
+    );
+    assert_eq!(err.error_tag(), "data-exists");
+    let err = edit(
+        &mut tree,
+        r#"<ntp><server nc:operation="delete">z</server></ntp>"#,
+        EditOperation::Merge,
+    )
+    .unwrap_err();
+    assert_eq!(err.error_tag(), "data-missing");
+    assert_eq!(err.path(), "/example-edit:system/ntp/server[.='z']");
+    let err = edit(
+        &mut tree,
+        r#"<hostname nc:operation="update">r2</hostname>"#,
+        EditOperation::Merge,
+    )
+    .unwrap_err();
+    assert_eq!(err.error_tag(), "bad-attribute");
+    assert_eq!(tree, base);
+
+    // Default operation "none" only traverses the existing nodes.
+    edit(
+        &mut tree,
+        r#"<hostname>ignored</hostname>
+           <user><name>alice</name><uid nc:operation="replace">5</uid></user>"#,
+        EditOperation::None,
+    )
+    .unwrap();
+    assert_eq!(
+        tree.get("/example-edit:system/hostname")
+            .unwrap()
+            .value()
+            .unwrap()
+            .to_string(),
+        "r1"
+    );
+    assert_eq!(
+        tree.get("/example-edit:system/user[name='alice']/uid")
+            .unwrap()
+            .value(),
+        Some(&LeafValue::Uint(5))
+    );
+    let err = edit(
+        &mut tree,
+        r#"<user><name>bob</name><uid nc:operation="create">5</uid></user>"#,
+        EditOperation::None,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        EditError::DataMissing(String::from("/example-edit:system/user[name='bob']"))
+    );
+
+    // Create the whole tree.
+    let mut tree = DataTree::new(base.schema.clone());
+    edit(
+        &mut tree,
+        r#"<hostname nc:operation="create">r1</hostname>"#,
+        EditOperation::Merge,
+    )
+    .unwrap();
+    assert_eq!(json(&tree), r#"{"hostname":"r1"}"#);
+}
+
+#[test]
+fn edit_insert_test() {
+    let schema = schema(EXAMPLE);
+    let base = DataTree::json_decode(schema, BASE).unwrap();
+
+    let mut tree = base.clone();
+    edit(
+        &mut tree,
+        r#"<rule yang:insert="first"><name>c</name></rule>
+           <rule yang:insert="after" yang:key="[name='a']"><name>d</name></rule>
+           <rule><name>e</name></rule>
+           <search yang:insert="before" yang:value="x">w</search>
+           <search nc:operation="merge" yang:insert="first">y</search>"#,
+        EditOperation::Merge,
+    )
+    .unwrap();
+    assert_eq!(
+        json(&tree),
+        r#"{"hostname":"r1","mtu":1500,"ntp":{"enabled":true,"server":["a","b"]},"user":[{"name":"alice","uid":1}],"rule":[{"name":"c"},{"name":"a"},{"name":"d"},{"name":"b"},{"name":"e"}],"search":["y","w","x"]}"#
+    );
+
+    // Key of the XML encoding has prefixes.
+    edit(
+        &mut tree,
+        r#"<rule nc:operation="replace" yang:insert="before" yang:key="[exe:name='c']"><name>b</name></rule>"#,
+        EditOperation::Merge,
+    )
+    .unwrap();
+    let rules: Vec<String> = tree
+        .get("/example-edit:system")
+        .unwrap()
+        .children()
+        .iter()
+        .filter(|n| n.name() == "rule")
+        .map(|n| n.key_values()[0].to_string())
+        .collect();
+    assert_eq!(rules, vec!["b", "c", "a", "d", "e"]);
+
+    let mut tree = base.clone();
+    let err = edit(
+        &mut tree,
+        r#"<rule yang:insert="after" yang:key="[name='z']"><name>c</name></rule>"#,
+        EditOperation::Merge,
+    )
+    .unwrap_err();
+    assert_eq!(err.error_tag(), "bad-attribute");
+    assert_eq!(err.app_tag(), Some("missing-instance"));
+    let err = edit(
+        &mut tree,
+        r#"<search yang:insert="before">w</search>"#,
+        EditOperation::Merge,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        EditError::MissingAttribute(
+            String::from("/example-edit:system/search[.='w']"),
+            String::from("value")
+        )
+    );
+    assert_eq!(err.error_tag(), "missing-attribute");
+    let err = edit(
+        &mut tree,
+        r#"<search yang:insert="middle">w</search>"#,
+        EditOperation::Merge,
+    )
+    .unwrap_err();
+    assert_eq!(err.error_tag(), "bad-attribute");
+    assert_eq!(tree, base);
+}
+
+#[test]
+fn edit_separate_schema_test() {
+    let base = DataTree::json_decode(schema(EXAMPLE), BASE).unwrap();
+
+    // Edit decoded with the same module compiled again.
+    let mut tree = base.clone();
+    let config = DataTree::json_decode(
+        schema(EXAMPLE),
+        r#"{"example-edit:system": {"user": [{"name": "x", "uid": 2}], "mtu": 9000}}"#,
+    )
+    .unwrap();
+    tree.edit(&config, EditOperation::Merge).unwrap();
+    assert_eq!(
+        json(&tree),
+        r#"{"hostname":"r1","mtu":9000,"ntp":{"enabled":true,"server":["a","b"]},"user":[{"name":"alice","uid":1},{"name":"x","uid":2}],"rule":[{"name":"a"},{"name":"b"}],"search":["x","y"]}"#
+    );
+    let uid = tree.get("/example-edit:system/user[name='x']/uid").unwrap();
+    assert_eq!(uid.value(), Some(&LeafValue::Uint(2)));
+
+    // Nodes which the schema doesn't have.
+    let mut tree = base.clone();
+    let other = schema(&EXAMPLE.replace("leaf mtu", "leaf speed"));
+    let config = DataTree::json_decode(other, r#"{"example-edit:system": {"speed": 10}}"#).unwrap();
+    let err = tree.edit(&config, EditOperation::Merge).unwrap_err();
+    assert_eq!(
+        err,
+        EditError::UnknownElement(String::from("/example-edit:system"), String::from("speed"))
+    );
+    assert_eq!(err.error_tag(), "unknown-element");
+    assert_eq!(tree, base);
+}
+
+#[test]
+fn edit_replace_nested_test() {
+    let schema = schema(EXAMPLE);
+    let base = DataTree::json_decode(schema, BASE).unwrap();
+
+    // Nested delete and remove apply to the replaced subtree.
+    let mut tree = base.clone();
+    edit(
+        &mut tree,
+        r#"<ntp nc:operation="replace">
+             <enabled>false</enabled>
+             <server nc:operation="delete">a</server>
+             <server nc:operation="remove">z</server>
+             <server>c</server>
+           </ntp>"#,
+        EditOperation::Merge,
+    )
+    .unwrap();
+    assert_eq!(
+        json(&tree),
+        r#"{"hostname":"r1","mtu":1500,"ntp":{"enabled":false,"server":["c"]},"user":[{"name":"alice","uid":1}],"rule":[{"name":"a"},{"name":"b"}],"search":["x","y"]}"#
+    );
+
+    // Replace by the default operation.
+    let mut tree = base.clone();
+    edit(
+        &mut tree,
+        r#"<hostname>r2</hostname>
+           <user><name>alice</name><uid nc:operation="delete"/></user>"#,
+        EditOperation::Replace,
+    )
+    .unwrap();
+    assert_eq!(
+        json(&tree),
+        r#"{"hostname":"r2","user":[{"name":"alice"}]}"#
+    );
+
+    // Deleted node must exist in the replaced subtree.
+    let mut tree = base.clone();
+    let err = edit(
+        &mut tree,
+        r#"<ntp nc:operation="replace"><server nc:operation="delete">q</server></ntp>"#,
+        EditOperation::Merge,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        EditError::DataMissing(String::from("/example-edit:system/ntp/server[.='q']"))
+    );
+    assert_eq!(tree, base);
+}
@@ -0,0 +1,81 @@
+use libyang::*;
+
+#[test]
+fn extension_instance_test() {
+    let literal = r#"module example-system {
+  yang-version 1.1;
+  namespace "urn:example:system";
+  prefix sys;
+  import ietf-netconf-acm {
+    prefix nacm;
+  }
+  import openconfig-extensions {
+    prefix oc-ext;
+  }
+  oc-ext:openconfig-version "1.2.0";
+  extension secret {
+    argument "reason" {
+      yin-element true;
+    }
+    description "Marks a secret node.";
+  }
+  container system {
+    nacm:default-deny-write;
+    leaf password {
+      type string {
+        sys:secret "hash";
+      }
+      sys:secret "credential";
+    }
+    list user {
+      key "name";
+      leaf name {
+        type string;
+      }
+      nacm:default-deny-all;
+    }
+  }
+}"#;
+    let (_, module) = yang_parse(literal).unwrap();
+
+    let extension = module.extensions.get("secret").unwrap();
+    let argument = extension.argument.as_ref().unwrap();
+    assert_eq!(argument.name, "reason");
+    assert_eq!(argument.yin_element, Some(true));
+
+    let version = find_ext_instance(
+        &module.ext_instances,
+        "openconfig-extensions",
+        "openconfig-version",
+    )
+    .unwrap();
+    assert_eq!(version.argument, Some(String::from("1.2.0")));
+
+    let system = &module.nodes[0];
+    assert!(find_ext_instance(
+        system.ext_instances(),
+        "ietf-netconf-acm",
+        "default-deny-write"
+    )
+    .is_some());
+
+    let nodes = system.nodes().unwrap();
+    let password = &nodes[0];
+    let secret = find_ext_instance(password.ext_instances(), "example-system", "secret").unwrap();
+    assert_eq!(secret.name(), "sys:secret");
+    assert_eq!(secret.argument, Some(String::from("credential")));
+    if let Node::Leaf(leaf) = password {
+        let typ = leaf.typ.as_ref().unwrap();
+        assert_eq!(
+            typ.ext_instances[0].module,
+            Some(String::from("example-system"))
+        );
+    } else {
+        panic!("password is not leaf");
+    }
+
+    let user = &nodes[1];
+    assert!(
+        find_ext_instance(user.ext_instances(), "ietf-netconf-acm", "default-deny-all").is_some()
+    );
+}
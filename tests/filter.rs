@@ -0,0 +1,156 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const EXAMPLE: &str = r#"module example-filter {
+  namespace "http://example.com/schema/1.2/config";
+  prefix t;
+  container top {
+    container users {
+      list user {
+        key "name";
+        leaf name {
+          type string;
+        }
+        leaf type {
+          type string;
+        }
+        leaf full-name {
+          type string;
+        }
+        leaf-list group {
+          type string;
+        }
+        container company-info {
+          leaf dept {
+            type uint32;
+          }
+          leaf id {
+            type uint32;
+          }
+        }
+      }
+    }
+  }
+}"#;
+
+const DATA: &str = r#"{"example-filter:top": {"users": {"user": [
+  {"name": "root", "type": "superuser", "full-name": "Charlie Root",
+   "group": ["wheel", "admin"], "company-info": {"dept": 1, "id": 1}},
+  {"name": "fred", "type": "admin", "full-name": "Fred Flintstone",
+   "group": ["admin"], "company-info": {"dept": 2, "id": 2}},
+  {"name": "barney", "type": "admin", "full-name": "Barney Rubble",
+   "company-info": {"dept": 2, "id": 3}}
+]}}}"#;
+
+fn filter(tree: &DataTree, filter: &str) -> String {
+    let filter = SubtreeFilter::xml_decode(&format!(
+        r#"<filter xmlns="urn:ietf:params:xml:ns:netconf:base:1.0" type="subtree">{}</filter>"#,
+        filter
+    ))
+    .unwrap();
+    tree.filter(&filter).to_json().to_string()
+}
+
+#[test]
+fn subtree_filter_test() {
+    let tree = DataTree::json_decode(schema(EXAMPLE), DATA).unwrap();
+
+    // RFC6241 6.4.1.  No Filter
+    assert_eq!(filter(&tree, ""), "{}");
+
+    // RFC6241 6.4.2.  Empty Filter
+    let all = tree.filter(
+        &SubtreeFilter::xml_decode(r#"<top xmlns="http://example.com/schema/1.2/config"/>"#)
+            .unwrap(),
+    );
+    assert_eq!(all, tree);
+    assert_eq!(
+        filter(
+            &tree,
+            r#"<top xmlns="http://example.com/schema/1.2/other"/>"#
+        ),
+        "{}"
+    );
+
+    // RFC6241 6.4.4.  Retrieve Only Specific Nodes of a Subtree.  Filter
+    // nodes without namespace match any namespace.
+    let names = r#"<top><users><user><name/></user></users></top>"#;
+    assert_eq!(
+        filter(&tree, names),
+        r#"{"example-filter:top":{"users":{"user":[{"name":"root"},{"name":"fred"},{"name":"barney"}]}}}"#
+    );
+
+    // List keys are included with the selected descendants.
+    assert_eq!(
+        filter(
+            &tree,
+            r#"<top xmlns="http://example.com/schema/1.2/config">
+                 <users><user><company-info><id/></company-info></user></users>
+               </top>"#
+        ),
+        r#"{"example-filter:top":{"users":{"user":[{"name":"root","company-info":{"id":1}},{"name":"fred","company-info":{"id":2}},{"name":"barney","company-info":{"id":3}}]}}}"#
+    );
+
+    // RFC6241 6.4.5.  Multiple Subtrees
+    assert_eq!(
+        filter(
+            &tree,
+            r#"<top xmlns="http://example.com/schema/1.2/config">
+                 <users>
+                   <user><name>root</name><company-info/></user>
+                   <user><name>fred</name><company-info><id/></company-info></user>
+                   <user><name>fred</name><type/></user>
+                 </users>
+               </top>"#
+        ),
+        r#"{"example-filter:top":{"users":{"user":[{"name":"root","company-info":{"dept":1,"id":1}},{"name":"fred","type":"admin","company-info":{"id":2}}]}}}"#
+    );
+
+    // Content match nodes only select all the siblings, content match of
+    // leaf-list selects the matching values.
+    assert_eq!(
+        filter(
+            &tree,
+            r#"<top xmlns="http://example.com/schema/1.2/config">
+                 <users><user><name>fred</name></user></users>
+               </top>"#
+        ),
+        r#"{"example-filter:top":{"users":{"user":[{"name":"fred","type":"admin","full-name":"Fred Flintstone","group":["admin"],"company-info":{"dept":2,"id":2}}]}}}"#
+    );
+    assert_eq!(
+        filter(
+            &tree,
+            r#"<top><users><user><group>admin</group><full-name/></user></users></top>"#
+        ),
+        r#"{"example-filter:top":{"users":{"user":[{"name":"root","full-name":"Charlie Root","group":["admin"]},{"name":"fred","full-name":"Fred Flintstone","group":["admin"]}]}}}"#
+    );
+
+    // Content match of descendants, values are compared by the type.
+    assert_eq!(
+        filter(
+            &tree,
+            r#"<top><users><user>
+                 <company-info><dept> 02 </dept></company-info>
+               </user></users></top>"#
+        ),
+        r#"{"example-filter:top":{"users":{"user":[{"name":"fred","company-info":{"dept":2,"id":2}},{"name":"barney","company-info":{"dept":2,"id":3}}]}}}"#
+    );
+
+    // False content match selects nothing.
+    assert_eq!(
+        filter(
+            &tree,
+            r#"<top><users><user><name>wilma</name></user></users></top>"#
+        ),
+        "{}"
+    );
+
+    assert!(matches!(
+        SubtreeFilter::xml_decode(
+            r#"<filter xmlns="urn:ietf:params:xml:ns:netconf:base:1.0" type="xpath"/>"#
+        ),
+        Err(DataError::InvalidValue(_, typ, _)) if typ == "xpath"
+    ));
+}
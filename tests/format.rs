@@ -0,0 +1,264 @@
+use libyang::*;
+
+const UNFORMATTED: &str = r#"// Example module.
+module example {
+    prefix ex; namespace "urn:example";
+    revision 2022-01-01 { description "Initial revision."; }
+    organization "Example";
+    import ietf-inet-types { prefix inet; }
+
+    /* Top level
+       container. */
+    container system {
+        leaf hostname {
+            description
+                "Name of the host.
+                 Must be unique.";
+            type inet:host;
+        }
+        leaf port { default 22; type uint16 { range "1..max"; } }
+        // Trailing comment.
+    }
+}
+"#;
+
+const FORMATTED: &str = r#"// Example module.
+module example {
+  namespace "urn:example";
+  prefix ex;
+
+  import ietf-inet-types {
+    prefix inet;
+  }
+
+  organization "Example";
+
+  revision 2022-01-01 {
+    description "Initial revision.";
+  }
+
+  /* Top level
+     container. */
+  container system {
+    leaf hostname {
+      type inet:host;
+      description
+        "Name of the host.
+         Must be unique.";
+    }
+    leaf port {
+      type uint16 {
+        range 1..max;
+      }
+      default 22;
+    }
+    // Trailing comment.
+  }
+}
+"#;
+
+#[test]
+fn format_yang_test() {
+    let opts = FormatOptions::default();
+    let formatted = format_yang(UNFORMATTED, &opts).unwrap();
+    assert_eq!(formatted, FORMATTED);
+
+    // Formatting is idempotent and the statement tree is kept.
+    assert_eq!(format_yang(&formatted, &opts).unwrap(), formatted);
+    let mut stmt = stmt_parse(UNFORMATTED).unwrap();
+    stmt.canonicalize();
+    assert_eq!(stmt_parse(&formatted).unwrap(), stmt);
+
+    // Statement order is kept without canonical ordering.
+    let opts = FormatOptions {
+        indent: 4,
+        canonical: false,
+        ..Default::default()
+    };
+    let formatted = format_yang(UNFORMATTED, &opts).unwrap();
+    assert!(formatted.starts_with(
+        "// Example module.\nmodule example {\n    prefix ex;\n    namespace \"urn:example\";\n"
+    ));
+    assert_eq!(
+        stmt_parse(&formatted).unwrap(),
+        stmt_parse(UNFORMATTED).unwrap()
+    );
+}
+
+#[test]
+fn check_format_test() {
+    let opts = FormatOptions::default();
+    assert_eq!(check_format(FORMATTED, &opts), Ok(vec![]));
+
+    let diffs = check_format(UNFORMATTED, &opts).unwrap();
+    assert_eq!(diffs[0].line, 3);
+    assert_eq!(
+        diffs[0].original[0],
+        "    prefix ex; namespace \"urn:example\";"
+    );
+    assert_eq!(diffs[0].formatted[0], "  namespace \"urn:example\";");
+    assert!(diffs[0]
+        .to_string()
+        .starts_with("@@ line 3 @@\n-    prefix ex; namespace \"urn:example\";\n"));
+
+    assert_eq!(
+        check_format("module example {\n  prefix ex\n}", &opts),
+        Err(StmtError::Syntax(3, 1))
+    );
+}
+
+// Comments on the same line stay there, and the strings of a
+// concatenation with comments are not joined.
+const COMMENTS: &str = r#"module example {
+  namespace "urn:example"; // Namespace.
+  prefix ex;
+
+  container system { // System.
+    leaf a {
+      type string;
+    } // Trailing a.
+    leaf b {
+      type string; /* String. */
+      description
+        "one "
+        // Middle.
+        + "two";
+    }
+  }
+}
+"#;
+
+#[test]
+fn format_comments_test() {
+    let opts = FormatOptions::default();
+    let literal = r#"module example { namespace "urn:example"; // Namespace.
+prefix ex; container system { // System.
+leaf a { type string; } // Trailing a.
+leaf b { description "one " + // Middle.
+"two"; type string; /* String. */
+} } }"#;
+    assert_eq!(format_yang(literal, &opts).unwrap(), COMMENTS);
+    assert_eq!(check_format(COMMENTS, &opts), Ok(vec![]));
+
+    let stmt = stmt_parse(COMMENTS).unwrap();
+    let b = &stmt.substmts[2].substmts[1];
+    assert_eq!(
+        b.substmt("description").unwrap().argument.as_deref(),
+        Some("one two")
+    );
+}
+
+const MODULE: &str = r#"module example {
+  yang-version 1.1;
+  prefix ex;
+  namespace "urn:example";
+  description
+    "Example module with a long description which is wrapped by the printer.";
+  revision 2022-01-01;
+  identity protocol;
+  identity ssh { base protocol; }
+  typedef port { type uint16 { range "1..max"; } }
+  grouping endpoint { leaf address { type string; } leaf port { type port; } }
+  container system {
+    presence "Enables the system.";
+    leaf hostname {
+      description
+        "Name of the host.
+         Must be unique.";
+      type string { pattern '[a-z]+\d*'; length "1..64"; }
+      mandatory true;
+    }
+    list server {
+      key name;
+      max-elements unbounded;
+      leaf name { type string { pattern 'x.*' { modifier invert-match; } } }
+      leaf protocol { type identityref { base protocol; } }
+      leaf mode { type enumeration { enum active { value 1; } enum passive; } }
+    }
+    choice transport {
+      default tcp;
+      case tcp { leaf tcp-port { type port; } }
+      leaf udp-port { type port; }
+    }
+    uses endpoint { refine port { default 22; } }
+  }
+}"#;
+
+#[test]
+fn module_to_yang_test() {
+    let (_, module) = yang_parse(MODULE).unwrap();
+    let opts = FormatOptions::default();
+    let text = module.to_yang(&opts);
+    assert!(text.contains(
+        "  description
+    \"Example module with a long description which is wrapped by the \"
+    + \"printer.\";
+"
+    ));
+    assert!(text.contains(
+        "  container system {
+    presence \"Enables the system.\";
+    leaf hostname {
+      type string {
+        length 1..64;
+        pattern '[a-z]+\\d*';
+      }
+      mandatory true;
+      description
+        \"Name of the host.
+         Must be unique.\";
+    }
+"
+    ));
+    assert!(text.contains("      max-elements unbounded;\n"));
+    assert!(text.contains(
+        "    choice transport {
+      default tcp;
+      case tcp {
+        leaf tcp-port {
+          type port;
+        }
+      }
+      leaf udp-port {
+        type port;
+      }
+    }
+"
+    ));
+    assert!(text.contains(
+        "    uses endpoint {
+      refine port {
+        default 22;
+      }
+    }
+"
+    ));
+    assert!(text.contains(
+        "  grouping endpoint {
+    leaf address {
+      type string;
+    }
+"
+    ));
+    assert!(text.contains(
+        "          pattern \"x.*\" {
+            modifier invert-match;
+          }
+"
+    ));
+
+    // The printed module parses to the same model.
+    let (_, reparsed) = yang_parse(&text).unwrap();
+    assert_eq!(reparsed.to_yang(&opts), text);
+    assert_eq!(format_yang(&text, &opts).unwrap(), text);
+}
+
+#[test]
+fn format_files_test() {
+    let opts = FormatOptions::default();
+    let text = std::fs::read_to_string("tests/ietf-dhcp@2016-08-25.yang").unwrap();
+    let formatted = format_yang(&text, &opts).unwrap();
+    assert_eq!(format_yang(&formatted, &opts).unwrap(), formatted);
+    assert_eq!(check_format(&formatted, &opts), Ok(vec![]));
+    assert!(formatted.lines().all(|line| line.len() <= 72));
+}
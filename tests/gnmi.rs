@@ -0,0 +1,303 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const EXAMPLE: &str = r#"module example-gnmi {
+  namespace "urn:example:gnmi";
+  prefix exg;
+  container system {
+    leaf hostname {
+      type string;
+    }
+    leaf load {
+      type decimal64 {
+        fraction-digits 2;
+      }
+    }
+    leaf debug {
+      type empty;
+    }
+    leaf-list dns {
+      type string;
+    }
+    container tls {
+      presence "TLS";
+    }
+    list server {
+      key "name";
+      leaf name {
+        type string;
+      }
+      leaf port {
+        type uint16;
+      }
+    }
+    anydata extra;
+  }
+}"#;
+
+const DATA: &str = r#"{
+  "ietf-interfaces:interfaces": {"interface": [
+    {"name": "eth0", "type": "iana-if-type:ethernetCsmacd", "enabled": false}
+  ]},
+  "example-gnmi:system": {
+    "hostname": "r1",
+    "load": "0.75",
+    "debug": [null],
+    "dns": ["10.0.0.1", "10.0.0.2"],
+    "tls": {}
+  }
+}"#;
+
+fn path(s: &str) -> GnmiPath {
+    let elem = s
+        .split('/')
+        .skip(1)
+        .map(|e| match e.split_once('[') {
+            Some((name, key)) => {
+                let (k, v) = key.trim_end_matches(']').split_once('=').unwrap();
+                let mut elem = PathElem::new(name.to_owned());
+                elem.key.push((k.to_owned(), v.to_owned()));
+                elem
+            }
+            None => PathElem::new(e.to_owned()),
+        })
+        .collect();
+    GnmiPath::new(elem)
+}
+
+#[test]
+fn gnmi_path_test() {
+    let schema = third_party_schema(&[EXAMPLE]);
+
+    let data_path = "/ietf-interfaces:interfaces/interface[name='eth0']/enabled";
+    let gnmi = schema.gnmi_path(data_path).unwrap();
+    assert_eq!(
+        gnmi.to_string(),
+        "/ietf-interfaces:interfaces/interface[name=eth0]/enabled"
+    );
+    assert_eq!(
+        gnmi.elem[1].key,
+        vec![(String::from("name"), String::from("eth0"))]
+    );
+    assert_eq!(schema.gnmi_data_path(&gnmi).unwrap(), data_path);
+
+    // Prefixes of data path are converted to module names, and the origin
+    // is the module of the unqualified top level element.
+    let gnmi = schema
+        .gnmi_path("/if:interfaces/if:interface[if:name='a]b']")
+        .unwrap();
+    assert_eq!(
+        gnmi.to_string(),
+        "/ietf-interfaces:interfaces/interface[name=a\\]b]"
+    );
+    let mut gnmi = path("/interfaces/interface[name=eth0]");
+    assert!(schema.gnmi_data_path(&gnmi).is_ok());
+    gnmi.origin = Some(String::from("example-gnmi"));
+    assert!(schema.gnmi_data_path(&gnmi).is_err());
+    gnmi.origin = Some(String::from("ietf-interfaces"));
+    assert_eq!(
+        schema.gnmi_data_path(&gnmi).unwrap(),
+        "/ietf-interfaces:interfaces/interface[name='eth0']"
+    );
+
+    assert!(matches!(
+        schema.gnmi_data_path(&path("/ietf-interfaces:interfaces/interface/name")),
+        Err(DataError::MissingKey(_, key)) if key == "name"
+    ));
+    assert!(schema
+        .gnmi_data_path(&path("/example-gnmi:system/hostname[name=a]"))
+        .is_err());
+    assert!(schema
+        .gnmi_path("/example-gnmi:system/dns[.='10.0.0.1']")
+        .is_err());
+}
+
+#[test]
+fn gnmi_notification_test() {
+    let schema = third_party_schema(&[EXAMPLE]);
+    let tree = DataTree::json_decode(schema.clone(), DATA).unwrap();
+
+    let notification = tree.gnmi_notification(1);
+    // Order of the top level nodes depends on the module order.
+    let mut updates: Vec<(String, TypedValue)> = notification
+        .update
+        .iter()
+        .map(|u| (u.path.to_string(), u.val.clone()))
+        .collect();
+    updates.sort_by_key(|u| !u.0.starts_with("/ietf"));
+    let s = |v: &str| TypedValue::String(v.to_owned());
+    assert_eq!(
+        updates,
+        vec![
+            (
+                String::from("/ietf-interfaces:interfaces/interface[name=eth0]/name"),
+                s("eth0")
+            ),
+            (
+                String::from("/ietf-interfaces:interfaces/interface[name=eth0]/type"),
+                s("iana-if-type:ethernetCsmacd")
+            ),
+            (
+                String::from("/ietf-interfaces:interfaces/interface[name=eth0]/enabled"),
+                TypedValue::Bool(false)
+            ),
+            (String::from("/example-gnmi:system/hostname"), s("r1")),
+            (
+                String::from("/example-gnmi:system/load"),
+                TypedValue::Decimal(75, 2)
+            ),
+            (
+                String::from("/example-gnmi:system/debug"),
+                TypedValue::JsonIetf(JsonValue::Array(vec![JsonValue::Null]))
+            ),
+            (
+                String::from("/example-gnmi:system/dns"),
+                TypedValue::LeafList(vec![s("10.0.0.1"), s("10.0.0.2")])
+            ),
+            (
+                String::from("/example-gnmi:system/tls"),
+                TypedValue::JsonIetf(JsonValue::Object(vec![]))
+            ),
+        ]
+    );
+
+    // Rebuild from the updates.
+    let rebuilt = DataTree::from_gnmi(schema.clone(), &[notification]).unwrap();
+    assert_eq!(rebuilt, tree);
+
+    // Updates and deletes relative to the prefix.
+    let mut tree = rebuilt;
+    let mut notification = Notification::new(2);
+    notification.prefix = Some(path("/example-gnmi:system"));
+    notification.delete = vec![path("/dns"), path("/tls"), path("/hostname")];
+    notification.update = vec![
+        Update::new(path("/load"), TypedValue::Double(1.5)),
+        Update::new(
+            path("/hostname"),
+            TypedValue::JsonIetf(JsonValue::String(String::from("r2"))),
+        ),
+        Update::new(path("/dns"), TypedValue::LeafList(vec![s("10.0.0.3")])),
+    ];
+    tree.gnmi_apply(&notification).unwrap();
+    assert_eq!(
+        tree.get("/example-gnmi:system")
+            .unwrap()
+            .to_json()
+            .to_string(),
+        r#"{"hostname":"r2","load":"1.5","debug":[null],"dns":["10.0.0.3"]}"#
+    );
+
+    // Deleting missing nodes is accepted, invalid updates are not
+    // applied at all.
+    let before = tree.clone();
+    let mut notification = Notification::new(3);
+    notification.delete = vec![path("/example-gnmi:system/tls")];
+    notification.update = vec![
+        Update::new(path("/example-gnmi:system/hostname"), s("r3")),
+        Update::new(
+            path("/example-gnmi:system/load"),
+            TypedValue::Decimal(12345, 3),
+        ),
+    ];
+    assert!(matches!(
+        tree.gnmi_apply(&notification),
+        Err(DataError::InvalidValue(p, _, _)) if p == "/example-gnmi:system/load"
+    ));
+    assert_eq!(tree, before);
+    notification.update.pop();
+    tree.gnmi_apply(&notification).unwrap();
+    assert!(tree.get("/example-gnmi:system/tls").is_err());
+
+    // Typed values of the leaf types.
+    let enabled = schema
+        .find_path("/ietf-interfaces:interfaces/interface/enabled")
+        .unwrap();
+    assert_eq!(
+        TypedValue::Bool(true).to_leaf(&schema, &enabled, "enabled"),
+        Ok(LeafValue::Boolean(true))
+    );
+    assert!(TypedValue::Int(1)
+        .to_leaf(&schema, &enabled, "enabled")
+        .is_err());
+    let speed = schema
+        .find_path("/ietf-interfaces:interfaces-state/interface/speed")
+        .unwrap();
+    assert_eq!(
+        TypedValue::JsonIetf(JsonValue::String(String::from("1000")))
+            .to_leaf(&schema, &speed, "speed"),
+        Ok(LeafValue::Uint(1000))
+    );
+    assert_eq!(
+        TypedValue::from(&LeafValue::Uint(1000)),
+        TypedValue::Uint(1000)
+    );
+}
+
+#[test]
+fn gnmi_json_ietf_test() {
+    let schema = third_party_schema(&[EXAMPLE]);
+    let tree = DataTree::json_decode(
+        schema.clone(),
+        r#"{"example-gnmi:system": {
+             "hostname": "r1",
+             "server": [{"name": "a", "port": 80}],
+             "extra": {"note": "x", "item": [{"id": "1"}, {"id": "2"}]}
+           }}"#,
+    )
+    .unwrap();
+    let notification = tree.gnmi_notification(1);
+    let extra = notification
+        .update
+        .iter()
+        .find(|u| u.path.to_string() == "/example-gnmi:system/extra")
+        .unwrap();
+    assert!(matches!(extra.val, TypedValue::JsonIetf(_)));
+    let rebuilt = DataTree::from_gnmi(schema.clone(), &[notification]).unwrap();
+    assert_eq!(rebuilt, tree);
+
+    // Children of the container and the list entry values are merged, the
+    // keys may be omitted.
+    let json = |text: &str| TypedValue::JsonIetf(json_parse(text).unwrap());
+    let mut tree = rebuilt;
+    let mut notification = Notification::new(2);
+    notification.update = vec![
+        Update::new(
+            path("/example-gnmi:system"),
+            json(r#"{"hostname": "r2", "server": [{"name": "b", "port": 443}]}"#),
+        ),
+        Update::new(
+            path("/example-gnmi:system/server[name=a]"),
+            json(r#"{"port": 8080}"#),
+        ),
+        Update::new(
+            path("/example-gnmi:system/server[name=c]"),
+            json(r#"{"name": "c"}"#),
+        ),
+        Update::new(path("/example-gnmi:system/extra"), json(r#"{"note": "y"}"#)),
+    ];
+    tree.gnmi_apply(&notification).unwrap();
+    assert_eq!(
+        tree.get("/example-gnmi:system")
+            .unwrap()
+            .to_json()
+            .to_string(),
+        r#"{"hostname":"r2","server":[{"name":"a","port":8080},{"name":"b","port":443},{"name":"c"}],"extra":{"note":"y"}}"#
+    );
+
+    // Keys of the value must be the keys of the path.
+    let before = tree.clone();
+    let mut notification = Notification::new(3);
+    notification.update = vec![Update::new(
+        path("/example-gnmi:system/server[name=a]"),
+        json(r#"{"name": "b"}"#),
+    )];
+    assert_eq!(
+        tree.gnmi_apply(&notification),
+        Err(DataError::KeyModify(String::from(
+            "/example-gnmi:system/server[name='a']"
+        )))
+    );
+    assert_eq!(tree, before);
+}
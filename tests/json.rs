@@ -0,0 +1,258 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const INTERFACES: &str = r#"{
+  "ietf-interfaces:interfaces": {
+    "interface": [
+      {
+        "name": "eth0",
+        "description": "Uplink \"A\"",
+        "type": "iana-if-type:ethernetCsmacd",
+        "enabled": true
+      },
+      {
+        "name": "lo0",
+        "type": "iana-if-type:softwareLoopback",
+        "enabled": false
+      }
+    ]
+  },
+  "ietf-interfaces:interfaces-state": {
+    "interface": [
+      {
+        "name": "eth0",
+        "type": "iana-if-type:ethernetCsmacd",
+        "admin-status": "up",
+        "oper-status": "down",
+        "last-change": "2024-01-01T00:00:00Z",
+        "if-index": 2,
+        "phys-address": "00:00:5e:00:53:01",
+        "higher-layer-if": [
+          "vlan10",
+          "vlan20"
+        ],
+        "speed": "10000000000",
+        "statistics": {
+          "discontinuity-time": "2024-01-01T00:00:00Z",
+          "in-octets": "18446744073709551615",
+          "in-errors": 0
+        }
+      }
+    ]
+  }
+}"#;
+
+#[test]
+fn json_round_trip_test() {
+    let schema = third_party_schema(&[]);
+    let tree = DataTree::json_decode(schema.clone(), INTERFACES).unwrap();
+
+    let entry = tree
+        .get("/ietf-interfaces:interfaces/interface[name='lo0']")
+        .unwrap();
+    assert_eq!(
+        entry.child("type").unwrap().value(),
+        Some(&LeafValue::Identity(
+            String::from("iana-if-type"),
+            String::from("softwareLoopback")
+        ))
+    );
+    let stats = tree
+        .get("/ietf-interfaces:interfaces-state/interface[name='eth0']/statistics/in-octets")
+        .unwrap();
+    assert_eq!(stats.value(), Some(&LeafValue::Uint(u64::MAX)));
+
+    // Encoding is the same as the input which is in the schema order.
+    assert_eq!(tree.json_encode(), INTERFACES);
+    let decoded = DataTree::json_decode(schema, &tree.json_encode()).unwrap();
+    assert_eq!(decoded, tree);
+}
+
+#[test]
+fn json_decode_error_test() {
+    let schema = third_party_schema(&[]);
+    let decode = |text: &str| DataTree::json_decode(schema.clone(), text);
+
+    // Top level member must be qualified.
+    assert!(matches!(
+        decode(r#"{"interfaces": {}}"#),
+        Err(DataError::UnknownNode(..))
+    ));
+    // uint64 is a JSON string and uint32 is a JSON number.
+    assert!(matches!(
+        decode(
+            r#"{"ietf-interfaces:interfaces-state": {"interface": [
+                 {"name": "eth0", "speed": 100}]}}"#
+        ),
+        Err(DataError::InvalidValue(..))
+    ));
+    assert!(matches!(
+        decode(
+            r#"{"ietf-interfaces:interfaces-state": {"interface": [
+                 {"name": "eth0", "if-index": "1"}]}}"#
+        ),
+        Err(DataError::InvalidValue(..))
+    ));
+    assert!(matches!(
+        decode(r#"{"ietf-interfaces:interfaces": {"interface": [{"enabled": true}]}}"#),
+        Err(DataError::MissingKey(..))
+    ));
+    assert!(matches!(
+        decode(
+            r#"{"ietf-interfaces:interfaces": {"interface": [{"name": "eth0", "enabled": "true"}]}}"#
+        ),
+        Err(DataError::InvalidValue(..))
+    ));
+    assert!(matches!(
+        decode(r#"{"ietf-interfaces:interfaces": "#),
+        Err(DataError::Syntax(..))
+    ));
+}
+
+const EXAMPLE: &str = r#"module example-json {
+  namespace "urn:example:json";
+  prefix exj;
+  import ietf-interfaces {
+    prefix if;
+  }
+  container top {
+    leaf counter {
+      type int64;
+    }
+    leaf ratio {
+      type decimal64 {
+        fraction-digits 3;
+      }
+    }
+    leaf flag {
+      type empty;
+    }
+    leaf port {
+      type union {
+        type int32;
+        type string;
+      }
+    }
+    leaf when-bool {
+      type union {
+        type boolean;
+        type string;
+      }
+    }
+    leaf-list tags {
+      type string;
+    }
+    anydata extra;
+  }
+  leaf md {
+    type string;
+  }
+}"#;
+
+const EXAMPLE_JSON: &str = r#"{
+  "example-json:top": {
+    "@": {
+      "ietf-origin:origin": "ietf-origin:intended"
+    },
+    "counter": "-9223372036854775808",
+    "ratio": "1.5",
+    "flag": [
+      null
+    ],
+    "@flag": {
+      "example-json:note": "set by default"
+    },
+    "port": "80",
+    "when-bool": true,
+    "tags": [
+      "a",
+      "b",
+      "c"
+    ],
+    "@tags": [
+      null,
+      {
+        "example-json:note": "second"
+      },
+      null
+    ],
+    "extra": {
+      "hello": "world",
+      "item": [
+        {
+          "v": "1"
+        },
+        {
+          "v": "2"
+        }
+      ],
+      "ietf-interfaces:if": "eth0"
+    }
+  }
+}"#;
+
+#[test]
+fn json_example_test() {
+    let schema = third_party_schema(&[EXAMPLE]);
+    let tree = DataTree::json_decode(schema.clone(), EXAMPLE_JSON).unwrap();
+
+    let top = tree.get("/example-json:top").unwrap();
+    assert_eq!(
+        top.annotation("ietf-origin", "origin"),
+        Some("ietf-origin:intended")
+    );
+    assert_eq!(
+        top.child("counter").unwrap().value(),
+        Some(&LeafValue::Int(i64::MIN))
+    );
+    assert_eq!(top.child("flag").unwrap().value(), Some(&LeafValue::Empty));
+    assert_eq!(
+        top.child("flag")
+            .unwrap()
+            .annotation("example-json", "note"),
+        Some("set by default")
+    );
+    // String "80" is not the int32 member of the union.
+    assert_eq!(
+        top.child("port").unwrap().value(),
+        Some(&LeafValue::String(String::from("80")))
+    );
+    assert_eq!(
+        top.child("when-bool").unwrap().value(),
+        Some(&LeafValue::Boolean(true))
+    );
+    let tag = tree.get("/example-json:top/tags[.='b']").unwrap();
+    assert_eq!(tag.annotation("example-json", "note"), Some("second"));
+    let extra = top.child("extra").unwrap();
+    match &extra.content {
+        DataContent::Anydata(any) => {
+            assert_eq!(any.len(), 4);
+            assert_eq!(any[3].module.as_deref(), Some("ietf-interfaces"));
+        }
+        _ => panic!("anydata expected"),
+    }
+
+    assert_eq!(tree.json_encode(), EXAMPLE_JSON);
+
+    // Number is the int32 member of the union.
+    let mut tree = DataTree::json_decode(
+        schema.clone(),
+        r#"{"example-json:top": {"port": 80}, "example-json:md": "x"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        tree.get("/example-json:top/port").unwrap().value(),
+        Some(&LeafValue::Int(80))
+    );
+    assert_eq!(
+        tree.to_json().to_string(),
+        r#"{"example-json:top":{"port":80},"example-json:md":"x"}"#
+    );
+    tree.set("/example-json:top/port", "http").unwrap();
+    assert_eq!(
+        tree.get("/example-json:top").unwrap().to_json().to_string(),
+        r#"{"port":"http"}"#
+    );
+}
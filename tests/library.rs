@@ -0,0 +1,196 @@
+use libyang::*;
+
+const EXAMPLE: &str = r#"module example {
+  namespace "urn:example";
+  prefix ex;
+  import example-types {
+    prefix et;
+    revision-date 2020-01-01;
+  }
+  include example-sub;
+  revision 2022-03-01;
+  feature tls;
+  container system {
+    leaf hostname {
+      type et:name;
+    }
+  }
+}"#;
+
+const EXAMPLE_SUB: &str = r#"submodule example-sub {
+  belongs-to example {
+    prefix ex;
+  }
+  container clock;
+}"#;
+
+const EXAMPLE_TYPES_2020: &str = r#"module example-types {
+  namespace "urn:example:types";
+  prefix et;
+  revision 2020-01-01;
+  typedef name {
+    type string;
+  }
+}"#;
+
+const EXAMPLE_TYPES_2021: &str = r#"module example-types {
+  namespace "urn:example:types";
+  prefix et;
+  revision 2021-01-01;
+  revision 2020-01-01;
+  typedef name {
+    type string;
+  }
+}"#;
+
+const EXAMPLE_DEVIATIONS: &str = r#"module example-deviations {
+  namespace "urn:example:deviations";
+  prefix exd;
+  import example {
+    prefix ex;
+  }
+  deviation /ex:system/ex:hostname {
+    deviate not-supported;
+  }
+}"#;
+
+const EXAMPLE_STATE: &str = r#"module example-state {
+  namespace "urn:example:state";
+  prefix exs;
+  container state {
+    config false;
+    leaf uptime {
+      type uint32;
+    }
+  }
+}"#;
+
+fn source() -> MemorySource {
+    let mut source = MemorySource::new();
+    for text in &[
+        EXAMPLE,
+        EXAMPLE_SUB,
+        EXAMPLE_TYPES_2020,
+        EXAMPLE_TYPES_2021,
+        EXAMPLE_DEVIATIONS,
+        EXAMPLE_STATE,
+    ] {
+        source.add(text).unwrap();
+    }
+    source
+}
+
+#[test]
+fn yang_library_from_modules_test() {
+    let source = source();
+    let mut ms = Modules::new();
+    ms.load(&source, "example-deviations").unwrap();
+    ms.load_revision(&source, "example-types", Some("2021-01-01"))
+        .unwrap();
+
+    let library = YangLibrary::from_modules(&ms, &[DATASTORE_RUNNING, DATASTORE_OPERATIONAL]);
+    let json = library.to_json();
+    let expected = r#"{"ietf-yang-library:yang-library":{"module-set":[{"name":"complete","module":[{"name":"example","revision":"2022-03-01","namespace":"urn:example","submodule":[{"name":"example-sub"}],"feature":["tls"],"deviation":["example-deviations"]},{"name":"example-deviations","namespace":"urn:example:deviations"},{"name":"example-types","revision":"2021-01-01","namespace":"urn:example:types"}],"import-only-module":[{"name":"example-types","revision":"2020-01-01","namespace":"urn:example:types"}]}],"schema":[{"name":"complete","module-set":["complete"]}],"datastore":[{"name":"ietf-datastores:running","schema":"complete"},{"name":"ietf-datastores:operational","schema":"complete"}],"content-id":""#;
+    assert!(json.to_string().starts_with(expected), "{}", json);
+    assert_eq!(library.content_id.len(), 16);
+    assert_eq!(
+        YangLibrary::from_modules(&ms, &[DATASTORE_RUNNING, DATASTORE_OPERATIONAL]),
+        library
+    );
+
+    // Round trip of the instance document.
+    let text = json.to_string_pretty();
+    let parsed = YangLibrary::from_json(&json_parse(&text).unwrap()).unwrap();
+    assert_eq!(parsed, library);
+    assert_eq!(
+        parsed.datastore_schema(DATASTORE_OPERATIONAL).unwrap().name,
+        "complete"
+    );
+}
+
+const LIBRARY: &str = r#"{
+  "ietf-yang-library:yang-library": {
+    "module-set": [
+      {
+        "name": "config",
+        "module": [
+          {"name": "example", "revision": "2022-03-01", "namespace": "urn:example",
+           "location": ["https://example.com/example.yang"]}
+        ],
+        "import-only-module": [
+          {"name": "example-types", "revision": "2020-01-01", "namespace": "urn:example:types"}
+        ]
+      },
+      {
+        "name": "state",
+        "module": [
+          {"name": "example-state", "namespace": "urn:example:state"}
+        ]
+      }
+    ],
+    "schema": [
+      {"name": "config-schema", "module-set": ["config"]},
+      {"name": "state-schema", "module-set": ["config", "state"]}
+    ],
+    "datastore": [
+      {"name": "ietf-datastores:running", "schema": "config-schema"},
+      {"name": "ietf-datastores:operational", "schema": "state-schema"}
+    ],
+    "content-id": "1"
+  }
+}"#;
+
+#[test]
+fn yang_library_load_test() {
+    let source = source();
+    let library = YangLibrary::from_json(&json_parse(LIBRARY).unwrap()).unwrap();
+    assert_eq!(
+        library.module_sets[0].modules[0].location,
+        vec![String::from("https://example.com/example.yang")]
+    );
+
+    // Schema of the running datastore.
+    let schema = library.datastore_schema(DATASTORE_RUNNING).unwrap();
+    let mut ms = Modules::new();
+    ms.load_library(&source, &library, Some(&schema.name))
+        .unwrap();
+    let implemented: Vec<String> = ms
+        .implemented()
+        .iter()
+        .map(|m| m.id().to_string())
+        .collect();
+    assert_eq!(implemented, vec!["example@2022-03-01"]);
+    assert!(ms.import_only.contains(&ModuleId::new(
+        String::from("example-types"),
+        Some(String::from("2020-01-01"))
+    )));
+    let schema = ms.schema().unwrap();
+    assert!(schema.find_path("/example:system/hostname").is_ok());
+    assert!(schema.module("example-types").is_none());
+
+    // Schema of the operational datastore has the module sets of both.
+    let schema = library.datastore_schema(DATASTORE_OPERATIONAL).unwrap();
+    let mut ms = Modules::new();
+    ms.load_library(&source, &library, Some(&schema.name))
+        .unwrap();
+    let implemented: Vec<String> = ms
+        .implemented()
+        .iter()
+        .map(|m| m.id().to_string())
+        .collect();
+    assert_eq!(implemented, vec!["example@2022-03-01", "example-state"]);
+
+    let mut ms = Modules::new();
+    assert_eq!(
+        ms.load_library(&source, &library, Some("none")),
+        Err(ModuleError::UnknownSchema(String::from("none")))
+    );
+
+    let invalid = r#"{"ietf-yang-library:yang-library": {"module-set": [{"module": []}]}}"#;
+    assert_eq!(
+        YangLibrary::from_json(&json_parse(invalid).unwrap()),
+        Err(DataError::MissingValue(String::from(
+            "/ietf-yang-library:yang-library/module-set/name"
+        )))
+    );
+}
@@ -0,0 +1,322 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+use std::fs;
+
+const EXAMPLE: &str = r#"module example {
+  namespace "urn:example";
+  prefix ex;
+  import example-types {
+    prefix et;
+    revision-date 2020-01-01;
+  }
+  include example-sub;
+  revision 2022-03-01;
+  container system {
+    leaf hostname {
+      type et:name;
+    }
+  }
+}"#;
+
+const EXAMPLE_SUB: &str = r#"submodule example-sub {
+  belongs-to example {
+    prefix ex;
+  }
+  import example-common {
+    prefix c;
+  }
+  container clock {
+    leaf zone {
+      type c:zone;
+    }
+  }
+}"#;
+
+const EXAMPLE_TYPES_2020: &str = r#"module example-types {
+  namespace "urn:example:types";
+  prefix et;
+  revision 2020-01-01;
+  typedef name {
+    type string;
+  }
+}"#;
+
+const EXAMPLE_TYPES_2021: &str = r#"module example-types {
+  namespace "urn:example:types";
+  prefix et;
+  revision 2021-01-01;
+  revision 2020-01-01;
+  typedef name {
+    type string {
+      length "1..64";
+    }
+  }
+}"#;
+
+const EXAMPLE_COMMON: &str = r#"module example-common {
+  namespace "urn:example:common";
+  prefix c;
+  typedef zone {
+    type string;
+  }
+}"#;
+
+#[test]
+fn load_test() {
+    let dir = yang_dirs(
+        "load",
+        &[
+            ("example.yang", EXAMPLE),
+            ("example-sub.yang", EXAMPLE_SUB),
+            ("example-types@2020-01-01.yang", EXAMPLE_TYPES_2020),
+            ("example-types@2021-01-01.yang", EXAMPLE_TYPES_2021),
+            ("example-common.yang", EXAMPLE_COMMON),
+        ],
+    );
+    let mut yang = Yang::new();
+    yang.add_path(dir.to_str().unwrap());
+
+    let mut ms = Modules::new();
+    let module = ms.load(&yang, "example").unwrap();
+    assert_eq!(module.id().to_string(), "example@2022-03-01");
+    // Definitions of the submodule are merged into the module.
+    assert_eq!(module.nodes.len(), 2);
+    assert!(module.imports.iter().any(|i| i.name == "example-common"));
+
+    let mut keys: Vec<String> = ms.modules.keys().map(|id| id.to_string()).collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec![
+            "example-common",
+            "example-types@2020-01-01",
+            "example@2022-03-01"
+        ]
+    );
+    let schema = ms.schema().unwrap();
+    assert!(schema.find_path("/ex:clock/ex:zone").is_ok());
+
+    // Unpinned load picks the latest revision.
+    let mut ms = Modules::new();
+    let module = ms.load(&yang, "example-types").unwrap();
+    assert_eq!(module.revision(), Some("2021-01-01"));
+
+    // Submodule can't be loaded as a module.
+    let mut ms = Modules::new();
+    assert_eq!(
+        ms.load(&yang, "example-sub").unwrap_err(),
+        ModuleError::NotModule(String::from("example-sub"))
+    );
+    assert!(matches!(
+        ms.load(&yang, "example-none"),
+        Err(ModuleError::NotFound(..))
+    ));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn load_error_test() {
+    let dir = yang_dirs(
+        "load-error",
+        &[
+            (
+                "cycle-a.yang",
+                r#"module cycle-a { namespace "urn:a"; prefix a; import cycle-b { prefix b; } }"#,
+            ),
+            (
+                "cycle-b.yang",
+                r#"module cycle-b { namespace "urn:b"; prefix b; import cycle-a { prefix a; } }"#,
+            ),
+            (
+                "pinned.yang",
+                r#"module pinned { namespace "urn:p"; prefix p; import cycle-b { prefix b; revision-date 2000-01-01; } }"#,
+            ),
+            (
+                "other.yang",
+                r#"module other { namespace "urn:o"; prefix o; include example-sub; }"#,
+            ),
+            ("example-sub.yang", EXAMPLE_SUB),
+        ],
+    );
+    let mut yang = Yang::new();
+    yang.add_path(dir.to_str().unwrap());
+
+    let mut ms = Modules::new();
+    assert_eq!(
+        ms.load(&yang, "cycle-a").unwrap_err(),
+        ModuleError::ImportCycle(vec![
+            String::from("cycle-a"),
+            String::from("cycle-b"),
+            String::from("cycle-a")
+        ])
+    );
+    assert!(ms.modules.is_empty());
+    assert!(matches!(
+        ms.load(&yang, "pinned"),
+        Err(ModuleError::RevisionMismatch(name, _, _)) if name == "cycle-b"
+    ));
+    assert!(matches!(
+        ms.load(&yang, "other"),
+        Err(ModuleError::BelongsTo(..))
+    ));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn load_ietf_test() {
+    let mut yang = Yang::new();
+    yang.add_path("yang/...");
+
+    let mut ms = Modules::new();
+    ms.load(&yang, "iana-if-type").unwrap();
+    let mut keys: Vec<String> = ms.modules.keys().map(|id| id.to_string()).collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec![
+            "iana-if-type@2017-01-19",
+            "ietf-interfaces@2018-02-20",
+            "ietf-yang-types@2013-07-15"
+        ]
+    );
+}
+
+#[test]
+fn modules_registry_test() {
+    let mut ms = Modules::new();
+    for literal in &[EXAMPLE_TYPES_2020, EXAMPLE_TYPES_2021, EXAMPLE_COMMON] {
+        let (_, module) = yang_parse(literal).unwrap();
+        ms.add(module);
+    }
+    // Modules with the same prefix don't collide.
+    let (_, module) = yang_parse(
+        r#"module other-common { namespace "urn:other"; prefix c; import example-types { prefix t; revision-date 2020-01-01; } }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        module.prefixes.get("t"),
+        Some(&ModuleId::new(
+            String::from("example-types"),
+            Some(String::from("2020-01-01"))
+        ))
+    );
+    assert_eq!(module.prefix_module("c"), Some("other-common"));
+    ms.add(module);
+    assert_eq!(ms.modules.len(), 4);
+
+    // Two revisions coexist, the most recent one is found by name and is
+    // implemented.
+    assert_eq!(
+        ms.find_module("example-types").unwrap().revision(),
+        Some("2021-01-01")
+    );
+    let id = ModuleId::new(
+        String::from("example-types"),
+        Some(String::from("2020-01-01")),
+    );
+    assert_eq!(
+        ms.find_revision("example-types", Some("2020-01-01"))
+            .unwrap()
+            .id(),
+        id
+    );
+    let other = ms.find_module("other-common").unwrap();
+    assert_eq!(ms.import_module(other, "t").unwrap().id(), id);
+    let names: Vec<String> = ms
+        .implemented()
+        .iter()
+        .map(|m| m.id().to_string())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["example-common", "example-types@2021-01-01", "other-common"]
+    );
+
+    assert_eq!(
+        ms.namespace_module("urn:example:common").unwrap().name,
+        "example-common"
+    );
+    assert!(ms.namespace_module("urn:none").is_none());
+
+    let schema = ms.schema().unwrap();
+    assert_eq!(schema.modules.len(), 3);
+}
+
+#[test]
+fn load_revision_test() {
+    let dir = yang_dirs(
+        "load-revision",
+        &[
+            (
+                "rev@2020-01-01.yang",
+                r#"module rev { namespace "urn:r"; prefix r; revision 2022-01-01; revision 2020-01-01; }"#,
+            ),
+            (
+                "rev@2021-01-01.yang",
+                r#"module rev { namespace "urn:r"; prefix r; revision 2021-01-01; }"#,
+            ),
+            (
+                "broken@2017-01-01.yang",
+                r#"module broken { namespace "urn:b"; prefix"#,
+            ),
+            (
+                "broken@2018-01-01.yang",
+                r#"module broken { namespace "urn:b"; prefix b; revision 2018-01-01; }"#,
+            ),
+            (
+                "order.yang",
+                r#"module order { namespace "urn:o"; prefix o; revision 2020-01-01; revision 2021-01-01; }"#,
+            ),
+        ],
+    );
+    let mut yang = Yang::new();
+    yang.add_path(dir.to_str().unwrap());
+
+    // The newest revision is selected by the revision statements, and the
+    // file name which disagrees with it is warned.
+    assert_eq!(
+        yang.find_path("rev").unwrap(),
+        dir.join("rev@2020-01-01.yang")
+    );
+    let mut ms = Modules::new();
+    let module = ms.load(&yang, "rev").unwrap();
+    assert_eq!(module.revision(), Some("2022-01-01"));
+    assert_eq!(
+        ms.warnings,
+        vec![ModuleWarning::FileRevision(
+            dir.join("rev@2020-01-01.yang"),
+            String::from("2022-01-01")
+        )]
+    );
+
+    // The revision of the file which can't be parsed is taken from the
+    // file name, and it is warned.
+    let mut ms = Modules::new();
+    let module = ms.load(&yang, "broken").unwrap();
+    assert_eq!(module.revision(), Some("2018-01-01"));
+    assert_eq!(
+        ms.warnings,
+        vec![ModuleWarning::Unparsed(dir.join("broken@2017-01-01.yang"))]
+    );
+
+    // Revisions must be listed newest first.
+    assert_eq!(
+        ms.load(&yang, "order").unwrap_err(),
+        ModuleError::Revision(RevisionError::Order(
+            String::from("order"),
+            String::from("2020-01-01"),
+            String::from("2021-01-01")
+        ))
+    );
+
+    // Invalid dates are rejected by the parser.
+    assert!(
+        yang_parse(r#"module bad { namespace "urn:b"; prefix b; revision 2021-02-29; }"#).is_err()
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -12,10 +12,10 @@ fn parse_ietf_inet_types_test() {
 
     match yang_parse(&data) {
         Ok((_, module)) => {
-            ms.modules.insert(module.prefix.to_owned(), module);
+            ms.add(module);
 
-            let entry = ms.modules.get(&"inet".to_string());
-            if let Some(_) = entry {
+            let entry = ms.find_module("ietf-inet-types");
+            if entry.is_some() {
                 // Success.
             } else {
                 // Module not found.
@@ -40,10 +40,10 @@ fn parse_ietf_yang_types_test() {
 
     match yang_parse(&data) {
         Ok((_, module)) => {
-            ms.modules.insert(module.prefix.to_owned(), module);
+            ms.add(module);
 
-            let entry = ms.modules.get(&"yang".to_string());
-            if let Some(_) = entry {
+            let entry = ms.find_module("ietf-yang-types");
+            if entry.is_some() {
                 // Success.
             } else {
                 // Module not found.
@@ -68,10 +68,10 @@ fn parse_ietf_interfaces_test() {
 
     match yang_parse(&data) {
         Ok((_, module)) => {
-            ms.modules.insert(module.prefix.to_owned(), module);
+            ms.add(module);
 
-            let entry = ms.modules.get(&"if".to_string());
-            if let Some(_) = entry {
+            let entry = ms.find_module("ietf-interfaces");
+            if entry.is_some() {
                 // Success.
             } else {
                 // Module not found.
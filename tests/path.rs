@@ -0,0 +1,118 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const EXAMPLE: &str = r#"module example-path {
+  namespace "urn:example:path";
+  prefix exp;
+  container routing {
+    list route {
+      key "prefix next-hop";
+      leaf prefix {
+        type string;
+      }
+      leaf next-hop {
+        type string;
+      }
+      leaf-list tag {
+        type string;
+      }
+    }
+  }
+}"#;
+
+#[test]
+fn schema_path_test() {
+    let schema = third_party_schema(&[EXAMPLE]);
+
+    let node = schema
+        .find_path("/if:interfaces/if:interface/if:name")
+        .unwrap();
+    assert_eq!(node.module, "ietf-interfaces");
+    assert_eq!(node.kind, SchemaKind::Leaf);
+    let node = schema
+        .find_path("/ietf-interfaces:interfaces/interface[name='eth0']/enabled")
+        .unwrap();
+    assert_eq!(node.name, "enabled");
+    assert!(matches!(
+        schema.find_path("/if:interfaces/if:unknown"),
+        Err(DataError::UnknownNode(parent, name)) if parent == "/if:interfaces" && name == "unknown"
+    ));
+    assert!(schema.find_path("/exp:interfaces").is_err());
+
+    // Descendant schema node identifier.
+    let interfaces = schema.find_path("/if:interfaces").unwrap();
+    let nodeid = schema_nodeid_parse("if:interface/type").unwrap();
+    let node = schema.find_descendant(&interfaces, &nodeid).unwrap();
+    assert_eq!(node.name, "type");
+
+    assert_eq!(
+        schema
+            .schema_path("/ietf-interfaces:interfaces/interface[name='eth0']/enabled")
+            .unwrap()
+            .to_string(),
+        "/if:interfaces/if:interface/if:enabled"
+    );
+    assert_eq!(
+        schema
+            .schema_path("/exp:routing/route[prefix='10.0.0.0/8'][next-hop='a']/tag[.='x']")
+            .unwrap()
+            .to_string(),
+        "/exp:routing/exp:route/exp:tag"
+    );
+}
+
+#[test]
+fn restconf_path_test() {
+    let schema = third_party_schema(&[EXAMPLE]);
+
+    for (data_path, api_path) in &[
+        (
+            "/ietf-interfaces:interfaces/interface[name='eth0/1']/enabled",
+            "/ietf-interfaces:interfaces/interface=eth0%2F1/enabled",
+        ),
+        (
+            "/example-path:routing/route[prefix='10.0.0.0/8'][next-hop='a,b']/tag[.='x y']",
+            "/example-path:routing/route=10.0.0.0%2F8,a%2Cb/tag=x%20y",
+        ),
+        ("/example-path:routing/route", "/example-path:routing/route"),
+    ] {
+        assert_eq!(&schema.restconf_path(data_path).unwrap(), api_path);
+        assert_eq!(&schema.restconf_data_path(api_path).unwrap(), data_path);
+    }
+
+    // Keys are in key order, prefixes are converted to module names.
+    assert_eq!(
+        schema
+            .restconf_path("/exp:routing/exp:route[next-hop='b'][prefix='p']")
+            .unwrap(),
+        "/example-path:routing/route=p,b"
+    );
+    assert_eq!(
+        schema.restconf_path("/exp:routing/route[prefix='p']"),
+        Err(DataError::MissingKey(
+            String::from("/exp:routing/route[prefix='p']"),
+            String::from("next-hop")
+        ))
+    );
+    assert_eq!(schema.restconf_data_path("/").unwrap(), "");
+    assert_eq!(
+        schema
+            .restconf_data_path("/example-path:routing/route=,")
+            .unwrap(),
+        "/example-path:routing/route[prefix=''][next-hop='']"
+    );
+
+    // Top level node must have the module name, and prefixes are not
+    // module names.
+    assert!(schema.restconf_data_path("/routing").is_err());
+    assert!(schema.restconf_data_path("/exp:routing").is_err());
+    assert!(schema
+        .restconf_data_path("/example-path:routing/route=a")
+        .is_err());
+    assert!(schema
+        .restconf_data_path("/example-path:routing/route=a,%2")
+        .is_err());
+    assert!(schema.restconf_data_path("example-path:routing").is_err());
+}
@@ -0,0 +1,178 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const EXAMPLE: &str = r#"module example-jukebox {
+  namespace "http://example.com/ns/example-jukebox";
+  prefix jbox;
+  container jukebox {
+    container library {
+      list artist {
+        key "name";
+        leaf name {
+          type string;
+        }
+        list album {
+          key "name";
+          leaf name {
+            type string;
+          }
+          leaf genre {
+            type string;
+            default "rock";
+          }
+          leaf year {
+            type uint16;
+          }
+        }
+      }
+    }
+    container player {
+      leaf gap {
+        type uint8;
+      }
+      leaf state {
+        config false;
+        type string;
+      }
+    }
+  }
+}"#;
+
+const DATA: &str = r#"{"example-jukebox:jukebox": {
+  "library": {"artist": [
+    {"name": "Foo Fighters", "album": [
+      {"name": "Wasting Light", "genre": "rock", "year": 2011},
+      {"name": "One by One", "year": 2002}]},
+    {"name": "Nick Cave", "album": [{"name": "Ghosteen", "genre": "art", "year": 2019}]}
+  ]},
+  "player": {"gap": 1, "state": "idle"}
+}}"#;
+
+fn get(tree: &DataTree, uri: &str) -> String {
+    let uri = RestconfUri::parse(&tree.schema, uri).unwrap();
+    tree.restconf_get(&uri).unwrap().to_string()
+}
+
+#[test]
+fn restconf_uri_test() {
+    let schema = schema(EXAMPLE);
+    let uri = RestconfUri::parse(
+        &schema,
+        "/restconf/data/example-jukebox:jukebox/library/artist=Foo%20Fighters/album=Wasting%20Light?depth=2&with-defaults=report-all",
+    )
+    .unwrap();
+    assert_eq!(
+        uri.path,
+        "/example-jukebox:jukebox/library/artist[name='Foo Fighters']/album[name='Wasting Light']"
+    );
+    assert_eq!(uri.query.depth, Some(2));
+    assert_eq!(uri.query.with_defaults, Some(WithDefaults::ReportAll));
+
+    let uri = RestconfUri::parse(&schema, "/restconf/data").unwrap();
+    assert_eq!(uri, RestconfUri::new(String::new(), QueryParams::new()));
+    assert!(RestconfUri::parse(&schema, "/restconf/operations").is_err());
+    assert!(RestconfUri::parse(&schema, "/restconf/data/jukebox").is_err());
+    assert!(RestconfUri::parse(&schema, "/restconf/data?depth=0").is_err());
+}
+
+#[test]
+fn restconf_get_test() {
+    let tree = DataTree::json_decode(schema(EXAMPLE), DATA).unwrap();
+
+    assert_eq!(
+        get(&tree, "/restconf/data?depth=1"),
+        r#"{"ietf-restconf:data":{"example-jukebox:jukebox":{}}}"#
+    );
+    assert_eq!(
+        get(
+            &tree,
+            "/restconf/data/example-jukebox:jukebox/library/artist=Nick%20Cave"
+        ),
+        r#"{"example-jukebox:artist":[{"name":"Nick Cave","album":[{"name":"Ghosteen","genre":"art","year":2019}]}]}"#
+    );
+    assert_eq!(
+        get(&tree, "/restconf/data/example-jukebox:jukebox/player/gap"),
+        r#"{"example-jukebox:gap":1}"#
+    );
+
+    // List entries beyond the depth have their keys.
+    assert_eq!(
+        get(
+            &tree,
+            "/restconf/data/example-jukebox:jukebox/library?depth=2"
+        ),
+        r#"{"example-jukebox:library":{"artist":[{"name":"Foo Fighters"},{"name":"Nick Cave"}]}}"#
+    );
+
+    // Fields are relative to the target and the depth counts from the
+    // selected nodes.
+    assert_eq!(
+        get(
+            &tree,
+            "/restconf/data/example-jukebox:jukebox/library?fields=artist(album/year)"
+        ),
+        r#"{"example-jukebox:library":{"artist":[{"name":"Foo Fighters","album":[{"name":"Wasting Light","year":2011},{"name":"One by One","year":2002}]},{"name":"Nick Cave","album":[{"name":"Ghosteen","year":2019}]}]}}"#
+    );
+    assert_eq!(
+        get(
+            &tree,
+            "/restconf/data?fields=example-jukebox:jukebox/library/artist&depth=2"
+        ),
+        r#"{"ietf-restconf:data":{"example-jukebox:jukebox":{"library":{"artist":[{"name":"Foo Fighters","album":[{"name":"Wasting Light"},{"name":"One by One"}]},{"name":"Nick Cave","album":[{"name":"Ghosteen"}]}]}}}}"#
+    );
+
+    // Content of configuration and state.
+    assert_eq!(
+        get(
+            &tree,
+            "/restconf/data/example-jukebox:jukebox/player?content=config"
+        ),
+        r#"{"example-jukebox:player":{"gap":1}}"#
+    );
+    assert_eq!(
+        get(
+            &tree,
+            "/restconf/data/example-jukebox:jukebox?content=nonconfig"
+        ),
+        r#"{"example-jukebox:jukebox":{"player":{"state":"idle"}}}"#
+    );
+
+    // Default values.
+    let album =
+        "/restconf/data/example-jukebox:jukebox/library/artist=Foo%20Fighters/album=One%20by%20One";
+    assert_eq!(
+        get(&tree, album),
+        r#"{"example-jukebox:album":[{"name":"One by One","year":2002}]}"#
+    );
+    assert_eq!(
+        get(&tree, &format!("{}?with-defaults=report-all", album)),
+        r#"{"example-jukebox:album":[{"name":"One by One","genre":"rock","year":2002}]}"#
+    );
+    assert_eq!(
+        get(&tree, &format!("{}?with-defaults=report-all-tagged", album)),
+        r#"{"example-jukebox:album":[{"name":"One by One","genre":"rock","@genre":{"ietf-netconf-with-defaults:default":true},"year":2002}]}"#
+    );
+    let album = "/restconf/data/example-jukebox:jukebox/library/artist=Foo%20Fighters/album=Wasting%20Light";
+    assert_eq!(
+        get(&tree, &format!("{}?with-defaults=trim", album)),
+        r#"{"example-jukebox:album":[{"name":"Wasting Light","year":2011}]}"#
+    );
+
+    let uri = RestconfUri::parse(
+        &tree.schema,
+        "/restconf/data/example-jukebox:jukebox/library/artist=Queen",
+    )
+    .unwrap();
+    assert!(tree.restconf_get(&uri).is_err());
+    let uri = RestconfUri::parse(
+        &tree.schema,
+        "/restconf/data/example-jukebox:jukebox/player/gap?content=nonconfig",
+    )
+    .unwrap();
+    assert!(matches!(
+        tree.restconf_get(&uri),
+        Err(DataError::NotFound(_))
+    ));
+}
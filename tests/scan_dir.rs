@@ -1,4 +1,8 @@
-use libyang::Yang;
+pub mod common;
+
+use common::yang_dirs;
+use libyang::{FindError, Yang};
+use std::fs;
 use std::path::PathBuf;
 
 #[test]
@@ -36,3 +40,82 @@ fn scan_dir_recursive_without_yang() {
         panic!("scan_dir should match to yang file.");
     }
 }
+
+#[test]
+fn find_path_first_match() {
+    let root = yang_dirs(
+        "find-path",
+        &[
+            ("a/m.yang", "module m { }"),
+            ("b/m.yang", "module m { }"),
+            ("b/n@2020-01-01.yang", "module n { revision 2020-01-01; }"),
+            (
+                "b/n@2021-01-01.yang",
+                "module other { revision 2021-01-01; }",
+            ),
+            ("c/sub/o.yang", "/* comment */\nmodule o { }"),
+        ],
+    );
+    let dir = |d: &str| root.join(d).to_str().unwrap().to_owned();
+
+    let mut yang = Yang::new();
+    yang.add_path(&format!("{}:{}", dir("a"), dir("b")));
+    yang.add_path(&format!("{}/...", dir("c")));
+    assert_eq!(yang.find_path("m").unwrap(), root.join("a/m.yang"));
+    assert_eq!(yang.find_path("o").unwrap(), root.join("c/sub/o.yang"));
+    assert_eq!(
+        yang.find_path("n").unwrap(),
+        root.join("b/n@2021-01-01.yang")
+    );
+    assert_eq!(
+        yang.find_path("n@2020-01-01").unwrap(),
+        root.join("b/n@2020-01-01.yang")
+    );
+    // Finding files does not change the load paths.
+    assert_eq!(yang.paths().len(), 3);
+
+    // File of the other module is skipped with validation.
+    yang.set_validate(true);
+    assert_eq!(
+        yang.find_path("n").unwrap(),
+        root.join("b/n@2020-01-01.yang")
+    );
+    assert_eq!(yang.find_path("o").unwrap(), root.join("c/sub/o.yang"));
+
+    match yang.find_path("p") {
+        Err(FindError::NotFound(name, tried)) => {
+            assert_eq!(name, "p");
+            assert_eq!(
+                tried,
+                vec![
+                    PathBuf::from("./p.yang"),
+                    root.join("a/p.yang"),
+                    root.join("b/p.yang"),
+                    root.join("c/p.yang"),
+                    root.join("c/sub/p.yang"),
+                ]
+            );
+        }
+        _ => panic!("find_path should fail"),
+    }
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn add_env_path() {
+    std::env::set_var("YANGPATH", "/usr/share/yang:/opt/yang");
+    std::env::set_var("YANG_MODPATH", "/var/yang");
+    let mut yang = Yang::new();
+    yang.add_path("yang/...");
+    yang.add_env_path();
+    assert_eq!(
+        yang.paths(),
+        &vec![
+            PathBuf::from("yang/..."),
+            PathBuf::from("/usr/share/yang"),
+            PathBuf::from("/opt/yang"),
+            PathBuf::from("/var/yang"),
+        ]
+    );
+}
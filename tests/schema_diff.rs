@@ -0,0 +1,121 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const OLD: &str = r#"module example {
+  namespace "urn:example";
+  prefix ex;
+  revision 2020-01-01;
+  extension secret {
+    argument "reason";
+  }
+  feature tls;
+  identity protocol;
+  identity ssh {
+    base protocol;
+  }
+  typedef port {
+    type uint16;
+  }
+  container system {
+    leaf hostname {
+      type string;
+      description "Host name.";
+    }
+    leaf port {
+      type port;
+    }
+    leaf password {
+      type string;
+      ex:secret "credential";
+    }
+    leaf location {
+      type string;
+    }
+  }
+}"#;
+
+const NEW: &str = r#"module example {
+  namespace "urn:example";
+  prefix ex;
+  revision 2021-01-01;
+  revision 2020-01-01;
+  extension secret {
+    argument "reason";
+    description "Secret node.";
+  }
+  feature tls;
+  feature ssh;
+  identity protocol;
+  typedef port {
+    type uint16 {
+      range "1..max";
+    }
+  }
+  typedef name {
+    type string;
+  }
+  container system {
+    ex:secret "all";
+    leaf hostname {
+      type name;
+      description "Name of the host.";
+    }
+    leaf port {
+      type port;
+    }
+    leaf password {
+      type string;
+    }
+    leaf domain {
+      type string;
+    }
+  }
+}"#;
+
+#[test]
+fn schema_diff_test() {
+    let old = load_modules(&[OLD], "example");
+    let new = load_modules(&[NEW], "example");
+    let diff = old.schema_diff(&new, "example").unwrap();
+    let expected = r#"--- example@2020-01-01
++++ example@2021-01-01
++ extension-usage /example:system example:secret "all"
+~ node /example:system/hostname
+    type: "string" -> "name"
+    description: "Host name." -> "Name of the host."
+~ node /example:system/port
+    range: (none) -> "1..max"
+- extension-usage /example:system/password example:secret "credential"
+- node /example:system/location
++ node /example:system/domain
++ typedef name
+~ typedef port
+    range: (none) -> "1..max"
+- identity ssh
++ feature ssh
+~ extension secret
+    description: (none) -> "Secret node."
+"#;
+    assert_eq!(diff.to_string(), expected);
+
+    let json = diff.to_json();
+    match json.get("entries") {
+        Some(JsonValue::Array(entries)) => {
+            assert_eq!(entries.len(), 11);
+            assert_eq!(
+                entries[0].to_string(),
+                r#"{"item":"extension-usage","name":"/example:system","op":"added","extension":"example:secret \"all\""}"#
+            );
+            assert_eq!(
+                entries[2].to_string(),
+                r#"{"item":"node","name":"/example:system/port","op":"modified","changes":[{"property":"range","old":null,"new":"1..max"}]}"#
+            );
+        }
+        _ => panic!("entries should be an array"),
+    }
+
+    let diff = old.schema_diff(&old, "example").unwrap();
+    assert!(diff.is_empty());
+}
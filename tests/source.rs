@@ -0,0 +1,148 @@
+use libyang::*;
+use std::path::PathBuf;
+
+const IETF_INTERFACES: &str = include_str!("../yang/third_party/ietf/ietf-interfaces.yang");
+const IETF_YANG_TYPES: &str = include_str!("../yang/third_party/ietf/ietf-yang-types.yang");
+
+const EXAMPLE: &str = r#"module example {
+  namespace "urn:example";
+  prefix ex;
+  import ietf-interfaces {
+    prefix if;
+  }
+  container system {
+    leaf interface {
+      type if:interface-ref;
+    }
+  }
+}"#;
+
+#[test]
+fn memory_source_load_test() {
+    let mut memory = MemorySource::new();
+    memory.add(IETF_INTERFACES).unwrap();
+    memory.add(IETF_YANG_TYPES).unwrap();
+
+    let mut sources = ModuleSources::new();
+    sources.add(|name: &str| match name {
+        "example" => Some(EXAMPLE.to_owned()),
+        _ => None,
+    });
+    sources.add(memory);
+
+    let mut ms = Modules::new();
+    ms.load(&sources, "example").unwrap();
+    let mut ids: Vec<String> = ms.modules.keys().map(|id| id.to_string()).collect();
+    ids.sort();
+    assert_eq!(
+        ids,
+        vec![
+            "example",
+            "ietf-interfaces@2018-02-20",
+            "ietf-yang-types@2013-07-15"
+        ]
+    );
+    assert!(ms.schema().is_ok());
+
+    // Every location of the sources is listed.
+    let mut ms = Modules::new();
+    match ms.load(&sources, "ietf-inet-types") {
+        Err(ModuleError::NotFound(name, message)) => {
+            assert_eq!(name, "ietf-inet-types");
+            assert_eq!(
+                message,
+                "Can't find ietf-inet-types, tried ietf-inet-types, ietf-inet-types.yang"
+            );
+        }
+        _ => panic!("ietf-inet-types should not be found"),
+    }
+}
+
+#[test]
+fn module_sources_order_test() {
+    let mut memory = MemorySource::new();
+    memory.insert("ietf-yang-types", "module ietf-yang-types { }");
+
+    let mut yang = Yang::new();
+    yang.add_path("yang/...");
+
+    // The first source which has the module is used.
+    let mut sources = ModuleSources::new();
+    sources.add(memory.clone());
+    sources.add(yang);
+    let mut tried = vec![];
+    let found = sources.find("ietf-yang-types", &mut tried).unwrap();
+    assert_eq!(
+        found.unwrap().location,
+        PathBuf::from("ietf-yang-types.yang")
+    );
+    let found = sources.find("ietf-inet-types", &mut tried).unwrap();
+    assert_eq!(
+        found.unwrap().location,
+        PathBuf::from("yang/third_party/ietf/ietf-inet-types.yang")
+    );
+
+    let mut yang = Yang::new();
+    yang.add_path("yang/...");
+    let mut sources = ModuleSources::new();
+    sources.add(yang);
+    sources.add(memory);
+    let found = sources.find("ietf-yang-types", &mut tried).unwrap();
+    assert_eq!(
+        found.unwrap().location,
+        PathBuf::from("yang/third_party/ietf/ietf-yang-types.yang")
+    );
+}
+
+#[cfg(feature = "tar")]
+#[test]
+fn tar_source_test() {
+    let mut builder = tar::Builder::new(vec![]);
+    for (path, text) in &[
+        ("models/ietf-interfaces.yang", IETF_INTERFACES),
+        ("models/ietf-yang-types@2013-07-15.yang", IETF_YANG_TYPES),
+        ("models/README", "not a module"),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(text.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, text.as_bytes())
+            .unwrap();
+    }
+    let archive = builder.into_inner().unwrap();
+
+    let source = MemorySource::from_tar(archive.as_slice()).unwrap();
+    let names: Vec<&String> = source.file_names().collect();
+    assert_eq!(
+        names,
+        vec!["ietf-interfaces.yang", "ietf-yang-types@2013-07-15.yang"]
+    );
+    let mut ms = Modules::new();
+    ms.load(&source, "ietf-interfaces").unwrap();
+    assert_eq!(ms.modules.len(), 2);
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn zip_source_test() {
+    use std::io::Write;
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+    for (path, text) in &[
+        ("models/ietf-interfaces.yang", IETF_INTERFACES),
+        ("models/ietf-yang-types.yang", IETF_YANG_TYPES),
+    ] {
+        writer
+            .start_file(*path, zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(text.as_bytes()).unwrap();
+    }
+    let archive = writer.finish().unwrap();
+
+    let source = MemorySource::from_zip(archive).unwrap();
+    let mut ms = Modules::new();
+    ms.load(&source, "ietf-interfaces").unwrap();
+    assert_eq!(ms.modules.len(), 2);
+}
@@ -0,0 +1,399 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+#[test]
+fn validate_interfaces_test() {
+    let ms = third_party_modules(&[]);
+    let schema = ms.schema().unwrap();
+    let mut tree = DataTree::json_decode(
+        schema,
+        r#"{"ietf-interfaces:interfaces": {"interface": [
+             {"name": "eth0", "type": "iana-if-type:ethernetCsmacd"},
+             {"name": "eth1"}]}}"#,
+    )
+    .unwrap();
+
+    // Mandatory state data of the interface isn't required in
+    // configuration.
+    assert_eq!(
+        validate(&tree, &ms),
+        Err(vec![ValidationError::Mandatory(String::from(
+            "/ietf-interfaces:interfaces/interface[name='eth1']/type"
+        ))])
+    );
+    tree.set(
+        "/ietf-interfaces:interfaces/interface[name='eth1']/type",
+        "iana-if-type:softwareLoopback",
+    )
+    .unwrap();
+    assert_eq!(validate(&tree, &ms), Ok(()));
+
+    // Modules which are not loaded.
+    let ms = third_party_modules(&[]);
+    let mut other = Modules::new();
+    other.modules = ms
+        .modules
+        .into_iter()
+        .filter(|(_, m)| m.name != "ietf-interfaces")
+        .collect();
+    let errors = validate(&tree, &other).unwrap_err();
+    assert_eq!(
+        errors,
+        vec![ValidationError::UnknownModule(
+            String::from("/ietf-interfaces:interfaces"),
+            String::from("ietf-interfaces")
+        )]
+    );
+}
+
+const EXAMPLE: &str = r#"module example-validate {
+  namespace "urn:example:validate";
+  prefix exv;
+  import ietf-interfaces {
+    prefix if;
+  }
+  typedef percent {
+    type uint8 {
+      range "0 .. 100";
+    }
+  }
+  container system {
+    leaf hostname {
+      type string {
+        length "1..16";
+        pattern "[a-z][a-z0-9-]*";
+      }
+      mandatory true;
+    }
+    leaf load {
+      type percent;
+    }
+    leaf uplink {
+      type leafref {
+        path "/if:interfaces/if:interface/if:name";
+      }
+    }
+    leaf-list dns {
+      type string;
+      max-elements 2;
+    }
+    list server {
+      key "name";
+      unique "address port";
+      min-elements 1;
+      leaf name {
+        type string;
+      }
+      leaf address {
+        type string;
+      }
+      leaf port {
+        type uint16;
+        must ". != 0" {
+          error-message "port must not be 0";
+        }
+      }
+      leaf secure {
+        when "../port = 443";
+        type boolean;
+      }
+    }
+  }
+}"#;
+
+#[test]
+fn validate_example_test() {
+    let ms = third_party_modules(&[EXAMPLE]);
+    let schema = ms.schema().unwrap();
+    let tree = DataTree::json_decode(
+        schema.clone(),
+        r#"{"example-validate:system": {
+             "hostname": "router-1",
+             "load": 10,
+             "dns": ["a", "b"],
+             "server": [
+               {"name": "s1", "address": "10.0.0.1", "port": 443, "secure": true},
+               {"name": "s2", "address": "10.0.0.1", "port": 80}
+             ]}}"#,
+    )
+    .unwrap();
+    assert_eq!(validate(&tree, &ms), Ok(()));
+
+    let mut tree = DataTree::json_decode(
+        schema.clone(),
+        r#"{"example-validate:system": {
+             "hostname": "Router",
+             "load": 101,
+             "uplink": "eth9",
+             "dns": ["a", "b", "a"],
+             "server": [
+               {"name": "s1", "address": "10.0.0.1", "port": 0, "secure": true},
+               {"name": "s2", "address": "10.0.0.1", "port": 0}
+             ]}}"#,
+    )
+    .unwrap();
+    let errors = validate(&tree, &ms).unwrap_err();
+    let paths: Vec<(&str, Option<&str>)> = errors.iter().map(|e| (e.path(), e.app_tag())).collect();
+    assert_eq!(
+        paths,
+        vec![
+            ("/example-validate:system/dns", Some("too-many-elements")),
+            ("/example-validate:system/dns[.='a']", None),
+            (
+                "/example-validate:system/server[name='s2']",
+                Some("data-not-unique")
+            ),
+            ("/example-validate:system/hostname", None),
+            ("/example-validate:system/load", None),
+            ("/example-validate:system/uplink", Some("instance-required")),
+            (
+                "/example-validate:system/server[name='s1']/port",
+                Some("must-violation")
+            ),
+            ("/example-validate:system/server[name='s1']/secure", None),
+            (
+                "/example-validate:system/server[name='s2']/port",
+                Some("must-violation")
+            ),
+        ]
+    );
+    assert!(matches!(
+        &errors[6],
+        ValidationError::Must { error_message: Some(m), .. } if m == "port must not be 0"
+    ));
+    assert!(matches!(&errors[7], ValidationError::When(..)));
+    assert!(matches!(&errors[3], ValidationError::InvalidValue(..)));
+
+    // Leafref target exists, and the list has no entries.
+    tree.delete("/example-validate:system/server[name='s1']")
+        .unwrap();
+    tree.delete("/example-validate:system/server[name='s2']")
+        .unwrap();
+    tree.delete("/example-validate:system/dns[.='a']").unwrap();
+    tree.set("/example-validate:system/hostname", "r1").unwrap();
+    tree.set("/example-validate:system/load", "100").unwrap();
+    tree.set(
+        "/ietf-interfaces:interfaces/interface[name='eth9']/type",
+        "iana-if-type:ethernetCsmacd",
+    )
+    .unwrap();
+    assert_eq!(
+        validate(&tree, &ms),
+        Err(vec![ValidationError::TooFew(
+            String::from("/example-validate:system/server"),
+            1
+        )])
+    );
+
+    // Mandatory leaf in the non-presence container.
+    let tree = DataTree::new(schema);
+    assert_eq!(
+        validate(&tree, &ms),
+        Err(vec![
+            ValidationError::Mandatory(String::from("/example-validate:system/hostname")),
+            ValidationError::TooFew(String::from("/example-validate:system/server"), 1),
+        ])
+    );
+}
+
+const PATTERN: &str = r#"module example-pattern {
+  namespace "urn:example:pattern";
+  prefix exp;
+  typedef element-name {
+    type string {
+      length "1..max";
+      pattern '[a-zA-Z_][a-zA-Z0-9\-_.]*';
+      pattern '[xX][mM][lL].*' {
+        modifier invert-match;
+      }
+    }
+  }
+  leaf-list element {
+    type element-name;
+  }
+}"#;
+
+#[test]
+fn validate_invert_match_test() {
+    let ms = third_party_modules(&[PATTERN]);
+    let schema = ms.schema().unwrap();
+    let node = schema.find_path("/exp:element").unwrap();
+    let typ = node.typ.as_ref().unwrap();
+    assert!(!typ.patterns[0].invert_match);
+    assert!(typ.patterns[1].invert_match);
+
+    let tree = DataTree::json_decode(
+        schema.clone(),
+        r#"{"example-pattern:element": ["interface", "name"]}"#,
+    )
+    .unwrap();
+    assert_eq!(validate(&tree, &ms), Ok(()));
+
+    let tree = DataTree::json_decode(
+        schema,
+        r#"{"example-pattern:element": ["interface", "xmlns", "10"]}"#,
+    )
+    .unwrap();
+    let errors = validate(&tree, &ms).unwrap_err();
+    let paths: Vec<&str> = errors.iter().map(|e| e.path()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "/example-pattern:element[.='xmlns']",
+            "/example-pattern:element[.='10']"
+        ]
+    );
+}
+
+const CHOICE: &str = r#"module example-choice {
+  namespace "urn:example:choice";
+  prefix exc;
+  container system {
+    choice transport {
+      mandatory true;
+      case tcp {
+        leaf tcp-port {
+          type uint16;
+        }
+        leaf tcp-host {
+          type string;
+          mandatory true;
+        }
+      }
+      leaf udp-port {
+        type uint16;
+      }
+    }
+    choice auth {
+      case password {
+        leaf password {
+          type string;
+          mandatory true;
+        }
+        choice hash {
+          mandatory true;
+          leaf md5 {
+            type string;
+          }
+          leaf sha {
+            type string;
+          }
+        }
+      }
+      leaf key {
+        type string;
+      }
+    }
+  }
+}"#;
+
+#[test]
+fn validate_choice_test() {
+    let ms = third_party_modules(&[CHOICE]);
+    let schema = ms.schema().unwrap();
+    let system = schema.find_path("/exc:system").unwrap();
+    let cases: Vec<&str> = system.choices[0]
+        .cases
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(cases, vec!["tcp", "udp-port"]);
+    assert!(schema.find_path("/exc:system/exc:md5").is_ok());
+
+    // Mandatory nodes of the cases which are not active are not required.
+    let tree = DataTree::json_decode(
+        schema.clone(),
+        r#"{"example-choice:system": {"udp-port": 53, "key": "k"}}"#,
+    )
+    .unwrap();
+    assert_eq!(validate(&tree, &ms), Ok(()));
+
+    let tree = DataTree::json_decode(
+        schema.clone(),
+        r#"{"example-choice:system": {"tcp-port": 22}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        validate(&tree, &ms),
+        Err(vec![ValidationError::Mandatory(String::from(
+            "/example-choice:system/tcp-host"
+        ))])
+    );
+
+    // Mandatory choice in the active case.
+    let tree = DataTree::json_decode(
+        schema.clone(),
+        r#"{"example-choice:system": {"password": "secret"}}"#,
+    )
+    .unwrap();
+    let errors = validate(&tree, &ms).unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            ValidationError::MissingChoice(
+                String::from("/example-choice:system"),
+                String::from("transport")
+            ),
+            ValidationError::MissingChoice(
+                String::from("/example-choice:system"),
+                String::from("hash")
+            ),
+        ]
+    );
+    assert_eq!(errors[0].app_tag(), Some("missing-choice"));
+
+    // Mandatory choice in the non-presence container.
+    let tree = DataTree::new(schema);
+    assert_eq!(
+        validate(&tree, &ms),
+        Err(vec![ValidationError::MissingChoice(
+            String::from("/example-choice:system"),
+            String::from("transport")
+        )])
+    );
+}
+
+const UNIQUE: &str = r#"module example-unique {
+  namespace "urn:example:unique";
+  prefix exu;
+  list server {
+    key "name";
+    unique "address port";
+    leaf name {
+      type string;
+    }
+    leaf address {
+      type string;
+    }
+    leaf port {
+      type uint16;
+      default 80;
+    }
+  }
+}"#;
+
+#[test]
+fn validate_unique_default_test() {
+    let ms = third_party_modules(&[UNIQUE]);
+    let schema = ms.schema().unwrap();
+
+    // The default value of the absent leaf is compared.
+    let tree = DataTree::json_decode(
+        schema,
+        r#"{"example-unique:server": [
+             {"name": "s1", "address": "a", "port": 80},
+             {"name": "s2", "address": "a"},
+             {"name": "s3", "address": "a", "port": 81},
+             {"name": "s4"}]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        validate(&tree, &ms),
+        Err(vec![ValidationError::NotUnique(
+            String::from("/example-unique:server[name='s2']"),
+            String::from("address port")
+        )])
+    );
+}
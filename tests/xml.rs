@@ -0,0 +1,198 @@
+pub mod common;
+
+use common::*;
+use libyang::*;
+
+const CONFIG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<config xmlns="urn:ietf:params:xml:ns:netconf:base:1.0"
+        xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0"
+        xmlns:t="urn:ietf:params:xml:ns:yang:iana-if-type">
+  <interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces">
+    <interface nc:operation="replace">
+      <name>eth0</name>
+      <description>Uplink &lt;A&gt;</description>
+      <type>t:ethernetCsmacd</type>
+      <enabled>true</enabled>
+    </interface>
+    <interface>
+      <name>lo0</name>
+      <enabled>false</enabled>
+      <type xmlns:x="urn:ietf:params:xml:ns:yang:iana-if-type">x:softwareLoopback</type>
+    </interface>
+  </interfaces>
+</config>"#;
+
+const ENCODED: &str = r#"<interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces">
+  <interface xmlns:nc="urn:ietf:params:xml:ns:netconf:base:1.0" nc:operation="replace">
+    <name>eth0</name>
+    <description>Uplink &lt;A&gt;</description>
+    <type xmlns:ianaift="urn:ietf:params:xml:ns:yang:iana-if-type">ianaift:ethernetCsmacd</type>
+    <enabled>true</enabled>
+  </interface>
+  <interface>
+    <name>lo0</name>
+    <type xmlns:ianaift="urn:ietf:params:xml:ns:yang:iana-if-type">ianaift:softwareLoopback</type>
+    <enabled>false</enabled>
+  </interface>
+</interfaces>"#;
+
+#[test]
+fn xml_round_trip_test() {
+    let schema = third_party_schema(&[]);
+    let tree = DataTree::xml_decode(schema.clone(), CONFIG).unwrap();
+
+    let eth0 = tree
+        .get("/ietf-interfaces:interfaces/interface[name='eth0']")
+        .unwrap();
+    assert_eq!(
+        eth0.annotation("ietf-netconf", "operation"),
+        Some("replace")
+    );
+    assert_eq!(
+        eth0.child("type").unwrap().value(),
+        Some(&LeafValue::Identity(
+            String::from("iana-if-type"),
+            String::from("ethernetCsmacd")
+        ))
+    );
+    assert_eq!(
+        tree.get("/ietf-interfaces:interfaces/interface[name='lo0']/type")
+            .unwrap()
+            .value()
+            .unwrap()
+            .to_string(),
+        "iana-if-type:softwareLoopback"
+    );
+
+    // Children are in the schema order and identities use module prefixes.
+    assert_eq!(tree.xml_encode(), ENCODED);
+    assert_eq!(DataTree::xml_decode(schema.clone(), ENCODED).unwrap(), tree);
+
+    // XML and JSON encodings decode into the same tree.
+    let json = DataTree::json_decode(schema, &tree.json_encode()).unwrap();
+    assert_eq!(json, tree);
+}
+
+#[test]
+fn xml_decode_error_test() {
+    let schema = third_party_schema(&[]);
+    let decode = |text: &str| DataTree::xml_decode(schema.clone(), text);
+
+    assert!(matches!(
+        decode("<interfaces/>"),
+        Err(DataError::UnknownNode(..))
+    ));
+    assert!(matches!(
+        decode(
+            r#"<interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces">
+                 <interface><enabled>true</enabled><name>eth0</name></interface>
+               </interfaces>"#
+        ),
+        Err(DataError::KeyOrder(..))
+    ));
+    assert!(matches!(
+        decode(
+            r#"<interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces">
+                 <interface><enabled>true</enabled></interface>
+               </interfaces>"#
+        ),
+        Err(DataError::MissingKey(..))
+    ));
+    // Prefix of identity is not declared.
+    assert!(matches!(
+        decode(
+            r#"<interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces">
+                 <interface><name>eth0</name><type>ianaift:ethernetCsmacd</type></interface>
+               </interfaces>"#
+        ),
+        Err(DataError::InvalidValue(..))
+    ));
+    assert!(matches!(
+        decode(r#"<interfaces xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces">"#),
+        Err(DataError::Syntax(..))
+    ));
+}
+
+const EXAMPLE: &str = r#"module example-xml {
+  namespace "urn:example:xml";
+  prefix exx;
+  import ietf-interfaces {
+    prefix if;
+  }
+  container top {
+    list route {
+      key "prefix table";
+      leaf table {
+        type uint32;
+      }
+      leaf prefix {
+        type string;
+      }
+      leaf target {
+        type instance-identifier;
+      }
+      leaf flag {
+        type empty;
+      }
+    }
+    anydata extra;
+  }
+}"#;
+
+#[test]
+fn xml_example_test() {
+    let schema = third_party_schema(&[EXAMPLE]);
+    let mut tree = DataTree::new(schema.clone());
+    tree.set(
+        "/example-xml:top/route[prefix='10.0.0.0/8'][table='1']/target",
+        "/ietf-interfaces:interfaces/ietf-interfaces:interface[ietf-interfaces:name='eth0']",
+    )
+    .unwrap();
+    tree.create("/example-xml:top/route[prefix='10.0.0.0/8'][table='1']/flag")
+        .unwrap_err();
+    tree.set(
+        "/example-xml:top/route[prefix='10.0.0.0/8'][table='1']/flag",
+        "",
+    )
+    .unwrap();
+    tree.create("/example-xml:top/extra").unwrap();
+
+    // Keys are encoded in the key order, instance-identifier prefixes are
+    // declared.
+    let encoded = tree.xml_encode();
+    assert_eq!(
+        encoded,
+        r#"<top xmlns="urn:example:xml">
+  <route>
+    <prefix>10.0.0.0/8</prefix>
+    <table>1</table>
+    <target xmlns:if="urn:ietf:params:xml:ns:yang:ietf-interfaces">/if:interfaces/if:interface[if:name='eth0']</target>
+    <flag/>
+  </route>
+  <extra/>
+</top>"#
+    );
+    let decoded = DataTree::xml_decode(schema.clone(), &encoded).unwrap();
+    assert_eq!(decoded, tree);
+
+    // Anydata content is kept with the namespaces.
+    let text = r#"<top xmlns="urn:example:xml">
+  <extra>
+    <a>1</a>
+    <b xmlns="urn:ietf:params:xml:ns:yang:ietf-interfaces">
+      <c>2</c>
+    </b>
+  </extra>
+</top>"#;
+    let tree = DataTree::xml_decode(schema, text).unwrap();
+    let extra = tree.get("/example-xml:top/extra").unwrap();
+    match &extra.content {
+        DataContent::Anydata(any) => {
+            assert_eq!(any[0].value.as_deref(), Some("1"));
+            assert_eq!(any[1].module.as_deref(), Some("ietf-interfaces"));
+            assert_eq!(any[1].children[0].value.as_deref(), Some("2"));
+        }
+        _ => panic!("anydata expected"),
+    }
+    assert_eq!(tree.xml_encode(), text);
+}
@@ -0,0 +1,69 @@
+use libyang::xpath::*;
+use libyang::*;
+
+#[test]
+fn must_when_compile_test() {
+    let literal = r#"module example-interfaces {
+  namespace "urn:example:interfaces";
+  prefix exif;
+  import ietf-interfaces {
+    prefix if;
+  }
+  container interfaces {
+    list interface {
+      key "name";
+      must "not(mtu) or mtu >= 64" {
+        error-message "MTU must be at least 64.";
+        error-app-tag "mtu-too-small";
+      }
+      leaf name {
+        type string;
+      }
+      leaf type {
+        type string;
+      }
+      leaf mtu {
+        when "../type = 'if:ethernetCsmacd'" {
+          description "Only for Ethernet.";
+        }
+        type uint32;
+      }
+    }
+  }
+}"#;
+    let (_, module) = yang_parse(literal).unwrap();
+    let interfaces = &module.nodes[0];
+    let interface = &interfaces.nodes().unwrap()[0];
+
+    let must = &interface.must()[0];
+    assert_eq!(must.name, "not(mtu) or mtu >= 64");
+    assert_eq!(
+        must.error_message,
+        Some(String::from("MTU must be at least 64."))
+    );
+    assert_eq!(must.error_app_tag, Some(String::from("mtu-too-small")));
+    let xpath = must.xpath.as_ref().unwrap();
+    assert!(matches!(xpath.expr, Expr::Binary(BinaryOp::Or, _, _)));
+
+    let mtu = &interface.nodes().unwrap()[2];
+    let when = mtu.when().unwrap();
+    assert_eq!(when.description, Some(String::from("Only for Ethernet.")));
+    let xpath = when.xpath.as_ref().unwrap();
+    assert_eq!(xpath.text, "../type = 'if:ethernetCsmacd'");
+    assert_eq!(xpath.prefix_module("exif"), Some("example-interfaces"));
+    assert_eq!(xpath.prefix_module("if"), Some("ietf-interfaces"));
+    assert_eq!(xpath.prefix_module("yang"), None);
+}
+
+#[test]
+fn must_syntax_error_test() {
+    let literal = r#"module example {
+  namespace "urn:example";
+  prefix ex;
+  leaf mtu {
+    must "../type = ";
+    type uint32;
+  }
+}"#;
+    assert!(yang_parse(literal).is_err());
+}
@@ -0,0 +1,171 @@
+use libyang::*;
+
+const EXAMPLE: &str = r#"module example {
+  yang-version 1.1;
+  namespace "urn:example";
+  prefix ex;
+  import example-ext {
+    prefix ext;
+  }
+  description
+    "Example module.
+     Text with <xml> & \"quotes\".";
+  revision 2022-01-01;
+  extension secret {
+    argument reason;
+  }
+  extension note {
+    argument text {
+      yin-element true;
+    }
+  }
+  container system {
+    ex:secret "credential";
+    ex:note "First
+             second";
+    leaf hostname {
+      type string {
+        pattern '[a-z]+';
+      }
+      must "string-length(.) > 1" {
+        error-message "Too short.";
+      }
+      ext:label "Host";
+    }
+  }
+}"#;
+
+const EXAMPLE_EXT: &str = r#"module example-ext {
+  namespace "urn:example:ext";
+  prefix ext;
+  extension label {
+    argument name;
+  }
+}"#;
+
+const EXAMPLE_YIN: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<module xmlns="urn:ietf:params:xml:ns:yang:yin:1" xmlns:ex="urn:example" xmlns:ext="urn:example:ext" name="example">
+  <yang-version value="1.1"/>
+  <namespace uri="urn:example"/>
+  <prefix value="ex"/>
+  <import module="example-ext">
+    <prefix value="ext"/>
+  </import>
+  <description>
+    <text>Example module.
+Text with &lt;xml&gt; &amp; "quotes".</text>
+  </description>
+  <revision date="2022-01-01"/>
+  <extension name="secret">
+    <argument name="reason"/>
+  </extension>
+  <extension name="note">
+    <argument name="text">
+      <yin-element value="true"/>
+    </argument>
+  </extension>
+  <container name="system">
+    <ex:secret reason="credential"/>
+    <ex:note>
+      <ex:text>First
+second</ex:text>
+    </ex:note>
+    <leaf name="hostname">
+      <type name="string">
+        <pattern value="[a-z]+"/>
+      </type>
+      <must condition="string-length(.) &gt; 1">
+        <error-message>
+          <value>Too short.</value>
+        </error-message>
+      </must>
+      <ext:label name="Host"/>
+    </leaf>
+  </container>
+</module>
+"#;
+
+fn context() -> YinContext {
+    let (_, module) = yang_parse(EXAMPLE_EXT).unwrap();
+    let mut ctx = YinContext::new();
+    ctx.add_module(&module);
+    ctx
+}
+
+#[test]
+fn yin_write_test() {
+    let ctx = context();
+    let stmt = stmt_parse(EXAMPLE).unwrap();
+    assert_eq!(yin_write(&stmt, &ctx).unwrap(), EXAMPLE_YIN);
+
+    // Namespace of the imported module is needed for its extensions.
+    assert_eq!(
+        yin_write(&stmt, &YinContext::new()),
+        Err(YinError::UnknownPrefix(String::from("ext")))
+    );
+}
+
+#[test]
+fn yin_parse_test() {
+    let ctx = context();
+    let stmt = yin_parse(EXAMPLE_YIN, &ctx).unwrap();
+    assert_eq!(stmt, stmt_parse(EXAMPLE).unwrap());
+
+    // Extension instances take the prefix of the import statement
+    // regardless of the XML prefix.
+    let yin = r#"<module name="a" xmlns="urn:ietf:params:xml:ns:yang:yin:1">
+  <namespace uri="urn:a"/>
+  <prefix value="a"/>
+  <import module="example-ext"><prefix value="ext"/></import>
+  <leaf name="b" xmlns:x="urn:example:ext">
+    <type name="string"/>
+    <x:label name="B"/>
+  </leaf>
+</module>"#;
+    let stmt = yin_parse(yin, &ctx).unwrap();
+    let label = &stmt.substmt("leaf").unwrap().substmts[1];
+    assert_eq!(label.keyword, "ext:label");
+    assert_eq!(label.argument.as_deref(), Some("B"));
+
+    assert_eq!(
+        yin_parse(r#"<module name="a"/>"#, &ctx),
+        Err(YinError::NotYin)
+    );
+    assert_eq!(
+        yin_parse(
+            r#"<module name="a" xmlns="urn:ietf:params:xml:ns:yang:yin:1"><leaf/></module>"#,
+            &ctx
+        ),
+        Err(YinError::MissingArgument(
+            String::from("leaf"),
+            String::from("name")
+        ))
+    );
+    assert_eq!(
+        yin_parse(
+            r#"<module name="a" xmlns="urn:ietf:params:xml:ns:yang:yin:1"><foo/></module>"#,
+            &ctx
+        ),
+        Err(YinError::UnknownStatement(String::from("foo")))
+    );
+}
+
+#[test]
+fn yin_module_test() {
+    let ctx = context();
+    let opts = FormatOptions::default();
+    let (_, module) = yang_parse(EXAMPLE).unwrap();
+    let text = module.to_yang(&opts);
+
+    // YIN and YANG parse to the same module.
+    let parsed = yin_parse_module(EXAMPLE_YIN, &ctx).unwrap();
+    assert_eq!(parsed.to_yang(&opts), text);
+    let secret = parsed.extensions.get("secret").unwrap();
+    assert_eq!(secret.argument.as_ref().unwrap().name, "reason");
+
+    // Round trip through YIN.
+    let yin = module.to_yin(&ctx).unwrap();
+    let parsed = yin_parse_module(&yin, &ctx).unwrap();
+    assert_eq!(parsed.to_yang(&opts), text);
+    assert_eq!(parsed.to_yin(&ctx).unwrap(), yin);
+}